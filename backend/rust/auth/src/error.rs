@@ -1,4 +1,5 @@
 use thiserror::Error;
+use crate::services::password_policy::PasswordViolation;
 
 #[derive(Error, Debug)]
 pub enum AuthError {
@@ -23,6 +24,15 @@ pub enum AuthError {
     #[error("Account is locked")]
     AccountLocked,
 
+    #[error("Sign-in blocked as too risky")]
+    LoginBlocked,
+
+    #[error("Additional verification required; sign in again")]
+    StepUpRequired,
+
+    #[error("Additional verification required, but no second factor is enrolled")]
+    SecondFactorRequired,
+
     #[error("Invalid token: {0}")]
     InvalidToken(String),
 
@@ -32,12 +42,105 @@ pub enum AuthError {
     #[error("Token revoked")]
     TokenRevoked,
 
+    #[error("Refresh token reuse detected")]
+    RefreshTokenReused,
+
+    #[error("Invalid MFA code")]
+    InvalidMfaCode,
+
+    #[error("MFA is not enrolled")]
+    MfaNotEnrolled,
+
+    #[error("MFA is already enabled")]
+    MfaAlreadyEnabled,
+
+    #[error("Invalid passkey: {0}")]
+    InvalidPasskey(String),
+
+    #[error("Passkey not found")]
+    PasskeyNotFound,
+
+    #[error("Passkey is already registered")]
+    PasskeyAlreadyRegistered,
+
+    #[error("API key not found")]
+    ApiKeyNotFound,
+
+    #[error("OAuth client not found")]
+    OAuthClientNotFound,
+
+    #[error("Invalid OAuth client credentials")]
+    InvalidClient,
+
+    #[error("Invalid grant: {0}")]
+    InvalidGrant(String),
+
+    #[error("Invalid scope: {0}")]
+    InvalidScope(String),
+
+    #[error("Unsupported grant type: {0}")]
+    UnsupportedGrantType(String),
+
+    #[error("Single sign-on is not configured for this tenant")]
+    SsoNotConfigured,
+
+    #[error("This tenant requires single sign-on")]
+    SsoRequired,
+
+    #[error("Identity provider error: {0}")]
+    SsoProvider(String),
+
+    #[error("Role not found")]
+    RoleNotFound,
+
+    #[error("Terminal not found")]
+    TerminalNotFound,
+
+    #[error("Terminal is locked after repeated PIN failures")]
+    TerminalLocked,
+
+    #[error("Invalid PIN")]
+    InvalidPin,
+
+    #[error("Weak PIN: {0}")]
+    WeakPin(String),
+
+    #[error("Invitation not found")]
+    InvitationNotFound,
+
+    #[error("Invitation has already been answered or revoked")]
+    InvitationClosed,
+
+    #[error("Invitation was sent to a different email address")]
+    InvitationEmailMismatch,
+
+    #[error("Already a member of this tenant")]
+    AlreadyMember,
+
+    #[error("Not a member of this tenant")]
+    NotTenantMember,
+
+    #[error("Role already exists")]
+    RoleAlreadyExists,
+
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    #[error("Insufficient permissions: {0}")]
+    InsufficientPermissions(String),
+
     #[error("Weak password: {0}")]
     WeakPassword(String),
 
+    #[error("Password does not meet policy: {}", format_violations(.0))]
+    PasswordPolicyViolation(Vec<PasswordViolation>),
+
     #[error("Password hash error: {0}")]
     PasswordHashError(String),
 
+    #[error("Email delivery error: {0}")]
+    Email(String),
+
     #[error("JWT error: {0}")]
     JwtError(String),
 
@@ -67,12 +170,98 @@ impl AuthError {
         match self {
             AuthError::InvalidCredentials
             | AuthError::WeakPassword(_)
-            | AuthError::Validation(_) => 400,
-            AuthError::InvalidToken(_) | AuthError::TokenExpired | AuthError::TokenRevoked => 401,
-            AuthError::AccountInactive | AuthError::TenantInactive | AuthError::AccountLocked => 403,
-            AuthError::UserNotFound | AuthError::TenantNotFound => 404,
-            AuthError::EmailAlreadyExists => 409,
+            | AuthError::PasswordPolicyViolation(_)
+            | AuthError::Validation(_)
+            | AuthError::MfaNotEnrolled
+            | AuthError::InvalidGrant(_)
+            | AuthError::InvalidScope(_)
+            | AuthError::UnsupportedGrantType(_)
+            | AuthError::InvalidFilter(_)
+            | AuthError::WeakPin(_) => 400,
+            AuthError::InvalidToken(_)
+            | AuthError::TokenExpired
+            | AuthError::TokenRevoked
+            | AuthError::RefreshTokenReused
+            | AuthError::InvalidMfaCode
+            | AuthError::InvalidPasskey(_)
+            | AuthError::InvalidPin
+            | AuthError::StepUpRequired
+            | AuthError::InvalidClient => 401,
+            AuthError::AccountInactive
+            | AuthError::TenantInactive
+            | AuthError::AccountLocked
+            | AuthError::TerminalLocked
+            | AuthError::LoginBlocked
+            | AuthError::SecondFactorRequired
+            | AuthError::SsoRequired
+            | AuthError::InvitationEmailMismatch
+            | AuthError::NotTenantMember
+            | AuthError::InsufficientPermissions(_) => 403,
+            AuthError::UserNotFound
+            | AuthError::TenantNotFound
+            | AuthError::ApiKeyNotFound
+            | AuthError::OAuthClientNotFound
+            | AuthError::SsoNotConfigured
+            | AuthError::RoleNotFound
+            | AuthError::TerminalNotFound
+            | AuthError::PasskeyNotFound
+            | AuthError::InvitationNotFound => 404,
+            AuthError::EmailAlreadyExists
+            | AuthError::MfaAlreadyEnabled
+            | AuthError::PasskeyAlreadyRegistered
+            | AuthError::InvitationClosed
+            | AuthError::AlreadyMember
+            | AuthError::RoleAlreadyExists => 409,
+            AuthError::SsoProvider(_) => 502,
             _ => 500,
         }
     }
-}
\ No newline at end of file
+
+    /// Structured detail for API error bodies, where the error carries any
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            AuthError::PasswordPolicyViolation(violations) => {
+                Some(serde_json::json!({ "violations": violations }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Error code for OAuth token, introspection and revocation responses (RFC 6749 section 5.2)
+    pub fn oauth_error_code(&self) -> &'static str {
+        match self {
+            AuthError::InvalidClient | AuthError::OAuthClientNotFound => "invalid_client",
+            AuthError::InvalidGrant(_)
+            | AuthError::InvalidToken(_)
+            | AuthError::TokenExpired
+            | AuthError::TokenRevoked
+            | AuthError::RefreshTokenReused => "invalid_grant",
+            AuthError::InvalidScope(_) => "invalid_scope",
+            AuthError::UnsupportedGrantType(_) => "unsupported_grant_type",
+            AuthError::Validation(_) => "invalid_request",
+            AuthError::InsufficientPermissions(_)
+            | AuthError::AccountInactive
+            | AuthError::TenantInactive
+            | AuthError::AccountLocked => "unauthorized_client",
+            _ => "server_error",
+        }
+    }
+
+    /// `scimType` for SCIM error responses (RFC 7644 section 3.12)
+    pub fn scim_type(&self) -> Option<&'static str> {
+        match self {
+            AuthError::InvalidFilter(_) => Some("invalidFilter"),
+            AuthError::EmailAlreadyExists | AuthError::RoleAlreadyExists => Some("uniqueness"),
+            AuthError::Validation(_) => Some("invalidValue"),
+            _ => None,
+        }
+    }
+}
+
+fn format_violations(violations: &[PasswordViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -12,6 +12,9 @@ use async_trait::async_trait;
 use olympus_shared::events::{
     EventHandler, EventContainer,
     UserRegisteredEvent, UserLoggedInEvent, TenantCreatedEvent, UserRoleChangedEvent, AccountLockedEvent,
+    RefreshTokenReuseDetectedEvent, ImpersonationStartedEvent, ImpersonationEndedEvent,
+    ImpersonatedRequestEvent, SessionRiskDetectedEvent, PasswordResetRequestedEvent,
+    MagicLinkRequestedEvent, UserInvitedEvent, PasskeySignCountRegressedEvent,
     auth_events, platform_events
 };
 use olympus_shared::events::subscriber::{HandlerPriority, HandlerHealth};
@@ -20,6 +23,8 @@ use tracing::{info, warn, error, debug, instrument};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use uuid::Uuid;
+use crate::services::mailer::Mailer;
 
 // ============================================================================
 // USER AUTHENTICATION EVENT HANDLER
@@ -59,8 +64,8 @@ impl UserAuthenticationHandler {
         // 4. Creating audit log entry
 
         if event.email_verification_required {
+            // The verification email is queued at registration and sent by EmailNotificationHandler
             info!("Email verification required for user {}", event.user_id);
-            // TODO: Trigger email verification process
         }
 
         debug!("User authentication profile initialized for user {}", event.user_id);
@@ -306,6 +311,77 @@ impl SecurityEventHandler {
 
         Ok(())
     }
+
+    /// Handle refresh token replay; the auth service has already revoked the token family
+    #[instrument(skip(self, event))]
+    async fn handle_refresh_token_reuse(&self, event: &RefreshTokenReuseDetectedEvent) -> Result<()> {
+        error!(
+            "Refresh token reuse detected for user {} (family {}, device {:?}) from IP {} - all sessions revoked",
+            event.user_id, event.family_id, event.device_id, event.ip_address
+        );
+
+        // Security response tasks:
+        // 1. Alert security team - a rotated token only resurfaces if it was copied
+        // 2. Notify the user that they have been signed out everywhere
+        // TODO: Trigger security alert
+
+        Ok(())
+    }
+
+    /// Handle risky sign-ins and refreshes; the auth service has already blocked or ended
+    /// the session where the risk engine asked it to
+    #[instrument(skip(self, event))]
+    async fn handle_session_risk(&self, event: &SessionRiskDetectedEvent) -> Result<()> {
+        let location = match (&event.city, &event.country_code) {
+            (Some(city), Some(country)) => format!("{}, {}", city, country),
+            (None, Some(country)) => country.clone(),
+            _ => "unknown location".to_string(),
+        };
+
+        match event.action.as_str() {
+            "block" => error!(
+                "Blocked sign-in for user {} from {} ({}) - risk {} {:?}",
+                event.user_id, event.ip_address, location, event.risk_score, event.signals
+            ),
+            _ => warn!(
+                "Risky session {:?} for user {} from {} ({}) - risk {} {:?}, action {}",
+                event.session_id, event.user_id, event.ip_address, location, event.risk_score, event.signals, event.action
+            ),
+        }
+
+        if let Some(speed) = event.travel_speed_kmh {
+            warn!("Impossible travel for user {}: {} km/h since the previous session", event.user_id, speed);
+        }
+
+        // Security response tasks:
+        // 1. Tell the user about the sign-in so they can revoke it from their sessions list
+        // 2. Alert the security team when sign-ins are blocked
+        // TODO: Send new sign-in notification
+
+        Ok(())
+    }
+
+    /// Handle passkey signature counters going backwards. Authenticators that don't keep a
+    /// counter always report zero and never get here, so two copies of one key are likely in use.
+    #[instrument(skip(self, event))]
+    async fn handle_passkey_sign_count_regressed(&self, event: &PasskeySignCountRegressedEvent) -> Result<()> {
+        error!(
+            "Passkey {} of user {} signed with counter {} after {} from IP {} - possible cloned authenticator ({})",
+            event.passkey_id,
+            event.user_id,
+            event.received_sign_count,
+            event.stored_sign_count,
+            event.ip_address,
+            if event.rejected { "sign-in refused" } else { "sign-in allowed" }
+        );
+
+        // Security response tasks:
+        // 1. Alert security team
+        // 2. Ask the user to remove the passkey and register a new one
+        // TODO: Trigger security alert
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -329,6 +405,27 @@ impl EventHandler for SecurityEventHandler {
                             Err(Error::Internal("Failed to deserialize AccountLockedEvent".to_string()))
                         }
                     }
+                    auth_events::REFRESH_TOKEN_REUSE_DETECTED => {
+                        if let Ok(reuse_event) = serde_json::from_value::<RefreshTokenReuseDetectedEvent>(domain_event.data.clone()) {
+                            self.handle_refresh_token_reuse(&reuse_event).await
+                        } else {
+                            Err(Error::Internal("Failed to deserialize RefreshTokenReuseDetectedEvent".to_string()))
+                        }
+                    }
+                    auth_events::SESSION_RISK_DETECTED => {
+                        if let Ok(risk_event) = serde_json::from_value::<SessionRiskDetectedEvent>(domain_event.data.clone()) {
+                            self.handle_session_risk(&risk_event).await
+                        } else {
+                            Err(Error::Internal("Failed to deserialize SessionRiskDetectedEvent".to_string()))
+                        }
+                    }
+                    auth_events::PASSKEY_SIGN_COUNT_REGRESSED => {
+                        if let Ok(passkey_event) = serde_json::from_value::<PasskeySignCountRegressedEvent>(domain_event.data.clone()) {
+                            self.handle_passkey_sign_count_regressed(&passkey_event).await
+                        } else {
+                            Err(Error::Internal("Failed to deserialize PasskeySignCountRegressedEvent".to_string()))
+                        }
+                    }
                     _ => {
                         debug!("Security handler ignoring event type: {}", domain_event.event_type);
                         Ok(())
@@ -351,6 +448,27 @@ impl EventHandler for SecurityEventHandler {
                             Err(Error::Internal("Failed to deserialize AccountLockedEvent from versioned event".to_string()))
                         }
                     }
+                    auth_events::REFRESH_TOKEN_REUSE_DETECTED => {
+                        if let Ok(reuse_event) = serde_json::from_value::<RefreshTokenReuseDetectedEvent>(versioned_event.data.clone()) {
+                            self.handle_refresh_token_reuse(&reuse_event).await
+                        } else {
+                            Err(Error::Internal("Failed to deserialize RefreshTokenReuseDetectedEvent from versioned event".to_string()))
+                        }
+                    }
+                    auth_events::SESSION_RISK_DETECTED => {
+                        if let Ok(risk_event) = serde_json::from_value::<SessionRiskDetectedEvent>(versioned_event.data.clone()) {
+                            self.handle_session_risk(&risk_event).await
+                        } else {
+                            Err(Error::Internal("Failed to deserialize SessionRiskDetectedEvent from versioned event".to_string()))
+                        }
+                    }
+                    auth_events::PASSKEY_SIGN_COUNT_REGRESSED => {
+                        if let Ok(passkey_event) = serde_json::from_value::<PasskeySignCountRegressedEvent>(versioned_event.data.clone()) {
+                            self.handle_passkey_sign_count_regressed(&passkey_event).await
+                        } else {
+                            Err(Error::Internal("Failed to deserialize PasskeySignCountRegressedEvent from versioned event".to_string()))
+                        }
+                    }
                     _ => {
                         debug!("Security handler ignoring versioned event type: {}", versioned_event.event_type);
                         Ok(())
@@ -372,6 +490,9 @@ impl EventHandler for SecurityEventHandler {
             auth_events::USER_LOGGED_OUT.to_string(),
             auth_events::ACCOUNT_LOCKED.to_string(),
             auth_events::PASSWORD_CHANGED.to_string(),
+            auth_events::REFRESH_TOKEN_REUSE_DETECTED.to_string(),
+            auth_events::SESSION_RISK_DETECTED.to_string(),
+            auth_events::PASSKEY_SIGN_COUNT_REGRESSED.to_string(),
         ]
     }
 
@@ -405,6 +526,7 @@ impl EventHandler for SecurityEventHandler {
 pub struct AuthAuditLogHandler {
     name: String,
     processed_count: AtomicU64,
+    impersonation_count: AtomicU64,
 }
 
 impl AuthAuditLogHandler {
@@ -412,32 +534,37 @@ impl AuthAuditLogHandler {
         Self {
             name: "AuthAuditLogHandler".to_string(),
             processed_count: AtomicU64::new(0),
+            impersonation_count: AtomicU64::new(0),
         }
     }
 
     /// Log all authentication events for compliance and auditing
     #[instrument(skip(self, event))]
     async fn log_auth_event(&self, event: &EventContainer) -> Result<()> {
-        let (event_type, tenant_id, user_id, timestamp) = match event {
+        let (event_type, tenant_id, user_id, timestamp, data) = match event {
             EventContainer::Legacy(domain_event) => (
                 domain_event.event_type.clone(),
                 domain_event.tenant_id,
                 domain_event.metadata.user_id,
                 domain_event.occurred_at,
+                &domain_event.data,
             ),
             EventContainer::Versioned(versioned_event) => (
                 versioned_event.event_type.clone(),
                 versioned_event.context.business_context.tenant_id,
                 versioned_event.context.user_id,
                 versioned_event.occurred_at,
+                &versioned_event.data,
             ),
         };
 
         info!(
-            "AUDIT: Authentication event '{}' for tenant {} at {}",
-            event_type, tenant_id, timestamp
+            "AUDIT: Authentication event '{}' for user {:?} in tenant {} at {}",
+            event_type, user_id, tenant_id, timestamp
         );
 
+        self.log_impersonation_event(&event_type, data)?;
+
         // Store audit log entry
         // This would typically involve:
         // 1. Writing to audit log database
@@ -448,6 +575,42 @@ impl AuthAuditLogHandler {
         debug!("Audit log entry created for event: {}", event_type);
         Ok(())
     }
+
+    /// Impersonation is recorded with who acted as whom, so it can be reviewed per session
+    fn log_impersonation_event(&self, event_type: &str, data: &serde_json::Value) -> Result<()> {
+        match event_type {
+            auth_events::IMPERSONATION_STARTED => {
+                let started: ImpersonationStartedEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize ImpersonationStartedEvent".to_string()))?;
+                warn!(
+                    "AUDIT: Impersonation session {} started by {} ({}) as user {} in tenant {}; read_only: {}, reason: {}, expires: {}, from {}",
+                    started.session_id, started.impersonator_id, started.impersonator_email, started.user_id,
+                    started.tenant_id, started.read_only, started.reason, started.expires_at, started.ip_address
+                );
+            }
+            auth_events::IMPERSONATION_ENDED => {
+                let ended: ImpersonationEndedEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize ImpersonationEndedEvent".to_string()))?;
+                warn!(
+                    "AUDIT: Impersonation session {} by {} as user {} ended at {}",
+                    ended.session_id, ended.impersonator_id, ended.user_id, ended.ended_at
+                );
+            }
+            auth_events::IMPERSONATED_REQUEST => {
+                let request: ImpersonatedRequestEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize ImpersonatedRequestEvent".to_string()))?;
+                info!(
+                    "AUDIT: Impersonation session {} ({} as {}): {} {} -> {}",
+                    request.session_id, request.impersonator_id, request.user_id,
+                    request.method, request.path, request.status
+                );
+            }
+            _ => return Ok(()),
+        }
+
+        self.impersonation_count.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -496,6 +659,149 @@ impl EventHandler for AuthAuditLogHandler {
     }
 }
 
+// ============================================================================
+// EMAIL NOTIFICATION HANDLER
+// ============================================================================
+
+/// Sends the emails that account events name. Every one of them carries a token, so the auth
+/// service queues it in the outbox itself and the event only names it; a redelivered event
+/// finds it already sent, and the outbox worker retries any that fail.
+pub struct EmailNotificationHandler {
+    name: String,
+    mailer: Arc<Mailer>,
+    processed_count: AtomicU64,
+    failed_count: AtomicU64,
+    last_error: Arc<RwLock<Option<String>>>,
+}
+
+impl EmailNotificationHandler {
+    pub fn new(mailer: Arc<Mailer>) -> Self {
+        Self {
+            name: "EmailNotificationHandler".to_string(),
+            mailer,
+            processed_count: AtomicU64::new(0),
+            failed_count: AtomicU64::new(0),
+            last_error: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    async fn send_email(&self, event_id: &str, event_type: &str, data: &serde_json::Value) -> Result<()> {
+        let sent = match event_type {
+            auth_events::USER_REGISTERED => {
+                let registered: UserRegisteredEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize UserRegisteredEvent".to_string()))?;
+                self.deliver_queued(registered.email_id).await
+            }
+            auth_events::PASSWORD_RESET_REQUESTED => {
+                let reset: PasswordResetRequestedEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize PasswordResetRequestedEvent".to_string()))?;
+                self.deliver_queued(reset.email_id).await
+            }
+            auth_events::MAGIC_LINK_REQUESTED => {
+                let link: MagicLinkRequestedEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize MagicLinkRequestedEvent".to_string()))?;
+                self.deliver_queued(link.email_id).await
+            }
+            auth_events::USER_INVITED => {
+                let invited: UserInvitedEvent = serde_json::from_value(data.clone())
+                    .map_err(|_| Error::Internal("Failed to deserialize UserInvitedEvent".to_string()))?;
+                self.deliver_queued(invited.email_id).await
+            }
+            _ => {
+                debug!("Email handler ignoring event type: {}", event_type);
+                return Ok(());
+            }
+        };
+
+        match sent {
+            Ok(true) => debug!("Sent email for {} event {}", event_type, event_id),
+            Ok(false) => debug!("No email to send for {} event {}", event_type, event_id),
+            Err(e) => return Err(Error::Internal(format!("Failed to send email for {} event: {}", event_type, e))),
+        }
+        Ok(())
+    }
+
+    /// Send an email the auth service already queued; the worker retries it if this fails
+    async fn deliver_queued(&self, email_id: Option<Uuid>) -> crate::error::Result<bool> {
+        match email_id {
+            Some(id) => Ok(self.mailer.deliver_queued(id).await?.attempted() > 0),
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for EmailNotificationHandler {
+    #[instrument(skip(self, event))]
+    async fn handle(&self, event: &EventContainer) -> Result<()> {
+        let result = match event {
+            EventContainer::Legacy(domain_event) => {
+                self.send_email(&domain_event.id.to_string(), &domain_event.event_type, &domain_event.data).await
+            }
+            EventContainer::Versioned(versioned_event) => {
+                self.send_email(&versioned_event.id.to_string(), &versioned_event.event_type, &versioned_event.data).await
+            }
+        };
+
+        match &result {
+            Ok(_) => {
+                self.processed_count.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.failed_count.fetch_add(1, Ordering::Relaxed);
+                let mut last_error = self.last_error.write().await;
+                *last_error = Some(e.to_string());
+            }
+        }
+
+        result
+    }
+
+    fn event_types(&self) -> Vec<String> {
+        vec![
+            auth_events::USER_REGISTERED.to_string(),
+            auth_events::PASSWORD_RESET_REQUESTED.to_string(),
+            auth_events::MAGIC_LINK_REQUESTED.to_string(),
+            auth_events::USER_INVITED.to_string(),
+        ]
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        HandlerPriority::High // Users are waiting on these emails
+    }
+
+    fn supports_concurrent_processing(&self) -> bool {
+        true
+    }
+
+    fn max_concurrent_events(&self) -> usize {
+        10 // Only renders and writes to the outbox; sending happens in the mailer worker
+    }
+
+    async fn health_check(&self) -> HandlerHealth {
+        let processed = self.processed_count.load(Ordering::Relaxed);
+        let failed = self.failed_count.load(Ordering::Relaxed);
+
+        if failed == 0 {
+            return HandlerHealth::Healthy;
+        }
+
+        let failure_rate = (failed as f64) / ((processed + failed) as f64);
+        if failure_rate > 0.5 {
+            let last_error = self.last_error.read().await;
+            HandlerHealth::Unhealthy(
+                last_error.clone().unwrap_or_else(|| "High failure rate".to_string())
+            )
+        } else {
+            HandlerHealth::Degraded(format!("Failure rate: {:.2}%", failure_rate * 100.0))
+        }
+    }
+}
+
 // ============================================================================
 // HANDLER FACTORY
 // ============================================================================
@@ -513,6 +819,13 @@ impl AuthEventHandlerFactory {
         ]
     }
 
+    /// Create all handlers, including email delivery through `mailer`
+    pub fn create_handlers_with_mailer(mailer: Arc<Mailer>) -> Vec<Arc<dyn EventHandler>> {
+        let mut handlers = Self::create_handlers();
+        handlers.push(Arc::new(EmailNotificationHandler::new(mailer)));
+        handlers
+    }
+
     /// Create a specific handler by name
     pub fn create_handler(name: &str) -> Option<Arc<dyn EventHandler>> {
         match name {
@@ -527,7 +840,7 @@ impl AuthEventHandlerFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use shared::events::{DomainEvent, EventMetadata};
+    use olympus_shared::events::{DomainEvent, EventMetadata};
     use chrono::Utc;
     use uuid::Uuid;
 
@@ -540,6 +853,8 @@ mod tests {
             last_name: Some("Doe".to_string()),
             email_verification_required: true,
             registration_source: "web".to_string(),
+            email_id: None,
+            locale: None,
         }
     }
 
@@ -592,6 +907,48 @@ mod tests {
         assert_eq!(handler.max_concurrent_events(), 10);
     }
 
+    #[tokio::test]
+    async fn test_security_handler_refresh_token_reuse() {
+        let handler = SecurityEventHandler::new();
+        let reuse_event = RefreshTokenReuseDetectedEvent {
+            user_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            family_id: Uuid::new_v4(),
+            token_id: Uuid::new_v4(),
+            device_id: Some("pos-tablet-3".to_string()),
+            ip_address: "10.0.0.12".to_string(),
+            user_agent: "OlympusPOS/2.1".to_string(),
+        };
+        let event_data = serde_json::to_value(&reuse_event).unwrap();
+        let event = create_test_domain_event(auth_events::REFRESH_TOKEN_REUSE_DETECTED, event_data);
+
+        assert!(handler.event_types().contains(&auth_events::REFRESH_TOKEN_REUSE_DETECTED.to_string()));
+        assert!(handler.handle(&event).await.is_ok());
+        assert_eq!(handler.processed_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_security_handler_passkey_sign_count_regressed() {
+        let handler = SecurityEventHandler::new();
+        let passkey_event = PasskeySignCountRegressedEvent {
+            user_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            passkey_id: Uuid::new_v4(),
+            credential_id: "q1w2e3".to_string(),
+            stored_sign_count: 41,
+            received_sign_count: 12,
+            rejected: true,
+            ip_address: "10.0.0.12".to_string(),
+            user_agent: "Mozilla/5.0".to_string(),
+        };
+        let event_data = serde_json::to_value(&passkey_event).unwrap();
+        let event = create_test_domain_event(auth_events::PASSKEY_SIGN_COUNT_REGRESSED, event_data);
+
+        assert!(handler.event_types().contains(&auth_events::PASSKEY_SIGN_COUNT_REGRESSED.to_string()));
+        assert!(handler.handle(&event).await.is_ok());
+        assert_eq!(handler.processed_count.load(Ordering::Relaxed), 1);
+    }
+
     #[tokio::test]
     async fn test_audit_log_handler() {
         let handler = AuthAuditLogHandler::new();
@@ -607,6 +964,57 @@ mod tests {
         assert!(handler.event_types().contains(&"*".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_audit_log_handler_records_impersonation() {
+        let handler = AuthAuditLogHandler::new();
+        let session_id = Uuid::new_v4();
+        let impersonator_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let tenant_id = Uuid::new_v4();
+
+        let started = ImpersonationStartedEvent {
+            session_id,
+            impersonator_id,
+            impersonator_email: "support@olympuscloud.io".to_string(),
+            user_id,
+            tenant_id,
+            reason: "TICKET-4821 menu sync".to_string(),
+            read_only: true,
+            expires_at: Utc::now(),
+            ip_address: "10.0.0.5".to_string(),
+        };
+        let request = ImpersonatedRequestEvent {
+            session_id,
+            impersonator_id,
+            user_id,
+            tenant_id,
+            method: "GET".to_string(),
+            path: "/api/v1/orders".to_string(),
+            status: 200,
+        };
+        let ended = ImpersonationEndedEvent {
+            session_id,
+            impersonator_id,
+            user_id,
+            tenant_id,
+            ended_at: Utc::now(),
+        };
+
+        for (event_type, data) in [
+            (auth_events::IMPERSONATION_STARTED, serde_json::to_value(&started).unwrap()),
+            (auth_events::IMPERSONATED_REQUEST, serde_json::to_value(&request).unwrap()),
+            (auth_events::IMPERSONATION_ENDED, serde_json::to_value(&ended).unwrap()),
+        ] {
+            assert!(handler.handle(&create_test_domain_event(event_type, data)).await.is_ok());
+        }
+        assert_eq!(handler.impersonation_count.load(Ordering::Relaxed), 3);
+
+        // Malformed impersonation records are surfaced rather than silently dropped
+        let malformed = create_test_domain_event(auth_events::IMPERSONATION_STARTED, serde_json::json!({}));
+        assert!(handler.handle(&malformed).await.is_err());
+        assert_eq!(handler.processed_count.load(Ordering::Relaxed), 3);
+    }
+
     #[test]
     fn test_handler_factory() {
         let handlers = AuthEventHandlerFactory::create_handlers();
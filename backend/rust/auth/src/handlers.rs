@@ -1,26 +1,39 @@
 use axum::{
-    extract::Extension,
-    http::{StatusCode, HeaderMap},
+    body::Bytes,
+    extract::{Extension, Form, Path, Query},
+    http::{header, StatusCode, HeaderMap, Method},
     response::IntoResponse,
     Json,
 };
 use axum_extra::{
-    headers::{authorization::Bearer, Authorization},
+    headers::{authorization::{Basic, Bearer}, Authorization},
     TypedHeader,
 };
 use std::sync::Arc;
+use uuid::Uuid;
 use validator::Validate;
+use olympus_shared::policy::RequestContext;
 use olympus_shared::types::ApiResponse;
+use crate::error::AuthError;
 use crate::models::*;
-use crate::services::AuthService;
+use crate::services::{scim, terminal, AuthService};
 
-fn extract_ip_address(headers: &HeaderMap) -> String {
-    headers
-        .get("x-forwarded-for")
-        .or_else(|| headers.get("x-real-ip"))
-        .and_then(|hv| hv.to_str().ok())
-        .unwrap_or("unknown")
-        .to_string()
+/// Client address from the connection, or the hop the trusted proxies recorded; never a
+/// header the client could set itself
+fn client_ip_address(context: &RequestContext) -> String {
+    context
+        .ip_address
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Error body that also carries structured details, such as password policy violations
+fn error_with_details<T: serde::Serialize>(e: &AuthError) -> ApiResponse<T> {
+    let mut response = ApiResponse::error(format!("{:?}", e), e.to_string());
+    if let Some(error) = response.error.as_mut() {
+        error.details = e.details();
+    }
+    response
 }
 
 fn extract_user_agent(headers: &HeaderMap) -> String {
@@ -34,6 +47,7 @@ fn extract_user_agent(headers: &HeaderMap) -> String {
 pub async fn login(
     Extension(auth_service): Extension<Arc<AuthService>>,
     headers: HeaderMap,
+    context: RequestContext,
     Json(request): Json<LoginRequest>,
 ) -> impl IntoResponse {
     if let Err(e) = request.validate() {
@@ -46,7 +60,7 @@ pub async fn login(
         );
     }
 
-    let ip_address = extract_ip_address(&headers);
+    let ip_address = client_ip_address(&context);
     let user_agent = extract_user_agent(&headers);
 
     match auth_service.login(request, ip_address, user_agent).await {
@@ -61,9 +75,11 @@ pub async fn login(
     }
 }
 
-pub async fn register(
+pub async fn verify_mfa(
     Extension(auth_service): Extension<Arc<AuthService>>,
-    Json(request): Json<RegisterRequest>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<VerifyMfaRequest>,
 ) -> impl IntoResponse {
     if let Err(e) = request.validate() {
         return (
@@ -75,8 +91,11 @@ pub async fn register(
         );
     }
 
-    match auth_service.register(request).await {
-        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.verify_mfa_login(request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
         Err(e) => (
             StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
             Json(ApiResponse::error(
@@ -87,12 +106,36 @@ pub async fn register(
     }
 }
 
+pub async fn register(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Json(request): Json<RegisterRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.register(request).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(error_with_details(&e)),
+        ),
+    }
+}
+
 pub async fn refresh_token(
     Extension(auth_service): Extension<Arc<AuthService>>,
     headers: HeaderMap,
+    context: RequestContext,
     Json(request): Json<RefreshTokenRequest>,
 ) -> impl IntoResponse {
-    let ip_address = extract_ip_address(&headers);
+    let ip_address = client_ip_address(&context);
     let user_agent = extract_user_agent(&headers);
 
     match auth_service.refresh_token(&request.refresh_token, ip_address, user_agent).await {
@@ -109,28 +152,12 @@ pub async fn refresh_token(
 
 pub async fn get_current_user(
     Extension(auth_service): Extension<Arc<AuthService>>,
-    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    let token = auth.token();
-
-    match auth_service.verify_token(token).await {
-        Ok(claims) => {
-            match auth_service.get_user(claims.sub).await {
-                Ok((user, tenant)) => {
-                    let response = user.to_response(&tenant);
-                    (StatusCode::OK, Json(ApiResponse::success(response)))
-                }
-                Err(e) => (
-                    StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-                    Json(ApiResponse::error(
-                        format!("{:?}", e),
-                        e.to_string(),
-                    )),
-                ),
-            }
-        }
+    match auth_service.get_current_user(&claims).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
         Err(e) => (
-            StatusCode::UNAUTHORIZED,
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
             Json(ApiResponse::error(
                 format!("{:?}", e),
                 e.to_string(),
@@ -141,30 +168,27 @@ pub async fn get_current_user(
 
 pub async fn logout(
     Extension(auth_service): Extension<Arc<AuthService>>,
-    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    let token = auth.token();
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
 
-    match auth_service.verify_token(token).await {
-        Ok(claims) => {
-            match auth_service.logout(claims.sub).await {
-                Ok(_) => (
-                    StatusCode::OK,
-                    Json(ApiResponse::success(serde_json::json!({
-                        "message": "Successfully logged out"
-                    }))),
-                ),
-                Err(e) => (
-                    StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-                    Json(ApiResponse::error(
-                        format!("{:?}", e),
-                        e.to_string(),
-                    )),
-                ),
-            }
-        }
+    match auth_service.logout(claims.sub, claims.session_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Successfully logged out"
+            }))),
+        ),
         Err(e) => (
-            StatusCode::UNAUTHORIZED,
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
             Json(ApiResponse::error(
                 format!("{:?}", e),
                 e.to_string(),
@@ -204,9 +228,11 @@ pub async fn forgot_password(
     }
 }
 
-pub async fn reset_password(
+pub async fn request_magic_link(
     Extension(auth_service): Extension<Arc<AuthService>>,
-    Json(request): Json<ResetPasswordRequest>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<MagicLinkRequest>,
 ) -> impl IntoResponse {
     if let Err(e) = request.validate() {
         return (
@@ -218,11 +244,14 @@ pub async fn reset_password(
         );
     }
 
-    match auth_service.reset_password(request).await {
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.request_magic_link(request, ip_address, user_agent).await {
         Ok(_) => (
             StatusCode::OK,
             Json(ApiResponse::success(serde_json::json!({
-                "message": "Password has been reset successfully"
+                "message": "If the email exists, a login link has been sent"
             }))),
         ),
         Err(e) => (
@@ -235,6 +264,155 @@ pub async fn reset_password(
     }
 }
 
+pub async fn login_with_magic_link(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<MagicLinkLoginRequest>,
+) -> impl IntoResponse {
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.login_with_magic_link(request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn start_sso_login(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Json(request): Json<SsoStartRequest>,
+) -> impl IntoResponse {
+    match auth_service.start_sso_login(request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn complete_sso_login(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<SsoCallbackRequest>,
+) -> impl IntoResponse {
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.complete_sso_login(request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn get_sso_config(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.get_sso_config(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn update_sso_config(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<UpdateSsoConfigRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.update_sso_config(claims.sub, request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn reset_password(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.reset_password(request).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Password has been reset successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(error_with_details(&e)),
+        ),
+    }
+}
+
 pub async fn change_password(
     Extension(auth_service): Extension<Arc<AuthService>>,
     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
@@ -252,21 +430,25 @@ pub async fn change_password(
         );
     }
 
-    // Verify the token and get user ID
-    let claims = match auth_service.verify_token(token).await {
-        Ok(claims) => claims,
-        Err(e) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ApiResponse::error(
-                    format!("{:?}", e),
-                    e.to_string(),
-                )),
-            )
-        }
+    // Verify the token and get user ID; a user who must rotate their password only holds a
+    // token that is accepted here
+    let user_id = match auth_service.verify_account_token(token).await {
+        Ok(claims) => claims.sub,
+        Err(e) => match auth_service.verify_password_change_token(token) {
+            Ok(user_id) => user_id,
+            Err(_) => {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::error(
+                        format!("{:?}", e),
+                        e.to_string(),
+                    )),
+                )
+            }
+        },
     };
 
-    match auth_service.change_password(claims.sub, request).await {
+    match auth_service.change_password(user_id, request).await {
         Ok(_) => (
             StatusCode::OK,
             Json(ApiResponse::success(serde_json::json!({
@@ -275,10 +457,7 @@ pub async fn change_password(
         ),
         Err(e) => (
             StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(ApiResponse::error(
-                format!("{:?}", e),
-                e.to_string(),
-            )),
+            Json(error_with_details(&e)),
         ),
     }
 }
@@ -306,24 +485,8 @@ pub async fn verify_email(
 
 pub async fn get_sessions(
     Extension(auth_service): Extension<Arc<AuthService>>,
-    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    let token = auth.token();
-
-    // Verify the token and get user ID
-    let claims = match auth_service.verify_token(token).await {
-        Ok(claims) => claims,
-        Err(e) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ApiResponse::error(
-                    format!("{:?}", e),
-                    e.to_string(),
-                )),
-            )
-        }
-    };
-
     match auth_service.get_active_sessions(claims.sub).await {
         Ok(sessions) => {
             let session_summaries: Vec<SessionSummary> = sessions
@@ -336,6 +499,7 @@ pub async fn get_sessions(
                     created_at: session.created_at,
                     last_used_at: session.last_used_at,
                     is_current: session.is_current,
+                    impersonation: session.impersonation,
                 })
                 .collect();
 
@@ -364,7 +528,7 @@ pub async fn revoke_session(
     let token = auth.token();
 
     // Verify the token and get user ID
-    let claims = match auth_service.verify_token(token).await {
+    let claims = match auth_service.verify_account_token(token).await {
         Ok(claims) => claims,
         Err(e) => {
             return (
@@ -401,7 +565,7 @@ pub async fn revoke_all_other_sessions(
     let token = auth.token();
 
     // Verify the token and get user ID
-    let claims = match auth_service.verify_token(token).await {
+    let claims = match auth_service.verify_account_token(token).await {
         Ok(claims) => claims,
         Err(e) => {
             return (
@@ -433,6 +597,1631 @@ pub async fn revoke_all_other_sessions(
     }
 }
 
+pub async fn enroll_totp(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.begin_totp_enrollment(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn activate_totp(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<ActivateMfaRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.activate_totp(claims.sub, request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn disable_mfa(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<DisableMfaRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.disable_mfa(claims.sub, request).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Multi-factor authentication disabled"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn list_passkeys(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.list_passkeys(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn start_passkey_registration(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.begin_passkey_registration(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn finish_passkey_registration(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<FinishPasskeyRegistrationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.finish_passkey_registration(claims.sub, request).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn delete_passkey(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(passkey_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.delete_passkey(claims.sub, passkey_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Passkey removed successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn start_passkey_login(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Json(request): Json<PasskeyLoginStartRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.begin_passkey_login(request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn passkey_login(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<PasskeyLoginRequest>,
+) -> impl IntoResponse {
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.finish_passkey_login(request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn start_passkey_mfa(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Json(request): Json<PasskeyMfaStartRequest>,
+) -> impl IntoResponse {
+    match auth_service.begin_passkey_mfa(request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn verify_passkey_mfa(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<PasskeyMfaRequest>,
+) -> impl IntoResponse {
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.verify_passkey_mfa(request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn create_api_key(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.create_api_key(claims.sub, request).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn list_api_keys(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.list_api_keys(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn get_api_key(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(key_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.get_api_key(claims.sub, key_id).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn update_api_key(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(key_id): Path<Uuid>,
+    Json(request): Json<UpdateApiKeyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.update_api_key(claims.sub, key_id, request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn revoke_api_key(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(key_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.revoke_api_key(claims.sub, key_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "API key revoked successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn require_password_change(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.require_password_change(claims.sub, user_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "User must change their password at next login"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn start_impersonation(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<StartImpersonationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    // Support staff start impersonating from their own account, never from a delegated token
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.start_impersonation(&claims, request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn end_impersonation(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> impl IntoResponse {
+    let token = auth.token();
+
+    let claims = match auth_service.verify_token(token).await {
+        Ok(claims) => claims,
+        Err(e) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error(
+                    format!("{:?}", e),
+                    e.to_string(),
+                )),
+            )
+        }
+    };
+
+    match auth_service.end_impersonation(&claims).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Impersonation session ended"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn register_oauth_client(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<RegisterOAuthClientRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.register_oauth_client(claims.sub, request).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn list_oauth_clients(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.list_oauth_clients(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn revoke_oauth_client(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(client_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.revoke_oauth_client(claims.sub, client_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "OAuth client revoked successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn register_terminal(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<RegisterTerminalRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.register_terminal(claims.sub, request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn list_terminals(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.list_terminals(claims.sub).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn revoke_terminal(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(terminal_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.revoke_terminal(claims.sub, terminal_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Terminal revoked successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn set_pin(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<SetPinRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.set_pin(claims.sub, request).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "PIN updated successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+/// Switch users on a registered POS terminal. The terminal authenticates with its
+/// session token and device ID headers; the user authenticates with their PIN.
+pub async fn switch_user_with_pin(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<PinSwitchRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    let header_value = |name: &str| headers.get(name).and_then(|hv| hv.to_str().ok()).map(str::to_string);
+    let (Some(terminal_token), Some(device_id)) = (
+        header_value(terminal::TERMINAL_TOKEN_HEADER),
+        header_value(terminal::DEVICE_ID_HEADER),
+    ) else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error(
+                "TERMINAL_REQUIRED".to_string(),
+                "Terminal token and device ID headers are required".to_string(),
+            )),
+        );
+    };
+
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service
+        .switch_user_with_pin(&terminal_token, &device_id, request, ip_address, user_agent)
+        .await
+    {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn create_invitation(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateInvitationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.create_invitation(&claims, request).await {
+        Ok(response) => (StatusCode::CREATED, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn list_invitations(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.list_invitations(&claims).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn revoke_invitation(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(invitation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.revoke_invitation(&claims, invitation_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Invitation revoked successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+/// Accept an invitation. With a bearer token the caller joins with their existing
+/// account; without one a new account is created from the request body.
+pub async fn accept_invitation(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    auth: Option<TypedHeader<Authorization<Bearer>>>,
+    Json(request): Json<AcceptInvitationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    let identity = match auth {
+        Some(TypedHeader(auth)) => match auth_service.verify_account_token(auth.token()).await {
+            Ok(claims) => Some(claims.sub),
+            Err(e) => {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::error(
+                        format!("{:?}", e),
+                        e.to_string(),
+                    )),
+                )
+            }
+        },
+        None => None,
+    };
+
+    match auth_service.accept_invitation(identity, request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn decline_invitation(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Json(request): Json<DeclineInvitationRequest>,
+) -> impl IntoResponse {
+    match auth_service.decline_invitation(request).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Invitation declined"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+/// Re-issue tokens for another tenant the caller is a member of
+pub async fn switch_tenant(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Json(request): Json<SwitchTenantRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.switch_tenant(&claims, request, ip_address, user_agent).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+/// Consent screen data for an authorization request made by a partner application
+pub async fn oauth_authorize_prompt(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Query(request): Query<AuthorizeRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.oauth_consent_prompt(&claims, request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn oauth_authorize(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<ConsentDecisionRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.oauth_authorize(&claims, request).await {
+        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+/// Client credentials sent with HTTP Basic authentication
+fn basic_credentials(basic: Option<TypedHeader<Authorization<Basic>>>) -> Option<(String, String)> {
+    basic.map(|TypedHeader(auth)| (auth.username().to_string(), auth.password().to_string()))
+}
+
+/// OAuth endpoints answer with RFC 6749 error bodies rather than `ApiResponse`
+fn oauth_error(e: &AuthError) -> (StatusCode, [(header::HeaderName, &'static str); 1], Json<serde_json::Value>) {
+    let code = e.oauth_error_code();
+    let status = match code {
+        "invalid_client" => StatusCode::UNAUTHORIZED,
+        "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+        _ => StatusCode::BAD_REQUEST,
+    };
+
+    (
+        status,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(serde_json::json!({
+            "error": code,
+            "error_description": e.to_string(),
+        })),
+    )
+}
+
+pub async fn oauth_token(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    basic: Option<TypedHeader<Authorization<Basic>>>,
+    headers: HeaderMap,
+    context: RequestContext,
+    Form(request): Form<OAuthTokenRequest>,
+) -> impl IntoResponse {
+    let ip_address = client_ip_address(&context);
+    let user_agent = extract_user_agent(&headers);
+
+    match auth_service.oauth_token(request, basic_credentials(basic), ip_address, user_agent).await {
+        Ok(response) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::to_value(response).unwrap_or_default()),
+        ),
+        Err(e) => oauth_error(&e),
+    }
+}
+
+/// RFC 7662 token introspection for partner resource servers
+pub async fn oauth_introspect(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    basic: Option<TypedHeader<Authorization<Basic>>>,
+    Form(request): Form<OAuthTokenActionRequest>,
+) -> impl IntoResponse {
+    let client = match auth_service
+        .authenticate_oauth_client(&request.client_id, &request.client_secret, basic_credentials(basic))
+        .await
+    {
+        Ok(client) => client,
+        Err(e) => return oauth_error(&e),
+    };
+
+    match auth_service.introspect_token(&client, &request).await {
+        Ok(response) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::to_value(response).unwrap_or_default()),
+        ),
+        Err(e) => oauth_error(&e),
+    }
+}
+
+/// RFC 7009 token revocation; succeeds for unknown tokens too
+pub async fn oauth_revoke(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    basic: Option<TypedHeader<Authorization<Basic>>>,
+    Form(request): Form<OAuthTokenActionRequest>,
+) -> impl IntoResponse {
+    let client = match auth_service
+        .authenticate_oauth_client(&request.client_id, &request.client_secret, basic_credentials(basic))
+        .await
+    {
+        Ok(client) => client,
+        Err(e) => return oauth_error(&e),
+    };
+
+    match auth_service.revoke_oauth_token(&client, &request).await {
+        Ok(_) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::json!({})),
+        ),
+        Err(e) => oauth_error(&e),
+    }
+}
+
+/// Public keys for verifying access tokens; consumed by services in other languages
+pub async fn jwks(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> impl IntoResponse {
+    match auth_service.jwks() {
+        Ok(jwks) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "public, max-age=300")],
+            Json(serde_json::to_value(jwks).unwrap_or_default()),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+pub async fn list_signing_keys(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.list_signing_keys(claims.sub).await {
+        Ok(keys) => (StatusCode::OK, Json(ApiResponse::success(keys))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn stage_signing_key(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<StageSigningKeyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(
+                "VALIDATION_ERROR".to_string(),
+                e.to_string(),
+            )),
+        );
+    }
+
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.stage_signing_key(claims.sub, request).await {
+        Ok(key) => (StatusCode::CREATED, Json(ApiResponse::success(key))),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn promote_signing_key(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+    Path(kid): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.promote_signing_key(claims.sub, &kid).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "message": "Signing key promoted successfully"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+pub async fn retire_signing_keys(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    if let Err(e) = auth_service.ensure_account_claims(&claims) {
+        return (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        );
+    }
+
+    match auth_service.retire_signing_keys(claims.sub).await {
+        Ok(retired) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "retired": retired
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(ApiResponse::error(
+                format!("{:?}", e),
+                e.to_string(),
+            )),
+        ),
+    }
+}
+
+type ScimResponse = (StatusCode, [(header::HeaderName, &'static str); 1], Json<serde_json::Value>);
+
+fn scim_response<T: serde::Serialize>(status: StatusCode, body: T) -> ScimResponse {
+    (
+        status,
+        [(header::CONTENT_TYPE, scim::CONTENT_TYPE)],
+        Json(serde_json::to_value(body).unwrap_or_default()),
+    )
+}
+
+/// SCIM endpoints answer with RFC 7644 error bodies rather than `ApiResponse`
+fn scim_error(e: &AuthError) -> ScimResponse {
+    let status = StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    scim_response(status, scim::error_body(e))
+}
+
+/// SCIM clients send `application/scim+json`, which the `Json` extractor refuses
+fn scim_body<T: serde::de::DeserializeOwned>(body: &Bytes) -> Result<T, ScimResponse> {
+    serde_json::from_slice(body).map_err(|e| scim_error(&AuthError::Validation(e.to_string())))
+}
+
+async fn scim_client(auth_service: &AuthService, auth: &Authorization<Bearer>, method: Method) -> Result<Claims, ScimResponse> {
+    auth_service
+        .authenticate_scim(auth.token(), &method)
+        .await
+        .map_err(|e| match e {
+            AuthError::InsufficientPermissions(_) => scim_error(&e),
+            e => scim_response(StatusCode::UNAUTHORIZED, scim::error_body(&e)),
+        })
+}
+
+pub async fn scim_service_provider_config() -> impl IntoResponse {
+    scim_response(StatusCode::OK, scim::service_provider_config())
+}
+
+pub async fn scim_list_users(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Query(query): Query<ScimListQuery>,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::GET).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_list_users(claims.tenant_id, query).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_get_user(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::GET).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_get_user(claims.tenant_id, &id).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_create_user(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::POST).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let resource = match scim_body(&body) {
+        Ok(resource) => resource,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_create_user(claims.tenant_id, resource).await {
+        Ok(response) => scim_response(StatusCode::CREATED, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_replace_user(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::PUT).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let resource = match scim_body(&body) {
+        Ok(resource) => resource,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_replace_user(claims.tenant_id, &id, resource).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_patch_user(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::PATCH).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let patch = match scim_body(&body) {
+        Ok(patch) => patch,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_patch_user(claims.tenant_id, &id, patch).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_delete_user(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ScimResponse> {
+    let claims = scim_client(&auth_service, &auth, Method::DELETE).await?;
+
+    auth_service
+        .scim_delete_user(claims.tenant_id, &id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| scim_error(&e))
+}
+
+pub async fn scim_list_groups(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Query(query): Query<ScimListQuery>,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::GET).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_list_groups(claims.tenant_id, query).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_get_group(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::GET).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_get_group(claims.tenant_id, &id).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_create_group(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::POST).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let resource = match scim_body(&body) {
+        Ok(resource) => resource,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_create_group(claims.tenant_id, claims.sub, resource).await {
+        Ok(response) => scim_response(StatusCode::CREATED, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_replace_group(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::PUT).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let resource = match scim_body(&body) {
+        Ok(resource) => resource,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_replace_group(claims.tenant_id, &id, claims.sub, resource).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_patch_group(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::PATCH).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let patch = match scim_body(&body) {
+        Ok(patch) => patch,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_patch_group(claims.tenant_id, &id, claims.sub, patch).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
+pub async fn scim_delete_group(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ScimResponse> {
+    let claims = scim_client(&auth_service, &auth, Method::DELETE).await?;
+
+    auth_service
+        .scim_delete_group(claims.tenant_id, &id, claims.sub)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| scim_error(&e))
+}
+
+/// Individual operations carry their own status; the bulk request itself succeeds
+pub async fn scim_bulk(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let claims = match scim_client(&auth_service, &auth, Method::POST).await {
+        Ok(claims) => claims,
+        Err(response) => return response,
+    };
+    let request = match scim_body(&body) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+
+    match auth_service.scim_bulk(claims.tenant_id, claims.sub, request).await {
+        Ok(response) => scim_response(StatusCode::OK, response),
+        Err(e) => scim_error(&e),
+    }
+}
+
 pub async fn health_check() -> impl IntoResponse {
     (
         StatusCode::OK,
@@ -5,17 +5,19 @@ pub mod handlers;
 pub mod middleware;
 pub mod models;
 pub mod services;
+pub mod startup;
 
 // Re-export event handlers for easy access
 pub use event_handlers::{
     UserAuthenticationHandler,
     SecurityEventHandler,
     AuthAuditLogHandler,
+    EmailNotificationHandler,
     AuthEventHandlerFactory,
 };
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -23,23 +25,135 @@ use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 
+use olympus_shared::authorization::{Authenticate, Authenticator};
 use crate::services::AuthService;
 
 pub fn create_router(auth_service: Arc<AuthService>) -> Router {
+    router(auth_service.clone(), auth_service)
+}
+
+fn router(auth_service: Arc<AuthService>, authenticator: Arc<dyn Authenticator>) -> Router {
+    // Protected routes; the authenticator enforces read-only impersonation and audits it
+    let protected = Router::new()
+        .route("/auth/me", get(handlers::get_current_user))
+        .route("/auth/logout", post(handlers::logout))
+        .route("/auth/sessions", get(handlers::get_sessions))
+        .route(
+            "/auth/users/:user_id/require-password-change",
+            post(handlers::require_password_change),
+        )
+        .route("/auth/impersonation", post(handlers::start_impersonation))
+        .route("/auth/mfa/totp/enroll", post(handlers::enroll_totp))
+        .route("/auth/mfa/totp/activate", post(handlers::activate_totp))
+        .route("/auth/mfa/disable", post(handlers::disable_mfa))
+        .route("/auth/passkeys", get(handlers::list_passkeys))
+        .route("/auth/passkeys/register/start", post(handlers::start_passkey_registration))
+        .route("/auth/passkeys/register", post(handlers::finish_passkey_registration))
+        .route("/auth/passkeys/:passkey_id", delete(handlers::delete_passkey))
+        .route(
+            "/auth/api-keys",
+            get(handlers::list_api_keys).post(handlers::create_api_key),
+        )
+        .route(
+            "/auth/api-keys/:key_id",
+            get(handlers::get_api_key)
+                .patch(handlers::update_api_key)
+                .delete(handlers::revoke_api_key),
+        )
+        .route(
+            "/auth/signing-keys",
+            get(handlers::list_signing_keys).post(handlers::stage_signing_key),
+        )
+        .route("/auth/signing-keys/retire", post(handlers::retire_signing_keys))
+        .route("/auth/signing-keys/:kid/promote", post(handlers::promote_signing_key))
+        .route(
+            "/auth/oauth/clients",
+            get(handlers::list_oauth_clients).post(handlers::register_oauth_client),
+        )
+        .route("/auth/oauth/clients/:client_id", delete(handlers::revoke_oauth_client))
+        .route(
+            "/auth/terminals",
+            get(handlers::list_terminals).post(handlers::register_terminal),
+        )
+        .route("/auth/terminals/:terminal_id", delete(handlers::revoke_terminal))
+        .route("/auth/pin", put(handlers::set_pin))
+        .route(
+            "/auth/invitations",
+            get(handlers::list_invitations).post(handlers::create_invitation),
+        )
+        .route("/auth/invitations/:invitation_id", delete(handlers::revoke_invitation))
+        .route("/auth/tenants/switch", post(handlers::switch_tenant))
+        .route(
+            "/auth/sso/config",
+            get(handlers::get_sso_config).put(handlers::update_sso_config),
+        )
+        .route(
+            "/oauth/authorize",
+            get(handlers::oauth_authorize_prompt).post(handlers::oauth_authorize),
+        )
+        .layer(Authenticate(authenticator));
+
     Router::new()
         // Public routes
         .route("/auth/login", post(handlers::login))
+        .route("/auth/mfa/verify", post(handlers::verify_mfa))
+        .route("/auth/mfa/passkey/start", post(handlers::start_passkey_mfa))
+        .route("/auth/mfa/passkey/verify", post(handlers::verify_passkey_mfa))
         .route("/auth/register", post(handlers::register))
         .route("/auth/refresh", post(handlers::refresh_token))
         .route("/auth/forgot-password", post(handlers::forgot_password))
         .route("/auth/reset-password", post(handlers::reset_password))
         .route("/auth/verify-email", post(handlers::verify_email))
-        // Protected routes
-        .route("/auth/me", get(handlers::get_current_user))
-        .route("/auth/logout", post(handlers::logout))
+        .route("/auth/magic-link", post(handlers::request_magic_link))
+        .route("/auth/magic-link/login", post(handlers::login_with_magic_link))
+        .route("/auth/sso/start", post(handlers::start_sso_login))
+        .route("/auth/sso/callback", post(handlers::complete_sso_login))
+        .route("/auth/passkeys/login/start", post(handlers::start_passkey_login))
+        .route("/auth/passkeys/login", post(handlers::passkey_login))
+        // Authenticated by the handler, so a read-only impersonation token can still end itself
+        .route("/auth/impersonation/end", post(handlers::end_impersonation))
+        // Authenticated by the handler, which also accepts the restricted token issued while a
+        // password change is required
         .route("/auth/change-password", post(handlers::change_password))
+        // Authenticated by the terminal session token and the staff member's PIN
+        .route("/auth/terminals/switch", post(handlers::switch_user_with_pin))
+        // Authenticated by the invitation token; accepting also takes an optional bearer token
+        .route("/auth/invitations/accept", post(handlers::accept_invitation))
+        .route("/auth/invitations/decline", post(handlers::decline_invitation))
+        // OAuth client endpoints, authenticated with client credentials
+        .route("/oauth/token", post(handlers::oauth_token))
+        .route("/oauth/introspect", post(handlers::oauth_introspect))
+        .route("/oauth/revoke", post(handlers::oauth_revoke))
+        // SCIM 2.0 provisioning, authenticated with a tenant API key
+        .route("/scim/v2/ServiceProviderConfig", get(handlers::scim_service_provider_config))
+        .route(
+            "/scim/v2/Users",
+            get(handlers::scim_list_users).post(handlers::scim_create_user),
+        )
+        .route(
+            "/scim/v2/Users/:id",
+            get(handlers::scim_get_user)
+                .put(handlers::scim_replace_user)
+                .patch(handlers::scim_patch_user)
+                .delete(handlers::scim_delete_user),
+        )
+        .route(
+            "/scim/v2/Groups",
+            get(handlers::scim_list_groups).post(handlers::scim_create_group),
+        )
+        .route(
+            "/scim/v2/Groups/:id",
+            get(handlers::scim_get_group)
+                .put(handlers::scim_replace_group)
+                .patch(handlers::scim_patch_group)
+                .delete(handlers::scim_delete_group),
+        )
+        .route("/scim/v2/Bulk", post(handlers::scim_bulk))
+        // Key discovery
+        .route("/.well-known/jwks.json", get(handlers::jwks))
         // Health check
         .route("/health", get(handlers::health_check))
+        .merge(protected)
         // Middleware
         .layer(
             ServiceBuilder::new()
@@ -52,9 +166,119 @@ pub fn create_router(auth_service: Arc<AuthService>) -> Router {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::{Extensions, Method, StatusCode};
+    use axum::response::Response;
+    use olympus_shared::integration::go_gateway::AuthContext;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use crate::models::Claims;
+    use crate::services::impersonation::InMemoryImpersonationAuditStore;
+    use crate::services::jwt::{ActorClaims, JwtService, TokenSubject};
+
+    const SECRET: &str = "router-test-secret-that-is-at-least-32-characters";
 
     #[test]
     fn test_module_compilation() {
         assert!(true);
     }
+
+    /// Delegates to the auth service and records every request it audits on completion
+    struct AuditSpy {
+        auth_service: Arc<AuthService>,
+        audited: std::sync::Mutex<Vec<(Method, String, Uuid, StatusCode)>>,
+    }
+
+    #[async_trait]
+    impl Authenticator for AuditSpy {
+        async fn authenticate(&self, request: &mut Request) -> Result<AuthContext, Response> {
+            self.auth_service.authenticate(request).await
+        }
+
+        async fn completed(&self, extensions: &Extensions, method: &Method, path: &str, status: StatusCode) {
+            if let Some(impersonation) = extensions.get::<Claims>().and_then(|c| c.impersonation.as_ref()) {
+                self.audited.lock().unwrap().push((method.clone(), path.to_string(), impersonation.impersonator_id, status));
+            }
+            self.auth_service.completed(extensions, method, path, status).await;
+        }
+    }
+
+    fn read_only_impersonation_token(impersonator_id: Uuid) -> String {
+        // Same issuer, audience and secret as `AuthService::new`
+        let jwt = JwtService::new(SECRET, "olympus-cloud".to_string(), "olympus-users".to_string(), 3600, 2592000).unwrap();
+        let (token, _) = jwt
+            .generate_impersonation_token(
+                TokenSubject {
+                    user_id: Uuid::new_v4(),
+                    tenant_id: Uuid::new_v4(),
+                    email: "guest@example.com".to_string(),
+                    roles: vec!["user".to_string()],
+                    permissions: vec![],
+                },
+                ActorClaims {
+                    sub: impersonator_id.to_string(),
+                    email: "support@olympuscloud.io".to_string(),
+                    read_only: true,
+                    reason: "TICKET-42".to_string(),
+                },
+                900,
+            )
+            .unwrap();
+        token
+    }
+
+    #[tokio::test]
+    async fn test_read_only_impersonation_rejects_writes_and_audits_reads() {
+        let db = Arc::new(sqlx::PgPool::connect_lazy("postgres://localhost/olympus").unwrap());
+        let audit = Arc::new(InMemoryImpersonationAuditStore::new());
+        let auth_service = Arc::new(AuthService::new(db, SECRET, None).unwrap().with_impersonation_audit_store(audit.clone()));
+        let spy = Arc::new(AuditSpy { auth_service: auth_service.clone(), audited: Default::default() });
+        let app = router(auth_service, spy.clone());
+
+        let impersonator_id = Uuid::new_v4();
+        let token = read_only_impersonation_token(impersonator_id);
+        let request = |method: Method, uri: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // Refused by the authenticator before the handler runs, so there is no JSON body
+        let response = app.clone().oneshot(request(Method::POST, "/auth/logout")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.is_empty());
+
+        // Reads reach the handler, which refuses account-level reads from the layer's claims,
+        // and are audited with its status
+        let response = app.oneshot(request(Method::GET, "/auth/signing-keys")).await.unwrap();
+        let status = response.status();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(!body.is_empty());
+
+        assert_eq!(
+            *spy.audited.lock().unwrap(),
+            vec![(Method::GET, "/auth/signing-keys".to_string(), impersonator_id, status)]
+        );
+
+        // Both the refused write and the read are kept in the audit trail
+        let recorded: Vec<_> = audit
+            .requests()
+            .into_iter()
+            .map(|r| (r.method, r.path, r.impersonator_id, r.status))
+            .collect();
+        assert_eq!(
+            recorded,
+            vec![
+                ("POST".to_string(), "/auth/logout".to_string(), impersonator_id, 403),
+                ("GET".to_string(), "/auth/signing-keys".to_string(), impersonator_id, status.as_u16()),
+            ]
+        );
+    }
 }
\ No newline at end of file
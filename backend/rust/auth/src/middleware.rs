@@ -1,38 +1,85 @@
+use async_trait::async_trait;
 use axum::{
-    extract::Extension,
-    http::{Request, StatusCode, HeaderMap},
-    middleware::Next,
-    response::Response,
+    extract::Request,
+    http::{Extensions, HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
 };
-use std::sync::Arc;
+use olympus_shared::authorization::Authenticator;
+use olympus_shared::integration::go_gateway::AuthContext;
+use crate::models::Claims;
 use crate::services::AuthService;
 
-pub async fn auth_middleware(
-    Extension(auth_service): Extension<Arc<AuthService>>,
-    headers: HeaderMap,
-    request: Request<axum::body::Body>,
-    next: Next,
-) -> Result<Response, StatusCode> {
+/// Credentials presented on an incoming request
+enum Credentials<'a> {
+    Bearer(&'a str),
+    ApiKey(&'a str),
+}
+
+fn extract_credentials(headers: &HeaderMap) -> Option<Credentials<'_>> {
+    if let Some(key) = headers.get("x-api-key").and_then(|header| header.to_str().ok()) {
+        return Some(Credentials::ApiKey(key.trim()));
+    }
+
     let authorization = headers
         .get("authorization")
-        .and_then(|header| header.to_str().ok())
-        .and_then(|header| {
-            if header.starts_with("Bearer ") {
-                Some(&header[7..])
-            } else {
-                None
-            }
-        });
-
-    let token = match authorization {
-        Some(token) => token,
-        None => return Err(StatusCode::UNAUTHORIZED),
-    };
-
-    match auth_service.verify_token(token).await {
-        Ok(_claims) => {
-            Ok(next.run(request).await)
+        .and_then(|header| header.to_str().ok())?;
+
+    if let Some(token) = authorization.strip_prefix("Bearer ") {
+        Some(Credentials::Bearer(token))
+    } else {
+        authorization.strip_prefix("ApiKey ").map(|key| Credentials::ApiKey(key.trim()))
+    }
+}
+
+/// Authenticate requests with either a JWT or an API key. Mounted with
+/// `olympus_shared::authorization::Authenticate` on the protected auth routes and on the
+/// platform and commerce routers; `Claims` are exposed to handlers next to the `AuthContext`.
+#[async_trait]
+impl Authenticator for AuthService {
+    async fn authenticate(&self, request: &mut Request) -> Result<AuthContext, Response> {
+        let claims = match extract_credentials(request.headers()) {
+            Some(Credentials::Bearer(token)) => self
+                .verify_token(token)
+                .await
+                .map_err(|_| StatusCode::UNAUTHORIZED.into_response())?,
+            Some(Credentials::ApiKey(key)) => self
+                .authenticate_api_key(key, request.method())
+                .await
+                .map_err(|e| {
+                    StatusCode::from_u16(e.status_code())
+                        .unwrap_or(StatusCode::UNAUTHORIZED)
+                        .into_response()
+                })?,
+            None => return Err(StatusCode::UNAUTHORIZED.into_response()),
+        };
+
+        // Impersonation tokens are read-only unless issued with writes allowed
+        let read_only = claims.impersonation.as_ref().is_some_and(|impersonation| impersonation.read_only);
+        if read_only && !is_safe_method(request.method()) {
+            self.record_impersonated_request(
+                &claims,
+                request.method(),
+                request.uri().path(),
+                StatusCode::FORBIDDEN.as_u16(),
+            )
+            .await;
+            return Err(StatusCode::FORBIDDEN.into_response());
+        }
+
+        let auth = claims.auth_context();
+        request.extensions_mut().insert(claims);
+        Ok(auth)
+    }
+
+    async fn completed(&self, extensions: &Extensions, method: &Method, path: &str, status: StatusCode) {
+        // Every request made with an impersonation token is audited
+        if let Some(claims) = extensions.get::<Claims>() {
+            self.record_impersonated_request(claims, method, path, status.as_u16())
+                .await;
         }
-        Err(_) => Err(StatusCode::UNAUTHORIZED),
     }
-}
\ No newline at end of file
+}
+
+fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use validator::Validate;
+use olympus_shared::integration::go_gateway::AuthContext;
+use olympus_shared::models::session::ApiKey;
 
 // Database Models
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -25,6 +27,7 @@ pub struct User {
     pub failed_login_attempts: i32,
     pub locked_until: Option<DateTime<Utc>>,
     pub password_changed_at: Option<DateTime<Utc>>,
+    pub force_password_change: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
@@ -47,6 +50,8 @@ pub struct Tenant {
 pub struct RefreshToken {
     pub id: Uuid,
     pub token_hash: String,
+    /// All tokens rotated from the same login share a family (and session) id
+    pub family_id: Uuid,
     pub user_id: Uuid,
     pub tenant_id: Uuid,
     pub device_id: Option<String>,
@@ -55,6 +60,231 @@ pub struct RefreshToken {
     pub user_agent: String,
     pub expires_at: DateTime<Utc>,
     pub revoked_at: Option<DateTime<Utc>>,
+    /// Set when the token was rotated; presenting it again indicates theft
+    pub replaced_by: Option<Uuid>,
+    /// The OAuth client the token was issued to; `None` for first-party logins
+    pub client_id: Option<Uuid>,
+    /// Scopes granted to the OAuth client; empty for first-party logins
+    pub scopes: Vec<String>,
+    /// GeoIP location the token was issued to; `{}` when unknown
+    pub location: serde_json::Value,
+    /// Risk score (0-100) and signals found when the token was issued
+    pub risk_score: i32,
+    pub risk_signals: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A passwordless login link; only the hash of the emailed token is stored
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MagicLinkToken {
+    pub id: Uuid,
+    pub token_hash: String,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set on first use; a consumed link can never be exchanged again
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A support user acting as a tenant user through a time-limited access token
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ImpersonationSession {
+    pub id: Uuid,
+    pub impersonator_id: Uuid,
+    pub impersonator_email: String,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub reason: String,
+    pub read_only: bool,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// A third-party application registered by a tenant to use the OAuth authorization server
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Public identifier sent by the client on every request
+    pub client_id: String,
+    /// `None` for public clients (e.g. mobile apps), which must rely on PKCE alone
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    /// Upper bound for the scopes any token issued to this client may carry
+    pub allowed_scopes: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub is_active: bool,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A pending authorization code; only its hash is stored and it can be exchanged once
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OAuthAuthorizationCode {
+    pub id: Uuid,
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: String,
+    pub code_challenge_method: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Scopes a user has agreed to grant a client, so the consent screen is only shown for new scopes
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OAuthConsent {
+    pub user_id: Uuid,
+    pub client_id: Uuid,
+    pub scopes: Vec<String>,
+    pub granted_at: DateTime<Utc>,
+}
+
+/// An SSO login in flight; the state parameter is stored hashed and consumed by the callback
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SsoLoginState {
+    pub id: Uuid,
+    pub state_hash: String,
+    pub tenant_id: Uuid,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Links a user to the subject identifier their identity provider knows them by
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub last_login: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The `externalId` a SCIM client assigned to one of our users or groups
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ScimExternalId {
+    pub tenant_id: Uuid,
+    /// `User` or `Group`
+    pub resource_type: String,
+    pub resource_id: Uuid,
+    pub external_id: String,
+}
+
+/// A shared point-of-sale device registered to one location; staff switch onto it with a PIN
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PosTerminal {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub location_id: Uuid,
+    pub name: String,
+    /// The device the terminal session token is bound to
+    pub device_id: String,
+    /// Consecutive wrong PINs entered on this terminal, whoever they were for
+    pub failed_pin_attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub registered_by: Uuid,
+    pub last_switch_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A user's quick-switch PIN; only the Argon2 hash is stored
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserPin {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub pin_hash: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Links an identity (a `User`, whose own `tenant_id` is its home tenant) to another tenant
+/// it was invited to, with the roles and locations it holds there
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TenantMembership {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    /// Locations the member's grants are limited to; empty for the whole tenant
+    pub location_ids: Vec<Uuid>,
+    pub invited_by: Option<Uuid>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An invitation to join a tenant; only the hash of the emailed token is stored
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub token_hash: String,
+    pub roles: Vec<String>,
+    pub location_ids: Vec<Uuid>,
+    pub invited_by: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub declined_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A passkey registered to a user. The public key is the COSE key from the authenticator.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebAuthnCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    /// Base64url, as the browser reports it in `rawId`
+    pub credential_id: String,
+    pub public_key: Vec<u8>,
+    /// COSE algorithm identifier of `public_key`
+    pub algorithm: i32,
+    /// Signature counter from the last assertion; 0 for authenticators without one
+    pub sign_count: i64,
+    pub transports: Vec<String>,
+    pub aaguid: Uuid,
+    pub name: String,
+    /// Whether the passkey may be synced between devices, and whether it has been
+    pub backup_eligible: bool,
+    pub backed_up: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A WebAuthn ceremony in flight; the challenge is stored hashed and consumed when the
+/// browser's response comes back
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebAuthnChallenge {
+    pub id: Uuid,
+    pub challenge_hash: String,
+    pub tenant_id: Uuid,
+    /// Unset for a login with discoverable credentials, where the passkey names the user
+    pub user_id: Option<Uuid>,
+    /// `registration`, `authentication` or `mfa`
+    pub ceremony: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -82,6 +312,9 @@ pub struct RegisterRequest {
     pub last_name: String,
     pub phone: Option<String>,
     pub tenant_slug: String,
+    /// Language for emails sent as a result, e.g. `fr-CA`; defaults to the tenant's
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +324,647 @@ pub struct TokenResponse {
     pub token_type: String,
     pub expires_in: i64,
     pub user: UserResponse,
+    /// The password has expired or an administrator forced a rotation
+    pub password_change_required: bool,
+}
+
+/// Result of the password step of a login: either tokens, or a pending MFA challenge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(Box<TokenResponse>),
+    MfaRequired(MfaChallengeResponse),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub methods: Vec<String>,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct VerifyMfaRequest {
+    pub mfa_token: String,
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ActivateMfaRequest {
+    #[validate(length(min = 6, max = 6))]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaActivatedResponse {
+    pub backup_codes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct DisableMfaRequest {
+    pub password: String,
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+/// `PublicKeyCredentialCreationOptions` for `navigator.credentials.create()`; binary fields
+/// are base64url
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyCreationOptions {
+    pub challenge: String,
+    pub rp: PasskeyRelyingParty,
+    pub user: PasskeyUser,
+    pub pub_key_cred_params: Vec<PasskeyCredentialParameters>,
+    /// Milliseconds
+    pub timeout: u64,
+    pub exclude_credentials: Vec<PasskeyCredentialDescriptor>,
+    pub authenticator_selection: PasskeyAuthenticatorSelection,
+    pub attestation: String,
+}
+
+/// `PublicKeyCredentialRequestOptions` for `navigator.credentials.get()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyRequestOptions {
+    pub challenge: String,
+    pub timeout: u64,
+    pub rp_id: String,
+    /// Empty to let the authenticator offer any discoverable passkey for the relying party
+    pub allow_credentials: Vec<PasskeyCredentialDescriptor>,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyRelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyUser {
+    /// The user handle: the user id's bytes, base64url
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyCredentialParameters {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub alg: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyCredentialDescriptor {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyAuthenticatorSelection {
+    pub resident_key: String,
+    pub require_resident_key: bool,
+    pub user_verification: String,
+}
+
+/// A `PublicKeyCredential` from `navigator.credentials.create()`, as `toJSON()` encodes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationCredential {
+    pub id: String,
+    pub raw_id: String,
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+/// A `PublicKeyCredential` from `navigator.credentials.get()`, as `toJSON()` encodes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationCredential {
+    pub id: String,
+    pub raw_id: String,
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(default)]
+    pub user_handle: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct FinishPasskeyRegistrationRequest {
+    pub credential: RegistrationCredential,
+    /// Label shown in the user's passkey list, e.g. "Work laptop"
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub transports: Vec<String>,
+    pub backed_up: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Backup codes are only generated, and shown once, when the passkey is the user's first factor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyRegisteredResponse {
+    pub passkey: PasskeyResponse,
+    pub backup_codes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PasskeyLoginStartRequest {
+    pub tenant_slug: String,
+    /// Without an email the browser offers the passkeys it holds for the site
+    #[validate(email)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyLoginRequest {
+    pub credential: AuthenticationCredential,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyMfaStartRequest {
+    pub mfa_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyMfaRequest {
+    pub mfa_token: String,
+    pub credential: AuthenticationCredential,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    pub scopes: Option<Vec<String>>,
+    #[validate(range(min = 1, max = 730))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateApiKeyRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub permissions: Option<Vec<String>>,
+    pub scopes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    pub permissions: Vec<String>,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returned only from the create call; the plaintext key is never retrievable again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedApiKeyResponse {
+    pub api_key: String,
+    pub key: ApiKeyResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StageSigningKeyRequest {
+    /// "EdDSA" or "RS256"
+    pub algorithm: String,
+    /// PEM encoded private key; Ed25519 keys are generated when omitted
+    pub private_key_pem: Option<String>,
+    #[validate(length(min = 1, max = 64))]
+    pub kid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RegisterOAuthClientRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(min = 1, max = 10))]
+    pub redirect_uris: Vec<String>,
+    #[validate(length(min = 1))]
+    pub scopes: Vec<String>,
+    /// Defaults to the authorization code and refresh token grants
+    pub grant_types: Option<Vec<String>>,
+    /// Public clients get no secret and must use PKCE
+    #[serde(default = "default_confidential")]
+    pub confidential: bool,
+}
+
+fn default_confidential() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientResponse {
+    pub id: Uuid,
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub confidential: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returned only from registration; the client secret is never retrievable again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredOAuthClientResponse {
+    pub client_secret: Option<String>,
+    pub client: OAuthClientResponse,
+}
+
+/// Query parameters of the authorization endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizeRequest {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: String,
+    pub code_challenge_method: Option<String>,
+}
+
+/// What the consent screen should show the signed-in user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentPromptResponse {
+    pub client_id: String,
+    pub client_name: String,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    /// Scopes the user has not granted this client before
+    pub new_scopes: Vec<String>,
+    pub consent_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentDecisionRequest {
+    #[serde(flatten)]
+    pub authorization: AuthorizeRequest,
+    pub approved: bool,
+}
+
+/// Where the user agent should be sent next, carrying either a code or an error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizeRedirectResponse {
+    pub redirect_uri: String,
+}
+
+/// Form body of the token endpoint (RFC 6749 section 4.1.3, 4.4.2 and 6)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokenRequest {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub scope: String,
+}
+
+/// Form body of the introspection (RFC 7662) and revocation (RFC 7009) endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokenActionRequest {
+    pub token: String,
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// RFC 7662 introspection response; only `active` is present for inactive tokens
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsoStartRequest {
+    pub tenant_slug: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsoStartResponse {
+    /// Identity provider URL the browser should be sent to
+    pub authorization_url: String,
+    pub expires_in: i64,
+}
+
+/// Parameters the identity provider appended to the redirect URI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsoCallbackRequest {
+    pub state: String,
+    pub code: String,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateSsoConfigRequest {
+    #[validate(url)]
+    pub issuer: String,
+    #[validate(length(min = 1, max = 255))]
+    pub client_id: String,
+    /// Omit to keep the secret already on file
+    pub client_secret: Option<String>,
+    #[validate(url)]
+    pub redirect_uri: String,
+    pub scopes: Option<Vec<String>>,
+    #[serde(default)]
+    pub sso_required: bool,
+    pub jit_provisioning: Option<bool>,
+    #[serde(default)]
+    pub allowed_email_domains: Vec<String>,
+    pub default_roles: Option<Vec<String>>,
+}
+
+/// SSO settings as shown to tenant admins; the client secret is write-only
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SsoConfigResponse {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret_set: bool,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub sso_required: bool,
+    pub jit_provisioning: bool,
+    pub allowed_email_domains: Vec<String>,
+    pub default_roles: Vec<String>,
+}
+
+/// SCIM 2.0 core User resource (RFC 7643 section 4.1); unsupported attributes are ignored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub user_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<ScimName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ScimMultiValue>,
+    #[serde(default)]
+    pub phone_numbers: Vec<ScimMultiValue>,
+    #[serde(default = "default_scim_active", deserialize_with = "deserialize_scim_bool")]
+    pub active: bool,
+    /// Read-only; membership is changed through the Groups endpoint
+    #[serde(default)]
+    pub groups: Vec<ScimMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+fn default_scim_active() -> bool {
+    true
+}
+
+/// Some SCIM clients send booleans as "True"/"False" strings
+fn deserialize_scim_bool<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(value) => Ok(value),
+        serde_json::Value::String(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+        serde_json::Value::String(value) if value.eq_ignore_ascii_case("false") => Ok(false),
+        other => Err(serde::de::Error::custom(format!("expected a boolean, got {}", other))),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimName {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+}
+
+/// An entry of a multi-valued attribute such as `emails` or `phoneNumbers`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimMultiValue {
+    pub value: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary: Option<bool>,
+}
+
+/// A reference from a group to a user, or from a user to a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimMember {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+    #[serde(rename = "$ref", default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+    pub created: DateTime<Utc>,
+    pub last_modified: DateTime<Utc>,
+    pub location: String,
+}
+
+/// SCIM 2.0 core Group resource (RFC 7643 section 4.2), backed by a tenant role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub display_name: String,
+    #[serde(default)]
+    pub members: Vec<ScimMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+/// Query parameters of the SCIM list endpoints (RFC 7644 section 3.4.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListQuery {
+    pub filter: Option<String>,
+    pub start_index: Option<usize>,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: usize,
+    pub start_index: usize,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimPatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimPatchOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimPatchOperation {
+    /// `add`, `replace` or `remove`; matched case-insensitively
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// Stop processing after this many failed operations
+    pub fail_on_errors: Option<usize>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimBulkOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkOperation {
+    pub method: String,
+    /// Lets later operations refer to a resource created earlier as `bulkId:<id>`
+    pub bulk_id: Option<String>,
+    pub path: String,
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimBulkResponse {
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimBulkOperationResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkOperationResponse {
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bulk_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<serde_json::Value>,
+}
+
+/// SCIM error body (RFC 7644 section 3.12)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimError {
+    pub schemas: Vec<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scim_type: Option<String>,
+    pub detail: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +998,26 @@ pub struct ForgotPasswordRequest {
     #[validate(email)]
     pub email: String,
     pub tenant_slug: String,
+    /// Language for emails sent as a result, e.g. `fr-CA`; defaults to the tenant's
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MagicLinkRequest {
+    #[validate(email)]
+    pub email: String,
+    pub tenant_slug: String,
+    /// Language for emails sent as a result, e.g. `fr-CA`; defaults to the tenant's
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MagicLinkLoginRequest {
+    pub token: String,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -145,6 +1039,157 @@ pub struct VerifyEmailRequest {
     pub token: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StartImpersonationRequest {
+    pub user_id: Uuid,
+    #[validate(length(min = 3, max = 500))]
+    pub reason: String,
+    /// Defaults to 15 minutes
+    #[validate(range(min = 1, max = 60))]
+    pub duration_minutes: Option<i64>,
+    /// Impersonation is read-only unless writes are explicitly requested
+    #[serde(default)]
+    pub allow_writes: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub session_id: Uuid,
+    pub read_only: bool,
+    pub user: UserResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RegisterTerminalRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub location_id: Uuid,
+    #[validate(length(min = 1, max = 255))]
+    pub device_id: String,
+}
+
+/// The terminal session token is only ever returned at registration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredTerminalResponse {
+    pub terminal: PosTerminalResponse,
+    pub terminal_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PosTerminalResponse {
+    pub id: Uuid,
+    pub location_id: Uuid,
+    pub name: String,
+    pub device_id: String,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_switch_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SetPinRequest {
+    /// 4 to 6 digits
+    #[validate(length(min = 4, max = 6))]
+    pub pin: String,
+    pub current_password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PinSwitchRequest {
+    pub user_id: Uuid,
+    #[validate(length(min = 4, max = 6))]
+    pub pin: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinSwitchResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub terminal_id: Uuid,
+    /// Location the token's grants are scoped to; send it as `X-Location-Id`
+    pub location_id: Uuid,
+    pub user: UserResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub roles: Vec<String>,
+    /// Limit the membership to these locations; omit for the whole tenant
+    #[serde(default)]
+    pub location_ids: Vec<Uuid>,
+    /// Defaults to 7 days
+    #[validate(range(min = 1, max = 30))]
+    pub expires_in_days: Option<i64>,
+    /// Language for the invitation email; defaults to the tenant's
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitationResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub roles: Vec<String>,
+    pub location_ids: Vec<Uuid>,
+    /// `pending`, `accepted`, `declined`, `revoked` or `expired`
+    pub status: String,
+    pub invited_by: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Accept an invitation. Signed-in users join with their existing identity; anyone else
+/// creates an account in the inviting tenant with the name and password given here.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+    #[validate(length(min = 1, max = 100))]
+    pub first_name: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub last_name: Option<String>,
+    #[validate(length(min = 8, max = 128))]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeclineInvitationRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchTenantRequest {
+    pub tenant_id: Uuid,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MembershipResponse {
+    pub tenant: TenantResponse,
+    pub roles: Vec<String>,
+    pub location_ids: Vec<Uuid>,
+    /// The tenant the account was created in
+    pub is_home: bool,
+    /// The tenant the presented access token was issued for
+    pub is_current: bool,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentUserResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub memberships: Vec<MembershipResponse>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RevokeSessionRequest {
     pub session_id: Uuid,
@@ -164,6 +1209,17 @@ pub struct SessionSummary {
     pub created_at: DateTime<Utc>,
     pub last_used_at: DateTime<Utc>,
     pub is_current: bool,
+    /// Set when this session belongs to support staff acting as the user
+    pub impersonation: Option<SessionImpersonation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionImpersonation {
+    pub impersonator_id: Uuid,
+    pub impersonator_email: String,
+    pub reason: String,
+    pub read_only: bool,
+    pub expires_at: DateTime<Utc>,
 }
 
 // JWT Claims
@@ -177,6 +1233,41 @@ pub struct Claims {
     pub session_id: Uuid,
     pub iat: i64,
     pub exp: i64,
+    /// Set when the token was issued to support staff acting as this user
+    #[serde(default)]
+    pub impersonation: Option<Impersonation>,
+    /// Set when the token was issued to a third-party OAuth client
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Set when the user switched onto a shared POS terminal with their PIN
+    #[serde(default)]
+    pub terminal_id: Option<Uuid>,
+    /// Set with `terminal_id` to the location the terminal is installed at
+    #[serde(default)]
+    pub location_id: Option<Uuid>,
+}
+
+impl Claims {
+    /// The caller as other services see it, for routers behind `olympus_shared::authorization::Authenticate`
+    pub fn auth_context(&self) -> AuthContext {
+        AuthContext {
+            user_id: self.sub,
+            tenant_id: self.tenant_id,
+            roles: self.roles.clone(),
+            permissions: self.permissions.clone(),
+            session_id: self.session_id.to_string(),
+            expires_at: DateTime::from_timestamp(self.exp, 0).unwrap_or_else(Utc::now),
+            location_id: self.location_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impersonation {
+    pub impersonator_id: Uuid,
+    pub impersonator_email: String,
+    pub read_only: bool,
+    pub reason: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -189,6 +1280,127 @@ pub struct RefreshClaims {
 }
 
 // Helper implementations
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            key_prefix: key.key_prefix,
+            permissions: key.permissions,
+            scopes: key.scopes,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            is_active: key.is_active,
+            created_at: key.created_at,
+        }
+    }
+}
+
+impl From<OAuthClient> for OAuthClientResponse {
+    fn from(client: OAuthClient) -> Self {
+        Self {
+            id: client.id,
+            confidential: client.client_secret_hash.is_some(),
+            client_id: client.client_id,
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            scopes: client.allowed_scopes,
+            grant_types: client.grant_types,
+            is_active: client.is_active,
+            created_at: client.created_at,
+        }
+    }
+}
+
+impl From<PosTerminal> for PosTerminalResponse {
+    fn from(terminal: PosTerminal) -> Self {
+        Self {
+            id: terminal.id,
+            location_id: terminal.location_id,
+            name: terminal.name,
+            device_id: terminal.device_id,
+            locked_until: terminal.locked_until,
+            last_switch_at: terminal.last_switch_at,
+            created_at: terminal.created_at,
+        }
+    }
+}
+
+impl From<WebAuthnCredential> for PasskeyResponse {
+    fn from(credential: WebAuthnCredential) -> Self {
+        Self {
+            id: credential.id,
+            name: credential.name,
+            transports: credential.transports,
+            backed_up: credential.backed_up,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(invitation: Invitation) -> Self {
+        Self {
+            id: invitation.id,
+            status: invitation.status().to_string(),
+            email: invitation.email,
+            roles: invitation.roles,
+            location_ids: invitation.location_ids,
+            invited_by: invitation.invited_by,
+            expires_at: invitation.expires_at,
+            created_at: invitation.created_at,
+        }
+    }
+}
+
+impl Invitation {
+    pub fn status(&self) -> &'static str {
+        if self.accepted_at.is_some() {
+            "accepted"
+        } else if self.declined_at.is_some() {
+            "declined"
+        } else if self.revoked_at.is_some() {
+            "revoked"
+        } else if self.expires_at <= Utc::now() {
+            "expired"
+        } else {
+            "pending"
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status() == "pending"
+    }
+}
+
+impl PosTerminal {
+    pub fn is_locked(&self) -> bool {
+        self.locked_until.is_some_and(|until| until > Utc::now())
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
+
+impl Tenant {
+    pub fn to_response(&self) -> TenantResponse {
+        TenantResponse {
+            id: self.id,
+            slug: self.slug.clone(),
+            name: self.name.clone(),
+            industry: self.industry.clone(),
+        }
+    }
+}
+
+impl OAuthClient {
+    pub fn allows_grant(&self, grant_type: &str) -> bool {
+        self.grant_types.iter().any(|g| g == grant_type)
+    }
+}
+
 impl User {
     pub fn to_response(&self, tenant: &Tenant) -> UserResponse {
         UserResponse {
@@ -200,12 +1412,18 @@ impl User {
             avatar_url: self.avatar_url.clone(),
             roles: self.roles.clone(),
             permissions: self.permissions.clone(),
-            tenant: TenantResponse {
-                id: tenant.id,
-                slug: tenant.slug.clone(),
-                name: tenant.name.clone(),
-                industry: tenant.industry.clone(),
-            },
+            tenant: tenant.to_response(),
+        }
+    }
+
+    /// The identity as a member of another tenant: the membership's roles and grants
+    /// replace the home tenant's
+    pub fn as_member(&self, membership: &TenantMembership) -> User {
+        User {
+            tenant_id: membership.tenant_id,
+            roles: membership.roles.clone(),
+            permissions: membership.permissions.clone(),
+            ..self.clone()
         }
     }
 
@@ -217,18 +1435,24 @@ impl User {
         }
     }
 
-    pub fn requires_password_change(&self) -> bool {
-        if let Some(changed_at) = self.password_changed_at {
-            // Require password change every 90 days
-            (Utc::now() - changed_at).num_days() > 90
-        } else {
-            true
+    /// Whether the user must rotate their password, either because an administrator
+    /// forced it or because it is older than the policy's maximum age
+    pub fn requires_password_change(&self, max_age_days: Option<u32>) -> bool {
+        if self.force_password_change {
+            return true;
+        }
+
+        match (self.password_changed_at, max_age_days) {
+            (Some(changed_at), Some(max_age)) => (Utc::now() - changed_at).num_days() > max_age as i64,
+            (None, _) => true,
+            (_, None) => false,
         }
     }
 
     pub fn update_password(&mut self, new_password_hash: String) {
         self.password_hash = new_password_hash;
         self.password_changed_at = Some(Utc::now());
+        self.force_password_change = false;
         self.updated_at = Utc::now();
     }
 
@@ -0,0 +1,82 @@
+// ============================================================================
+// OLYMPUS CLOUD - IMPERSONATION AUDIT TRAIL
+// ============================================================================
+// Module: auth/src/services/impersonation.rs
+// Description: Durable record of every request made with an impersonation token
+// Author: Claude Code Agent
+// Date: 2025-01-23
+// ============================================================================
+
+use async_trait::async_trait;
+use olympus_shared::database::DbPool;
+use olympus_shared::events::ImpersonatedRequestEvent;
+use std::sync::{Arc, RwLock};
+use crate::error::{AuthError, Result};
+
+/// Where requests made while impersonating are recorded for review
+#[async_trait]
+pub trait ImpersonationAuditStore: Send + Sync {
+    async fn record(&self, request: &ImpersonatedRequestEvent) -> Result<()>;
+}
+
+/// Process-local audit trail, for tests and single-node development
+#[derive(Default)]
+pub struct InMemoryImpersonationAuditStore {
+    requests: RwLock<Vec<ImpersonatedRequestEvent>>,
+}
+
+impl InMemoryImpersonationAuditStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every recorded request, oldest first
+    pub fn requests(&self) -> Vec<ImpersonatedRequestEvent> {
+        self.requests.read().map(|requests| requests.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl ImpersonationAuditStore for InMemoryImpersonationAuditStore {
+    async fn record(&self, request: &ImpersonatedRequestEvent) -> Result<()> {
+        self.requests
+            .write()
+            .map_err(|_| AuthError::Internal("Impersonation audit lock poisoned".to_string()))?
+            .push(request.clone());
+        Ok(())
+    }
+}
+
+/// Audit trail kept in `auth.impersonation_requests`
+pub struct PostgresImpersonationAuditStore {
+    db: Arc<DbPool>,
+}
+
+impl PostgresImpersonationAuditStore {
+    pub fn new(db: Arc<DbPool>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ImpersonationAuditStore for PostgresImpersonationAuditStore {
+    async fn record(&self, request: &ImpersonatedRequestEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO auth.impersonation_requests
+                (session_id, impersonator_id, user_id, tenant_id, method, path, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(request.session_id)
+        .bind(request.impersonator_id)
+        .bind(request.user_id)
+        .bind(request.tenant_id)
+        .bind(&request.method)
+        .bind(&request.path)
+        .bind(request.status as i16)
+        .execute(self.db.as_ref())
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,589 @@
+// ============================================================================
+// OLYMPUS CLOUD - TRANSACTIONAL EMAIL
+// ============================================================================
+// Module: auth/src/services/mailer/mod.rs
+// Description: Renders account emails into the outbox and delivers them with retries
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+pub mod outbox;
+pub mod provider;
+pub mod templates;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use olympus_shared::config::EmailConfig;
+use olympus_shared::database::DbPool;
+use olympus_shared::events::{
+    MagicLinkRequestedEvent, PasswordResetRequestedEvent, UserInvitedEvent, UserRegisteredEvent,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+
+pub use outbox::{InMemoryOutboxStore, OutboxEmail, OutboxStatus, OutboxStore, PostgresOutboxStore};
+pub use provider::{EmailMessage, EmailProvider, FileEmailProvider, InMemoryEmailProvider, SmtpEmailProvider};
+pub use templates::{EmailTemplates, RenderedEmail, TenantBranding};
+
+/// Where templates get the tenant's name, logo and colours
+#[async_trait]
+pub trait BrandingStore: Send + Sync {
+    async fn branding(&self, tenant_id: Uuid) -> Result<TenantBranding>;
+}
+
+/// Branding from the tenant's row; unknown tenants get the sender name
+pub struct PostgresBrandingStore {
+    db: Arc<DbPool>,
+    fallback_name: String,
+}
+
+impl PostgresBrandingStore {
+    pub fn new(db: Arc<DbPool>, fallback_name: String) -> Self {
+        Self { db, fallback_name }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct BrandingRow {
+    name: String,
+    logo_url: Option<String>,
+    primary_color: Option<String>,
+    secondary_color: Option<String>,
+    support_email: Option<String>,
+    locale: Option<String>,
+}
+
+#[async_trait]
+impl BrandingStore for PostgresBrandingStore {
+    async fn branding(&self, tenant_id: Uuid) -> Result<TenantBranding> {
+        let row = sqlx::query_as::<_, BrandingRow>(
+            r#"
+            SELECT COALESCE(display_name, name) AS name, logo_url, primary_color, secondary_color,
+                support_email, locale
+            FROM tenants WHERE id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.as_ref())
+        .await?;
+
+        Ok(match row {
+            Some(row) => TenantBranding {
+                name: row.name,
+                logo_url: row.logo_url,
+                primary_color: row.primary_color,
+                secondary_color: row.secondary_color,
+                support_email: row.support_email,
+                locale: row.locale,
+            },
+            None => TenantBranding {
+                name: self.fallback_name.clone(),
+                ..Default::default()
+            },
+        })
+    }
+}
+
+/// Fixed branding for development and tests
+#[derive(Default)]
+pub struct StaticBrandingStore {
+    tenants: HashMap<Uuid, TenantBranding>,
+    default: TenantBranding,
+}
+
+impl StaticBrandingStore {
+    pub fn new(default: TenantBranding) -> Self {
+        Self { tenants: HashMap::new(), default }
+    }
+
+    pub fn with_tenant(mut self, tenant_id: Uuid, branding: TenantBranding) -> Self {
+        self.tenants.insert(tenant_id, branding);
+        self
+    }
+}
+
+#[async_trait]
+impl BrandingStore for StaticBrandingStore {
+    async fn branding(&self, tenant_id: Uuid) -> Result<TenantBranding> {
+        Ok(self.tenants.get(&tenant_id).cloned().unwrap_or_else(|| self.default.clone()))
+    }
+}
+
+/// An email to render and queue
+#[derive(Debug, Clone)]
+pub struct EmailRequest {
+    /// Identifies the send; a second request with the same key is ignored
+    pub dedupe_key: String,
+    pub tenant_id: Uuid,
+    pub to: String,
+    pub template: String,
+    /// The recipient's preferred language, if known
+    pub locale: Option<String>,
+    pub data: serde_json::Value,
+}
+
+/// Outcome of one pass over the outbox
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryReport {
+    pub sent: usize,
+    pub retried: usize,
+    pub failed: usize,
+}
+
+impl DeliveryReport {
+    pub fn attempted(&self) -> usize {
+        self.sent + self.retried + self.failed
+    }
+}
+
+/// Transactional email for account flows.
+///
+/// Emails are rendered when queued and written to the outbox; delivery happens in the
+/// worker (`spawn_worker`), so a send that fails, or that was queued just before a
+/// restart, is retried with exponential backoff until `max_attempts`.
+pub struct Mailer {
+    config: EmailConfig,
+    provider: Arc<dyn EmailProvider>,
+    outbox: Arc<dyn OutboxStore>,
+    branding: Arc<dyn BrandingStore>,
+    templates: EmailTemplates,
+}
+
+impl Mailer {
+    pub fn new(
+        config: EmailConfig,
+        provider: Arc<dyn EmailProvider>,
+        outbox: Arc<dyn OutboxStore>,
+        branding: Arc<dyn BrandingStore>,
+    ) -> Self {
+        let templates = EmailTemplates::new(
+            Some(PathBuf::from(&config.template_path)),
+            &config.delivery.default_locale,
+        );
+        Self { config, provider, outbox, branding, templates }
+    }
+
+    /// The configured provider with the Postgres outbox and tenant branding
+    pub fn from_config(config: &EmailConfig, db: Arc<DbPool>) -> Result<Self> {
+        Ok(Self::new(
+            config.clone(),
+            provider_from_config(config)?,
+            Arc::new(PostgresOutboxStore::new(db.clone())),
+            Arc::new(PostgresBrandingStore::new(db, config.from_name.clone())),
+        ))
+    }
+
+    /// Render an email and add it to the outbox, returning its id; `None` for a duplicate.
+    pub async fn enqueue(&self, request: EmailRequest) -> Result<Option<Uuid>> {
+        let branding = self.branding.branding(request.tenant_id).await?;
+        let locales = self.templates.locale_chain(&[request.locale.as_deref(), branding.locale.as_deref()]);
+        let rendered = self.templates
+            .render(request.tenant_id, &request.template, &locales, &branding, &request.data)
+            .await?;
+
+        let now = Utc::now();
+        let email = OutboxEmail {
+            id: Uuid::new_v4(),
+            tenant_id: request.tenant_id,
+            dedupe_key: request.dedupe_key,
+            template: request.template,
+            locale: locales.first().cloned().unwrap_or_default(),
+            message: EmailMessage {
+                to: request.to,
+                from_email: self.config.from_email.clone(),
+                from_name: branding.name.clone(),
+                subject: rendered.subject,
+                html_body: rendered.html_body,
+                text_body: rendered.text_body,
+            },
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+            sent_at: None,
+        };
+
+        if !self.outbox.enqueue(&email).await? {
+            return Ok(None);
+        }
+        info!("Queued {} email {} for tenant {}", email.template, email.id, email.tenant_id);
+        Ok(Some(email.id))
+    }
+
+    /// Queue the reset email carrying `token`; the token is only ever written to the outbox
+    pub async fn send_password_reset(&self, event: &PasswordResetRequestedEvent, token: &str) -> Result<Option<Uuid>> {
+        let action_url = action_url(&self.config.reset_url_template, token);
+        self.enqueue(EmailRequest {
+            dedupe_key: format!("{}:{}", templates::PASSWORD_RESET, event.reset_token_id),
+            tenant_id: event.tenant_id,
+            to: event.email.clone(),
+            template: templates::PASSWORD_RESET.to_string(),
+            locale: event.locale.clone(),
+            data: json!({
+                "email": event.email,
+                "action_url": action_url,
+                "expires_at": format_time(event.expires_at),
+            }),
+        })
+        .await
+    }
+
+    /// Queue the verification email carrying `token` for a new account; nothing is queued when
+    /// verification isn't required. The token is only ever written to the outbox.
+    pub async fn send_email_verification(&self, event: &UserRegisteredEvent, token: &str) -> Result<Option<Uuid>> {
+        if !event.email_verification_required {
+            return Ok(None);
+        }
+
+        let action_url = action_url(&self.config.verification_url_template, token);
+        self.enqueue(EmailRequest {
+            dedupe_key: format!("{}:{}", templates::EMAIL_VERIFICATION, event.user_id),
+            tenant_id: event.tenant_id,
+            to: event.email.clone(),
+            template: templates::EMAIL_VERIFICATION.to_string(),
+            locale: event.locale.clone(),
+            data: json!({
+                "email": event.email,
+                "first_name": event.first_name,
+                "last_name": event.last_name,
+                "action_url": action_url,
+            }),
+        })
+        .await
+    }
+
+    /// Queue the login link email carrying `token`; the token is only ever written to the outbox
+    pub async fn send_magic_link(&self, event: &MagicLinkRequestedEvent, token: &str) -> Result<Option<Uuid>> {
+        let url_template = self.config.magic_link_url_template.as_deref()
+            .ok_or_else(|| AuthError::Email("email.magic_link_url_template is not configured".to_string()))?;
+
+        self.enqueue(EmailRequest {
+            dedupe_key: format!("{}:{}", templates::MAGIC_LINK, event.token_id),
+            tenant_id: event.tenant_id,
+            to: event.email.clone(),
+            template: templates::MAGIC_LINK.to_string(),
+            locale: event.locale.clone(),
+            data: json!({
+                "email": event.email,
+                "action_url": action_url(url_template, token),
+                "expires_at": format_time(event.expires_at),
+            }),
+        })
+        .await
+    }
+
+    /// Queue the invitation email carrying `token`; the token is only ever written to the outbox
+    pub async fn send_invitation(&self, event: &UserInvitedEvent, token: &str) -> Result<Option<Uuid>> {
+        let url_template = self.config.invitation_url_template.as_deref()
+            .ok_or_else(|| AuthError::Email("email.invitation_url_template is not configured".to_string()))?;
+
+        self.enqueue(EmailRequest {
+            dedupe_key: format!("{}:{}", templates::INVITATION, event.invitation_id),
+            tenant_id: event.tenant_id,
+            to: event.email.clone(),
+            template: templates::INVITATION.to_string(),
+            locale: event.locale.clone(),
+            data: json!({
+                "email": event.email,
+                "roles": event.roles,
+                "action_url": action_url(url_template, token),
+                "expires_at": format_time(event.expires_at),
+            }),
+        })
+        .await
+    }
+
+    /// Send one batch of due emails, scheduling retries for the ones that fail
+    pub async fn deliver_due(&self) -> Result<DeliveryReport> {
+        let delivery = &self.config.delivery;
+        let now = Utc::now();
+        let lease_until = now + Duration::seconds(delivery.claim_timeout_seconds as i64);
+        let batch = self.outbox.claim_due(now, lease_until, delivery.batch_size).await?;
+
+        let mut report = DeliveryReport::default();
+        for email in batch {
+            self.deliver(&email, &mut report).await?;
+        }
+        Ok(report)
+    }
+
+    /// Send one queued email now instead of waiting for the worker, e.g. when the event that
+    /// names it arrives; an email that is already sent or leased is left alone
+    pub async fn deliver_queued(&self, id: Uuid) -> Result<DeliveryReport> {
+        let now = Utc::now();
+        let lease_until = now + Duration::seconds(self.config.delivery.claim_timeout_seconds as i64);
+
+        let mut report = DeliveryReport::default();
+        if let Some(email) = self.outbox.claim(id, now, lease_until).await? {
+            self.deliver(&email, &mut report).await?;
+        }
+        Ok(report)
+    }
+
+    /// Send a claimed email, scheduling a retry or giving up if the provider fails
+    async fn deliver(&self, email: &OutboxEmail, report: &mut DeliveryReport) -> Result<()> {
+        match self.provider.send(&email.message).await {
+            Ok(()) => {
+                self.outbox.mark_sent(email.id, Utc::now()).await?;
+                report.sent += 1;
+            }
+            Err(e) if email.attempts >= self.config.delivery.max_attempts => {
+                warn!(
+                    "Giving up on {} email {} to {} after {} attempts: {}",
+                    email.template, email.id, email.message.to, email.attempts, e
+                );
+                self.outbox.mark_failed(email.id, &e.to_string(), None).await?;
+                report.failed += 1;
+            }
+            Err(e) => {
+                let retry_at = Utc::now() + self.retry_delay(email.attempts);
+                warn!(
+                    "Failed to send {} email {} (attempt {}), retrying at {}: {}",
+                    email.template, email.id, email.attempts, retry_at, e
+                );
+                self.outbox.mark_failed(email.id, &e.to_string(), Some(retry_at)).await?;
+                report.retried += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Poll the outbox in the background; a full batch is followed immediately by the next
+    pub fn spawn_worker(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let interval = std::time::Duration::from_secs(self.config.delivery.poll_interval_seconds.max(1));
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                loop {
+                    match self.deliver_due().await {
+                        Ok(report) if report.attempted() >= self.config.delivery.batch_size as usize => continue,
+                        Ok(_) => break,
+                        Err(e) => {
+                            warn!("Failed to process email outbox: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Backoff before retry `attempts + 1`: doubling from the base delay, capped
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        let delivery = &self.config.delivery;
+        let factor = 1u64 << attempts.saturating_sub(1).min(20);
+        let seconds = delivery.retry_base_seconds.saturating_mul(factor).min(delivery.retry_max_seconds);
+        Duration::seconds(seconds as i64)
+    }
+}
+
+/// Build the provider named by `email.provider`
+pub fn provider_from_config(config: &EmailConfig) -> Result<Arc<dyn EmailProvider>> {
+    match config.provider.as_str() {
+        "smtp" => {
+            let host = config.smtp_host.as_deref()
+                .ok_or_else(|| AuthError::Email("email.smtp_host is required for the smtp provider".to_string()))?;
+            Ok(Arc::new(SmtpEmailProvider::new(
+                host,
+                config.smtp_port,
+                config.smtp_username.clone(),
+                config.smtp_password.clone(),
+                config.smtp_insecure,
+            )?))
+        }
+        "file" => {
+            let path = config.file_sink_path.as_deref()
+                .ok_or_else(|| AuthError::Email("email.file_sink_path is required for the file provider".to_string()))?;
+            Ok(Arc::new(FileEmailProvider::new(path)))
+        }
+        "memory" => Ok(Arc::new(InMemoryEmailProvider::default())),
+        other => Err(AuthError::Email(format!("Unsupported email provider: {}", other))),
+    }
+}
+
+fn action_url(url_template: &str, token: &str) -> String {
+    let token: String = form_urlencoded::byte_serialize(token.as_bytes()).collect();
+    url_template.replace("{token}", &token)
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olympus_shared::AppConfig;
+
+    /// Fails every send, to exercise retries
+    struct RejectingProvider;
+
+    #[async_trait]
+    impl EmailProvider for RejectingProvider {
+        async fn send(&self, _message: &EmailMessage) -> Result<()> {
+            Err(AuthError::Email("mailbox unavailable".to_string()))
+        }
+    }
+
+    fn config() -> EmailConfig {
+        let mut config = AppConfig::default().email;
+        // No template directory, so only the built-in templates are used
+        config.template_path = std::env::temp_dir()
+            .join(format!("olympus-no-templates-{}", Uuid::new_v4()))
+            .display()
+            .to_string();
+        config.delivery.max_attempts = 3;
+        config
+    }
+
+    fn mailer(provider: Arc<dyn EmailProvider>, outbox: Arc<InMemoryOutboxStore>, tenant_id: Uuid) -> Mailer {
+        let branding = StaticBrandingStore::new(TenantBranding { name: "Olympus Cloud".to_string(), ..Default::default() })
+            .with_tenant(tenant_id, TenantBranding {
+                name: "Harbor Bistro".to_string(),
+                locale: Some("en_US".to_string()),
+                ..Default::default()
+            });
+        Mailer::new(config(), provider, outbox, Arc::new(branding))
+    }
+
+    fn reset_event(tenant_id: Uuid) -> PasswordResetRequestedEvent {
+        PasswordResetRequestedEvent {
+            user_id: Uuid::new_v4(),
+            tenant_id,
+            email: "guest@example.com".to_string(),
+            reset_token_id: Uuid::new_v4(),
+            email_id: None,
+            expires_at: Utc::now() + Duration::hours(1),
+            request_source: "web".to_string(),
+            locale: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_is_queued_once_and_delivered() {
+        let tenant_id = Uuid::new_v4();
+        let provider = Arc::new(InMemoryEmailProvider::default());
+        let outbox = Arc::new(InMemoryOutboxStore::default());
+        let mailer = mailer(provider.clone(), outbox.clone(), tenant_id);
+        let event = reset_event(tenant_id);
+
+        let email_id = mailer.send_password_reset(&event, "header.payload.signature").await.unwrap();
+        assert!(email_id.is_some());
+        assert!(mailer.send_password_reset(&event, "header.payload.signature").await.unwrap().is_none());
+
+        let queued = outbox.emails();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(Some(queued[0].id), email_id);
+        assert_eq!(queued[0].locale, "en-US");
+        assert_eq!(queued[0].message.from_name, "Harbor Bistro");
+        assert!(queued[0].message.text_body.contains("https://app.olympuscloud.io/reset?token=header.payload.signature"));
+
+        let report = mailer.deliver_due().await.unwrap();
+        assert_eq!(report, DeliveryReport { sent: 1, retried: 0, failed: 0 });
+        assert_eq!(provider.sent()[0].subject, "Reset your Harbor Bistro password");
+        assert_eq!(outbox.emails()[0].status, OutboxStatus::Sent);
+
+        // Nothing left to send
+        assert_eq!(mailer.deliver_due().await.unwrap().attempted(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_sends_back_off_then_give_up() {
+        let tenant_id = Uuid::new_v4();
+        let outbox = Arc::new(InMemoryOutboxStore::default());
+        let mailer = mailer(Arc::new(RejectingProvider), outbox.clone(), tenant_id);
+        mailer.send_password_reset(&reset_event(tenant_id), "header.payload.signature").await.unwrap();
+
+        assert_eq!(mailer.deliver_due().await.unwrap().retried, 1);
+        let email = outbox.emails().remove(0);
+        assert_eq!(email.attempts, 1);
+        assert_eq!(email.last_error.as_deref(), Some("Email delivery error: mailbox unavailable"));
+        assert!(email.next_attempt_at > Utc::now() + Duration::seconds(25));
+
+        // Not due yet
+        assert_eq!(mailer.deliver_due().await.unwrap().attempted(), 0);
+
+        for attempt in 2..=3 {
+            // Make the retry due now
+            outbox.mark_failed(email.id, "forced", Some(Utc::now() - Duration::seconds(1))).await.unwrap();
+            let report = mailer.deliver_due().await.unwrap();
+            if attempt < 3 {
+                assert_eq!(report.retried, 1);
+            } else {
+                assert_eq!(report.failed, 1);
+            }
+        }
+        assert_eq!(outbox.emails()[0].status, OutboxStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_queued_email_is_delivered_by_id_once() {
+        let tenant_id = Uuid::new_v4();
+        let provider = Arc::new(InMemoryEmailProvider::default());
+        let outbox = Arc::new(InMemoryOutboxStore::default());
+        let mailer = mailer(provider.clone(), outbox.clone(), tenant_id);
+        let email_id = mailer
+            .send_password_reset(&reset_event(tenant_id), "header.payload.signature")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(mailer.deliver_queued(email_id).await.unwrap().sent, 1);
+        assert_eq!(provider.sent().len(), 1);
+
+        // Neither a redelivered event nor the worker sends it again
+        assert_eq!(mailer.deliver_queued(email_id).await.unwrap().attempted(), 0);
+        assert_eq!(mailer.deliver_due().await.unwrap().attempted(), 0);
+        assert_eq!(provider.sent().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verification_only_when_required() {
+        let tenant_id = Uuid::new_v4();
+        let outbox = Arc::new(InMemoryOutboxStore::default());
+        let mailer = mailer(Arc::new(InMemoryEmailProvider::default()), outbox.clone(), tenant_id);
+        let mut event = UserRegisteredEvent {
+            user_id: Uuid::new_v4(),
+            tenant_id,
+            email: "new@example.com".to_string(),
+            first_name: Some("Ada".to_string()),
+            last_name: None,
+            email_verification_required: false,
+            registration_source: "web".to_string(),
+            email_id: None,
+            locale: None,
+        };
+
+        assert!(mailer.send_email_verification(&event, "token").await.unwrap().is_none());
+        event.email_verification_required = true;
+        let email_id = mailer.send_email_verification(&event, "token").await.unwrap();
+        assert_eq!(email_id, Some(outbox.emails()[0].id));
+        assert!(outbox.emails()[0].message.html_body.contains("Hi Ada"));
+        assert!(outbox.emails()[0].message.text_body.contains("token"));
+        // Registering again doesn't queue a second email
+        assert!(mailer.send_email_verification(&event, "token").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_cap() {
+        let outbox = Arc::new(InMemoryOutboxStore::default());
+        let mailer = mailer(Arc::new(InMemoryEmailProvider::default()), outbox, Uuid::new_v4());
+        assert_eq!(mailer.retry_delay(1), Duration::seconds(30));
+        assert_eq!(mailer.retry_delay(3), Duration::seconds(120));
+        assert_eq!(mailer.retry_delay(12), Duration::seconds(3600));
+    }
+
+    #[test]
+    fn test_unknown_provider_is_rejected() {
+        let mut config = config();
+        config.provider = "sendgrid".to_string();
+        assert!(provider_from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+// ============================================================================
+// OLYMPUS CLOUD - EMAIL OUTBOX
+// ============================================================================
+// Module: auth/src/services/mailer/outbox.rs
+// Description: Durable queue of rendered emails awaiting delivery
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use olympus_shared::database::DbPool;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+use super::provider::EmailMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    Pending,
+    Sent,
+    /// Gave up after the configured number of attempts
+    Failed,
+}
+
+impl OutboxStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxStatus::Pending => "pending",
+            OutboxStatus::Sent => "sent",
+            OutboxStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(status: &str) -> Result<Self> {
+        match status {
+            "pending" => Ok(OutboxStatus::Pending),
+            "sent" => Ok(OutboxStatus::Sent),
+            "failed" => Ok(OutboxStatus::Failed),
+            other => Err(AuthError::Internal(format!("Unknown outbox status {}", other))),
+        }
+    }
+}
+
+/// A queued email. `dedupe_key` makes enqueueing idempotent, so redelivered events
+/// don't send twice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEmail {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub dedupe_key: String,
+    pub template: String,
+    pub locale: String,
+    pub message: EmailMessage,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+/// Storage for the outbox. Claiming pushes `next_attempt_at` out by a lease, so a
+/// message claimed by a worker that dies is picked up again once the lease runs out.
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    /// Returns false if an email with the same `dedupe_key` is already queued
+    async fn enqueue(&self, email: &OutboxEmail) -> Result<bool>;
+    /// Pending emails due by `now`, with their attempt counted and their lease taken
+    async fn claim_due(&self, now: DateTime<Utc>, lease_until: DateTime<Utc>, limit: u32) -> Result<Vec<OutboxEmail>>;
+    /// Claim one email by id, like `claim_due`; `None` if it is sent, failed or leased elsewhere
+    async fn claim(&self, id: Uuid, now: DateTime<Utc>, lease_until: DateTime<Utc>) -> Result<Option<OutboxEmail>>;
+    async fn mark_sent(&self, id: Uuid, sent_at: DateTime<Utc>) -> Result<()>;
+    /// Record a failed attempt; `retry_at` of `None` gives up on the email
+    async fn mark_failed(&self, id: Uuid, error: &str, retry_at: Option<DateTime<Utc>>) -> Result<()>;
+    /// Delete sent emails (and the tokens in them) created before `before`
+    async fn purge_sent(&self, before: DateTime<Utc>) -> Result<u64>;
+}
+
+/// Outbox in `auth.email_outbox`; workers on several instances share it safely
+pub struct PostgresOutboxStore {
+    db: Arc<DbPool>,
+}
+
+impl PostgresOutboxStore {
+    pub fn new(db: Arc<DbPool>) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct OutboxRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    dedupe_key: String,
+    template: String,
+    locale: String,
+    recipient: String,
+    from_email: String,
+    from_name: String,
+    subject: String,
+    html_body: String,
+    text_body: String,
+    status: String,
+    attempts: i32,
+    next_attempt_at: DateTime<Utc>,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    sent_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<OutboxRow> for OutboxEmail {
+    type Error = AuthError;
+
+    fn try_from(row: OutboxRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            dedupe_key: row.dedupe_key,
+            template: row.template,
+            locale: row.locale,
+            message: EmailMessage {
+                to: row.recipient,
+                from_email: row.from_email,
+                from_name: row.from_name,
+                subject: row.subject,
+                html_body: row.html_body,
+                text_body: row.text_body,
+            },
+            status: OutboxStatus::parse(&row.status)?,
+            attempts: row.attempts.max(0) as u32,
+            next_attempt_at: row.next_attempt_at,
+            last_error: row.last_error,
+            created_at: row.created_at,
+            sent_at: row.sent_at,
+        })
+    }
+}
+
+#[async_trait]
+impl OutboxStore for PostgresOutboxStore {
+    async fn enqueue(&self, email: &OutboxEmail) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO auth.email_outbox (
+                id, tenant_id, dedupe_key, template, locale, recipient, from_email, from_name,
+                subject, html_body, text_body, status, attempts, next_attempt_at, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (dedupe_key) DO NOTHING
+            "#,
+        )
+        .bind(email.id)
+        .bind(email.tenant_id)
+        .bind(&email.dedupe_key)
+        .bind(&email.template)
+        .bind(&email.locale)
+        .bind(&email.message.to)
+        .bind(&email.message.from_email)
+        .bind(&email.message.from_name)
+        .bind(&email.message.subject)
+        .bind(&email.message.html_body)
+        .bind(&email.message.text_body)
+        .bind(email.status.as_str())
+        .bind(email.attempts as i32)
+        .bind(email.next_attempt_at)
+        .bind(email.created_at)
+        .execute(self.db.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn claim_due(&self, now: DateTime<Utc>, lease_until: DateTime<Utc>, limit: u32) -> Result<Vec<OutboxEmail>> {
+        let rows = sqlx::query_as::<_, OutboxRow>(
+            r#"
+            UPDATE auth.email_outbox
+            SET attempts = attempts + 1, next_attempt_at = $2
+            WHERE id IN (
+                SELECT id FROM auth.email_outbox
+                WHERE status = 'pending' AND next_attempt_at <= $1
+                ORDER BY next_attempt_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, tenant_id, dedupe_key, template, locale, recipient, from_email, from_name,
+                subject, html_body, text_body, status, attempts, next_attempt_at, last_error,
+                created_at, sent_at
+            "#,
+        )
+        .bind(now)
+        .bind(lease_until)
+        .bind(limit as i64)
+        .fetch_all(self.db.as_ref())
+        .await?;
+
+        rows.into_iter().map(OutboxEmail::try_from).collect()
+    }
+
+    async fn claim(&self, id: Uuid, now: DateTime<Utc>, lease_until: DateTime<Utc>) -> Result<Option<OutboxEmail>> {
+        let row = sqlx::query_as::<_, OutboxRow>(
+            r#"
+            UPDATE auth.email_outbox
+            SET attempts = attempts + 1, next_attempt_at = $3
+            WHERE id = $1 AND status = 'pending' AND next_attempt_at <= $2
+            RETURNING id, tenant_id, dedupe_key, template, locale, recipient, from_email, from_name,
+                subject, html_body, text_body, status, attempts, next_attempt_at, last_error,
+                created_at, sent_at
+            "#,
+        )
+        .bind(id)
+        .bind(now)
+        .bind(lease_until)
+        .fetch_optional(self.db.as_ref())
+        .await?;
+
+        row.map(OutboxEmail::try_from).transpose()
+    }
+
+    async fn mark_sent(&self, id: Uuid, sent_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE auth.email_outbox SET status = 'sent', sent_at = $2, last_error = NULL WHERE id = $1")
+            .bind(id)
+            .bind(sent_at)
+            .execute(self.db.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: Uuid, error: &str, retry_at: Option<DateTime<Utc>>) -> Result<()> {
+        let status = if retry_at.is_some() { OutboxStatus::Pending } else { OutboxStatus::Failed };
+        sqlx::query(
+            r#"
+            UPDATE auth.email_outbox
+            SET status = $2, last_error = $3, next_attempt_at = COALESCE($4, next_attempt_at)
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(status.as_str())
+        .bind(error)
+        .bind(retry_at)
+        .execute(self.db.as_ref())
+        .await?;
+        Ok(())
+    }
+
+    async fn purge_sent(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM auth.email_outbox WHERE status = 'sent' AND created_at < $1")
+            .bind(before)
+            .execute(self.db.as_ref())
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Single-process outbox for development and tests; lost on restart
+#[derive(Default)]
+pub struct InMemoryOutboxStore {
+    emails: RwLock<Vec<OutboxEmail>>,
+}
+
+impl InMemoryOutboxStore {
+    pub fn emails(&self) -> Vec<OutboxEmail> {
+        self.emails.read().map(|emails| emails.clone()).unwrap_or_default()
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Vec<OutboxEmail>>> {
+        self.emails.write()
+            .map_err(|_| AuthError::Internal("Outbox lock poisoned".to_string()))
+    }
+}
+
+#[async_trait]
+impl OutboxStore for InMemoryOutboxStore {
+    async fn enqueue(&self, email: &OutboxEmail) -> Result<bool> {
+        let mut emails = self.write()?;
+        if emails.iter().any(|queued| queued.dedupe_key == email.dedupe_key) {
+            return Ok(false);
+        }
+        emails.push(email.clone());
+        Ok(true)
+    }
+
+    async fn claim_due(&self, now: DateTime<Utc>, lease_until: DateTime<Utc>, limit: u32) -> Result<Vec<OutboxEmail>> {
+        let mut emails = self.write()?;
+        let mut due: Vec<&mut OutboxEmail> = emails
+            .iter_mut()
+            .filter(|email| email.status == OutboxStatus::Pending && email.next_attempt_at <= now)
+            .collect();
+        due.sort_by_key(|email| email.next_attempt_at);
+
+        Ok(due
+            .into_iter()
+            .take(limit as usize)
+            .map(|email| {
+                email.attempts += 1;
+                email.next_attempt_at = lease_until;
+                email.clone()
+            })
+            .collect())
+    }
+
+    async fn claim(&self, id: Uuid, now: DateTime<Utc>, lease_until: DateTime<Utc>) -> Result<Option<OutboxEmail>> {
+        let mut emails = self.write()?;
+        Ok(emails
+            .iter_mut()
+            .find(|email| email.id == id && email.status == OutboxStatus::Pending && email.next_attempt_at <= now)
+            .map(|email| {
+                email.attempts += 1;
+                email.next_attempt_at = lease_until;
+                email.clone()
+            }))
+    }
+
+    async fn mark_sent(&self, id: Uuid, sent_at: DateTime<Utc>) -> Result<()> {
+        if let Some(email) = self.write()?.iter_mut().find(|email| email.id == id) {
+            email.status = OutboxStatus::Sent;
+            email.sent_at = Some(sent_at);
+            email.last_error = None;
+        }
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: Uuid, error: &str, retry_at: Option<DateTime<Utc>>) -> Result<()> {
+        if let Some(email) = self.write()?.iter_mut().find(|email| email.id == id) {
+            email.last_error = Some(error.to_string());
+            match retry_at {
+                Some(at) => email.next_attempt_at = at,
+                None => email.status = OutboxStatus::Failed,
+            }
+        }
+        Ok(())
+    }
+
+    async fn purge_sent(&self, before: DateTime<Utc>) -> Result<u64> {
+        let mut emails = self.write()?;
+        let count = emails.len();
+        emails.retain(|email| !(email.status == OutboxStatus::Sent && email.created_at < before));
+        Ok((count - emails.len()) as u64)
+    }
+}
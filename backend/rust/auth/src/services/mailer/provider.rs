@@ -0,0 +1,189 @@
+// ============================================================================
+// OLYMPUS CLOUD - EMAIL PROVIDERS
+// ============================================================================
+// Module: auth/src/services/mailer/provider.rs
+// Description: Transports that hand rendered messages to SMTP, a directory, or memory
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+
+/// A fully rendered email, ready for a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailMessage {
+    pub to: String,
+    pub from_email: String,
+    pub from_name: String,
+    pub subject: String,
+    pub html_body: String,
+    pub text_body: String,
+}
+
+impl EmailMessage {
+    /// Build the MIME message: a plain text part with an HTML alternative
+    pub fn to_mime(&self) -> Result<Message> {
+        let from_address: Address = self.from_email.parse()
+            .map_err(|e| AuthError::Email(format!("Invalid sender address {}: {}", self.from_email, e)))?;
+        let to: Mailbox = self.to.parse()
+            .map_err(|e| AuthError::Email(format!("Invalid recipient address {}: {}", self.to, e)))?;
+
+        Message::builder()
+            .from(Mailbox::new(Some(self.from_name.clone()), from_address))
+            .to(to)
+            .subject(&self.subject)
+            .multipart(MultiPart::alternative_plain_html(self.text_body.clone(), self.html_body.clone()))
+            .map_err(|e| AuthError::Email(format!("Failed to build message: {}", e)))
+    }
+}
+
+/// Delivers rendered messages. An error means the message was not accepted and the
+/// outbox should retry it.
+#[async_trait]
+pub trait EmailProvider: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<()>;
+}
+
+/// Relays through an SMTP server
+pub struct SmtpEmailProvider {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpEmailProvider {
+    /// Port 465 uses implicit TLS, anything else STARTTLS; `insecure` disables TLS for a
+    /// local mail catcher
+    pub fn new(
+        host: &str,
+        port: Option<u16>,
+        username: Option<String>,
+        password: Option<String>,
+        insecure: bool,
+    ) -> Result<Self> {
+        let mut builder = if insecure {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        } else if port == Some(465) {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| AuthError::Email(format!("Invalid SMTP relay {}: {}", host, e)))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| AuthError::Email(format!("Invalid SMTP relay {}: {}", host, e)))?
+        };
+
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (username, password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self { transport: builder.build() })
+    }
+}
+
+#[async_trait]
+impl EmailProvider for SmtpEmailProvider {
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        let mime = message.to_mime()?;
+        self.transport.send(mime).await
+            .map_err(|e| AuthError::Email(format!("SMTP send to {} failed: {}", message.to, e)))?;
+        Ok(())
+    }
+}
+
+/// Writes each message to `<dir>/<uuid>.eml`, for development and end-to-end tests
+pub struct FileEmailProvider {
+    dir: PathBuf,
+}
+
+impl FileEmailProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl EmailProvider for FileEmailProvider {
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        let mime = message.to_mime()?;
+        tokio::fs::create_dir_all(&self.dir).await
+            .map_err(|e| AuthError::Email(format!("Failed to create {}: {}", self.dir.display(), e)))?;
+
+        let path = self.dir.join(format!("{}.eml", Uuid::new_v4()));
+        tokio::fs::write(&path, mime.formatted()).await
+            .map_err(|e| AuthError::Email(format!("Failed to write {}: {}", path.display(), e)))?;
+        Ok(())
+    }
+}
+
+/// Keeps sent messages in memory for tests
+#[derive(Default)]
+pub struct InMemoryEmailProvider {
+    sent: RwLock<Vec<EmailMessage>>,
+}
+
+impl InMemoryEmailProvider {
+    pub fn sent(&self) -> Vec<EmailMessage> {
+        self.sent.read().map(|sent| sent.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl EmailProvider for InMemoryEmailProvider {
+    async fn send(&self, message: &EmailMessage) -> Result<()> {
+        self.sent.write()
+            .map_err(|_| AuthError::Internal("Email sink lock poisoned".to_string()))?
+            .push(message.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> EmailMessage {
+        EmailMessage {
+            to: "guest@example.com".to_string(),
+            from_email: "noreply@olympuscloud.io".to_string(),
+            from_name: "Harbor Bistro".to_string(),
+            subject: "Reset your password".to_string(),
+            html_body: "<p>Reset</p>".to_string(),
+            text_body: "Reset".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_mime_has_both_parts() {
+        let formatted = String::from_utf8(message().to_mime().unwrap().formatted()).unwrap();
+        assert!(formatted.contains("Subject: Reset your password"));
+        assert!(formatted.contains("Harbor Bistro"));
+        assert!(formatted.contains("<noreply@olympuscloud.io>"));
+        assert!(formatted.contains("text/plain"));
+        assert!(formatted.contains("text/html"));
+    }
+
+    #[test]
+    fn test_invalid_recipient_is_rejected() {
+        let mut message = message();
+        message.to = "not an address".to_string();
+        assert!(matches!(message.to_mime(), Err(AuthError::Email(_))));
+    }
+
+    #[tokio::test]
+    async fn test_file_provider_writes_eml() {
+        let dir = std::env::temp_dir().join(format!("olympus-mail-{}", Uuid::new_v4()));
+        FileEmailProvider::new(&dir).send(&message()).await.unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
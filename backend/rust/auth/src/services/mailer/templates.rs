@@ -0,0 +1,347 @@
+// ============================================================================
+// OLYMPUS CLOUD - EMAIL TEMPLATES
+// ============================================================================
+// Module: auth/src/services/mailer/templates.rs
+// Description: Handlebars templates with per-tenant overrides and locale fallback
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::path::PathBuf;
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+
+pub const PASSWORD_RESET: &str = "password_reset";
+pub const EMAIL_VERIFICATION: &str = "email_verification";
+pub const MAGIC_LINK: &str = "magic_link";
+pub const INVITATION: &str = "invitation";
+
+/// The three files that make up a template: `<name>.subject.hbs`, `<name>.html.hbs`
+/// and `<name>.txt.hbs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Subject,
+    Html,
+    Text,
+}
+
+impl Part {
+    fn extension(&self) -> &'static str {
+        match self {
+            Part::Subject => "subject.hbs",
+            Part::Html => "html.hbs",
+            Part::Text => "txt.hbs",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html_body: String,
+    pub text_body: String,
+}
+
+/// Tenant look and feel made available to templates as `brand`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TenantBranding {
+    pub name: String,
+    pub logo_url: Option<String>,
+    pub primary_color: Option<String>,
+    pub secondary_color: Option<String>,
+    pub support_email: Option<String>,
+    /// The tenant's default language, used when the recipient has none
+    #[serde(skip)]
+    pub locale: Option<String>,
+}
+
+/// Templates are looked up under the template directory as
+/// `tenants/<tenant_id>/<locale>/<file>`, then `default/<locale>/<file>`, for each locale
+/// from most to least specific (`fr-CA`, `fr`, then the default locale). Each file is
+/// resolved on its own, so a tenant can override just the HTML of one email. The
+/// built-in English templates are used when no file is found.
+pub struct EmailTemplates {
+    root: Option<PathBuf>,
+    default_locale: String,
+    html: Handlebars<'static>,
+    plain: Handlebars<'static>,
+}
+
+impl EmailTemplates {
+    pub fn new(root: Option<PathBuf>, default_locale: &str) -> Self {
+        let html = Handlebars::new();
+        let mut plain = Handlebars::new();
+        plain.register_escape_fn(handlebars::no_escape);
+
+        Self {
+            root,
+            default_locale: normalize_locale(default_locale).unwrap_or_else(|| "en".to_string()),
+            html,
+            plain,
+        }
+    }
+
+    /// Locales to try, most specific first; malformed locales are ignored
+    pub fn locale_chain(&self, preferred: &[Option<&str>]) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        let requested = preferred.iter().flatten().filter_map(|locale| normalize_locale(locale));
+
+        for locale in requested.chain(std::iter::once(self.default_locale.clone())) {
+            let mut candidates = vec![locale.clone()];
+            if let Some((language, _)) = locale.split_once('-') {
+                candidates.push(language.to_string());
+            }
+            for candidate in candidates {
+                if !chain.contains(&candidate) {
+                    chain.push(candidate);
+                }
+            }
+        }
+        chain
+    }
+
+    /// Render template `name` for a tenant; `data` is extended with `brand`
+    pub async fn render<T: Serialize>(
+        &self,
+        tenant_id: Uuid,
+        name: &str,
+        locales: &[String],
+        branding: &TenantBranding,
+        data: &T,
+    ) -> Result<RenderedEmail> {
+        let mut context = serde_json::to_value(data)?;
+        if let Some(object) = context.as_object_mut() {
+            object.insert("brand".to_string(), serde_json::to_value(branding)?);
+        }
+
+        let subject = self.source(tenant_id, name, locales, Part::Subject).await?;
+        let html_body = self.source(tenant_id, name, locales, Part::Html).await?;
+        let text_body = self.source(tenant_id, name, locales, Part::Text).await?;
+
+        Ok(RenderedEmail {
+            subject: self.plain.render_template(&subject, &context)
+                .map_err(|e| template_error(name, e))?
+                .trim()
+                .to_string(),
+            html_body: self.html.render_template(&html_body, &context)
+                .map_err(|e| template_error(name, e))?,
+            text_body: self.plain.render_template(&text_body, &context)
+                .map_err(|e| template_error(name, e))?,
+        })
+    }
+
+    async fn source(&self, tenant_id: Uuid, name: &str, locales: &[String], part: Part) -> Result<String> {
+        let file = format!("{}.{}", name, part.extension());
+
+        if let Some(root) = &self.root {
+            for locale in locales {
+                let candidates = [
+                    root.join("tenants").join(tenant_id.to_string()).join(locale).join(&file),
+                    root.join("default").join(locale).join(&file),
+                ];
+                for path in candidates {
+                    match tokio::fs::read_to_string(&path).await {
+                        Ok(source) => return Ok(source),
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                        Err(e) => {
+                            return Err(AuthError::Email(format!("Failed to read {}: {}", path.display(), e)));
+                        }
+                    }
+                }
+            }
+        }
+
+        builtin(name, part)
+            .map(str::to_string)
+            .ok_or_else(|| AuthError::Email(format!("No template named {}", file)))
+    }
+}
+
+/// `en_US` and `en-us` both become `en-US`; anything that isn't a plain language tag is
+/// rejected, since locales become path segments
+pub fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.trim().replace('_', "-");
+    let mut parts = locale.split('-');
+    let language = parts.next()?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut normalized = language.to_ascii_lowercase();
+    if let Some(region) = parts.next() {
+        if !(2..=4).contains(&region.len()) || !region.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        normalized.push('-');
+        normalized.push_str(&region.to_ascii_uppercase());
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(normalized)
+}
+
+fn template_error(name: &str, e: handlebars::RenderError) -> AuthError {
+    AuthError::Email(format!("Failed to render {} template: {}", name, e))
+}
+
+macro_rules! html_layout {
+    ($($body:tt)+) => {
+        concat!(
+            "<!DOCTYPE html>\n<html>\n<body style=\"margin:0;padding:24px;background:#f4f5f7;font-family:Helvetica,Arial,sans-serif;color:#1f2933\">\n",
+            "<div style=\"max-width:560px;margin:0 auto;background:#ffffff;border-radius:8px;padding:32px\">\n",
+            "{{#if brand.logo_url}}<img src=\"{{brand.logo_url}}\" alt=\"{{brand.name}}\" style=\"max-height:48px;margin-bottom:24px\">{{else}}<h2>{{brand.name}}</h2>{{/if}}\n",
+            $($body)+,
+            "\n<p style=\"margin-top:32px;font-size:12px;color:#7b8794\">{{brand.name}}{{#if brand.support_email}} &middot; {{brand.support_email}}{{/if}}</p>\n",
+            "</div>\n</body>\n</html>\n"
+        )
+    };
+}
+
+macro_rules! html_button {
+    ($label:literal) => {
+        concat!(
+            "<p><a href=\"{{action_url}}\" style=\"display:inline-block;padding:12px 20px;border-radius:6px;color:#ffffff;text-decoration:none;background:{{#if brand.primary_color}}{{brand.primary_color}}{{else}}#3050c8{{/if}}\">",
+            $label,
+            "</a></p>\n<p style=\"font-size:12px;color:#7b8794\">{{action_url}}</p>"
+        )
+    };
+}
+
+fn builtin(name: &str, part: Part) -> Option<&'static str> {
+    let source = match (name, part) {
+        (PASSWORD_RESET, Part::Subject) => "Reset your {{brand.name}} password",
+        (PASSWORD_RESET, Part::Html) => html_layout!(concat!(
+            "<p>Someone asked to reset the password for {{email}}. If it was you, choose a new password below.</p>\n",
+            html_button!("Reset password"),
+            "\n<p>The link expires at {{expires_at}}. If you didn't ask for this, you can ignore this email.</p>"
+        )),
+        (PASSWORD_RESET, Part::Text) => concat!(
+            "Someone asked to reset the password for {{email}} at {{brand.name}}.\n\n",
+            "Choose a new password: {{action_url}}\n\n",
+            "The link expires at {{expires_at}}. If you didn't ask for this, you can ignore this email.\n"
+        ),
+        (EMAIL_VERIFICATION, Part::Subject) => "Confirm your email for {{brand.name}}",
+        (EMAIL_VERIFICATION, Part::Html) => html_layout!(concat!(
+            "<p>{{#if first_name}}Hi {{first_name}}, welcome{{else}}Welcome{{/if}} to {{brand.name}}. Please confirm your email address.</p>\n",
+            html_button!("Confirm email")
+        )),
+        (EMAIL_VERIFICATION, Part::Text) => concat!(
+            "{{#if first_name}}Hi {{first_name}}, welcome{{else}}Welcome{{/if}} to {{brand.name}}.\n\n",
+            "Confirm your email address: {{action_url}}\n"
+        ),
+        (MAGIC_LINK, Part::Subject) => "Your {{brand.name}} sign-in link",
+        (MAGIC_LINK, Part::Html) => html_layout!(concat!(
+            "<p>Use the button below to sign in as {{email}}. The link works once and expires at {{expires_at}}.</p>\n",
+            html_button!("Sign in")
+        )),
+        (MAGIC_LINK, Part::Text) => concat!(
+            "Sign in to {{brand.name}} as {{email}}: {{action_url}}\n\n",
+            "The link works once and expires at {{expires_at}}.\n"
+        ),
+        (INVITATION, Part::Subject) => "You're invited to join {{brand.name}}",
+        (INVITATION, Part::Html) => html_layout!(concat!(
+            "<p>You have been invited to join {{brand.name}}{{#if roles}} as {{#each roles}}{{#unless @first}}, {{/unless}}{{this}}{{/each}}{{/if}}.</p>\n",
+            html_button!("Accept invitation"),
+            "\n<p>The invitation expires at {{expires_at}}.</p>"
+        )),
+        (INVITATION, Part::Text) => concat!(
+            "You have been invited to join {{brand.name}}{{#if roles}} as {{#each roles}}{{#unless @first}}, {{/unless}}{{this}}{{/each}}{{/if}}.\n\n",
+            "Accept the invitation: {{action_url}}\n\n",
+            "The invitation expires at {{expires_at}}.\n"
+        ),
+        _ => return None,
+    };
+    Some(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn branding() -> TenantBranding {
+        TenantBranding {
+            name: "Harbor <Bistro>".to_string(),
+            logo_url: None,
+            primary_color: Some("#0a7d4f".to_string()),
+            secondary_color: None,
+            support_email: Some("help@harbor.example".to_string()),
+            locale: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("en_US").as_deref(), Some("en-US"));
+        assert_eq!(normalize_locale("FR-ca").as_deref(), Some("fr-CA"));
+        assert_eq!(normalize_locale("de").as_deref(), Some("de"));
+        assert_eq!(normalize_locale("../../etc"), None);
+        assert_eq!(normalize_locale("en-US-x-private"), None);
+    }
+
+    #[test]
+    fn test_locale_chain() {
+        let templates = EmailTemplates::new(None, "en");
+        assert_eq!(
+            templates.locale_chain(&[Some("fr_CA"), Some("es")]),
+            vec!["fr-CA", "fr", "es", "en"]
+        );
+        assert_eq!(templates.locale_chain(&[None, Some("bogus/path")]), vec!["en"]);
+    }
+
+    #[tokio::test]
+    async fn test_builtin_template_escapes_html_only() {
+        let templates = EmailTemplates::new(None, "en");
+        let rendered = templates
+            .render(
+                Uuid::new_v4(),
+                PASSWORD_RESET,
+                &templates.locale_chain(&[]),
+                &branding(),
+                &json!({ "email": "guest@example.com", "action_url": "https://app/reset?token=abc", "expires_at": "soon" }),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rendered.subject, "Reset your Harbor <Bistro> password");
+        assert!(rendered.html_body.contains("Harbor &lt;Bistro&gt;"));
+        assert!(rendered.html_body.contains("background:#0a7d4f"));
+        assert!(rendered.text_body.contains("https://app/reset?token=abc"));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_and_locale_overrides() {
+        let root = std::env::temp_dir().join(format!("olympus-templates-{}", Uuid::new_v4()));
+        let tenant_id = Uuid::new_v4();
+        let tenant_dir = root.join("tenants").join(tenant_id.to_string()).join("fr");
+        let default_dir = root.join("default").join("fr");
+        std::fs::create_dir_all(&tenant_dir).unwrap();
+        std::fs::create_dir_all(&default_dir).unwrap();
+        std::fs::write(default_dir.join("magic_link.subject.hbs"), "Connexion à {{brand.name}}").unwrap();
+        std::fs::write(tenant_dir.join("magic_link.txt.hbs"), "Bonjour ! {{action_url}}").unwrap();
+
+        let templates = EmailTemplates::new(Some(root.clone()), "en");
+        let data = json!({ "email": "guest@example.com", "action_url": "https://app/m", "expires_at": "soon" });
+        let rendered = templates
+            .render(tenant_id, MAGIC_LINK, &templates.locale_chain(&[Some("fr-CA")]), &branding(), &data)
+            .await
+            .unwrap();
+
+        assert_eq!(rendered.subject, "Connexion à Harbor <Bistro>");
+        assert_eq!(rendered.text_body, "Bonjour ! https://app/m");
+        // No French HTML anywhere, so the built-in English one is used
+        assert!(rendered.html_body.contains("Sign in"));
+
+        // Another tenant only gets the shared French files
+        let other = templates
+            .render(Uuid::new_v4(), MAGIC_LINK, &templates.locale_chain(&[Some("fr")]), &branding(), &data)
+            .await
+            .unwrap();
+        assert!(other.text_body.starts_with("Sign in to"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+// ============================================================================
+// OLYMPUS CLOUD - TENANT MEMBERSHIP HELPERS
+// ============================================================================
+// Module: auth/src/services/membership.rs
+// Description: Location limits and membership listings for identities that belong to several tenants
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use uuid::Uuid;
+use crate::models::{MembershipResponse, Tenant, TenantMembership, User};
+use super::terminal::location_scoped_permissions;
+
+/// Narrow grants to the locations a member was invited to. Each tenant-wide allow becomes
+/// one grant per location; denies and `@own` grants are kept once. `RequirePermission`
+/// refuses the narrowed grants outright, and `RequireScopedPermission` routes only accept
+/// them for resources at one of the member's locations.
+pub fn scope_to_locations(permissions: &[String], location_ids: &[Uuid]) -> Vec<String> {
+    let mut scoped: Vec<String> = Vec::new();
+    for location_id in location_ids {
+        for grant in location_scoped_permissions(permissions, *location_id) {
+            if !scoped.contains(&grant) {
+                scoped.push(grant);
+            }
+        }
+    }
+    scoped
+}
+
+/// Invitations are addressed to a mailbox, so case and surrounding whitespace don't matter
+pub fn same_email(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// The identity's place in the tenant it was created in. An account created from an
+/// invitation also has a membership row there, carrying the invitation's locations.
+pub fn home_membership(
+    user: &User,
+    tenant: &Tenant,
+    row: Option<&TenantMembership>,
+    current_tenant_id: Uuid,
+) -> MembershipResponse {
+    MembershipResponse {
+        tenant: tenant.to_response(),
+        roles: user.roles.clone(),
+        location_ids: row.map(|m| m.location_ids.clone()).unwrap_or_default(),
+        is_home: true,
+        is_current: tenant.id == current_tenant_id,
+        joined_at: user.created_at,
+    }
+}
+
+pub fn membership_response(membership: &TenantMembership, tenant: &Tenant, current_tenant_id: Uuid) -> MembershipResponse {
+    MembershipResponse {
+        tenant: tenant.to_response(),
+        roles: membership.roles.clone(),
+        location_ids: membership.location_ids.clone(),
+        is_home: false,
+        is_current: tenant.id == current_tenant_id,
+        joined_at: membership.created_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::Path, http::StatusCode, routing::get, Extension, Router};
+    use olympus_shared::authorization::{GrantedScope, RequirePermission, RequireScopedPermission};
+    use olympus_shared::models::permission::PermissionSet;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_scope_to_locations() {
+        let north = Uuid::new_v4();
+        let south = Uuid::new_v4();
+        let elsewhere = Uuid::new_v4();
+        let grants = vec![
+            "commerce.orders.*".to_string(),
+            format!("commerce.inventory.read@location:{}", elsewhere),
+            "commerce.products.read@own".to_string(),
+            "!commerce.orders.export".to_string(),
+        ];
+
+        assert_eq!(
+            scope_to_locations(&grants, &[north, south]),
+            vec![
+                format!("commerce.orders.*@location:{}", north),
+                "commerce.products.read@own".to_string(),
+                "!commerce.orders.export".to_string(),
+                format!("commerce.orders.*@location:{}", south),
+            ]
+        );
+        assert!(scope_to_locations(&grants, &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_location_limited_member_is_refused_elsewhere() {
+        let north = Uuid::new_v4();
+        let elsewhere = Uuid::new_v4();
+        let grants = scope_to_locations(&["commerce.orders.*".to_string()], &[north]);
+
+        // The path stands in for the location of the order the handler loaded
+        let app = Router::new()
+            .route(
+                "/locations/:location_id/orders",
+                get(|Path(location_id): Path<Uuid>, Extension(scope): Extension<GrantedScope>| async move {
+                    match scope.check(Some(location_id), None) {
+                        Ok(()) => StatusCode::OK,
+                        Err(_) => StatusCode::FORBIDDEN,
+                    }
+                })
+                .route_layer(RequireScopedPermission("commerce.orders.read")),
+            )
+            .route(
+                "/orders/export",
+                get(|| async { StatusCode::OK }).route_layer(RequirePermission("commerce.orders.read")),
+            );
+
+        let call = |uri: String| {
+            let app = app.clone();
+            let grants = grants.clone();
+            async move {
+                let mut request = axum::http::Request::builder().uri(uri).body(Body::empty()).unwrap();
+                request.extensions_mut().insert(PermissionSet::from_strings(&grants));
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(call(format!("/locations/{}/orders", north)).await, StatusCode::OK);
+        assert_eq!(call(format!("/locations/{}/orders", elsewhere)).await, StatusCode::FORBIDDEN);
+        // Tenant-wide routes are closed to location-limited members
+        assert_eq!(call("/orders/export".to_string()).await, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_same_email() {
+        assert!(same_email("Owner@Franchise.example ", "owner@franchise.example"));
+        assert!(!same_email("owner@franchise.example", "manager@franchise.example"));
+    }
+}
@@ -2,8 +2,14 @@ use std::sync::Arc;
 use uuid::Uuid;
 use chrono::Utc;
 use olympus_shared::database::DbPool;
+use olympus_shared::models::permission::{Role, UserRole};
+use olympus_shared::models::session::{ApiKey, MfaType, UserMfa};
 use crate::error::{AuthError, Result};
-use crate::models::{User, Tenant, RefreshToken};
+use crate::models::{
+    User, Tenant, RefreshToken, MagicLinkToken, ImpersonationSession, OAuthClient,
+    OAuthAuthorizationCode, OAuthConsent, SsoLoginState, UserIdentity, ScimExternalId, PosTerminal,
+    UserPin, Invitation, TenantMembership, WebAuthnCredential, WebAuthnChallenge,
+};
 
 pub struct UserRepository {
     _db: Arc<DbPool>,
@@ -36,6 +42,7 @@ impl UserRepository {
                 failed_login_attempts: 0,
                 locked_until: None,
                 password_changed_at: Some(Utc::now()),
+                force_password_change: false,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
                 deleted_at: None,
@@ -66,6 +73,7 @@ impl UserRepository {
             failed_login_attempts: 0,
             locked_until: None,
             password_changed_at: Some(Utc::now()),
+            force_password_change: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
             deleted_at: None,
@@ -124,6 +132,7 @@ impl UserRepository {
         Ok(RefreshToken {
             id: Uuid::new_v4(),
             token_hash: "hash".to_string(),
+            family_id: Uuid::new_v4(),
             user_id: Uuid::new_v4(),
             tenant_id: Uuid::new_v4(),
             device_id: None,
@@ -132,6 +141,12 @@ impl UserRepository {
             user_agent: "test".to_string(),
             expires_at: Utc::now() + chrono::Duration::days(30),
             revoked_at: None,
+            replaced_by: None,
+            client_id: None,
+            scopes: vec![],
+            location: serde_json::json!({}),
+            risk_score: 0,
+            risk_signals: vec![],
             created_at: Utc::now(),
         })
     }
@@ -144,12 +159,20 @@ impl UserRepository {
         Ok(())
     }
 
+    /// Store `replacement` and revoke the token it rotates, linking the two, in one transaction.
+    /// The replacement is inserted first so `replaced_by` can reference it; if the old token was
+    /// already revoked the transaction rolls back and false is returned.
+    pub async fn rotate_refresh_token(&self, _token_id: Uuid, _replacement: &RefreshToken) -> Result<bool> {
+        Ok(true)
+    }
+
     pub async fn find_active_refresh_tokens(&self, _user_id: Uuid) -> Result<Vec<RefreshToken>> {
         // Mock implementation - return a couple of sample sessions
         Ok(vec![
             RefreshToken {
                 id: Uuid::new_v4(),
                 token_hash: "hash1".to_string(),
+                family_id: Uuid::new_v4(),
                 user_id: Uuid::new_v4(),
                 tenant_id: Uuid::new_v4(),
                 device_id: Some("device1".to_string()),
@@ -158,11 +181,18 @@ impl UserRepository {
                 user_agent: "Mozilla/5.0 (iPhone; CPU iPhone OS 16_0 like Mac OS X)".to_string(),
                 expires_at: Utc::now() + chrono::Duration::days(30),
                 revoked_at: None,
+                replaced_by: None,
+                client_id: None,
+                scopes: vec![],
+                location: serde_json::json!({}),
+                risk_score: 0,
+                risk_signals: vec![],
                 created_at: Utc::now() - chrono::Duration::hours(2),
             },
             RefreshToken {
                 id: Uuid::new_v4(),
                 token_hash: "hash2".to_string(),
+                family_id: Uuid::new_v4(),
                 user_id: Uuid::new_v4(),
                 tenant_id: Uuid::new_v4(),
                 device_id: Some("device2".to_string()),
@@ -171,15 +201,27 @@ impl UserRepository {
                 user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7)".to_string(),
                 expires_at: Utc::now() + chrono::Duration::days(30),
                 revoked_at: None,
+                replaced_by: None,
+                client_id: None,
+                scopes: vec![],
+                location: serde_json::json!({}),
+                risk_score: 0,
+                risk_signals: vec![],
                 created_at: Utc::now() - chrono::Duration::days(1),
             },
         ])
     }
 
+    /// The user's most recent refresh tokens, newest first, including revoked and expired ones
+    pub async fn find_recent_refresh_tokens(&self, _user_id: Uuid, _limit: i64) -> Result<Vec<RefreshToken>> {
+        Ok(vec![])
+    }
+
     pub async fn find_refresh_token_by_id(&self, token_id: Uuid) -> Result<RefreshToken> {
         Ok(RefreshToken {
             id: token_id,
             token_hash: "hash".to_string(),
+            family_id: Uuid::new_v4(),
             user_id: Uuid::new_v4(),
             tenant_id: Uuid::new_v4(),
             device_id: Some("device1".to_string()),
@@ -188,6 +230,12 @@ impl UserRepository {
             user_agent: "test".to_string(),
             expires_at: Utc::now() + chrono::Duration::days(30),
             revoked_at: None,
+            replaced_by: None,
+            client_id: None,
+            scopes: vec![],
+            location: serde_json::json!({}),
+            risk_score: 0,
+            risk_signals: vec![],
             created_at: Utc::now(),
         })
     }
@@ -195,4 +243,265 @@ impl UserRepository {
     pub async fn revoke_all_user_tokens_except(&self, _user_id: Uuid, _except_token_id: Uuid) -> Result<()> {
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub async fn find_user_mfa(&self, _user_id: Uuid, _mfa_type: MfaType) -> Result<Option<UserMfa>> {
+        Ok(None)
+    }
+
+    pub async fn save_user_mfa(&self, _mfa: &UserMfa) -> Result<()> {
+        Ok(())
+    }
+
+    /// Advance a factor's last used TOTP step from `previous` to `step`; false if another
+    /// request moved it first
+    pub async fn claim_totp_step(&self, _mfa_id: Uuid, _previous: Option<i64>, _step: i64) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Remove an unused backup code from a factor in a single conditional update
+    /// (`UPDATE auth.user_mfa SET backup_codes = array_remove(backup_codes, $2)
+    /// WHERE id = $1 AND $2 = ANY(backup_codes) RETURNING id`); false if no row changed,
+    /// meaning the code was already used
+    pub async fn consume_backup_code(&self, _mfa_id: Uuid, _code_hash: &str) -> Result<bool> {
+        Ok(true)
+    }
+
+    pub async fn store_api_key(&self, _key: &ApiKey) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_api_key_by_hash(&self, _key_hash: &str) -> Result<ApiKey> {
+        Err(AuthError::ApiKeyNotFound)
+    }
+
+    pub async fn find_api_key_by_id(&self, _key_id: Uuid, _tenant_id: Uuid) -> Result<ApiKey> {
+        Err(AuthError::ApiKeyNotFound)
+    }
+
+    pub async fn find_api_keys_by_tenant(&self, _tenant_id: Uuid) -> Result<Vec<ApiKey>> {
+        Ok(vec![])
+    }
+
+    pub async fn update_api_key(&self, _key: &ApiKey) -> Result<()> {
+        Ok(())
+    }
+
+    /// Previous password hashes for a user, newest first
+    pub async fn find_password_history(&self, _user_id: Uuid, _limit: u32) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    pub async fn add_password_history(&self, _user_id: Uuid, _password_hash: &str) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn store_magic_link_token(&self, _token: &MagicLinkToken) -> Result<()> {
+        Ok(())
+    }
+
+    /// Atomically mark an unexpired, unused link as consumed; false if it was already used
+    pub async fn consume_magic_link_token(&self, _token_hash: &str) -> Result<bool> {
+        Ok(true)
+    }
+
+    pub async fn store_impersonation_session(&self, _session: &ImpersonationSession) -> Result<()> {
+        Ok(())
+    }
+
+    /// Mark a session ended; false if it was already ended
+    pub async fn end_impersonation_session(&self, _session_id: Uuid) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Unexpired, unended impersonation sessions targeting a user
+    pub async fn find_active_impersonation_sessions(&self, _user_id: Uuid) -> Result<Vec<ImpersonationSession>> {
+        Ok(vec![])
+    }
+
+    pub async fn store_oauth_client(&self, _client: &OAuthClient) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_oauth_client_by_client_id(&self, _client_id: &str) -> Result<OAuthClient> {
+        Err(AuthError::OAuthClientNotFound)
+    }
+
+    pub async fn find_oauth_client_by_id(&self, _id: Uuid) -> Result<OAuthClient> {
+        Err(AuthError::OAuthClientNotFound)
+    }
+
+    pub async fn find_oauth_clients_by_tenant(&self, _tenant_id: Uuid) -> Result<Vec<OAuthClient>> {
+        Ok(vec![])
+    }
+
+    pub async fn update_oauth_client(&self, _client: &OAuthClient) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn store_authorization_code(&self, _code: &OAuthAuthorizationCode) -> Result<()> {
+        Ok(())
+    }
+
+    /// Atomically mark an unexpired, unused code as consumed and return it; `None` if it was already used
+    pub async fn consume_authorization_code(&self, _code_hash: &str) -> Result<Option<OAuthAuthorizationCode>> {
+        Ok(None)
+    }
+
+    pub async fn find_oauth_consent(&self, _user_id: Uuid, _client_id: Uuid) -> Result<Option<OAuthConsent>> {
+        Ok(None)
+    }
+
+    pub async fn save_oauth_consent(&self, _consent: &OAuthConsent) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn store_sso_state(&self, _state: &SsoLoginState) -> Result<()> {
+        Ok(())
+    }
+
+    /// Atomically mark an unexpired, unused SSO state as consumed and return it; `None` if it was already used
+    pub async fn consume_sso_state(&self, _state_hash: &str) -> Result<Option<SsoLoginState>> {
+        Ok(None)
+    }
+
+    pub async fn find_user_by_identity(&self, _tenant_id: Uuid, _issuer: &str, _subject: &str) -> Result<Option<User>> {
+        Ok(None)
+    }
+
+    /// Insert the identity link, or refresh its email and last login if it already exists
+    pub async fn link_user_identity(&self, _identity: &UserIdentity) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_users_by_tenant(&self, _tenant_id: Uuid) -> Result<Vec<User>> {
+        Ok(vec![])
+    }
+
+    pub async fn find_roles_by_tenant(&self, _tenant_id: Uuid) -> Result<Vec<Role>> {
+        Ok(vec![])
+    }
+
+    pub async fn find_role_by_id(&self, _role_id: Uuid, _tenant_id: Uuid) -> Result<Role> {
+        Err(AuthError::RoleNotFound)
+    }
+
+    pub async fn store_role(&self, _role: &Role) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn update_role(&self, _role: &Role) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn delete_role(&self, _role_id: Uuid) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_user_roles_by_role(&self, _role_id: Uuid) -> Result<Vec<UserRole>> {
+        Ok(vec![])
+    }
+
+    pub async fn add_user_role(&self, _assignment: &UserRole) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn remove_user_role(&self, _user_id: Uuid, _role_id: Uuid) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_scim_external_ids(&self, _tenant_id: Uuid, _resource_type: &str) -> Result<Vec<ScimExternalId>> {
+        Ok(vec![])
+    }
+
+    /// Insert or replace the external id of a resource
+    pub async fn save_scim_external_id(&self, _mapping: &ScimExternalId) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn store_terminal(&self, _terminal: &PosTerminal) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_terminal_by_id(&self, _terminal_id: Uuid, _tenant_id: Uuid) -> Result<PosTerminal> {
+        Err(AuthError::TerminalNotFound)
+    }
+
+    pub async fn find_terminals_by_tenant(&self, _tenant_id: Uuid) -> Result<Vec<PosTerminal>> {
+        Ok(vec![])
+    }
+
+    pub async fn update_terminal(&self, _terminal: &PosTerminal) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_user_pin(&self, _user_id: Uuid) -> Result<Option<UserPin>> {
+        Ok(None)
+    }
+
+    /// Insert or replace the user's PIN
+    pub async fn save_user_pin(&self, _pin: &UserPin) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn store_invitation(&self, _invitation: &Invitation) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn find_invitation_by_id(&self, _invitation_id: Uuid) -> Result<Invitation> {
+        Err(AuthError::InvitationNotFound)
+    }
+
+    pub async fn find_invitations_by_tenant(&self, _tenant_id: Uuid) -> Result<Vec<Invitation>> {
+        Ok(vec![])
+    }
+
+    /// Record an invitation's answer or revocation; false if it was no longer pending
+    pub async fn close_invitation(&self, _invitation: &Invitation) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Memberships in tenants other than the user's home tenant
+    pub async fn find_memberships_by_user(&self, _user_id: Uuid) -> Result<Vec<TenantMembership>> {
+        Ok(vec![])
+    }
+
+    pub async fn find_membership(&self, _user_id: Uuid, _tenant_id: Uuid) -> Result<Option<TenantMembership>> {
+        Ok(None)
+    }
+
+    /// Insert or replace the user's membership in a tenant
+    pub async fn save_membership(&self, _membership: &TenantMembership) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn store_webauthn_challenge(&self, _challenge: &WebAuthnChallenge) -> Result<()> {
+        Ok(())
+    }
+
+    /// Mark a challenge consumed and return it; None if unknown or already used
+    pub async fn consume_webauthn_challenge(&self, _challenge_hash: &str) -> Result<Option<WebAuthnChallenge>> {
+        Ok(None)
+    }
+
+    pub async fn find_webauthn_credentials(&self, _user_id: Uuid) -> Result<Vec<WebAuthnCredential>> {
+        Ok(vec![])
+    }
+
+    /// Credential ids are unique across tenants, as authenticators generate them
+    pub async fn find_webauthn_credential(&self, _credential_id: &str) -> Result<Option<WebAuthnCredential>> {
+        Ok(None)
+    }
+
+    pub async fn store_webauthn_credential(&self, _credential: &WebAuthnCredential) -> Result<()> {
+        Ok(())
+    }
+
+    /// Save the signature counter, backup state and last use after an assertion
+    pub async fn update_webauthn_credential(&self, _credential: &WebAuthnCredential) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn delete_webauthn_credential(&self, _id: Uuid) -> Result<()> {
+        Ok(())
+    }
+}
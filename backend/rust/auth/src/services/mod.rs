@@ -1,25 +1,82 @@
 pub mod jwt;
 pub mod password;
+pub mod password_policy;
+pub mod totp;
+pub mod api_keys;
+pub mod keys;
+pub mod revocation;
+pub mod oauth;
+pub mod sso;
+pub mod scim;
+pub mod terminal;
+pub mod membership;
+pub mod risk;
+pub mod mailer;
+pub mod webauthn;
 pub mod mock_repository;
+pub mod impersonation;
 
 pub use mock_repository::UserRepository;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{Duration, Utc, DateTime};
+use olympus_shared::config::{Argon2Config, PasswordPolicy};
 use olympus_shared::database::DbPool;
-use olympus_shared::events::EventPublisher;
+use olympus_shared::events::{
+    auth_events, AuthenticationFailureReason, AuthenticationResult, DomainEvent, EventPublisher,
+    ImpersonatedRequestEvent, ImpersonationEndedEvent, ImpersonationStartedEvent,
+    InvitationRespondedEvent, UserInvitedEvent, MagicLinkRequestedEvent, MfaEnrolledEvent, RefreshTokenReuseDetectedEvent, SessionRiskDetectedEvent,
+    PasskeySignCountRegressedEvent, PasswordResetRequestedEvent, UserRegisteredEvent,
+};
+use olympus_shared::models::permission::{permissions, PermissionSet, Role, RoleHierarchy, UserRole};
+use olympus_shared::models::session::{ApiKey, MfaType, UserMfa};
 use crate::error::{AuthError, Result};
 use crate::models::*;
-use jwt::{JwtService, DeviceInfo};
+use jwt::{
+    ActorClaims, JwtService, DeviceInfo, OAuthTokenGrant, TokenSubject, INVITATION_DURATION_SECONDS, MAGIC_LINK_DURATION_SECONDS,
+    PIN_SWITCH_DURATION_SECONDS, TERMINAL_SESSION_DURATION_SECONDS,
+};
+use impersonation::{ImpersonationAuditStore, InMemoryImpersonationAuditStore};
+use keys::{InMemoryKeyStore, KeyStore, SigningKey, SigningKeyInfo};
+use mailer::Mailer;
+use risk::{GeoLocation, RiskAction, RiskAssessment, RiskEngine};
+use revocation::{InMemoryRevocationStore, RevocationKind, RevocationList, RevocationStore};
+use sso::{InMemorySsoConfigStore, OidcClient, OidcProviderConfig, SsoConfigStore, SSO_STATE_DURATION_SECONDS};
 use password::PasswordService;
+use totp::TotpService;
+use webauthn::{RelyingParty, VerifiedAssertion};
 use serde::{Serialize, Deserialize};
+use subtle::ConstantTimeEq;
+use tracing::{info, warn};
+use axum::http::Method;
+
+const BACKUP_CODE_COUNT: usize = 10;
+const IMPERSONATION_DEFAULT_MINUTES: i64 = 15;
+
+/// The device and connection a session is signed in from, recorded on its refresh token
+struct ClientDevice {
+    device_id: Option<String>,
+    device_name: Option<String>,
+    ip_address: String,
+    user_agent: String,
+}
 
 pub struct AuthService {
     db: Arc<DbPool>,
     jwt: JwtService,
     password: PasswordService,
+    totp: TotpService,
     user_repo: UserRepository,
+    revocations: Arc<RevocationList>,
+    sso_configs: Arc<dyn SsoConfigStore>,
+    oidc: OidcClient,
+    risk: RiskEngine,
+    webauthn: RelyingParty,
+    key_store: Arc<dyn KeyStore>,
+    impersonation_audit: Arc<dyn ImpersonationAuditStore>,
+    mailer: Option<Arc<Mailer>>,
     event_publisher: Option<Arc<tokio::sync::Mutex<EventPublisher>>>,
 }
 
@@ -41,16 +98,86 @@ impl AuthService {
             db: db.clone(),
             jwt,
             password: PasswordService::new(),
+            totp: TotpService::new("Olympus Cloud".to_string()),
             user_repo: UserRepository::new(db),
+            revocations: Arc::new(RevocationList::new(Arc::new(InMemoryRevocationStore::default()))),
+            sso_configs: Arc::new(InMemorySsoConfigStore::default()),
+            oidc: OidcClient::new(),
+            risk: RiskEngine::default(),
+            webauthn: RelyingParty::default(),
+            key_store: Arc::new(InMemoryKeyStore::new()),
+            impersonation_audit: Arc::new(InMemoryImpersonationAuditStore::new()),
+            mailer: None,
             event_publisher,
         })
     }
 
-    pub async fn login(&self, request: LoginRequest, ip_address: String, user_agent: String) -> Result<TokenResponse> {
+    /// Share the access token denylist with other instances (e.g. `RedisRevocationStore`)
+    pub fn with_revocation_store(mut self, store: Arc<dyn RevocationStore>) -> Self {
+        self.revocations = Arc::new(RevocationList::new(store));
+        self
+    }
+
+    /// Keep the impersonation audit trail in a persistent store (e.g. `PostgresImpersonationAuditStore`)
+    pub fn with_impersonation_audit_store(mut self, store: Arc<dyn ImpersonationAuditStore>) -> Self {
+        self.impersonation_audit = store;
+        self
+    }
+
+    /// Read tenant SSO settings from a persistent store, e.g. the platform configuration service
+    pub fn with_sso_config_store(mut self, store: Arc<dyn SsoConfigStore>) -> Self {
+        self.sso_configs = store;
+        self
+    }
+
+    /// Enforce a configured password policy instead of the defaults
+    pub fn with_password_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.password = self.password.with_policy(policy);
+        self
+    }
+
+    /// Hash new passwords with these Argon2id parameters; older hashes are upgraded at login
+    pub fn with_argon2_config(mut self, config: Argon2Config) -> Result<Self> {
+        self.password = self.password.with_argon2(config)?;
+        Ok(self)
+    }
+
+    /// Score sessions with a GeoIP database and anonymizer lists (`RiskEngine::from_config`)
+    pub fn with_risk_engine(mut self, engine: RiskEngine) -> Self {
+        self.risk = engine;
+        self
+    }
+
+    /// Accept passkeys for a configured relying party and origins (`RelyingParty::from_config`)
+    pub fn with_webauthn(mut self, relying_party: RelyingParty) -> Self {
+        self.webauthn = relying_party;
+        self
+    }
+
+    /// Persist the JWT keyset (e.g. `PostgresKeyStore`) so keys survive restarts and reach every instance
+    pub fn with_key_store(mut self, store: Arc<dyn KeyStore>) -> Self {
+        self.key_store = store;
+        self
+    }
+
+    /// Queue emails that carry a token (verification, password reset, login links, invitations)
+    /// in the outbox directly, so the token is never published in an event
+    pub fn with_mailer(mut self, mailer: Arc<Mailer>) -> Self {
+        self.mailer = Some(mailer);
+        self
+    }
+
+    /// The denylist, for spawning its background sync task
+    pub fn revocation_list(&self) -> Arc<RevocationList> {
+        self.revocations.clone()
+    }
+
+    pub async fn login(&self, request: LoginRequest, ip_address: String, user_agent: String) -> Result<LoginResponse> {
         let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
         if !tenant.is_active {
             return Err(AuthError::TenantInactive);
         }
+        self.ensure_password_login_allowed(tenant.id).await?;
 
         let mut user = self.user_repo.find_user_by_email(&request.email, tenant.id).await?;
 
@@ -71,305 +198,3795 @@ impl AuthService {
             return Err(AuthError::AccountInactive);
         }
 
+        let risk = self.screen_sign_in(&user, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+
         user.failed_login_attempts = 0;
         user.locked_until = None;
+
+        // Saved by the update_user below, whichever way the login continues
+        self.upgrade_password_hash(&mut user, &request.password);
+
+        // Password alone is not enough when a second factor is enrolled; the challenge
+        // also answers any step-up the risk engine asked for
+        if user.two_factor_enabled {
+            self.user_repo.update_user(&user).await?;
+            let mfa_token = self.jwt.generate_mfa_pending_token(user.id, tenant.id)?;
+            return Ok(LoginResponse::MfaRequired(MfaChallengeResponse {
+                mfa_required: true,
+                mfa_token,
+                methods: self.mfa_methods(&user).await?,
+                expires_in: 300,
+            }));
+        }
+
         user.last_login = Some(Utc::now());
         self.user_repo.update_user(&user).await?;
 
-        let session_id = Uuid::new_v4().to_string();
-        let device_info = DeviceInfo {
-            device_id: request.device_id.clone(),
-            user_agent: Some(user_agent.clone()),
-            ip_address: Some(ip_address.clone()),
+        let client = ClientDevice {
+            device_id: request.device_id,
+            device_name: request.device_name,
+            ip_address,
+            user_agent,
         };
+        let response = self.issue_tokens(&user, &tenant, client, risk).await?;
+        Ok(LoginResponse::Authenticated(Box::new(response)))
+    }
 
-        let token_pair = self.jwt.generate_token_pair(
-            user.id,
-            tenant.id,
-            user.email.clone(),
-            user.roles.clone(),
-            user.permissions.clone(),
-            session_id,
-            device_info,
-        )?;
+    /// Complete a login that was interrupted by an MFA challenge
+    pub async fn verify_mfa_login(&self, request: VerifyMfaRequest, ip_address: String, user_agent: String) -> Result<TokenResponse> {
+        let user_id = self.mfa_pending_user(&request.mfa_token)?;
 
-        let refresh_token = RefreshToken {
-            id: Uuid::new_v4(),
-            token_hash: self.password.hash_token(&token_pair.refresh_token)?,
-            user_id: user.id,
-            tenant_id: tenant.id,
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
+
+        if user.is_locked() {
+            return Err(AuthError::AccountLocked);
+        }
+
+        if !user.is_active || !tenant.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        let risk = self.screen_sign_in(&user, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+
+        // Backup codes are held by whichever factor was enrolled first
+        let mut verified = false;
+        for mut mfa in self.code_factors(user.id).await? {
+            if self.verify_second_factor(&mut mfa, &request.code).await? {
+                verified = true;
+                break;
+            }
+        }
+
+        if !verified {
+            user.failed_login_attempts += 1;
+            if user.failed_login_attempts >= 5 {
+                user.locked_until = Some(Utc::now() + Duration::minutes(30));
+            }
+            self.user_repo.update_user(&user).await?;
+            return Err(AuthError::InvalidMfaCode);
+        }
+
+        user.failed_login_attempts = 0;
+        user.last_login = Some(Utc::now());
+        self.user_repo.update_user(&user).await?;
+
+        let client = ClientDevice {
             device_id: request.device_id,
             device_name: request.device_name,
             ip_address,
             user_agent,
-            expires_at: Utc::now() + Duration::days(30),
-            revoked_at: None,
-            created_at: Utc::now(),
         };
-        self.user_repo.store_refresh_token(&refresh_token).await?;
+        self.issue_tokens(&user, &tenant, client, risk).await
+    }
 
-        Ok(TokenResponse {
-            access_token: token_pair.access_token,
-            refresh_token: token_pair.refresh_token,
-            token_type: "Bearer".to_string(),
-            expires_in: token_pair.expires_in,
-            user: user.to_response(&tenant),
+    /// Start TOTP enrollment by generating a fresh secret for the user
+    pub async fn begin_totp_enrollment(&self, user_id: Uuid) -> Result<TotpEnrollmentResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+
+        let mut mfa = match self.user_repo.find_user_mfa(user.id, MfaType::Totp).await? {
+            Some(mfa) if mfa.is_enabled => return Err(AuthError::MfaAlreadyEnabled),
+            Some(mfa) => mfa,
+            None => UserMfa::new(user.id, MfaType::Totp),
+        };
+
+        let secret = self.totp.generate_secret();
+        mfa.set_secret_key(secret.clone());
+        self.user_repo.save_user_mfa(&mfa).await?;
+
+        Ok(TotpEnrollmentResponse {
+            otpauth_uri: self.totp.provisioning_uri(&secret, &user.email),
+            secret,
         })
     }
 
-    pub async fn register(&self, request: RegisterRequest) -> Result<UserResponse> {
-        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
-        if !tenant.is_active {
-            return Err(AuthError::TenantInactive);
+    /// Confirm TOTP enrollment with a first valid code and hand out backup codes
+    pub async fn activate_totp(&self, user_id: Uuid, request: ActivateMfaRequest) -> Result<MfaActivatedResponse> {
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+        let mut mfa = self.user_repo.find_user_mfa(user.id, MfaType::Totp).await?
+            .ok_or(AuthError::MfaNotEnrolled)?;
+
+        if mfa.is_enabled {
+            return Err(AuthError::MfaAlreadyEnabled);
         }
 
-        if self.user_repo.user_exists(&request.email, tenant.id).await? {
-            return Err(AuthError::EmailAlreadyExists);
+        if !self.verify_totp(&mut mfa, &request.code).await? {
+            return Err(AuthError::InvalidMfaCode);
         }
 
-        let password_hash = self.password.hash_password(&request.password)?;
+        let backup_codes = self.issue_backup_codes(&mut mfa)?;
+        mfa.enable();
+        self.user_repo.save_user_mfa(&mfa).await?;
 
-        let user = User {
-            id: Uuid::new_v4(),
-            tenant_id: tenant.id,
-            email: request.email.clone(),
-            password_hash,
-            first_name: request.first_name,
-            last_name: request.last_name,
-            display_name: None,
-            phone: request.phone,
-            avatar_url: None,
-            roles: vec!["user".to_string()],
-            permissions: vec![],
-            is_active: true,
-            email_verified: false,
-            phone_verified: false,
-            two_factor_enabled: false,
-            last_login: None,
-            failed_login_attempts: 0,
-            locked_until: None,
-            password_changed_at: Some(Utc::now()),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            deleted_at: None,
-        };
+        user.two_factor_enabled = true;
+        user.updated_at = Utc::now();
+        self.user_repo.update_user(&user).await?;
 
-        let created_user = self.user_repo.create_user(&user).await?;
-        Ok(created_user.to_response(&tenant))
+        self.publish_event(
+            auth_events::MFA_ENROLLED,
+            user.id,
+            user.tenant_id,
+            MfaEnrolledEvent {
+                user_id: user.id,
+                tenant_id: user.tenant_id,
+                mfa_type: olympus_shared::events::MfaType::TOTP,
+                backup_codes_generated: backup_codes.len() as u32,
+            },
+        ).await;
+
+        Ok(MfaActivatedResponse { backup_codes })
     }
 
-    pub async fn refresh_token(&self, refresh_token_str: &str, _ip_address: String, _user_agent: String) -> Result<TokenResponse> {
-        let token_hash = self.password.hash_token(refresh_token_str)?;
-        let refresh_token = self.user_repo.find_refresh_token(&token_hash).await?;
-
-        if refresh_token.revoked_at.is_some() {
-            return Err(AuthError::TokenRevoked);
-        }
+    /// Turn off MFA after re-checking both the password and a second factor
+    pub async fn disable_mfa(&self, user_id: Uuid, request: DisableMfaRequest) -> Result<()> {
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
 
-        if refresh_token.expires_at < Utc::now() {
-            return Err(AuthError::TokenExpired);
+        if !self.password.verify_password(&request.password, &user.password_hash)? {
+            return Err(AuthError::InvalidCredentials);
         }
 
-        let user = self.user_repo.find_user_by_id(refresh_token.user_id).await?;
-        let tenant = self.user_repo.find_tenant_by_id(refresh_token.tenant_id).await?;
+        let mut mfa = self.user_repo.find_user_mfa(user.id, MfaType::Totp).await?
+            .filter(|mfa| mfa.is_enabled)
+            .ok_or(AuthError::MfaNotEnrolled)?;
 
-        if !user.is_active || !tenant.is_active {
-            return Err(AuthError::AccountInactive);
+        if !self.verify_second_factor(&mut mfa, &request.code).await? {
+            return Err(AuthError::InvalidMfaCode);
         }
 
-        self.user_repo.revoke_refresh_token(refresh_token.id).await?;
+        mfa.disable();
+        mfa.backup_codes.clear();
+        self.user_repo.save_user_mfa(&mfa).await?;
 
-        let session_id = Uuid::new_v4().to_string();
-        let device_info = DeviceInfo {
-            device_id: refresh_token.device_id.clone(),
-            user_agent: Some(refresh_token.user_agent.clone()),
-            ip_address: Some(refresh_token.ip_address.clone()),
-        };
+        // Registered passkeys remain a second factor
+        user.two_factor_enabled = self.is_mfa_enabled(user.id, MfaType::WebAuthn).await?;
+        user.updated_at = Utc::now();
+        self.user_repo.update_user(&user).await?;
+        Ok(())
+    }
 
-        let token_pair = self.jwt.generate_token_pair(
-            user.id,
-            tenant.id,
-            user.email.clone(),
-            user.roles.clone(),
-            user.permissions.clone(),
-            session_id,
-            device_info,
-        )?;
+    /// Accept either a current TOTP code or an unused backup code
+    async fn verify_second_factor(&self, mfa: &mut UserMfa, code: &str) -> Result<bool> {
+        let code = code.trim();
 
-        Ok(TokenResponse {
-            access_token: token_pair.access_token,
-            refresh_token: token_pair.refresh_token,
-            token_type: "Bearer".to_string(),
-            expires_in: token_pair.expires_in,
-            user: user.to_response(&tenant),
-        })
-    }
+        if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+            return self.verify_totp(mfa, code).await;
+        }
 
-    pub async fn logout(&self, user_id: Uuid) -> Result<()> {
-        self.user_repo.revoke_all_user_tokens(user_id).await?;
-        Ok(())
-    }
+        let code_hash = self.password.hash_token(code)?;
+        if !mfa.backup_codes.contains(&code_hash) {
+            return Ok(false);
+        }
 
-    pub async fn verify_token(&self, token: &str) -> Result<Claims> {
-        let validation = self.jwt.validate_access_token(token)
-            .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        // Removed in one conditional update, so two logins racing with the same code can't both pass
+        if !self.user_repo.consume_backup_code(mfa.id, &code_hash).await? {
+            return Ok(false);
+        }
+        mfa.use_backup_code(&code_hash);
 
-        // Convert from JWT TokenValidation to our Claims format
-        Ok(Claims {
-            sub: validation.claims.sub.parse()
-                .map_err(|_| AuthError::InvalidToken("Invalid user ID".to_string()))?,
-            tenant_id: validation.claims.tenant_id.parse()
-                .map_err(|_| AuthError::InvalidToken("Invalid tenant ID".to_string()))?,
-            email: validation.claims.email,
-            roles: validation.claims.roles,
-            permissions: validation.claims.permissions,
-            session_id: validation.claims.session_id.parse()
-                .map_err(|_| AuthError::InvalidToken("Invalid session ID".to_string()))?,
-            iat: validation.claims.iat,
-            exp: validation.claims.exp,
-        })
+        Ok(true)
     }
 
-    pub async fn get_user(&self, user_id: Uuid) -> Result<(User, Tenant)> {
-        let user = self.user_repo.find_user_by_id(user_id).await?;
-        let tenant = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
-        Ok((user, tenant))
-    }
+    /// Check a TOTP code and consume its time step, so a code observed in transit cannot be
+    /// replayed for the rest of its validity window
+    async fn verify_totp(&self, mfa: &mut UserMfa, code: &str) -> Result<bool> {
+        let Some(secret) = mfa.secret_key.clone() else {
+            return Ok(false);
+        };
+        let Some(step) = self.totp.verify_step(&secret, code)? else {
+            return Ok(false);
+        };
 
-    pub async fn forgot_password(&self, request: ForgotPasswordRequest) -> Result<()> {
-        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
-        if !tenant.is_active {
-            return Err(AuthError::TenantInactive);
+        let step = step as i64;
+        let previous = mfa.last_used_step;
+        if !mfa.record_totp_step(step) {
+            return Ok(false);
         }
 
-        // Check if user exists - but don't reveal whether they exist or not for security
-        if let Ok(user) = self.user_repo.find_user_by_email(&request.email, tenant.id).await {
-            if user.is_active {
-                // Generate password reset token
-                let reset_token = self.jwt.generate_password_reset_token(user.id, &user.email)?;
+        // Conditional on the step we read, so two concurrent logins with one code can't both pass
+        self.user_repo.claim_totp_step(mfa.id, previous, step).await
+    }
+
+    /// Generate backup codes on a factor; they are shown once and only their hashes are kept
+    fn issue_backup_codes(&self, mfa: &mut UserMfa) -> Result<Vec<String>> {
+        mfa.generate_backup_codes(BACKUP_CODE_COUNT);
+        let backup_codes = mfa.backup_codes.clone();
+        mfa.backup_codes = backup_codes
+            .iter()
+            .map(|code| self.password.hash_token(code))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(backup_codes)
+    }
 
-                // In a real implementation, you would:
-                // 1. Store the token in the database with expiration
-                // 2. Send an email with the reset link
-                // For now, we'll just log it (DO NOT do this in production!)
-                println!("Password reset token for {}: {}", request.email, reset_token);
+    async fn is_mfa_enabled(&self, user_id: Uuid, mfa_type: MfaType) -> Result<bool> {
+        Ok(self.user_repo.find_user_mfa(user_id, mfa_type).await?.is_some_and(|mfa| mfa.is_enabled))
+    }
 
-                // TODO: Implement email service and token storage
-                // self.email_service.send_password_reset_email(&user.email, &reset_token).await?;
+    /// Enabled factors that `verify_second_factor` can check a code against
+    async fn code_factors(&self, user_id: Uuid) -> Result<Vec<UserMfa>> {
+        let mut factors = vec![];
+        for mfa_type in [MfaType::Totp, MfaType::WebAuthn] {
+            if let Some(mfa) = self.user_repo.find_user_mfa(user_id, mfa_type).await?.filter(|mfa| mfa.is_enabled) {
+                factors.push(mfa);
             }
         }
 
-        // Always return success to avoid email enumeration attacks
-        Ok(())
+        if factors.is_empty() {
+            return Err(AuthError::MfaNotEnrolled);
+        }
+        Ok(factors)
     }
 
-    pub async fn reset_password(&self, request: ResetPasswordRequest) -> Result<()> {
-        // Verify the reset token
-        let user_id = self.jwt.verify_special_token(&request.token, "password_reset")
-            .map_err(|_| AuthError::InvalidToken("Invalid or expired reset token".to_string()))?;
-
-        let mut user = self.user_repo.find_user_by_id(user_id).await?;
-
-        if !user.is_active {
-            return Err(AuthError::AccountInactive);
+    /// The ways a user can answer an MFA challenge
+    async fn mfa_methods(&self, user: &User) -> Result<Vec<String>> {
+        let mut methods = vec![];
+        if self.is_mfa_enabled(user.id, MfaType::Totp).await? {
+            methods.push("totp".to_string());
+        }
+        if !self.user_repo.find_webauthn_credentials(user.id).await?.is_empty() {
+            methods.push("passkey".to_string());
         }
+        methods.push("backup_code".to_string());
+        Ok(methods)
+    }
 
-        // Hash the new password
-        let new_password_hash = self.password.hash_password(&request.new_password)?;
+    /// The user an `mfa_pending` token was issued to
+    fn mfa_pending_user(&self, mfa_token: &str) -> Result<Uuid> {
+        let claims = self.jwt.validate_purpose_token(mfa_token, "mfa_pending")
+            .map_err(|_| AuthError::InvalidToken("Invalid or expired MFA token".to_string()))?;
+        claims.sub.parse()
+            .map_err(|_| AuthError::InvalidToken("Invalid user ID".to_string()))
+    }
 
-        // Update the user's password
-        user.update_password(new_password_hash);
-        self.user_repo.update_user(&user).await?;
+    /// Start registering a passkey for the signed-in user
+    pub async fn begin_passkey_registration(&self, user_id: Uuid) -> Result<PasskeyCreationOptions> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        let existing = self.user_repo.find_webauthn_credentials(user.id).await?;
 
-        // Revoke all existing refresh tokens for security
-        self.user_repo.revoke_all_user_tokens(user_id).await?;
+        let challenge = self
+            .start_webauthn_ceremony(user.tenant_id, Some(user.id), webauthn::CEREMONY_REGISTRATION)
+            .await?;
+        let display_name = user
+            .display_name
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", user.first_name, user.last_name));
 
-        Ok(())
+        Ok(self.webauthn.creation_options(challenge, user.id, &user.email, &display_name, &existing))
     }
 
-    pub async fn change_password(&self, user_id: Uuid, request: ChangePasswordRequest) -> Result<()> {
+    /// Store the passkey the browser created. The first passkey also enrolls passkeys as a
+    /// second factor, with backup codes if the user had no other factor.
+    pub async fn finish_passkey_registration(
+        &self,
+        user_id: Uuid,
+        request: FinishPasskeyRegistrationRequest,
+    ) -> Result<PasskeyRegisteredResponse> {
         let mut user = self.user_repo.find_user_by_id(user_id).await?;
 
-        if !user.is_active {
-            return Err(AuthError::AccountInactive);
+        let (ceremony, challenge) = self
+            .consume_webauthn_ceremony(&request.credential.response.client_data_json, webauthn::CEREMONY_REGISTRATION)
+            .await?;
+        if ceremony.user_id != Some(user.id) {
+            return Err(AuthError::InvalidPasskey("Challenge was issued to a different user".to_string()));
         }
 
-        // Verify current password
-        if !self.password.verify_password(&request.current_password, &user.password_hash)? {
-            return Err(AuthError::InvalidCredentials);
+        let verified = self.webauthn.verify_registration(&challenge, &request.credential)?;
+        if self.user_repo.find_webauthn_credential(&verified.credential_id).await?.is_some() {
+            return Err(AuthError::PasskeyAlreadyRegistered);
         }
 
-        // Hash the new password
-        let new_password_hash = self.password.hash_password(&request.new_password)?;
+        let credential = WebAuthnCredential {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            tenant_id: user.tenant_id,
+            credential_id: verified.credential_id,
+            public_key: verified.public_key,
+            algorithm: verified.algorithm as i32,
+            sign_count: verified.sign_count as i64,
+            transports: request.credential.response.transports,
+            aaguid: verified.aaguid,
+            name: request.name.unwrap_or_else(|| "Passkey".to_string()),
+            backup_eligible: verified.backup_eligible,
+            backed_up: verified.backed_up,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        self.user_repo.store_webauthn_credential(&credential).await?;
+        info!("User {} registered passkey {}", user.id, credential.id);
+
+        let mut mfa = self
+            .user_repo
+            .find_user_mfa(user.id, MfaType::WebAuthn)
+            .await?
+            .unwrap_or_else(|| UserMfa::new(user.id, MfaType::WebAuthn));
+
+        let mut backup_codes = vec![];
+        if !mfa.is_enabled {
+            // Users who already have TOTP keep its backup codes
+            if !user.two_factor_enabled {
+                backup_codes = self.issue_backup_codes(&mut mfa)?;
+            }
+            mfa.enable();
+            self.user_repo.save_user_mfa(&mfa).await?;
 
-        // Update the user's password
-        user.update_password(new_password_hash);
-        self.user_repo.update_user(&user).await?;
+            user.two_factor_enabled = true;
+            user.updated_at = Utc::now();
+            self.user_repo.update_user(&user).await?;
 
-        // Revoke all existing refresh tokens except current session for security
-        // In a real implementation, you might want to keep the current session active
-        self.user_repo.revoke_all_user_tokens(user_id).await?;
+            self.publish_event(
+                auth_events::MFA_ENROLLED,
+                user.id,
+                user.tenant_id,
+                MfaEnrolledEvent {
+                    user_id: user.id,
+                    tenant_id: user.tenant_id,
+                    mfa_type: olympus_shared::events::MfaType::Passkey,
+                    backup_codes_generated: backup_codes.len() as u32,
+                },
+            ).await;
+        }
 
-        Ok(())
+        Ok(PasskeyRegisteredResponse {
+            passkey: credential.into(),
+            backup_codes,
+        })
     }
 
-    pub async fn verify_email(&self, token: &str) -> Result<()> {
-        // Verify the email verification token
-        let user_id = self.jwt.verify_special_token(token, "email_verification")
-            .map_err(|_| AuthError::InvalidToken("Invalid or expired verification token".to_string()))?;
+    pub async fn list_passkeys(&self, user_id: Uuid) -> Result<Vec<PasskeyResponse>> {
+        let credentials = self.user_repo.find_webauthn_credentials(user_id).await?;
+        Ok(credentials.into_iter().map(PasskeyResponse::from).collect())
+    }
 
+    /// Remove one of the user's passkeys; removing the last one unenrolls the passkey factor
+    pub async fn delete_passkey(&self, user_id: Uuid, passkey_id: Uuid) -> Result<()> {
         let mut user = self.user_repo.find_user_by_id(user_id).await?;
+        let credentials = self.user_repo.find_webauthn_credentials(user.id).await?;
+        if !credentials.iter().any(|credential| credential.id == passkey_id) {
+            return Err(AuthError::PasskeyNotFound);
+        }
 
-        // Mark email as verified
-        user.verify_email();
-        self.user_repo.update_user(&user).await?;
+        self.user_repo.delete_webauthn_credential(passkey_id).await?;
+        info!("User {} removed passkey {}", user.id, passkey_id);
+
+        if credentials.len() == 1 {
+            if let Some(mut mfa) = self.user_repo.find_user_mfa(user.id, MfaType::WebAuthn).await? {
+                mfa.disable();
+                mfa.backup_codes.clear();
+                self.user_repo.save_user_mfa(&mfa).await?;
+            }
+
+            user.two_factor_enabled = self.is_mfa_enabled(user.id, MfaType::Totp).await?;
+            user.updated_at = Utc::now();
+            self.user_repo.update_user(&user).await?;
+        }
 
         Ok(())
     }
 
-    pub async fn get_active_sessions(&self, user_id: Uuid) -> Result<Vec<SessionInfo>> {
-        // Get all active refresh tokens for the user (which represent sessions)
+    /// Begin a passwordless login. With an email the user's passkeys are listed; without
+    /// one the browser offers the passkeys it holds for the site.
+    pub async fn begin_passkey_login(&self, request: PasskeyLoginStartRequest) -> Result<PasskeyRequestOptions> {
+        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        self.ensure_password_login_allowed(tenant.id).await?;
+
+        // An unknown email gets the same answer as no email, so accounts can't be probed
+        let (user_id, allowed) = match &request.email {
+            Some(email) => match self.user_repo.find_user_by_email(email, tenant.id).await {
+                Ok(user) => (Some(user.id), self.user_repo.find_webauthn_credentials(user.id).await?),
+                Err(_) => (None, vec![]),
+            },
+            None => (None, vec![]),
+        };
+
+        let challenge = self
+            .start_webauthn_ceremony(tenant.id, user_id, webauthn::CEREMONY_AUTHENTICATION)
+            .await?;
+        Ok(self.webauthn.request_options(challenge, &allowed))
+    }
+
+    /// Finish a passwordless login. A passkey that verified the user (PIN or biometric)
+    /// counts as both factors; one that only confirmed presence replaces the password alone.
+    pub async fn finish_passkey_login(&self, request: PasskeyLoginRequest, ip_address: String, user_agent: String) -> Result<LoginResponse> {
+        let (ceremony, challenge) = self
+            .consume_webauthn_ceremony(&request.credential.response.client_data_json, webauthn::CEREMONY_AUTHENTICATION)
+            .await?;
+
+        let mut stored = self
+            .user_repo
+            .find_webauthn_credential(request.credential.raw_id.trim_end_matches('='))
+            .await?
+            .filter(|credential| {
+                credential.tenant_id == ceremony.tenant_id
+                    && (ceremony.user_id.is_none() || ceremony.user_id == Some(credential.user_id))
+            })
+            .ok_or_else(|| AuthError::InvalidPasskey("Unknown passkey".to_string()))?;
+
+        let mut user = self.user_repo.find_user_by_id(stored.user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(ceremony.tenant_id).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        self.ensure_password_login_allowed(tenant.id).await?;
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+        if user.is_locked() {
+            return Err(AuthError::AccountLocked);
+        }
+
+        let assertion = self
+            .verify_passkey_assertion(&challenge, &request.credential, &mut stored, &ip_address, &user_agent)
+            .await?;
+
+        let risk = self.screen_sign_in(&user, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+
+        user.failed_login_attempts = 0;
+
+        if !assertion.user_verified && user.two_factor_enabled {
+            self.user_repo.update_user(&user).await?;
+            let mfa_token = self.jwt.generate_mfa_pending_token(user.id, tenant.id)?;
+            return Ok(LoginResponse::MfaRequired(MfaChallengeResponse {
+                mfa_required: true,
+                mfa_token,
+                methods: self.mfa_methods(&user).await?,
+                expires_in: 300,
+            }));
+        }
+
+        user.last_login = Some(Utc::now());
+        self.user_repo.update_user(&user).await?;
+
+        let client = ClientDevice {
+            device_id: request.device_id,
+            device_name: request.device_name,
+            ip_address,
+            user_agent,
+        };
+        let response = self.issue_tokens(&user, &tenant, client, risk).await?;
+        Ok(LoginResponse::Authenticated(Box::new(response)))
+    }
+
+    /// Start answering an MFA challenge with one of the user's passkeys
+    pub async fn begin_passkey_mfa(&self, request: PasskeyMfaStartRequest) -> Result<PasskeyRequestOptions> {
+        let user_id = self.mfa_pending_user(&request.mfa_token)?;
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+
+        let credentials = self.user_repo.find_webauthn_credentials(user.id).await?;
+        if credentials.is_empty() {
+            return Err(AuthError::MfaNotEnrolled);
+        }
+
+        let challenge = self
+            .start_webauthn_ceremony(user.tenant_id, Some(user.id), webauthn::CEREMONY_MFA)
+            .await?;
+        Ok(self.webauthn.request_options(challenge, &credentials))
+    }
+
+    /// Complete a login that was interrupted by an MFA challenge with a passkey assertion
+    pub async fn verify_passkey_mfa(&self, request: PasskeyMfaRequest, ip_address: String, user_agent: String) -> Result<TokenResponse> {
+        let user_id = self.mfa_pending_user(&request.mfa_token)?;
+
+        let (ceremony, challenge) = self
+            .consume_webauthn_ceremony(&request.credential.response.client_data_json, webauthn::CEREMONY_MFA)
+            .await?;
+        if ceremony.user_id != Some(user_id) {
+            return Err(AuthError::InvalidPasskey("Challenge was issued to a different user".to_string()));
+        }
+
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
+
+        if user.is_locked() {
+            return Err(AuthError::AccountLocked);
+        }
+
+        if !user.is_active || !tenant.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        let risk = self.screen_sign_in(&user, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+
+        let mut stored = self
+            .user_repo
+            .find_webauthn_credential(request.credential.raw_id.trim_end_matches('='))
+            .await?
+            .filter(|credential| credential.user_id == user.id)
+            .ok_or_else(|| AuthError::InvalidPasskey("Unknown passkey".to_string()))?;
+
+        if let Err(e) = self
+            .verify_passkey_assertion(&challenge, &request.credential, &mut stored, &ip_address, &user_agent)
+            .await
+        {
+            user.failed_login_attempts += 1;
+            if user.failed_login_attempts >= 5 {
+                user.locked_until = Some(Utc::now() + Duration::minutes(30));
+            }
+            self.user_repo.update_user(&user).await?;
+            return Err(e);
+        }
+
+        user.failed_login_attempts = 0;
+        user.last_login = Some(Utc::now());
+        self.user_repo.update_user(&user).await?;
+
+        let client = ClientDevice {
+            device_id: request.device_id,
+            device_name: request.device_name,
+            ip_address,
+            user_agent,
+        };
+        self.issue_tokens(&user, &tenant, client, risk).await
+    }
+
+    /// Store a fresh challenge for a ceremony, hashed, and return it
+    async fn start_webauthn_ceremony(&self, tenant_id: Uuid, user_id: Option<Uuid>, ceremony: &str) -> Result<String> {
+        let challenge = self.webauthn.generate_challenge()?;
+
+        self.user_repo.store_webauthn_challenge(&WebAuthnChallenge {
+            id: Uuid::new_v4(),
+            challenge_hash: self.password.hash_token(&challenge)?,
+            tenant_id,
+            user_id,
+            ceremony: ceremony.to_string(),
+            expires_at: Utc::now() + Duration::seconds(self.webauthn.challenge_timeout_seconds()),
+            consumed_at: None,
+            created_at: Utc::now(),
+        }).await?;
+
+        Ok(challenge)
+    }
+
+    /// Consume the stored ceremony a browser response claims to answer, returning it with
+    /// its challenge; a replayed response finds it already consumed
+    async fn consume_webauthn_ceremony(&self, client_data_json: &str, ceremony: &str) -> Result<(WebAuthnChallenge, String)> {
+        let challenge = self.webauthn.client_challenge(client_data_json)?;
+        let challenge_hash = self.password.hash_token(&challenge)?;
+
+        let stored = self
+            .user_repo
+            .consume_webauthn_challenge(&challenge_hash)
+            .await?
+            .filter(|stored| stored.ceremony == ceremony && stored.expires_at > Utc::now())
+            .ok_or_else(|| AuthError::InvalidPasskey("Unknown or expired challenge".to_string()))?;
+
+        Ok((stored, challenge))
+    }
+
+    /// Verify an assertion with a stored passkey and record its use. A signature counter
+    /// that went backwards is reported, and refused unless configured otherwise.
+    async fn verify_passkey_assertion(
+        &self,
+        challenge: &str,
+        credential: &AuthenticationCredential,
+        stored: &mut WebAuthnCredential,
+        ip_address: &str,
+        user_agent: &str,
+    ) -> Result<VerifiedAssertion> {
+        let assertion = self.webauthn.verify_assertion(challenge, credential, stored)?;
+        let stored_count = stored.sign_count.clamp(0, u32::MAX as i64) as u32;
+
+        if webauthn::sign_count_regressed(stored_count, assertion.sign_count) {
+            let rejected = self.webauthn.rejects_sign_count_regression();
+            warn!(
+                "Passkey {} of user {} signed with counter {} after {}",
+                stored.id, stored.user_id, assertion.sign_count, stored_count
+            );
+            self.publish_event(
+                auth_events::PASSKEY_SIGN_COUNT_REGRESSED,
+                stored.user_id,
+                stored.tenant_id,
+                PasskeySignCountRegressedEvent {
+                    user_id: stored.user_id,
+                    tenant_id: stored.tenant_id,
+                    passkey_id: stored.id,
+                    credential_id: stored.credential_id.clone(),
+                    stored_sign_count: stored_count,
+                    received_sign_count: assertion.sign_count,
+                    rejected,
+                    ip_address: ip_address.to_string(),
+                    user_agent: user_agent.to_string(),
+                },
+            ).await;
+
+            if rejected {
+                return Err(AuthError::InvalidPasskey(
+                    "Signature counter went backwards; the passkey may have been cloned".to_string(),
+                ));
+            }
+        }
+
+        // Kept at its highest so a lagging copy keeps being reported
+        stored.sign_count = stored.sign_count.max(assertion.sign_count as i64);
+        stored.backed_up = assertion.backed_up;
+        stored.last_used_at = Some(Utc::now());
+        self.user_repo.update_webauthn_credential(stored).await?;
+
+        Ok(assertion)
+    }
+
+    /// Issue a token pair for an authenticated user and persist the refresh token
+    async fn issue_tokens(
+        &self,
+        user: &User,
+        tenant: &Tenant,
+        client: ClientDevice,
+        risk: RiskAssessment,
+    ) -> Result<TokenResponse> {
+        let permissions = self.effective_permissions(user).await?;
+
+        // Every login starts a new refresh token family; the family id doubles as the session id
+        let (response, refresh_token) =
+            self.sign_family_tokens(user, tenant, permissions, Uuid::new_v4(), client, &risk)?;
+        self.user_repo.store_refresh_token(&refresh_token).await?;
+        self.publish_session_risk(user, &risk, &refresh_token).await;
+
+        Ok(response)
+    }
+
+    /// Score a sign-in or refresh against the user's recent sessions
+    async fn assess_risk(&self, user: &User, device_id: Option<&str>, ip_address: &str) -> Result<RiskAssessment> {
+        let history = self.user_repo.find_recent_refresh_tokens(user.id, risk::RISK_HISTORY_LIMIT).await?;
+        Ok(self.risk.assess(ip_address, device_id, &history, Utc::now()))
+    }
+
+    /// Refuse sign-ins the risk engine blocks, or asks to step up when the user has no second
+    /// factor; anything less is recorded on the session and published when tokens are issued
+    async fn screen_sign_in(&self, user: &User, device_id: Option<&str>, ip_address: &str, user_agent: &str) -> Result<RiskAssessment> {
+        let risk = self.assess_risk(user, device_id, ip_address).await?;
+
+        if risk.action == RiskAction::Block {
+            warn!("Blocked sign-in for user {} from {} (risk {}: {:?})", user.id, ip_address, risk.score, risk.signals);
+            self.publish_event(
+                auth_events::SESSION_RISK_DETECTED,
+                user.id,
+                user.tenant_id,
+                session_risk_event(user, None, &risk, device_id, ip_address, user_agent),
+            ).await;
+            return Err(AuthError::LoginBlocked);
+        }
+
+        // Users with a second factor answer the step-up through the MFA challenge; without one
+        // there is nothing to step up to, so the sign-in is refused rather than trusted
+        if risk.action == RiskAction::StepUpMfa && !user.two_factor_enabled {
+            warn!("Refused risky sign-in for user {} without a second factor enrolled (risk {})", user.id, risk.score);
+            self.publish_event(
+                auth_events::SESSION_RISK_DETECTED,
+                user.id,
+                user.tenant_id,
+                session_risk_event(user, None, &risk, device_id, ip_address, user_agent),
+            ).await;
+            return Err(AuthError::SecondFactorRequired);
+        }
+
+        Ok(risk)
+    }
+
+    /// Publish a scored session for notification, unless it was unremarkable
+    async fn publish_session_risk(&self, user: &User, risk: &RiskAssessment, token: &RefreshToken) {
+        if risk.action == RiskAction::Allow {
+            return;
+        }
+
+        self.publish_event(
+            auth_events::SESSION_RISK_DETECTED,
+            user.id,
+            user.tenant_id,
+            session_risk_event(
+                user,
+                Some(token.family_id),
+                risk,
+                token.device_id.as_deref(),
+                &token.ip_address,
+                &token.user_agent,
+            ),
+        ).await;
+    }
+
+    /// A user's direct grants plus those of their roles and every role those inherit from,
+    /// limited to the locations their membership in the tenant covers
+    async fn effective_permissions(&self, user: &User) -> Result<Vec<String>> {
+        let roles = self.user_repo.find_roles_by_tenant(user.tenant_id).await?;
+        let permissions = RoleHierarchy::new(&roles)
+            .permission_set(&user.roles, &user.permissions)
+            .to_strings();
+
+        match self.user_repo.find_membership(user.id, user.tenant_id).await? {
+            Some(membership) if !membership.location_ids.is_empty() => {
+                Ok(membership::scope_to_locations(&permissions, &membership.location_ids))
+            }
+            _ => Ok(permissions),
+        }
+    }
+
+    /// The identity as it acts in a tenant: itself in its home tenant, elsewhere with the
+    /// roles of its active membership
+    async fn as_member_of(&self, user: User, tenant_id: Uuid) -> Result<User> {
+        if user.tenant_id == tenant_id {
+            return Ok(user);
+        }
+
+        match self.user_repo.find_membership(user.id, tenant_id).await? {
+            Some(membership) if membership.is_active => Ok(user.as_member(&membership)),
+            _ => Err(AuthError::NotTenantMember),
+        }
+    }
+
+    /// Re-hash a just-verified password if its stored hash uses outdated parameters or bcrypt.
+    /// A failure only delays the upgrade to a later login.
+    fn upgrade_password_hash(&self, user: &mut User, password: &str) {
+        if !self.password.needs_rehash(&user.password_hash) {
+            return;
+        }
+
+        match self.password.rehash_password(password) {
+            Ok(hash) => {
+                info!("Upgrading password hash parameters for user {}", user.id);
+                user.password_hash = hash;
+            }
+            Err(e) => warn!("Failed to upgrade password hash for user {}: {}", user.id, e),
+        }
+    }
+
+    /// Sign a token pair within a refresh token family and build the refresh token record to store
+    fn sign_family_tokens(
+        &self,
+        user: &User,
+        tenant: &Tenant,
+        permissions: Vec<String>,
+        family_id: Uuid,
+        client: ClientDevice,
+        risk: &RiskAssessment,
+    ) -> Result<(TokenResponse, RefreshToken)> {
+        let device_info = DeviceInfo {
+            device_id: client.device_id.clone(),
+            user_agent: Some(client.user_agent.clone()),
+            ip_address: Some(client.ip_address.clone()),
+        };
+
+        let token_pair = self.jwt.generate_token_pair(
+            user.id,
+            tenant.id,
+            user.email.clone(),
+            user.roles.clone(),
+            permissions,
+            family_id.to_string(),
+            device_info,
+        )?;
+
+        let refresh_token = RefreshToken {
+            id: Uuid::new_v4(),
+            token_hash: self.password.hash_token(&token_pair.refresh_token)?,
+            family_id,
+            user_id: user.id,
+            tenant_id: tenant.id,
+            device_id: client.device_id,
+            device_name: client.device_name,
+            ip_address: client.ip_address,
+            user_agent: client.user_agent,
+            expires_at: Utc::now() + Duration::days(30),
+            revoked_at: None,
+            replaced_by: None,
+            client_id: None,
+            scopes: vec![],
+            location: risk
+                .location
+                .as_ref()
+                .map(serde_json::to_value)
+                .transpose()?
+                .unwrap_or_else(|| serde_json::json!({})),
+            risk_score: risk.score as i32,
+            risk_signals: risk.signal_names(),
+            created_at: Utc::now(),
+        };
+
+        // Until the password is rotated the access token only allows changing it; a refresh
+        // re-checks, and changing the password revokes the refresh token
+        let password_change_required = user.requires_password_change(self.password.policy().max_age_days);
+        let access_token = if password_change_required {
+            self.jwt.generate_password_change_token(user.id, tenant.id)?
+        } else {
+            token_pair.access_token
+        };
+
+        let response = TokenResponse {
+            access_token,
+            refresh_token: token_pair.refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in: token_pair.expires_in,
+            user: user.to_response(tenant),
+            password_change_required,
+        };
+
+        Ok((response, refresh_token))
+    }
+
+    /// The mailer for an email carrying a token; without one the email cannot be sent
+    fn mailer(&self, email: &str) -> Option<&Mailer> {
+        if self.mailer.is_none() {
+            warn!("No mailer configured, {} email not sent", email);
+        }
+        self.mailer.as_deref()
+    }
+
+    /// Publish a domain event if an event publisher is configured; failures are logged, not returned
+    async fn publish_event<T: Serialize>(&self, event_type: &str, user_id: Uuid, tenant_id: Uuid, data: T) {
+        let Some(publisher) = &self.event_publisher else {
+            return;
+        };
+
+        let event = match DomainEvent::builder(event_type.to_string(), user_id, "User".to_string(), tenant_id).data(data) {
+            Ok(builder) => builder
+                .user_id(user_id)
+                .source_service("auth".to_string())
+                .build(),
+            Err(e) => {
+                warn!("Failed to serialize {} event: {}", event_type, e);
+                return;
+            }
+        };
+
+        if let Err(e) = publisher.lock().await.publish(&event).await {
+            warn!("Failed to publish {} event: {}", event_type, e);
+        }
+    }
+
+    pub async fn register(&self, request: RegisterRequest) -> Result<UserResponse> {
+        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+
+        if self.user_repo.user_exists(&request.email, tenant.id).await? {
+            return Err(AuthError::EmailAlreadyExists);
+        }
+
+        let password_hash = self.password.hash_password(&request.password)?;
+
+        let user = User {
+            id: Uuid::new_v4(),
+            tenant_id: tenant.id,
+            email: request.email.clone(),
+            password_hash,
+            first_name: request.first_name,
+            last_name: request.last_name,
+            display_name: None,
+            phone: request.phone,
+            avatar_url: None,
+            roles: vec!["user".to_string()],
+            permissions: vec![],
+            is_active: true,
+            email_verified: false,
+            phone_verified: false,
+            two_factor_enabled: false,
+            last_login: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            password_changed_at: Some(Utc::now()),
+            force_password_change: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        };
+
+        let created_user = self.user_repo.create_user(&user).await?;
+        self.user_repo.add_password_history(created_user.id, &created_user.password_hash).await?;
+
+        // The verification link is only ever written to the mailer outbox
+        let verification_token = self.jwt.generate_email_verification_token(created_user.id, &created_user.email)?;
+        let mut event = UserRegisteredEvent {
+            user_id: created_user.id,
+            tenant_id: tenant.id,
+            email: created_user.email.clone(),
+            first_name: Some(created_user.first_name.clone()),
+            last_name: Some(created_user.last_name.clone()),
+            email_verification_required: !created_user.email_verified,
+            registration_source: "web".to_string(),
+            email_id: None,
+            locale: request.locale,
+        };
+        if let Some(mailer) = self.mailer("verification") {
+            event.email_id = mailer.send_email_verification(&event, &verification_token).await?;
+        }
+
+        self.publish_event(auth_events::USER_REGISTERED, created_user.id, tenant.id, event).await;
+
+        Ok(created_user.to_response(&tenant))
+    }
+
+    /// Exchange a refresh token for a new token pair, rotating the refresh token.
+    ///
+    /// A rotated token can never be used again; if one is presented it has been copied,
+    /// so every session the user has is revoked and a security event is raised.
+    pub async fn refresh_token(&self, refresh_token_str: &str, ip_address: String, user_agent: String) -> Result<TokenResponse> {
+        let token_hash = self.password.hash_token(refresh_token_str)?;
+        let refresh_token = self.user_repo.find_refresh_token(&token_hash).await?;
+
+        // OAuth grants are scoped to their client and must be refreshed at /oauth/token
+        if refresh_token.client_id.is_some() {
+            return Err(AuthError::InvalidToken("Refresh token belongs to an OAuth client".to_string()));
+        }
+
+        if refresh_token.revoked_at.is_some() {
+            if refresh_token.replaced_by.is_some() {
+                self.handle_refresh_token_reuse(&refresh_token, ip_address, user_agent).await?;
+                return Err(AuthError::RefreshTokenReused);
+            }
+            return Err(AuthError::TokenRevoked);
+        }
+
+        if refresh_token.expires_at < Utc::now() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        let user = self.user_repo.find_user_by_id(refresh_token.user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(refresh_token.tenant_id).await?;
+
+        if !user.is_active || !tenant.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        // Sessions in another tenant end once the membership is removed
+        let user = self.as_member_of(user, tenant.id).await?;
+
+        // A refresh cannot answer an MFA challenge, so a risky one ends the session and the
+        // user has to sign in again
+        let risk = self.assess_risk(&user, refresh_token.device_id.as_deref(), &ip_address).await?;
+        if risk.action >= RiskAction::StepUpMfa {
+            warn!(
+                "Ending session {} for user {} on refresh from {} (risk {}: {:?})",
+                refresh_token.family_id, user.id, ip_address, risk.score, risk.signals
+            );
+            self.user_repo.revoke_refresh_token(refresh_token.id).await?;
+            self.deny_session(refresh_token.family_id).await?;
+            self.publish_event(
+                auth_events::SESSION_RISK_DETECTED,
+                user.id,
+                user.tenant_id,
+                session_risk_event(
+                    &user,
+                    Some(refresh_token.family_id),
+                    &risk,
+                    refresh_token.device_id.as_deref(),
+                    &ip_address,
+                    &user_agent,
+                ),
+            ).await;
+            return Err(if risk.action == RiskAction::Block {
+                AuthError::LoginBlocked
+            } else {
+                AuthError::StepUpRequired
+            });
+        }
+
+        // Re-resolve grants so role changes take effect at the next refresh
+        let permissions = self.effective_permissions(&user).await?;
+        let (response, rotated) = self.sign_family_tokens(
+            &user,
+            &tenant,
+            permissions,
+            refresh_token.family_id,
+            ClientDevice {
+                device_id: refresh_token.device_id.clone(),
+                device_name: refresh_token.device_name.clone(),
+                ip_address: ip_address.clone(),
+                user_agent: user_agent.clone(),
+            },
+            &risk,
+        )?;
+
+        // Conditional update: if a concurrent request already rotated this token, treat it as reuse
+        if !self.user_repo.rotate_refresh_token(refresh_token.id, &rotated).await? {
+            self.handle_refresh_token_reuse(&refresh_token, ip_address, user_agent).await?;
+            return Err(AuthError::RefreshTokenReused);
+        }
+
+        self.publish_session_risk(&user, &risk, &rotated).await;
+
+        Ok(response)
+    }
+
+    async fn handle_refresh_token_reuse(&self, token: &RefreshToken, ip_address: String, user_agent: String) -> Result<()> {
+        warn!(
+            "Refresh token reuse detected for user {} (family {}); revoking all sessions",
+            token.user_id, token.family_id
+        );
+
+        self.deny_session(token.family_id).await?;
+        for active in self.user_repo.find_active_refresh_tokens(token.user_id).await? {
+            self.deny_session(active.family_id).await?;
+        }
+        self.user_repo.revoke_all_user_tokens(token.user_id).await?;
+
+        self.publish_event(
+            auth_events::REFRESH_TOKEN_REUSE_DETECTED,
+            token.user_id,
+            token.tenant_id,
+            RefreshTokenReuseDetectedEvent {
+                user_id: token.user_id,
+                tenant_id: token.tenant_id,
+                family_id: token.family_id,
+                token_id: token.id,
+                device_id: token.device_id.clone(),
+                ip_address,
+                user_agent,
+            },
+        ).await;
+
+        Ok(())
+    }
+
+    pub async fn logout(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
+        self.deny_session(session_id).await?;
+        for token in self.user_repo.find_active_refresh_tokens(user_id).await? {
+            self.deny_session(token.family_id).await?;
+        }
+
+        self.user_repo.revoke_all_user_tokens(user_id).await?;
+        Ok(())
+    }
+
+    /// Reject every outstanding access token of a session until they would have expired anyway
+    async fn deny_session(&self, session_id: Uuid) -> Result<()> {
+        let ttl = std::time::Duration::from_secs(self.jwt.access_token_duration().max(0) as u64);
+        self.revocations
+            .revoke(RevocationKind::Session, &session_id.to_string(), ttl)
+            .await
+    }
+
+    /// Reject a single access token for the rest of its lifetime
+    pub async fn revoke_access_token(&self, jti: &str, exp: i64) -> Result<()> {
+        let remaining = (exp - Utc::now().timestamp()).max(0) as u64;
+        if remaining == 0 {
+            return Ok(());
+        }
+
+        self.revocations
+            .revoke(RevocationKind::Token, jti, std::time::Duration::from_secs(remaining))
+            .await
+    }
+
+    pub async fn verify_token(&self, token: &str) -> Result<Claims> {
+        let validation = self.jwt.validate_access_token(token)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+
+        if !validation.is_valid {
+            return Err(AuthError::TokenExpired);
+        }
+
+        if self.revocations.is_revoked(&validation.claims.jti, &validation.claims.session_id).await? {
+            return Err(AuthError::TokenRevoked);
+        }
+
+        // Convert from JWT TokenValidation to our Claims format
+        Ok(Claims {
+            sub: validation.claims.sub.parse()
+                .map_err(|_| AuthError::InvalidToken("Invalid user ID".to_string()))?,
+            tenant_id: validation.claims.tenant_id.parse()
+                .map_err(|_| AuthError::InvalidToken("Invalid tenant ID".to_string()))?,
+            email: validation.claims.email,
+            roles: validation.claims.roles,
+            permissions: validation.claims.permissions,
+            session_id: validation.claims.session_id.parse()
+                .map_err(|_| AuthError::InvalidToken("Invalid session ID".to_string()))?,
+            iat: validation.claims.iat,
+            exp: validation.claims.exp,
+            impersonation: validation.claims.act
+                .map(|act| -> Result<Impersonation> {
+                    Ok(Impersonation {
+                        impersonator_id: act.sub.parse()
+                            .map_err(|_| AuthError::InvalidToken("Invalid impersonator ID".to_string()))?,
+                        impersonator_email: act.email,
+                        read_only: act.read_only,
+                        reason: act.reason,
+                    })
+                })
+                .transpose()?,
+            client_id: validation.claims.client_id,
+            terminal_id: validation.claims.terminal_id
+                .map(|id| id.parse::<Uuid>())
+                .transpose()
+                .map_err(|_| AuthError::InvalidToken("Invalid terminal ID".to_string()))?,
+            location_id: validation.claims.location_id
+                .map(|id| id.parse::<Uuid>())
+                .transpose()
+                .map_err(|_| AuthError::InvalidToken("Invalid location ID".to_string()))?,
+        })
+    }
+
+    /// Verify a token for changes to the account itself (credentials, MFA, keys, sessions),
+    /// which support staff may never make while impersonating and partners never make at all
+    pub async fn verify_account_token(&self, token: &str) -> Result<Claims> {
+        let claims = self.verify_token(token).await?;
+        self.ensure_account_claims(&claims)?;
+        Ok(claims)
+    }
+
+    /// The `verify_account_token` checks for claims the authentication layer already verified,
+    /// which may also come from an API key
+    pub fn ensure_account_claims(&self, claims: &Claims) -> Result<()> {
+        if claims.impersonation.is_some() {
+            return Err(AuthError::InsufficientPermissions(
+                "Not permitted while impersonating a user".to_string(),
+            ));
+        }
+        if claims.client_id.is_some() {
+            return Err(AuthError::InsufficientPermissions(
+                "Not permitted with an OAuth client token".to_string(),
+            ));
+        }
+        if claims.terminal_id.is_some() {
+            return Err(AuthError::InsufficientPermissions(
+                "Not permitted from a shared terminal".to_string(),
+            ));
+        }
+        if claims.roles.iter().any(|role| role == api_keys::API_KEY_ROLE) {
+            return Err(AuthError::InsufficientPermissions(
+                "Not permitted with an API key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn get_user(&self, user_id: Uuid) -> Result<(User, Tenant)> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
+        Ok((user, tenant))
+    }
+
+    /// The caller as a member of the tenant their token was issued for, with every
+    /// tenant they can switch to
+    pub async fn get_current_user(&self, claims: &Claims) -> Result<CurrentUserResponse> {
+        let user = self.user_repo.find_user_by_id(claims.sub).await?;
+        let tenant = self.user_repo.find_tenant_by_id(claims.tenant_id).await?;
+        let memberships = self.list_memberships(&user, claims.tenant_id).await?;
+        let member = self.as_member_of(user, tenant.id).await?;
+
+        Ok(CurrentUserResponse {
+            user: member.to_response(&tenant),
+            memberships,
+        })
+    }
+
+    /// The home tenant first, then every active membership elsewhere
+    async fn list_memberships(&self, user: &User, current_tenant_id: Uuid) -> Result<Vec<MembershipResponse>> {
+        let rows = self.user_repo.find_memberships_by_user(user.id).await?;
+        let home = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
+
+        let mut memberships = vec![membership::home_membership(
+            user,
+            &home,
+            rows.iter().find(|m| m.tenant_id == user.tenant_id),
+            current_tenant_id,
+        )];
+        for row in rows.iter().filter(|m| m.is_active && m.tenant_id != user.tenant_id) {
+            let tenant = self.user_repo.find_tenant_by_id(row.tenant_id).await?;
+            if tenant.is_active {
+                memberships.push(membership::membership_response(row, &tenant, current_tenant_id));
+            }
+        }
+        Ok(memberships)
+    }
+
+    /// Re-issue tokens for another tenant the caller belongs to. The caller's current
+    /// session proves who they are, so no password is asked for; the new session is
+    /// scored like any other sign-in.
+    pub async fn switch_tenant(
+        &self,
+        claims: &Claims,
+        request: SwitchTenantRequest,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<TokenResponse> {
+        let user = self.user_repo.find_user_by_id(claims.sub).await?;
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+        if user.is_locked() {
+            return Err(AuthError::AccountLocked);
+        }
+
+        let tenant = self.user_repo.find_tenant_by_id(request.tenant_id).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        // The home sign-in must not bypass another tenant's SSO requirement
+        if tenant.id != user.tenant_id {
+            self.ensure_password_login_allowed(tenant.id).await?;
+        }
+
+        let member = self.as_member_of(user, tenant.id).await?;
+        let risk = self.screen_sign_in(&member, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+        info!("User {} switched from tenant {} to {}", member.id, claims.tenant_id, tenant.id);
+
+        let client = ClientDevice {
+            device_id: request.device_id,
+            device_name: request.device_name,
+            ip_address,
+            user_agent,
+        };
+        self.issue_tokens(&member, &tenant, client, risk).await
+    }
+
+    /// Invite someone by email to the caller's current tenant. The token is only ever
+    /// written to the mailer outbox; the `UserInvited` event names the queued email.
+    pub async fn create_invitation(&self, claims: &Claims, request: CreateInvitationRequest) -> Result<InvitationResponse> {
+        let actor = self.user_repo.find_user_by_id(claims.sub).await?;
+        let actor = self.as_member_of(actor, claims.tenant_id).await?;
+        self.ensure_can_manage_users(&actor)?;
+        self.ensure_can_assign_roles(&actor, &request.roles)?;
+
+        if self.user_repo.user_exists(&request.email, actor.tenant_id).await? {
+            return Err(AuthError::AlreadyMember);
+        }
+
+        let duration = request
+            .expires_in_days
+            .map(|days| days * 86400)
+            .unwrap_or(INVITATION_DURATION_SECONDS);
+        let id = Uuid::new_v4();
+        let token = self.jwt.generate_invitation_token(id, actor.tenant_id, duration)?;
+        let invitation = Invitation {
+            id,
+            tenant_id: actor.tenant_id,
+            email: request.email.trim().to_lowercase(),
+            token_hash: self.password.hash_token(&token)?,
+            roles: request.roles,
+            location_ids: request.location_ids,
+            invited_by: actor.id,
+            expires_at: Utc::now() + Duration::seconds(duration),
+            accepted_at: None,
+            declined_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+        self.user_repo.store_invitation(&invitation).await?;
+        info!("User {} invited {} to tenant {}", actor.id, invitation.email, invitation.tenant_id);
+
+        let mut event = UserInvitedEvent {
+            invitation_id: invitation.id,
+            tenant_id: invitation.tenant_id,
+            email: invitation.email.clone(),
+            roles: invitation.roles.clone(),
+            location_ids: invitation.location_ids.clone(),
+            invited_by: actor.id,
+            email_id: None,
+            expires_at: invitation.expires_at,
+            locale: request.locale,
+        };
+        if let Some(mailer) = self.mailer("invitation") {
+            event.email_id = mailer.send_invitation(&event, &token).await?;
+        }
+
+        self.publish_event(auth_events::USER_INVITED, actor.id, invitation.tenant_id, event).await;
+
+        Ok(invitation.into())
+    }
+
+    pub async fn list_invitations(&self, claims: &Claims) -> Result<Vec<InvitationResponse>> {
+        let actor = self.user_repo.find_user_by_id(claims.sub).await?;
+        let actor = self.as_member_of(actor, claims.tenant_id).await?;
+        self.ensure_can_manage_users(&actor)?;
+
+        let invitations = self.user_repo.find_invitations_by_tenant(actor.tenant_id).await?;
+        Ok(invitations.into_iter().map(Into::into).collect())
+    }
+
+    /// Withdraw a pending invitation so its token can no longer be used
+    pub async fn revoke_invitation(&self, claims: &Claims, invitation_id: Uuid) -> Result<()> {
+        let actor = self.user_repo.find_user_by_id(claims.sub).await?;
+        let actor = self.as_member_of(actor, claims.tenant_id).await?;
+        self.ensure_can_manage_users(&actor)?;
+
+        let mut invitation = self.user_repo.find_invitation_by_id(invitation_id).await?;
+        if invitation.tenant_id != actor.tenant_id {
+            return Err(AuthError::InvitationNotFound);
+        }
+
+        invitation.revoked_at = Some(Utc::now());
+        if !self.user_repo.close_invitation(&invitation).await? {
+            return Err(AuthError::InvitationClosed);
+        }
+        info!("User {} revoked invitation {}", actor.id, invitation.id);
+        Ok(())
+    }
+
+    /// Accept an invitation. A signed-in caller (`identity`) joins with their existing
+    /// account, which must use the invited address; anyone else gets a new account in the
+    /// inviting tenant. Either way the invitation can only be answered once.
+    pub async fn accept_invitation(&self, identity: Option<Uuid>, request: AcceptInvitationRequest) -> Result<MembershipResponse> {
+        let mut invitation = self.open_invitation(&request.token).await?;
+        let tenant = self.user_repo.find_tenant_by_id(invitation.tenant_id).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+
+        let (user_id, membership) = match identity {
+            Some(user_id) => {
+                let user = self.user_repo.find_user_by_id(user_id).await?;
+                if !membership::same_email(&user.email, &invitation.email) {
+                    return Err(AuthError::InvitationEmailMismatch);
+                }
+                if user.tenant_id == tenant.id || self.as_member_of(user.clone(), tenant.id).await.is_ok() {
+                    return Err(AuthError::AlreadyMember);
+                }
+                self.close_accepted_invitation(&mut invitation).await?;
+
+                let membership = self.invited_membership(user.id, &invitation);
+                self.user_repo.save_membership(&membership).await?;
+                (user.id, membership::membership_response(&membership, &tenant, tenant.id))
+            }
+            None => {
+                let (Some(first_name), Some(last_name), Some(password)) =
+                    (request.first_name, request.last_name, request.password)
+                else {
+                    return Err(AuthError::Validation(
+                        "first_name, last_name and password are required to accept without signing in".to_string(),
+                    ));
+                };
+                if self.user_repo.user_exists(&invitation.email, tenant.id).await? {
+                    return Err(AuthError::EmailAlreadyExists);
+                }
+                let password_hash = self.password.hash_password(&password)?;
+                self.close_accepted_invitation(&mut invitation).await?;
+
+                let user = self.create_invited_user(&invitation, first_name, last_name, password_hash).await?;
+                // Carries the invitation's locations for the new home tenant
+                let membership = self.invited_membership(user.id, &invitation);
+                if !membership.location_ids.is_empty() {
+                    self.user_repo.save_membership(&membership).await?;
+                }
+                (user.id, membership::home_membership(&user, &tenant, Some(&membership), tenant.id))
+            }
+        };
+
+        self.publish_event(
+            auth_events::INVITATION_ACCEPTED,
+            invitation.invited_by,
+            invitation.tenant_id,
+            InvitationRespondedEvent {
+                invitation_id: invitation.id,
+                tenant_id: invitation.tenant_id,
+                email: invitation.email.clone(),
+                user_id: Some(user_id),
+                accepted: true,
+            },
+        ).await;
+
+        Ok(membership)
+    }
+
+    /// Decline an invitation; only the token is needed, so no account is required
+    pub async fn decline_invitation(&self, request: DeclineInvitationRequest) -> Result<()> {
+        let mut invitation = self.open_invitation(&request.token).await?;
+
+        invitation.declined_at = Some(Utc::now());
+        if !self.user_repo.close_invitation(&invitation).await? {
+            return Err(AuthError::InvitationClosed);
+        }
+
+        self.publish_event(
+            auth_events::INVITATION_DECLINED,
+            invitation.invited_by,
+            invitation.tenant_id,
+            InvitationRespondedEvent {
+                invitation_id: invitation.id,
+                tenant_id: invitation.tenant_id,
+                email: invitation.email.clone(),
+                user_id: None,
+                accepted: false,
+            },
+        ).await;
+
+        Ok(())
+    }
+
+    /// Look up the pending invitation an emailed token belongs to
+    async fn open_invitation(&self, token: &str) -> Result<Invitation> {
+        let invalid_token = || AuthError::InvalidToken("Invalid or expired invitation".to_string());
+
+        let claims = self.jwt.validate_purpose_token(token, "invitation")
+            .map_err(|_| invalid_token())?;
+        let invitation_id: Uuid = claims.sub.parse().map_err(|_| invalid_token())?;
+
+        let invitation = self.user_repo.find_invitation_by_id(invitation_id).await?;
+        if invitation.token_hash != self.password.hash_token(token)? {
+            return Err(invalid_token());
+        }
+
+        match invitation.status() {
+            "pending" => Ok(invitation),
+            "expired" => Err(AuthError::TokenExpired),
+            _ => Err(AuthError::InvitationClosed),
+        }
+    }
+
+    /// Conditional update: of two concurrent answers to one invitation, only one wins
+    async fn close_accepted_invitation(&self, invitation: &mut Invitation) -> Result<()> {
+        invitation.accepted_at = Some(Utc::now());
+        if !self.user_repo.close_invitation(invitation).await? {
+            return Err(AuthError::InvitationClosed);
+        }
+        Ok(())
+    }
+
+    fn invited_membership(&self, user_id: Uuid, invitation: &Invitation) -> TenantMembership {
+        TenantMembership {
+            id: Uuid::new_v4(),
+            user_id,
+            tenant_id: invitation.tenant_id,
+            roles: invitation.roles.clone(),
+            permissions: vec![],
+            location_ids: invitation.location_ids.clone(),
+            invited_by: Some(invitation.invited_by),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    async fn create_invited_user(
+        &self,
+        invitation: &Invitation,
+        first_name: String,
+        last_name: String,
+        password_hash: String,
+    ) -> Result<User> {
+        let user = User {
+            id: Uuid::new_v4(),
+            tenant_id: invitation.tenant_id,
+            email: invitation.email.clone(),
+            password_hash,
+            first_name,
+            last_name,
+            display_name: None,
+            phone: None,
+            avatar_url: None,
+            roles: invitation.roles.clone(),
+            permissions: vec![],
+            is_active: true,
+            // The token arrived by email, which proves control of the mailbox
+            email_verified: true,
+            phone_verified: false,
+            two_factor_enabled: false,
+            last_login: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            password_changed_at: Some(Utc::now()),
+            force_password_change: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        };
+
+        let created_user = self.user_repo.create_user(&user).await?;
+        self.user_repo.add_password_history(created_user.id, &created_user.password_hash).await?;
+        Ok(created_user)
+    }
+
+    pub async fn forgot_password(&self, request: ForgotPasswordRequest) -> Result<()> {
+        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+
+        // Check if user exists - but don't reveal whether they exist or not for security
+        if let Ok(user) = self.user_repo.find_user_by_email(&request.email, tenant.id).await {
+            if user.is_active {
+                // The token is only ever written to the mailer outbox
+                let reset_token = self.jwt.generate_password_reset_token(user.id, &user.email)?;
+                let claims = self.jwt.validate_purpose_token(&reset_token, "password_reset")?;
+
+                let mut event = PasswordResetRequestedEvent {
+                    user_id: user.id,
+                    tenant_id: tenant.id,
+                    email: user.email.clone(),
+                    reset_token_id: Uuid::parse_str(&claims.jti).unwrap_or_else(|_| Uuid::new_v4()),
+                    email_id: None,
+                    expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
+                    request_source: "web".to_string(),
+                    locale: request.locale,
+                };
+                if let Some(mailer) = self.mailer("password reset") {
+                    event.email_id = mailer.send_password_reset(&event, &reset_token).await?;
+                }
+
+                self.publish_event(auth_events::PASSWORD_RESET_REQUESTED, user.id, tenant.id, event).await;
+            }
+        }
+
+        // Always return success to avoid email enumeration attacks
+        Ok(())
+    }
+
+    /// Send a passwordless login link. Like `forgot_password`, this always succeeds so
+    /// that it cannot be used to discover which emails have accounts.
+    pub async fn request_magic_link(&self, request: MagicLinkRequest, ip_address: String, user_agent: String) -> Result<()> {
+        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        self.ensure_password_login_allowed(tenant.id).await?;
+
+        let Ok(user) = self.user_repo.find_user_by_email(&request.email, tenant.id).await else {
+            return Ok(());
+        };
+        if !user.is_active || user.is_locked() {
+            return Ok(());
+        }
+
+        let token = self.jwt.generate_magic_link_token(user.id, tenant.id)?;
+        let link = MagicLinkToken {
+            id: Uuid::new_v4(),
+            token_hash: self.password.hash_token(&token)?,
+            user_id: user.id,
+            tenant_id: tenant.id,
+            ip_address,
+            user_agent,
+            expires_at: Utc::now() + Duration::seconds(MAGIC_LINK_DURATION_SECONDS),
+            consumed_at: None,
+            created_at: Utc::now(),
+        };
+        self.user_repo.store_magic_link_token(&link).await?;
+
+        let mut event = MagicLinkRequestedEvent {
+            user_id: user.id,
+            tenant_id: tenant.id,
+            email: user.email.clone(),
+            token_id: link.id,
+            email_id: None,
+            expires_at: link.expires_at,
+            locale: request.locale,
+        };
+        if let Some(mailer) = self.mailer("login link") {
+            event.email_id = mailer.send_magic_link(&event, &token).await?;
+        }
+
+        self.publish_event(auth_events::MAGIC_LINK_REQUESTED, user.id, tenant.id, event).await;
+
+        Ok(())
+    }
+
+    /// Exchange a magic link for tokens. The link replaces the password only, so users
+    /// with MFA enrolled still get an MFA challenge.
+    pub async fn login_with_magic_link(&self, request: MagicLinkLoginRequest, ip_address: String, user_agent: String) -> Result<LoginResponse> {
+        let invalid_link = || AuthError::InvalidToken("Invalid or expired login link".to_string());
+
+        let claims = self.jwt.validate_purpose_token(&request.token, "magic_link")
+            .map_err(|_| invalid_link())?;
+        let user_id: Uuid = claims.sub.parse().map_err(|_| invalid_link())?;
+        let tenant_id: Uuid = claims.tenant_id.parse().map_err(|_| invalid_link())?;
+
+        // Single use: a replayed link fails here even while its signature is still valid
+        let token_hash = self.password.hash_token(&request.token)?;
+        if !self.user_repo.consume_magic_link_token(&token_hash).await? {
+            return Err(AuthError::InvalidToken("Login link has already been used".to_string()));
+        }
+
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+        if user.tenant_id != tenant_id {
+            return Err(invalid_link());
+        }
+        let tenant = self.user_repo.find_tenant_by_id(tenant_id).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        // Links sent before SSO was made mandatory must not bypass it
+        self.ensure_password_login_allowed(tenant.id).await?;
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+        if user.is_locked() {
+            return Err(AuthError::AccountLocked);
+        }
+
+        let risk = self.screen_sign_in(&user, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+
+        // Following the link proves control of the mailbox
+        user.email_verified = true;
+        user.failed_login_attempts = 0;
+
+        if user.two_factor_enabled {
+            self.user_repo.update_user(&user).await?;
+            let mfa_token = self.jwt.generate_mfa_pending_token(user.id, tenant.id)?;
+            return Ok(LoginResponse::MfaRequired(MfaChallengeResponse {
+                mfa_required: true,
+                mfa_token,
+                methods: self.mfa_methods(&user).await?,
+                expires_in: 300,
+            }));
+        }
+
+        user.last_login = Some(Utc::now());
+        self.user_repo.update_user(&user).await?;
+
+        let client = ClientDevice {
+            device_id: request.device_id,
+            device_name: request.device_name,
+            ip_address,
+            user_agent,
+        };
+        let response = self.issue_tokens(&user, &tenant, client, risk).await?;
+        Ok(LoginResponse::Authenticated(Box::new(response)))
+    }
+
+    /// Begin an OIDC login with the tenant's identity provider
+    pub async fn start_sso_login(&self, request: SsoStartRequest) -> Result<SsoStartResponse> {
+        let tenant = self.user_repo.find_tenant_by_slug(&request.tenant_slug).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        let config = self.sso_configs.load(tenant.id).await?.ok_or(AuthError::SsoNotConfigured)?;
+
+        // The state ties the callback to this attempt; the nonce ties the ID token to it
+        let state = oauth::generate_authorization_code();
+        let nonce = oauth::generate_authorization_code();
+        let code_verifier = oauth::generate_authorization_code();
+
+        let authorization_url = self
+            .oidc
+            .authorization_url(&config, &state, &nonce, &oauth::pkce_challenge(&code_verifier))
+            .await?;
+
+        let login_state = SsoLoginState {
+            id: Uuid::new_v4(),
+            state_hash: self.password.hash_token(&state)?,
+            tenant_id: tenant.id,
+            nonce,
+            code_verifier,
+            expires_at: Utc::now() + Duration::seconds(SSO_STATE_DURATION_SECONDS),
+            consumed_at: None,
+            created_at: Utc::now(),
+        };
+        self.user_repo.store_sso_state(&login_state).await?;
+
+        Ok(SsoStartResponse {
+            authorization_url,
+            expires_in: SSO_STATE_DURATION_SECONDS,
+        })
+    }
+
+    /// Finish an OIDC login: redeem the code, validate the ID token and sign the user in,
+    /// provisioning them on first login when the tenant allows it
+    pub async fn complete_sso_login(&self, request: SsoCallbackRequest, ip_address: String, user_agent: String) -> Result<TokenResponse> {
+        let state_hash = self.password.hash_token(&request.state)?;
+        let login_state = self
+            .user_repo
+            .consume_sso_state(&state_hash)
+            .await?
+            .filter(|state| state.expires_at > Utc::now())
+            .ok_or_else(|| AuthError::InvalidToken("Invalid or expired SSO login".to_string()))?;
+
+        let tenant = self.user_repo.find_tenant_by_id(login_state.tenant_id).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+        let config = self.sso_configs.load(tenant.id).await?.ok_or(AuthError::SsoNotConfigured)?;
+
+        let claims = self
+            .oidc
+            .exchange_code(&config, &request.code, &login_state.code_verifier, &login_state.nonce)
+            .await?;
+
+        let mut user = match self.user_repo.find_user_by_identity(tenant.id, &claims.iss, &claims.sub).await? {
+            Some(user) => user,
+            None => self.provision_sso_user(&tenant, &config, &claims).await?,
+        };
+        if user.tenant_id != tenant.id {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        self.user_repo.link_user_identity(&UserIdentity {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            tenant_id: tenant.id,
+            issuer: claims.iss.clone(),
+            subject: claims.sub.clone(),
+            email: claims.email.clone(),
+            last_login: Some(Utc::now()),
+            created_at: Utc::now(),
+        }).await?;
+
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+        if user.is_locked() {
+            return Err(AuthError::AccountLocked);
+        }
+
+        // The identity provider is responsible for the second factor, including any step-up
+        let risk = self.screen_sign_in(&user, request.device_id.as_deref(), &ip_address, &user_agent).await?;
+
+        user.failed_login_attempts = 0;
+        user.last_login = Some(Utc::now());
+        self.user_repo.update_user(&user).await?;
+
+        let client = ClientDevice {
+            device_id: request.device_id,
+            device_name: request.device_name,
+            ip_address,
+            user_agent,
+        };
+        self.issue_tokens(&user, &tenant, client, risk).await
+    }
+
+    /// Find the account for a first-time SSO identity by verified email, or create it
+    async fn provision_sso_user(&self, tenant: &Tenant, config: &OidcProviderConfig, claims: &sso::IdTokenClaims) -> Result<User> {
+        // An unverified email could belong to anyone, so it must never link to an existing account
+        let email = match (&claims.email, claims.email_verified) {
+            (Some(email), Some(true)) => email.to_lowercase(),
+            _ => return Err(AuthError::InvalidToken("Identity provider did not supply a verified email".to_string())),
+        };
+        if !config.email_domain_allowed(&email) {
+            return Err(AuthError::InsufficientPermissions(format!(
+                "Email domain of {} is not allowed for single sign-on",
+                email
+            )));
+        }
+
+        match self.user_repo.find_user_by_email(&email, tenant.id).await {
+            Ok(user) => return Ok(user),
+            Err(AuthError::UserNotFound) if config.jit_provisioning => {}
+            Err(e) => return Err(e),
+        }
+
+        // SSO users never use a password; this one is random and never shown to anyone
+        let password_hash = self.password.hash_password(&self.password.generate_random_password(32))?;
+        let (first_name, last_name) = match (&claims.given_name, &claims.family_name) {
+            (Some(given), Some(family)) => (given.clone(), family.clone()),
+            _ => {
+                let name = claims.name.clone().unwrap_or_else(|| email.clone());
+                match name.split_once(' ') {
+                    Some((first, last)) => (first.to_string(), last.to_string()),
+                    None => (name, String::new()),
+                }
+            }
+        };
+
+        let user = User {
+            id: Uuid::new_v4(),
+            tenant_id: tenant.id,
+            email,
+            password_hash,
+            first_name,
+            last_name,
+            display_name: claims.name.clone(),
+            phone: None,
+            avatar_url: None,
+            roles: config.default_roles.clone(),
+            permissions: vec![],
+            is_active: true,
+            email_verified: true,
+            phone_verified: false,
+            two_factor_enabled: false,
+            last_login: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            password_changed_at: Some(Utc::now()),
+            force_password_change: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        };
+
+        info!("Provisioning user {} in tenant {} from single sign-on", user.id, tenant.id);
+        self.user_repo.create_user(&user).await
+    }
+
+    pub async fn get_sso_config(&self, user_id: Uuid) -> Result<SsoConfigResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_sso(&user)?;
+
+        let config = self.sso_configs.load(user.tenant_id).await?.ok_or(AuthError::SsoNotConfigured)?;
+        Ok(config.into())
+    }
+
+    /// Create or replace the tenant's identity provider settings. The provider's discovery
+    /// document is fetched first so a typo in the issuer cannot lock the tenant out.
+    pub async fn update_sso_config(&self, user_id: Uuid, request: UpdateSsoConfigRequest) -> Result<SsoConfigResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_sso(&user)?;
+
+        let existing = self.sso_configs.load(user.tenant_id).await?;
+        let client_secret = match (request.client_secret, existing) {
+            (Some(secret), _) => secret,
+            (None, Some(existing)) => existing.client_secret,
+            (None, None) => return Err(AuthError::Validation("client_secret is required".to_string())),
+        };
+
+        let default_roles = request.default_roles.unwrap_or_else(sso::default_roles);
+        let is_admin = user.roles.iter().any(|r| r == "admin");
+        if let Some(role) = default_roles.iter().find(|role| !is_admin && !user.roles.contains(role)) {
+            return Err(AuthError::InsufficientPermissions(format!("Cannot grant role {}", role)));
+        }
+
+        let config = OidcProviderConfig {
+            issuer: request.issuer.trim_end_matches('/').to_string(),
+            client_id: request.client_id,
+            client_secret,
+            redirect_uri: request.redirect_uri,
+            scopes: request.scopes.unwrap_or_else(sso::default_scopes),
+            sso_required: request.sso_required,
+            jit_provisioning: request.jit_provisioning.unwrap_or(true),
+            allowed_email_domains: request
+                .allowed_email_domains
+                .into_iter()
+                .map(|domain| domain.to_lowercase())
+                .collect(),
+            default_roles,
+        };
+        if !config.scopes.iter().any(|s| s == "openid") {
+            return Err(AuthError::Validation("scopes must include openid".to_string()));
+        }
+
+        self.oidc.discover(&config.issuer).await?;
+        self.sso_configs.save(user.tenant_id, &config, user.id).await?;
+
+        info!("User {} updated SSO configuration for tenant {}", user.id, user.tenant_id);
+        Ok(config.into())
+    }
+
+    /// Password and magic link logins are refused when the tenant requires SSO
+    async fn ensure_password_login_allowed(&self, tenant_id: Uuid) -> Result<()> {
+        match self.sso_configs.load(tenant_id).await? {
+            Some(config) if config.sso_required => Err(AuthError::SsoRequired),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn reset_password(&self, request: ResetPasswordRequest) -> Result<()> {
+        // Verify the reset token
+        let user_id = self.jwt.verify_special_token(&request.token, "password_reset")
+            .map_err(|_| AuthError::InvalidToken("Invalid or expired reset token".to_string()))?;
+
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        let new_password_hash = self.replacement_password_hash(&user, &request.new_password).await?;
+
+        // Update the user's password
+        user.update_password(new_password_hash);
+        self.user_repo.update_user(&user).await?;
+        self.user_repo.add_password_history(user.id, &user.password_hash).await?;
+
+        // Revoke all existing refresh tokens for security
+        self.user_repo.revoke_all_user_tokens(user_id).await?;
+
+        Ok(())
+    }
+
+    pub async fn change_password(&self, user_id: Uuid, request: ChangePasswordRequest) -> Result<()> {
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        // Verify current password
+        if !self.password.verify_password(&request.current_password, &user.password_hash)? {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let new_password_hash = self.replacement_password_hash(&user, &request.new_password).await?;
+
+        // Update the user's password
+        user.update_password(new_password_hash);
+        self.user_repo.update_user(&user).await?;
+        self.user_repo.add_password_history(user.id, &user.password_hash).await?;
+
+        // Revoke all existing refresh tokens except current session for security
+        // In a real implementation, you might want to keep the current session active
+        self.user_repo.revoke_all_user_tokens(user_id).await?;
+
+        Ok(())
+    }
+
+    /// Verify the restricted token issued while a password change is required, returning the user id
+    pub fn verify_password_change_token(&self, token: &str) -> Result<Uuid> {
+        self.jwt
+            .verify_special_token(token, "password_change")
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+
+    /// Check a new password against the policy and the user's recent passwords, then hash it
+    async fn replacement_password_hash(&self, user: &User, new_password: &str) -> Result<String> {
+        let history_count = self.password.policy().password_history_count;
+        let mut previous_hashes = vec![user.password_hash.clone()];
+        previous_hashes.extend(
+            self.user_repo.find_password_history(user.id, history_count).await?
+                .into_iter()
+                .filter(|hash| *hash != user.password_hash),
+        );
+
+        self.password.validate_new_password(new_password, &previous_hashes)?;
+        self.password.hash_password(new_password)
+    }
+
+    /// Force a user to choose a new password at their next login
+    pub async fn require_password_change(&self, actor_id: Uuid, user_id: Uuid) -> Result<()> {
+        let actor = self.user_repo.find_user_by_id(actor_id).await?;
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+
+        self.ensure_can_manage_users(&actor)?;
+        if actor.tenant_id != user.tenant_id {
+            return Err(AuthError::UserNotFound);
+        }
+
+        user.force_password_change = true;
+        user.updated_at = Utc::now();
+        self.user_repo.update_user(&user).await?;
+
+        info!("Password change required for user {} by {}", user_id, actor_id);
+        Ok(())
+    }
+
+    pub async fn verify_email(&self, token: &str) -> Result<()> {
+        // Verify the email verification token
+        let user_id = self.jwt.verify_special_token(token, "email_verification")
+            .map_err(|_| AuthError::InvalidToken("Invalid or expired verification token".to_string()))?;
+
+        let mut user = self.user_repo.find_user_by_id(user_id).await?;
+
+        // Mark email as verified
+        user.verify_email();
+        self.user_repo.update_user(&user).await?;
+
+        Ok(())
+    }
+
+    pub async fn get_active_sessions(&self, user_id: Uuid) -> Result<Vec<SessionInfo>> {
+        // Get all active refresh tokens for the user (which represent sessions)
         let refresh_tokens = self.user_repo.find_active_refresh_tokens(user_id).await?;
 
-        let sessions = refresh_tokens
+        let mut sessions: Vec<SessionInfo> = refresh_tokens
+            .into_iter()
+            .map(|token| SessionInfo {
+                id: token.id,
+                device_id: token.device_id,
+                device_name: token.device_name,
+                ip_address: token.ip_address,
+                user_agent: token.user_agent,
+                created_at: token.created_at,
+                last_used_at: token.created_at, // TODO: Track actual last usage
+                is_current: false, // TODO: Determine current session
+                impersonation: None,
+                location: serde_json::from_value::<GeoLocation>(token.location)
+                    .ok()
+                    .filter(|location| *location != GeoLocation::default()),
+                risk_score: token.risk_score,
+                risk_signals: token.risk_signals,
+            })
+            .collect();
+
+        // Users can always see when support staff are acting as them
+        let impersonations = self.user_repo.find_active_impersonation_sessions(user_id).await?;
+        sessions.extend(impersonations.into_iter().map(|session| SessionInfo {
+            id: session.id,
+            device_id: None,
+            device_name: None,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+            created_at: session.started_at,
+            last_used_at: session.started_at,
+            is_current: false,
+            impersonation: Some(SessionImpersonation {
+                impersonator_id: session.impersonator_id,
+                impersonator_email: session.impersonator_email,
+                reason: session.reason,
+                read_only: session.read_only,
+                expires_at: session.expires_at,
+            }),
+            location: None,
+            risk_score: 0,
+            risk_signals: vec![],
+        }));
+
+        Ok(sessions)
+    }
+
+    /// Issue a time-limited token that lets platform support act as a tenant user.
+    /// Read-only unless writes are requested; start and end are published for the audit log.
+    pub async fn start_impersonation(
+        &self,
+        actor: &Claims,
+        request: StartImpersonationRequest,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<ImpersonationResponse> {
+        if actor.impersonation.is_some() {
+            return Err(AuthError::InsufficientPermissions(
+                "Cannot start impersonation while impersonating".to_string(),
+            ));
+        }
+
+        // Granted directly or through a role, like any other permission
+        let impersonator = self.user_repo.find_user_by_id(actor.sub).await?;
+        let impersonator_permissions = PermissionSet::from_strings(&self.effective_permissions(&impersonator).await?);
+        if !impersonator_permissions.allows(permissions::SUPPORT_IMPERSONATE) {
+            return Err(AuthError::InsufficientPermissions(permissions::SUPPORT_IMPERSONATE.to_string()));
+        }
+        if impersonator.id == request.user_id {
+            return Err(AuthError::Validation("Cannot impersonate yourself".to_string()));
+        }
+
+        let user = self.user_repo.find_user_by_id(request.user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        let read_only = !request.allow_writes;
+        let duration = Duration::minutes(request.duration_minutes.unwrap_or(IMPERSONATION_DEFAULT_MINUTES));
+        let permissions = self.effective_permissions(&user).await?;
+
+        let (access_token, issued) = self.jwt.generate_impersonation_token(
+            TokenSubject {
+                user_id: user.id,
+                tenant_id: tenant.id,
+                email: user.email.clone(),
+                roles: user.roles.clone(),
+                permissions,
+            },
+            ActorClaims {
+                sub: impersonator.id.to_string(),
+                email: impersonator.email.clone(),
+                read_only,
+                reason: request.reason.clone(),
+            },
+            duration.num_seconds(),
+        )?;
+
+        let session = ImpersonationSession {
+            id: issued.session_id.parse()
+                .map_err(|_| AuthError::Internal("Invalid impersonation session ID".to_string()))?,
+            impersonator_id: impersonator.id,
+            impersonator_email: impersonator.email.clone(),
+            user_id: user.id,
+            tenant_id: tenant.id,
+            reason: request.reason,
+            read_only,
+            ip_address,
+            user_agent,
+            started_at: Utc::now(),
+            expires_at: Utc::now() + duration,
+            ended_at: None,
+        };
+        self.user_repo.store_impersonation_session(&session).await?;
+
+        warn!(
+            "User {} started impersonating user {} in tenant {} (read_only: {}, reason: {})",
+            impersonator.id, user.id, tenant.id, read_only, session.reason
+        );
+
+        self.publish_event(
+            auth_events::IMPERSONATION_STARTED,
+            user.id,
+            tenant.id,
+            ImpersonationStartedEvent {
+                session_id: session.id,
+                impersonator_id: impersonator.id,
+                impersonator_email: impersonator.email,
+                user_id: user.id,
+                tenant_id: tenant.id,
+                reason: session.reason.clone(),
+                read_only,
+                expires_at: session.expires_at,
+                ip_address: session.ip_address.clone(),
+            },
+        ).await;
+
+        Ok(ImpersonationResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: duration.num_seconds(),
+            session_id: session.id,
+            read_only,
+            user: user.to_response(&tenant),
+        })
+    }
+
+    /// End the impersonation session the token belongs to and revoke the token
+    pub async fn end_impersonation(&self, claims: &Claims) -> Result<()> {
+        let Some(impersonation) = &claims.impersonation else {
+            return Err(AuthError::Validation("Token is not an impersonation token".to_string()));
+        };
+
+        let remaining = (claims.exp - Utc::now().timestamp()).max(1) as u64;
+        self.revocations
+            .revoke(RevocationKind::Session, &claims.session_id.to_string(), std::time::Duration::from_secs(remaining))
+            .await?;
+
+        if self.user_repo.end_impersonation_session(claims.session_id).await? {
+            self.publish_event(
+                auth_events::IMPERSONATION_ENDED,
+                claims.sub,
+                claims.tenant_id,
+                ImpersonationEndedEvent {
+                    session_id: claims.session_id,
+                    impersonator_id: impersonation.impersonator_id,
+                    user_id: claims.sub,
+                    tenant_id: claims.tenant_id,
+                    ended_at: Utc::now(),
+                },
+            ).await;
+        }
+
+        Ok(())
+    }
+
+    /// Audit a request made under an impersonation token
+    pub async fn record_impersonated_request(&self, claims: &Claims, method: &Method, path: &str, status: u16) {
+        let Some(impersonation) = &claims.impersonation else {
+            return;
+        };
+
+        let request = ImpersonatedRequestEvent {
+            session_id: claims.session_id,
+            impersonator_id: impersonation.impersonator_id,
+            user_id: claims.sub,
+            tenant_id: claims.tenant_id,
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+        };
+
+        // Kept even without an event bus; the response is already decided, so a failure is only logged
+        if let Err(e) = self.impersonation_audit.record(&request).await {
+            warn!(
+                "Failed to record impersonated request {} {} in session {}: {}",
+                request.method, request.path, request.session_id, e
+            );
+        }
+
+        self.publish_event(auth_events::IMPERSONATED_REQUEST, claims.sub, claims.tenant_id, request).await;
+    }
+
+    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
+        // Find the refresh token and verify it belongs to the user
+        let refresh_token = self.user_repo.find_refresh_token_by_id(session_id).await?;
+
+        if refresh_token.user_id != user_id {
+            return Err(AuthError::InvalidToken("Session does not belong to user".to_string()));
+        }
+
+        // Revoke the specific session, including access tokens already issued for it
+        self.user_repo.revoke_refresh_token(session_id).await?;
+        self.deny_session(refresh_token.family_id).await?;
+        Ok(())
+    }
+
+    pub async fn revoke_all_other_sessions(&self, user_id: Uuid, current_session_id: Option<Uuid>) -> Result<()> {
+        for token in self.user_repo.find_active_refresh_tokens(user_id).await? {
+            if Some(token.id) != current_session_id {
+                self.deny_session(token.family_id).await?;
+            }
+        }
+
+        // Revoke all sessions except the current one
+        if let Some(current_id) = current_session_id {
+            self.user_repo.revoke_all_user_tokens_except(user_id, current_id).await?;
+        } else {
+            self.user_repo.revoke_all_user_tokens(user_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Register a shared POS terminal at a location. The returned terminal token is bound
+    /// to the device and is only ever returned here.
+    pub async fn register_terminal(
+        &self,
+        user_id: Uuid,
+        request: RegisterTerminalRequest,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<RegisteredTerminalResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_terminals(&user)?;
+
+        let terminal = PosTerminal {
+            id: Uuid::new_v4(),
+            tenant_id: user.tenant_id,
+            location_id: request.location_id,
+            name: request.name,
+            device_id: request.device_id,
+            failed_pin_attempts: 0,
+            locked_until: None,
+            registered_by: user.id,
+            last_switch_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+
+        let terminal_token = self.jwt.generate_terminal_token(
+            terminal.id,
+            terminal.tenant_id,
+            terminal.location_id,
+            &DeviceInfo {
+                device_id: Some(terminal.device_id.clone()),
+                ip_address: Some(ip_address),
+                user_agent: Some(user_agent),
+            },
+        )?;
+        self.user_repo.store_terminal(&terminal).await?;
+        info!("Registered terminal {} at location {} by {}", terminal.id, terminal.location_id, user.id);
+
+        Ok(RegisteredTerminalResponse {
+            terminal: terminal.into(),
+            terminal_token,
+            expires_in: TERMINAL_SESSION_DURATION_SECONDS,
+        })
+    }
+
+    pub async fn list_terminals(&self, user_id: Uuid) -> Result<Vec<PosTerminalResponse>> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_terminals(&user)?;
+
+        let terminals = self.user_repo.find_terminals_by_tenant(user.tenant_id).await?;
+        Ok(terminals.into_iter().filter(|t| !t.is_revoked()).map(Into::into).collect())
+    }
+
+    /// Retire a terminal, ending the session of whoever is switched onto it
+    pub async fn revoke_terminal(&self, user_id: Uuid, terminal_id: Uuid) -> Result<()> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_terminals(&user)?;
+
+        let mut terminal = self.user_repo.find_terminal_by_id(terminal_id, user.tenant_id).await?;
+        terminal.revoked_at = Some(Utc::now());
+        self.user_repo.update_terminal(&terminal).await?;
+
+        // Switch tokens use the terminal ID as their session ID
+        self.revocations
+            .revoke(
+                RevocationKind::Session,
+                &terminal.id.to_string(),
+                std::time::Duration::from_secs(PIN_SWITCH_DURATION_SECONDS as u64),
+            )
+            .await?;
+        info!("Revoked terminal {} by {}", terminal.id, user.id);
+        Ok(())
+    }
+
+    /// Set or replace the caller's quick-switch PIN; the account password confirms the change
+    pub async fn set_pin(&self, user_id: Uuid, request: SetPinRequest) -> Result<()> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        if !user.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        if !self.password.verify_password(&request.current_password, &user.password_hash)? {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let pin = UserPin {
+            user_id: user.id,
+            tenant_id: user.tenant_id,
+            pin_hash: self.password.hash_pin(&request.pin)?,
+            updated_at: Utc::now(),
+        };
+        self.user_repo.save_user_pin(&pin).await?;
+        Ok(())
+    }
+
+    /// Switch the active user on a registered terminal with their PIN. Wrong PINs count
+    /// against the terminal rather than the user, so one device cannot be used to guess
+    /// through the whole staff list; every attempt is published for the audit log.
+    pub async fn switch_user_with_pin(
+        &self,
+        terminal_token: &str,
+        device_id: &str,
+        request: PinSwitchRequest,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<PinSwitchResponse> {
+        let mut terminal = self.verify_terminal(terminal_token, device_id).await?;
+        let client = ClientDevice {
+            device_id: Some(device_id.to_string()),
+            device_name: Some(terminal.name.clone()),
+            ip_address,
+            user_agent,
+        };
+
+        if terminal.is_locked() {
+            self.publish_pin_attempt(
+                &terminal,
+                request.user_id,
+                None,
+                AuthenticationResult::Blocked,
+                Some(AuthenticationFailureReason::TooManyAttempts),
+                &client,
+            ).await;
+            return Err(AuthError::TerminalLocked);
+        }
+
+        let user = match self.user_repo.find_user_by_id(request.user_id).await {
+            Ok(user) if user.tenant_id == terminal.tenant_id => Some(user),
+            Ok(_) | Err(AuthError::UserNotFound) => None,
+            Err(e) => return Err(e),
+        };
+        let failure = match &user {
+            None => Some(AuthenticationFailureReason::AccountNotFound),
+            Some(user) if !user.is_active || user.is_locked() => Some(AuthenticationFailureReason::AccountDisabled),
+            Some(user) => match self.user_repo.find_user_pin(user.id).await? {
+                Some(pin) if self.password.verify_password(&request.pin, &pin.pin_hash)? => None,
+                _ => Some(AuthenticationFailureReason::InvalidCredentials),
+            },
+        };
+
+        if let Some(reason) = failure {
+            let locked = terminal::record_failed_pin(&mut terminal);
+            self.user_repo.update_terminal(&terminal).await?;
+            if locked {
+                warn!("Terminal {} locked after repeated PIN failures", terminal.id);
+            }
+
+            let (result, reason) = if locked {
+                (AuthenticationResult::AccountLocked, AuthenticationFailureReason::TooManyAttempts)
+            } else {
+                (AuthenticationResult::Failed, reason)
+            };
+            self.publish_pin_attempt(&terminal, request.user_id, user.as_ref(), result, Some(reason), &client)
+                .await;
+            return Err(if locked { AuthError::TerminalLocked } else { AuthError::InvalidPin });
+        }
+
+        let user = user.ok_or(AuthError::UserNotFound)?;
+        let tenant = self.user_repo.find_tenant_by_id(user.tenant_id).await?;
+        if !tenant.is_active {
+            return Err(AuthError::TenantInactive);
+        }
+
+        terminal.failed_pin_attempts = 0;
+        terminal.last_switch_at = Some(Utc::now());
+        self.user_repo.update_terminal(&terminal).await?;
+
+        let permissions = terminal::location_scoped_permissions(
+            &self.effective_permissions(&user).await?,
+            terminal.location_id,
+        );
+        let (access_token, _) = self.jwt.generate_terminal_access_token(
+            TokenSubject {
+                user_id: user.id,
+                tenant_id: tenant.id,
+                email: user.email.clone(),
+                roles: user.roles.clone(),
+                permissions,
+            },
+            terminal.id,
+            terminal.location_id,
+        )?;
+
+        self.publish_pin_attempt(
+            &terminal,
+            user.id,
+            Some(&user),
+            AuthenticationResult::Success,
+            None,
+            &client,
+        ).await;
+
+        Ok(PinSwitchResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: PIN_SWITCH_DURATION_SECONDS,
+            terminal_id: terminal.id,
+            location_id: terminal.location_id,
+            user: user.to_response(&tenant),
+        })
+    }
+
+    /// Load the terminal a session token was issued to, checking the device it is presented from
+    async fn verify_terminal(&self, terminal_token: &str, device_id: &str) -> Result<PosTerminal> {
+        let claims = self.jwt.validate_terminal_token(terminal_token, device_id)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        let terminal_id: Uuid = claims.sub.parse()
+            .map_err(|_| AuthError::InvalidToken("Invalid terminal ID".to_string()))?;
+        let tenant_id: Uuid = claims.tenant_id.parse()
+            .map_err(|_| AuthError::InvalidToken("Invalid tenant ID".to_string()))?;
+
+        let terminal = self.user_repo.find_terminal_by_id(terminal_id, tenant_id).await?;
+        if terminal.is_revoked() {
+            return Err(AuthError::TokenRevoked);
+        }
+        if terminal.device_id != device_id {
+            return Err(AuthError::InvalidToken("Terminal token was issued to another device".to_string()));
+        }
+        Ok(terminal)
+    }
+
+    async fn publish_pin_attempt(
+        &self,
+        terminal: &PosTerminal,
+        user_id: Uuid,
+        user: Option<&User>,
+        result: AuthenticationResult,
+        failure_reason: Option<AuthenticationFailureReason>,
+        client: &ClientDevice,
+    ) {
+        self.publish_event(
+            auth_events::USER_AUTHENTICATION_ATTEMPT,
+            user_id,
+            terminal.tenant_id,
+            terminal::pin_switch_attempt(
+                terminal,
+                user.map(|user| user.id),
+                user.map(|user| user.email.clone()).unwrap_or_default(),
+                result,
+                failure_reason,
+                client.ip_address.clone(),
+                client.user_agent.clone(),
+            ),
+        ).await;
+    }
+
+    /// Create a tenant API key; the plaintext key is only ever returned here
+    pub async fn create_api_key(&self, user_id: Uuid, request: CreateApiKeyRequest) -> Result<CreatedApiKeyResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_api_keys(&user)?;
+        self.ensure_can_grant(&user, &request.permissions)?;
+
+        let scopes = api_keys::normalize_scopes(request.scopes)?;
+        let generated = api_keys::generate_api_key();
+        let expires_at = request.expires_in_days.map(|days| Utc::now() + Duration::days(days));
+
+        let mut key = ApiKey::new(
+            user.id,
+            request.name,
+            self.password.hash_token(&generated.plaintext)?,
+            generated.prefix,
+            expires_at,
+        );
+        for permission in request.permissions {
+            key.add_permission(permission);
+        }
+        key.scopes = scopes;
+
+        self.user_repo.store_api_key(&key).await?;
+
+        Ok(CreatedApiKeyResponse {
+            api_key: generated.plaintext,
+            key: key.into(),
+        })
+    }
+
+    pub async fn list_api_keys(&self, user_id: Uuid) -> Result<Vec<ApiKeyResponse>> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_api_keys(&user)?;
+
+        let keys = self.user_repo.find_api_keys_by_tenant(user.tenant_id).await?;
+        Ok(keys.into_iter().map(ApiKeyResponse::from).collect())
+    }
+
+    pub async fn get_api_key(&self, user_id: Uuid, key_id: Uuid) -> Result<ApiKeyResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_api_keys(&user)?;
+
+        let key = self.user_repo.find_api_key_by_id(key_id, user.tenant_id).await?;
+        Ok(key.into())
+    }
+
+    pub async fn update_api_key(&self, user_id: Uuid, key_id: Uuid, request: UpdateApiKeyRequest) -> Result<ApiKeyResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_api_keys(&user)?;
+
+        let mut key = self.user_repo.find_api_key_by_id(key_id, user.tenant_id).await?;
+
+        if let Some(name) = request.name {
+            key.name = name;
+        }
+        if let Some(permissions) = request.permissions {
+            self.ensure_can_grant(&user, &permissions)?;
+            key.permissions.clear();
+            for permission in permissions {
+                key.add_permission(permission);
+            }
+        }
+        if let Some(scopes) = request.scopes {
+            key.scopes = api_keys::normalize_scopes(Some(scopes))?;
+        }
+        key.updated_at = Utc::now();
+
+        self.user_repo.update_api_key(&key).await?;
+        Ok(key.into())
+    }
+
+    pub async fn revoke_api_key(&self, user_id: Uuid, key_id: Uuid) -> Result<()> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_api_keys(&user)?;
+
+        let mut key = self.user_repo.find_api_key_by_id(key_id, user.tenant_id).await?;
+        key.revoke();
+        self.user_repo.update_api_key(&key).await?;
+        Ok(())
+    }
+
+    /// Resolve an API key presented on a request into tenant claims
+    pub async fn authenticate_api_key(&self, raw_key: &str, method: &Method) -> Result<Claims> {
+        if !api_keys::is_well_formed(raw_key) {
+            return Err(AuthError::InvalidToken("Malformed API key".to_string()));
+        }
+
+        let key_hash = self.password.hash_token(raw_key)?;
+        let mut key = self.user_repo.find_api_key_by_hash(&key_hash).await
+            .map_err(|_| AuthError::InvalidToken("Unknown API key".to_string()))?;
+
+        if key.is_expired() {
+            return Err(AuthError::TokenExpired);
+        }
+        if !key.is_valid() {
+            return Err(AuthError::TokenRevoked);
+        }
+
+        if !api_keys::scopes_allow_method(&key.scopes, method) {
+            return Err(AuthError::InsufficientPermissions(format!(
+                "API key scopes {:?} do not allow {} requests",
+                key.scopes, method
+            )));
+        }
+
+        let owner = self.user_repo.find_user_by_id(key.user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(owner.tenant_id).await?;
+        if !owner.is_active || !tenant.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        key.record_usage();
+        self.user_repo.update_api_key(&key).await?;
+
+        // The claims only live for this request, but bound them like an access token so a
+        // key without an expiry never produces claims that look valid forever
+        let now = Utc::now().timestamp();
+        let session_exp = now + self.jwt.access_token_duration();
+        Ok(Claims {
+            sub: owner.id,
+            tenant_id: tenant.id,
+            email: format!("api-key:{}", key.key_prefix),
+            roles: vec![api_keys::API_KEY_ROLE.to_string()],
+            permissions: key.permissions,
+            session_id: key.id,
+            iat: now,
+            exp: key.expires_at.map_or(session_exp, |exp| exp.timestamp().min(session_exp)),
+            impersonation: None,
+            client_id: None,
+            terminal_id: None,
+            location_id: None,
+        })
+    }
+
+    /// Register a third-party application for the caller's tenant. Confidential clients
+    /// get a secret that is only ever returned here.
+    pub async fn register_oauth_client(&self, user_id: Uuid, request: RegisterOAuthClientRequest) -> Result<RegisteredOAuthClientResponse> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_oauth_clients(&user)?;
+        self.ensure_can_grant(&user, &request.scopes)?;
+
+        for uri in &request.redirect_uris {
+            oauth::validate_redirect_uri(uri)?;
+        }
+
+        let mut grant_types = request.grant_types.unwrap_or_else(|| {
+            vec![oauth::GRANT_AUTHORIZATION_CODE.to_string(), oauth::GRANT_REFRESH_TOKEN.to_string()]
+        });
+        grant_types.sort();
+        grant_types.dedup();
+        if let Some(unknown) = grant_types.iter().find(|g| {
+            !matches!(
+                g.as_str(),
+                oauth::GRANT_AUTHORIZATION_CODE | oauth::GRANT_CLIENT_CREDENTIALS | oauth::GRANT_REFRESH_TOKEN
+            )
+        }) {
+            return Err(AuthError::UnsupportedGrantType(unknown.clone()));
+        }
+        // A public client cannot keep a secret, so it cannot act on its own behalf
+        if !request.confidential && grant_types.iter().any(|g| g == oauth::GRANT_CLIENT_CREDENTIALS) {
+            return Err(AuthError::Validation("Public clients cannot use the client_credentials grant".to_string()));
+        }
+
+        let mut scopes = request.scopes;
+        scopes.sort();
+        scopes.dedup();
+
+        let client_secret = request.confidential.then(oauth::generate_client_secret);
+        let client = OAuthClient {
+            id: Uuid::new_v4(),
+            tenant_id: user.tenant_id,
+            client_id: oauth::generate_client_id(),
+            client_secret_hash: client_secret
+                .as_deref()
+                .map(|secret| self.password.hash_token(secret))
+                .transpose()?,
+            name: request.name,
+            redirect_uris: request.redirect_uris,
+            allowed_scopes: scopes,
+            grant_types,
+            is_active: true,
+            created_by: user.id,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        self.user_repo.store_oauth_client(&client).await?;
+
+        info!("Registered OAuth client {} for tenant {} by {}", client.client_id, client.tenant_id, user.id);
+        Ok(RegisteredOAuthClientResponse {
+            client_secret,
+            client: client.into(),
+        })
+    }
+
+    pub async fn list_oauth_clients(&self, user_id: Uuid) -> Result<Vec<OAuthClientResponse>> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_oauth_clients(&user)?;
+
+        let clients = self.user_repo.find_oauth_clients_by_tenant(user.tenant_id).await?;
+        Ok(clients.into_iter().map(OAuthClientResponse::from).collect())
+    }
+
+    /// Deactivate a client; tokens it already holds stop introspecting as active
+    pub async fn revoke_oauth_client(&self, user_id: Uuid, id: Uuid) -> Result<()> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_oauth_clients(&user)?;
+
+        let mut client = self.user_repo.find_oauth_client_by_id(id).await?;
+        if client.tenant_id != user.tenant_id {
+            return Err(AuthError::OAuthClientNotFound);
+        }
+
+        client.is_active = false;
+        client.updated_at = Utc::now();
+        self.user_repo.update_oauth_client(&client).await?;
+        info!("Revoked OAuth client {} by {}", client.client_id, user.id);
+        Ok(())
+    }
+
+    /// Describe an authorization request for the consent screen
+    pub async fn oauth_consent_prompt(&self, claims: &Claims, request: AuthorizeRequest) -> Result<ConsentPromptResponse> {
+        let (client, scopes) = self.validate_authorization_request(claims, &request).await?;
+
+        let granted = self.user_repo.find_oauth_consent(claims.sub, client.id).await?
+            .map(|consent| consent.scopes)
+            .unwrap_or_default();
+        let new_scopes: Vec<String> = scopes.iter().filter(|s| !granted.contains(s)).cloned().collect();
+
+        Ok(ConsentPromptResponse {
+            client_id: client.client_id,
+            client_name: client.name,
+            redirect_uri: request.redirect_uri,
+            scopes,
+            consent_required: !new_scopes.is_empty(),
+            new_scopes,
+        })
+    }
+
+    /// Record the user's consent decision and return the redirect carrying a code or an error
+    pub async fn oauth_authorize(&self, claims: &Claims, decision: ConsentDecisionRequest) -> Result<AuthorizeRedirectResponse> {
+        let request = decision.authorization;
+        let (client, scopes) = self.validate_authorization_request(claims, &request).await?;
+
+        let mut params: Vec<(&str, &str)> = Vec::new();
+        if let Some(state) = request.state.as_deref() {
+            params.push(("state", state));
+        }
+
+        if !decision.approved {
+            params.push(("error", "access_denied"));
+            return Ok(AuthorizeRedirectResponse {
+                redirect_uri: oauth::build_redirect(&request.redirect_uri, &params),
+            });
+        }
+
+        // The client never gets more than the user could do themselves
+        let user = self.user_repo.find_user_by_id(claims.sub).await?;
+        let is_admin = user.roles.iter().any(|r| r == "admin");
+        let scopes: Vec<String> = scopes
+            .into_iter()
+            .filter(|scope| is_admin || user.permissions.contains(scope))
+            .collect();
+
+        let mut consent = self.user_repo.find_oauth_consent(user.id, client.id).await?
+            .unwrap_or(OAuthConsent {
+                user_id: user.id,
+                client_id: client.id,
+                scopes: vec![],
+                granted_at: Utc::now(),
+            });
+        consent.scopes.extend(scopes.iter().cloned());
+        consent.scopes.sort();
+        consent.scopes.dedup();
+        consent.granted_at = Utc::now();
+        self.user_repo.save_oauth_consent(&consent).await?;
+
+        let code = oauth::generate_authorization_code();
+        let authorization = OAuthAuthorizationCode {
+            id: Uuid::new_v4(),
+            code_hash: self.password.hash_token(&code)?,
+            client_id: client.id,
+            user_id: user.id,
+            tenant_id: user.tenant_id,
+            redirect_uri: request.redirect_uri.clone(),
+            scopes,
+            code_challenge: request.code_challenge.clone(),
+            code_challenge_method: oauth::PKCE_METHOD_S256.to_string(),
+            expires_at: Utc::now() + Duration::seconds(oauth::AUTHORIZATION_CODE_DURATION_SECONDS),
+            consumed_at: None,
+            created_at: Utc::now(),
+        };
+        self.user_repo.store_authorization_code(&authorization).await?;
+
+        params.insert(0, ("code", code.as_str()));
+        Ok(AuthorizeRedirectResponse {
+            redirect_uri: oauth::build_redirect(&request.redirect_uri, &params),
+        })
+    }
+
+    /// Checks shared by the consent prompt and decision; errors here must not redirect,
+    /// since the redirect URI itself may be untrusted
+    async fn validate_authorization_request(&self, claims: &Claims, request: &AuthorizeRequest) -> Result<(OAuthClient, Vec<String>)> {
+        if claims.client_id.is_some() {
+            return Err(AuthError::InsufficientPermissions(
+                "OAuth client tokens cannot authorize other clients".to_string(),
+            ));
+        }
+
+        let client = self.user_repo.find_oauth_client_by_client_id(&request.client_id).await?;
+        if !client.is_active || client.tenant_id != claims.tenant_id {
+            return Err(AuthError::OAuthClientNotFound);
+        }
+        if !oauth::redirect_uri_registered(&client.redirect_uris, &request.redirect_uri) {
+            return Err(AuthError::Validation("redirect_uri is not registered for this client".to_string()));
+        }
+        if request.response_type != "code" {
+            return Err(AuthError::Validation(format!("Unsupported response_type: {}", request.response_type)));
+        }
+        if !client.allows_grant(oauth::GRANT_AUTHORIZATION_CODE) {
+            return Err(AuthError::UnsupportedGrantType(oauth::GRANT_AUTHORIZATION_CODE.to_string()));
+        }
+        if request.code_challenge.is_empty()
+            || request.code_challenge_method.as_deref() != Some(oauth::PKCE_METHOD_S256)
+        {
+            return Err(AuthError::Validation("PKCE with code_challenge_method S256 is required".to_string()));
+        }
+
+        let scopes = oauth::parse_scope(request.scope.as_deref(), &client.allowed_scopes)?;
+        Ok((client, scopes))
+    }
+
+    /// The OAuth token endpoint: authorization code, refresh token and client credentials grants
+    pub async fn oauth_token(
+        &self,
+        request: OAuthTokenRequest,
+        credentials: Option<(String, String)>,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<OAuthTokenResponse> {
+        let client = self.authenticate_oauth_client(&request.client_id, &request.client_secret, credentials).await?;
+        if !client.allows_grant(&request.grant_type) {
+            return Err(AuthError::UnsupportedGrantType(request.grant_type));
+        }
+
+        match request.grant_type.as_str() {
+            oauth::GRANT_AUTHORIZATION_CODE => self.exchange_authorization_code(&client, request, ip_address, user_agent).await,
+            oauth::GRANT_REFRESH_TOKEN => self.refresh_oauth_token(&client, request, ip_address, user_agent).await,
+            oauth::GRANT_CLIENT_CREDENTIALS => self.client_credentials_token(&client, request),
+            _ => Err(AuthError::UnsupportedGrantType(request.grant_type)),
+        }
+    }
+
+    async fn exchange_authorization_code(
+        &self,
+        client: &OAuthClient,
+        request: OAuthTokenRequest,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<OAuthTokenResponse> {
+        let (Some(code), Some(redirect_uri), Some(code_verifier)) = (request.code, request.redirect_uri, request.code_verifier) else {
+            return Err(AuthError::Validation("code, redirect_uri and code_verifier are required".to_string()));
+        };
+
+        let code_hash = self.password.hash_token(&code)?;
+        let authorization = self.user_repo.consume_authorization_code(&code_hash).await?
+            .ok_or_else(|| AuthError::InvalidGrant("Invalid or already used authorization code".to_string()))?;
+
+        if authorization.client_id != client.id || authorization.redirect_uri != redirect_uri {
+            return Err(AuthError::InvalidGrant("Authorization code was issued to another client".to_string()));
+        }
+        if authorization.expires_at < Utc::now() {
+            return Err(AuthError::InvalidGrant("Authorization code expired".to_string()));
+        }
+        if !oauth::verify_pkce(&code_verifier, &authorization.code_challenge) {
+            return Err(AuthError::InvalidGrant("PKCE verification failed".to_string()));
+        }
+
+        let user = self.user_repo.find_user_by_id(authorization.user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(authorization.tenant_id).await?;
+        if !user.is_active || !tenant.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        // Each authorization starts its own refresh token family, like a login does
+        let (response, refresh_token) = self.sign_oauth_tokens(
+            client,
+            &user,
+            Uuid::new_v4(),
+            authorization.scopes,
+            ip_address,
+            user_agent,
+        )?;
+        if let Some(refresh_token) = &refresh_token {
+            self.user_repo.store_refresh_token(refresh_token).await?;
+        }
+
+        info!("Issued OAuth tokens to client {} for user {}", client.client_id, user.id);
+        Ok(response)
+    }
+
+    /// Rotate an OAuth refresh token; reuse is handled exactly as for first-party sessions
+    async fn refresh_oauth_token(
+        &self,
+        client: &OAuthClient,
+        request: OAuthTokenRequest,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<OAuthTokenResponse> {
+        let raw_token = request.refresh_token
+            .ok_or_else(|| AuthError::Validation("refresh_token is required".to_string()))?;
+        let token_hash = self.password.hash_token(&raw_token)?;
+        let refresh_token = self.user_repo.find_refresh_token(&token_hash).await
+            .map_err(|_| AuthError::InvalidGrant("Unknown refresh token".to_string()))?;
+
+        if refresh_token.client_id != Some(client.id) {
+            return Err(AuthError::InvalidGrant("Refresh token was issued to another client".to_string()));
+        }
+        if refresh_token.revoked_at.is_some() {
+            if refresh_token.replaced_by.is_some() {
+                self.handle_refresh_token_reuse(&refresh_token, ip_address, user_agent).await?;
+                return Err(AuthError::RefreshTokenReused);
+            }
+            return Err(AuthError::TokenRevoked);
+        }
+        if refresh_token.expires_at < Utc::now() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        // A refresh may narrow the grant but never widen it
+        let scopes = oauth::parse_scope(request.scope.as_deref(), &refresh_token.scopes)?;
+
+        let user = self.user_repo.find_user_by_id(refresh_token.user_id).await?;
+        let tenant = self.user_repo.find_tenant_by_id(refresh_token.tenant_id).await?;
+        if !user.is_active || !tenant.is_active {
+            return Err(AuthError::AccountInactive);
+        }
+
+        let (response, rotated) = self.sign_oauth_tokens(
+            client,
+            &user,
+            refresh_token.family_id,
+            scopes,
+            ip_address.clone(),
+            user_agent.clone(),
+        )?;
+        let rotated = rotated.ok_or_else(|| AuthError::Internal("Refresh token was not issued".to_string()))?;
+
+        if !self.user_repo.rotate_refresh_token(refresh_token.id, &rotated).await? {
+            self.handle_refresh_token_reuse(&refresh_token, ip_address, user_agent).await?;
+            return Err(AuthError::RefreshTokenReused);
+        }
+
+        Ok(response)
+    }
+
+    /// Tokens for a client acting on its own behalf; there is no user and no refresh token
+    fn client_credentials_token(&self, client: &OAuthClient, request: OAuthTokenRequest) -> Result<OAuthTokenResponse> {
+        if client.client_secret_hash.is_none() {
+            return Err(AuthError::InvalidClient);
+        }
+
+        let scopes = oauth::parse_scope(request.scope.as_deref(), &client.allowed_scopes)?;
+        let scope = oauth::format_scope(&scopes);
+        let (access_token, _) = self.jwt.generate_oauth_access_token(OAuthTokenGrant {
+            subject: client.id,
+            tenant_id: client.tenant_id,
+            email: format!("oauth-client:{}", client.client_id),
+            permissions: scopes,
+            session_id: Uuid::new_v4().to_string(),
+            client_id: client.client_id.clone(),
+            scope: scope.clone(),
+        })?;
+
+        Ok(OAuthTokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: self.jwt.access_token_duration(),
+            refresh_token: None,
+            scope,
+        })
+    }
+
+    /// Sign a delegated access token, plus a refresh token when the client may use one
+    fn sign_oauth_tokens(
+        &self,
+        client: &OAuthClient,
+        user: &User,
+        family_id: Uuid,
+        scopes: Vec<String>,
+        ip_address: String,
+        user_agent: String,
+    ) -> Result<(OAuthTokenResponse, Option<RefreshToken>)> {
+        let scope = oauth::format_scope(&scopes);
+        let (access_token, _) = self.jwt.generate_oauth_access_token(OAuthTokenGrant {
+            subject: user.id,
+            tenant_id: user.tenant_id,
+            email: user.email.clone(),
+            permissions: scopes.clone(),
+            session_id: family_id.to_string(),
+            client_id: client.client_id.clone(),
+            scope: scope.clone(),
+        })?;
+
+        let (refresh_token, record) = if client.allows_grant(oauth::GRANT_REFRESH_TOKEN) {
+            let token = self.jwt.generate_refresh_token(user.id, user.tenant_id, family_id.to_string())?;
+            let record = RefreshToken {
+                id: Uuid::new_v4(),
+                token_hash: self.password.hash_token(&token)?,
+                family_id,
+                user_id: user.id,
+                tenant_id: user.tenant_id,
+                device_id: None,
+                device_name: Some(client.name.clone()),
+                ip_address,
+                user_agent,
+                expires_at: Utc::now() + Duration::seconds(self.jwt.refresh_token_duration()),
+                revoked_at: None,
+                replaced_by: None,
+                client_id: Some(client.id),
+                scopes,
+                location: serde_json::json!({}),
+                risk_score: 0,
+                risk_signals: vec![],
+                created_at: Utc::now(),
+            };
+            (Some(token), Some(record))
+        } else {
+            (None, None)
+        };
+
+        let response = OAuthTokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: self.jwt.access_token_duration(),
+            refresh_token,
+            scope,
+        };
+        Ok((response, record))
+    }
+
+    /// Authenticate a client from HTTP Basic credentials or the request body (RFC 6749 section 2.3.1).
+    /// Public clients identify themselves with a client id only.
+    pub async fn authenticate_oauth_client(
+        &self,
+        client_id: &Option<String>,
+        client_secret: &Option<String>,
+        basic: Option<(String, String)>,
+    ) -> Result<OAuthClient> {
+        let (client_id, client_secret) = match basic {
+            Some((id, secret)) => (id, Some(secret)),
+            None => (
+                client_id.clone().ok_or(AuthError::InvalidClient)?,
+                client_secret.clone(),
+            ),
+        };
+
+        let client = self.user_repo.find_oauth_client_by_client_id(&client_id).await
+            .map_err(|_| AuthError::InvalidClient)?;
+        if !client.is_active {
+            return Err(AuthError::InvalidClient);
+        }
+
+        match (&client.client_secret_hash, client_secret) {
+            (Some(expected), Some(secret))
+                if bool::from(self.password.hash_token(&secret)?.as_bytes().ct_eq(expected.as_bytes())) =>
+            {
+                Ok(client)
+            }
+            (None, None) => Ok(client),
+            _ => Err(AuthError::InvalidClient),
+        }
+    }
+
+    /// RFC 7662 token introspection for resource servers. Only confidential clients may
+    /// introspect, and only tokens from their own tenant are reported as active.
+    pub async fn introspect_token(&self, client: &OAuthClient, request: &OAuthTokenActionRequest) -> Result<IntrospectionResponse> {
+        if client.client_secret_hash.is_none() {
+            return Err(AuthError::InvalidClient);
+        }
+
+        if let Ok(validation) = self.jwt.validate_access_token(&request.token) {
+            let claims = validation.claims;
+            let active = validation.is_valid
+                && claims.tenant_id == client.tenant_id.to_string()
+                && !self.revocations.is_revoked(&claims.jti, &claims.session_id).await?;
+            if !active {
+                return Ok(IntrospectionResponse::default());
+            }
+
+            return Ok(IntrospectionResponse {
+                active: true,
+                scope: claims.scope.or_else(|| Some(claims.permissions.join(" "))),
+                client_id: claims.client_id,
+                sub: Some(claims.sub),
+                tenant_id: Some(claims.tenant_id),
+                token_type: Some("access_token".to_string()),
+                exp: Some(claims.exp),
+                iat: Some(claims.iat),
+                iss: Some(claims.iss),
+                jti: Some(claims.jti),
+            });
+        }
+
+        let token_hash = self.password.hash_token(&request.token)?;
+        let Ok(refresh_token) = self.user_repo.find_refresh_token(&token_hash).await else {
+            return Ok(IntrospectionResponse::default());
+        };
+        if refresh_token.revoked_at.is_some()
+            || refresh_token.expires_at < Utc::now()
+            || refresh_token.client_id != Some(client.id)
+        {
+            return Ok(IntrospectionResponse::default());
+        }
+
+        Ok(IntrospectionResponse {
+            active: true,
+            scope: Some(oauth::format_scope(&refresh_token.scopes)),
+            client_id: Some(client.client_id.clone()),
+            sub: Some(refresh_token.user_id.to_string()),
+            tenant_id: Some(refresh_token.tenant_id.to_string()),
+            token_type: Some("refresh_token".to_string()),
+            exp: Some(refresh_token.expires_at.timestamp()),
+            iat: Some(refresh_token.created_at.timestamp()),
+            iss: Some(self.jwt.issuer().to_string()),
+            jti: None,
+        })
+    }
+
+    /// RFC 7009 token revocation. Unknown tokens and tokens of other clients are ignored,
+    /// so the response never reveals whether a token exists.
+    pub async fn revoke_oauth_token(&self, client: &OAuthClient, request: &OAuthTokenActionRequest) -> Result<()> {
+        if let Ok(validation) = self.jwt.validate_access_token(&request.token) {
+            let claims = validation.claims;
+            if claims.client_id.as_deref() == Some(client.client_id.as_str()) {
+                self.revoke_access_token(&claims.jti, claims.exp).await?;
+            }
+            return Ok(());
+        }
+
+        let token_hash = self.password.hash_token(&request.token)?;
+        if let Ok(refresh_token) = self.user_repo.find_refresh_token(&token_hash).await {
+            if refresh_token.client_id == Some(client.id) && refresh_token.revoked_at.is_none() {
+                // Revoking the grant also invalidates the access tokens issued under it
+                self.user_repo.revoke_refresh_token(refresh_token.id).await?;
+                self.deny_session(refresh_token.family_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Authenticate a SCIM client. Identity providers are configured with a static bearer
+    /// token, which is a tenant API key granted `admin:scim`.
+    pub async fn authenticate_scim(&self, token: &str, method: &Method) -> Result<Claims> {
+        let claims = self.authenticate_api_key(token, method).await?;
+        if !claims.permissions.iter().any(|p| p == permissions::ADMIN_SCIM) {
+            return Err(AuthError::InsufficientPermissions(permissions::ADMIN_SCIM.to_string()));
+        }
+        Ok(claims)
+    }
+
+    pub async fn scim_list_users(&self, tenant_id: Uuid, query: ScimListQuery) -> Result<ScimListResponse<ScimUser>> {
+        let filter = query.filter.as_deref().map(scim::parse_filter).transpose()?;
+        let roles = self.user_repo.find_roles_by_tenant(tenant_id).await?;
+        let external_ids = self.scim_external_ids(tenant_id, scim::RESOURCE_USER).await?;
+
+        let mut resources = Vec::new();
+        for user in self.user_repo.find_users_by_tenant(tenant_id).await? {
+            if user.deleted_at.is_some() {
+                continue;
+            }
+            let resource = scim::user_resource(&user, &roles, &external_ids);
+            if let Some(filter) = &filter {
+                if !filter.matches(&serde_json::to_value(&resource)?) {
+                    continue;
+                }
+            }
+            resources.push(resource);
+        }
+
+        Ok(scim::page(resources, query.start_index, query.count))
+    }
+
+    pub async fn scim_get_user(&self, tenant_id: Uuid, id: &str) -> Result<ScimUser> {
+        let user = self.find_scim_user(tenant_id, id).await?;
+        self.scim_user(tenant_id, &user).await
+    }
+
+    /// Onboard a user from the tenant's HR system; they sign in through SSO or a password reset
+    pub async fn scim_create_user(&self, tenant_id: Uuid, resource: ScimUser) -> Result<ScimUser> {
+        let tenant = self.user_repo.find_tenant_by_id(tenant_id).await?;
+
+        let mut user = User {
+            id: Uuid::new_v4(),
+            tenant_id: tenant.id,
+            email: String::new(),
+            password_hash: self.password.hash_password(&self.password.generate_random_password(32))?,
+            first_name: String::new(),
+            last_name: String::new(),
+            display_name: None,
+            phone: None,
+            avatar_url: None,
+            roles: vec!["user".to_string()],
+            permissions: vec![],
+            is_active: true,
+            email_verified: false,
+            phone_verified: false,
+            two_factor_enabled: false,
+            last_login: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            password_changed_at: Some(Utc::now()),
+            force_password_change: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        };
+        scim::apply_scim_user(&mut user, &resource)?;
+
+        if self.user_repo.user_exists(&user.email, tenant.id).await? {
+            return Err(AuthError::EmailAlreadyExists);
+        }
+
+        let user = self.user_repo.create_user(&user).await?;
+        self.save_scim_external_id(tenant_id, scim::RESOURCE_USER, user.id, resource.external_id).await?;
+
+        info!("Provisioned user {} in tenant {} through SCIM", user.id, tenant_id);
+        self.scim_user(tenant_id, &user).await
+    }
+
+    pub async fn scim_replace_user(&self, tenant_id: Uuid, id: &str, resource: ScimUser) -> Result<ScimUser> {
+        let user = self.find_scim_user(tenant_id, id).await?;
+        self.save_scim_user(user, resource).await
+    }
+
+    pub async fn scim_patch_user(&self, tenant_id: Uuid, id: &str, patch: ScimPatchRequest) -> Result<ScimUser> {
+        let user = self.find_scim_user(tenant_id, id).await?;
+
+        let mut document = serde_json::to_value(self.scim_user(tenant_id, &user).await?)?;
+        scim::apply_patch(&mut document, &patch.operations)?;
+        let resource: ScimUser = serde_json::from_value(document)
+            .map_err(|e| AuthError::Validation(format!("Patched user is invalid: {}", e)))?;
+
+        self.save_scim_user(user, resource).await
+    }
+
+    /// Offboard a user: the account is soft-deleted and every session ends immediately
+    pub async fn scim_delete_user(&self, tenant_id: Uuid, id: &str) -> Result<()> {
+        let mut user = self.find_scim_user(tenant_id, id).await?;
+
+        user.is_active = false;
+        user.deleted_at = Some(Utc::now());
+        user.updated_at = Utc::now();
+        self.user_repo.update_user(&user).await?;
+        self.revoke_all_other_sessions(user.id, None).await?;
+
+        info!("Deprovisioned user {} in tenant {} through SCIM", user.id, tenant_id);
+        Ok(())
+    }
+
+    /// Write a full SCIM representation back to the user, ending their sessions on deactivation
+    async fn save_scim_user(&self, mut user: User, resource: ScimUser) -> Result<ScimUser> {
+        let was_active = user.is_active;
+        let previous_email = user.email.clone();
+
+        scim::apply_scim_user(&mut user, &resource)?;
+        if user.email != previous_email && self.user_repo.user_exists(&user.email, user.tenant_id).await? {
+            return Err(AuthError::EmailAlreadyExists);
+        }
+
+        self.user_repo.update_user(&user).await?;
+        self.save_scim_external_id(user.tenant_id, scim::RESOURCE_USER, user.id, resource.external_id).await?;
+
+        if was_active && !user.is_active {
+            // Passing no current session revokes every refresh token and denies every access token
+            self.revoke_all_other_sessions(user.id, None).await?;
+            info!("Deactivated user {} in tenant {} through SCIM", user.id, user.tenant_id);
+        }
+
+        self.scim_user(user.tenant_id, &user).await
+    }
+
+    async fn find_scim_user(&self, tenant_id: Uuid, id: &str) -> Result<User> {
+        let user_id: Uuid = id.parse().map_err(|_| AuthError::UserNotFound)?;
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        if user.tenant_id != tenant_id || user.deleted_at.is_some() {
+            return Err(AuthError::UserNotFound);
+        }
+        Ok(user)
+    }
+
+    async fn scim_user(&self, tenant_id: Uuid, user: &User) -> Result<ScimUser> {
+        let roles = self.user_repo.find_roles_by_tenant(tenant_id).await?;
+        let external_ids = self.scim_external_ids(tenant_id, scim::RESOURCE_USER).await?;
+        Ok(scim::user_resource(user, &roles, &external_ids))
+    }
+
+    pub async fn scim_list_groups(&self, tenant_id: Uuid, query: ScimListQuery) -> Result<ScimListResponse<ScimGroup>> {
+        let filter = query.filter.as_deref().map(scim::parse_filter).transpose()?;
+        let users = self.scim_user_directory(tenant_id).await?;
+        let external_ids = self.scim_external_ids(tenant_id, scim::RESOURCE_GROUP).await?;
+
+        let mut resources = Vec::new();
+        for role in self.user_repo.find_roles_by_tenant(tenant_id).await? {
+            let resource = self.scim_group_resource(&role, &users, &external_ids).await?;
+            if let Some(filter) = &filter {
+                if !filter.matches(&serde_json::to_value(&resource)?) {
+                    continue;
+                }
+            }
+            resources.push(resource);
+        }
+
+        Ok(scim::page(resources, query.start_index, query.count))
+    }
+
+    pub async fn scim_get_group(&self, tenant_id: Uuid, id: &str) -> Result<ScimGroup> {
+        let role = self.find_scim_role(tenant_id, id).await?;
+        self.scim_group(tenant_id, &role).await
+    }
+
+    /// Create a tenant role for an HR group. New roles carry no permissions until an admin grants them.
+    pub async fn scim_create_group(&self, tenant_id: Uuid, actor: Uuid, resource: ScimGroup) -> Result<ScimGroup> {
+        let name = resource.display_name.trim().to_string();
+        if name.is_empty() {
+            return Err(AuthError::Validation("displayName is required".to_string()));
+        }
+
+        let roles = self.user_repo.find_roles_by_tenant(tenant_id).await?;
+        if roles.iter().any(|role| role.name.eq_ignore_ascii_case(&name)) {
+            return Err(AuthError::RoleAlreadyExists);
+        }
+
+        let mut role = Role::new(tenant_id, name.clone(), vec![]);
+        role.display_name = Some(name);
+        self.user_repo.store_role(&role).await?;
+        self.save_scim_external_id(tenant_id, scim::RESOURCE_GROUP, role.id, resource.external_id).await?;
+        self.set_scim_members(&role, actor, &resource.members).await?;
+
+        self.scim_group(tenant_id, &role).await
+    }
+
+    pub async fn scim_replace_group(&self, tenant_id: Uuid, id: &str, actor: Uuid, resource: ScimGroup) -> Result<ScimGroup> {
+        let role = self.find_scim_role(tenant_id, id).await?;
+        self.save_scim_group(role, actor, resource).await
+    }
+
+    pub async fn scim_patch_group(&self, tenant_id: Uuid, id: &str, actor: Uuid, patch: ScimPatchRequest) -> Result<ScimGroup> {
+        let role = self.find_scim_role(tenant_id, id).await?;
+
+        let mut document = serde_json::to_value(self.scim_group(tenant_id, &role).await?)?;
+        scim::apply_patch(&mut document, &patch.operations)?;
+        let resource: ScimGroup = serde_json::from_value(document)
+            .map_err(|e| AuthError::Validation(format!("Patched group is invalid: {}", e)))?;
+
+        self.save_scim_group(role, actor, resource).await
+    }
+
+    pub async fn scim_delete_group(&self, tenant_id: Uuid, id: &str, actor: Uuid) -> Result<()> {
+        let role = self.find_scim_role(tenant_id, id).await?;
+        if role.is_system {
+            return Err(AuthError::InsufficientPermissions("System roles cannot be deleted".to_string()));
+        }
+
+        self.set_scim_members(&role, actor, &[]).await?;
+        self.user_repo.delete_role(role.id).await?;
+        Ok(())
+    }
+
+    /// Only the display name changes on rename; the role name stays stable because it is
+    /// what users' tokens carry
+    async fn save_scim_group(&self, mut role: Role, actor: Uuid, resource: ScimGroup) -> Result<ScimGroup> {
+        let display_name = resource.display_name.trim().to_string();
+        if display_name.is_empty() {
+            return Err(AuthError::Validation("displayName is required".to_string()));
+        }
+
+        if role.display_name.as_deref() != Some(display_name.as_str()) {
+            role.display_name = Some(display_name);
+            role.updated_at = Utc::now();
+            self.user_repo.update_role(&role).await?;
+        }
+        self.save_scim_external_id(role.tenant_id, scim::RESOURCE_GROUP, role.id, resource.external_id).await?;
+        self.set_scim_members(&role, actor, &resource.members).await?;
+
+        self.scim_group(role.tenant_id, &role).await
+    }
+
+    /// Make the role's members exactly `members`, keeping `User::roles` in step with `user_roles`
+    async fn set_scim_members(&self, role: &Role, actor: Uuid, members: &[ScimMember]) -> Result<()> {
+        let mut desired = Vec::with_capacity(members.len());
+        for member in members {
+            let user_id: Uuid = member
+                .value
+                .parse()
+                .map_err(|_| AuthError::Validation(format!("Invalid member {}", member.value)))?;
+            if !desired.contains(&user_id) {
+                desired.push(user_id);
+            }
+        }
+
+        let current: Vec<Uuid> = self
+            .user_repo
+            .find_user_roles_by_role(role.id)
+            .await?
             .into_iter()
-            .map(|token| SessionInfo {
-                id: token.id,
-                device_id: token.device_id,
-                device_name: token.device_name,
-                ip_address: token.ip_address,
-                user_agent: token.user_agent,
-                created_at: token.created_at,
-                last_used_at: token.created_at, // TODO: Track actual last usage
-                is_current: false, // TODO: Determine current session
+            .map(|assignment| assignment.user_id)
+            .collect();
+
+        for user_id in desired.iter().filter(|id| !current.contains(id)) {
+            let mut user = self.find_scim_user(role.tenant_id, &user_id.to_string()).await?;
+            self.user_repo.add_user_role(&UserRole {
+                user_id: user.id,
+                role_id: role.id,
+                assigned_at: Utc::now(),
+                assigned_by: Some(actor),
+            }).await?;
+
+            if !user.roles.contains(&role.name) {
+                user.roles.push(role.name.clone());
+                user.updated_at = Utc::now();
+                self.user_repo.update_user(&user).await?;
+            }
+        }
+
+        for user_id in current.iter().filter(|id| !desired.contains(id)) {
+            self.user_repo.remove_user_role(*user_id, role.id).await?;
+
+            let mut user = self.user_repo.find_user_by_id(*user_id).await?;
+            user.roles.retain(|name| name != &role.name);
+            user.updated_at = Utc::now();
+            self.user_repo.update_user(&user).await?;
+
+            // Tokens already issued still carry the role, so make the user sign in again
+            self.revoke_all_other_sessions(user.id, None).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn find_scim_role(&self, tenant_id: Uuid, id: &str) -> Result<Role> {
+        let role_id: Uuid = id.parse().map_err(|_| AuthError::RoleNotFound)?;
+        self.user_repo.find_role_by_id(role_id, tenant_id).await
+    }
+
+    async fn scim_group(&self, tenant_id: Uuid, role: &Role) -> Result<ScimGroup> {
+        let users = self.scim_user_directory(tenant_id).await?;
+        let external_ids = self.scim_external_ids(tenant_id, scim::RESOURCE_GROUP).await?;
+        self.scim_group_resource(role, &users, &external_ids).await
+    }
+
+    async fn scim_group_resource(
+        &self,
+        role: &Role,
+        users: &HashMap<Uuid, User>,
+        external_ids: &HashMap<Uuid, String>,
+    ) -> Result<ScimGroup> {
+        let members = self
+            .user_repo
+            .find_user_roles_by_role(role.id)
+            .await?
+            .into_iter()
+            .filter_map(|assignment| users.get(&assignment.user_id))
+            .map(|user| ScimMember {
+                value: user.id.to_string(),
+                display: Some(user.email.clone()),
+                reference: Some(scim::user_location(&user.id.to_string())),
             })
             .collect();
 
-        Ok(sessions)
+        Ok(scim::role_to_scim(role, external_ids.get(&role.id).cloned(), members))
     }
 
-    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
-        // Find the refresh token and verify it belongs to the user
-        let refresh_token = self.user_repo.find_refresh_token_by_id(session_id).await?;
+    async fn scim_user_directory(&self, tenant_id: Uuid) -> Result<HashMap<Uuid, User>> {
+        Ok(self
+            .user_repo
+            .find_users_by_tenant(tenant_id)
+            .await?
+            .into_iter()
+            .filter(|user| user.deleted_at.is_none())
+            .map(|user| (user.id, user))
+            .collect())
+    }
 
-        if refresh_token.user_id != user_id {
-            return Err(AuthError::InvalidToken("Session does not belong to user".to_string()));
+    async fn scim_external_ids(&self, tenant_id: Uuid, resource_type: &str) -> Result<HashMap<Uuid, String>> {
+        Ok(self
+            .user_repo
+            .find_scim_external_ids(tenant_id, resource_type)
+            .await?
+            .into_iter()
+            .map(|mapping| (mapping.resource_id, mapping.external_id))
+            .collect())
+    }
+
+    async fn save_scim_external_id(
+        &self,
+        tenant_id: Uuid,
+        resource_type: &str,
+        resource_id: Uuid,
+        external_id: Option<String>,
+    ) -> Result<()> {
+        let Some(external_id) = external_id else {
+            return Ok(());
+        };
+
+        self.user_repo.save_scim_external_id(&ScimExternalId {
+            tenant_id,
+            resource_type: resource_type.to_string(),
+            resource_id,
+            external_id,
+        }).await
+    }
+
+    /// Run a SCIM bulk request in order. Later operations may refer to resources created
+    /// earlier in the same request as `bulkId:<id>`.
+    pub async fn scim_bulk(&self, tenant_id: Uuid, actor: Uuid, request: ScimBulkRequest) -> Result<ScimBulkResponse> {
+        if request.operations.len() > scim::MAX_BULK_OPERATIONS {
+            return Err(AuthError::Validation(format!(
+                "Bulk requests are limited to {} operations",
+                scim::MAX_BULK_OPERATIONS
+            )));
         }
 
-        // Revoke the specific session
-        self.user_repo.revoke_refresh_token(session_id).await?;
+        let mut created: HashMap<String, String> = HashMap::new();
+        let mut errors = 0;
+        let mut results = Vec::with_capacity(request.operations.len());
+
+        for operation in request.operations {
+            if request.fail_on_errors.is_some_and(|limit| errors >= limit) {
+                break;
+            }
+
+            let mut path = operation.path.clone();
+            let mut data = operation.data.clone();
+            for (bulk_id, id) in &created {
+                let reference = format!("bulkId:{}", bulk_id);
+                path = path.replace(&reference, id);
+                if let Some(value) = &data {
+                    data = Some(serde_json::from_str(&value.to_string().replace(&reference, id))?);
+                }
+            }
+
+            let method = operation.method.to_ascii_uppercase();
+            let outcome = self.scim_bulk_operation(tenant_id, actor, &method, &path, data).await;
+
+            let result = match outcome {
+                Ok((status, location, response)) => {
+                    if let (Some(bulk_id), Some(id)) = (&operation.bulk_id, response.as_ref().and_then(|r| r.get("id"))) {
+                        if let Some(id) = id.as_str() {
+                            created.insert(bulk_id.clone(), id.to_string());
+                        }
+                    }
+                    ScimBulkOperationResponse {
+                        method,
+                        bulk_id: operation.bulk_id,
+                        location,
+                        status: status.to_string(),
+                        response: None,
+                    }
+                }
+                Err(e) => {
+                    errors += 1;
+                    ScimBulkOperationResponse {
+                        method,
+                        bulk_id: operation.bulk_id,
+                        location: None,
+                        status: e.status_code().to_string(),
+                        response: Some(serde_json::to_value(scim::error_body(&e))?),
+                    }
+                }
+            };
+            results.push(result);
+        }
+
+        Ok(ScimBulkResponse {
+            schemas: vec![scim::SCHEMA_BULK_RESPONSE.to_string()],
+            operations: results,
+        })
+    }
+
+    /// Returns the status, the resource location and the resource itself
+    async fn scim_bulk_operation(
+        &self,
+        tenant_id: Uuid,
+        actor: Uuid,
+        method: &str,
+        path: &str,
+        data: Option<serde_json::Value>,
+    ) -> Result<(u16, Option<String>, Option<serde_json::Value>)> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let body = || data.clone().ok_or_else(|| AuthError::Validation(format!("{} {} requires data", method, path)));
+
+        let resource = match (method, segments.as_slice()) {
+            ("POST", ["Users"]) => {
+                let user = self.scim_create_user(tenant_id, serde_json::from_value(body()?)?).await?;
+                let value = serde_json::to_value(user)?;
+                return Ok((201, scim_location(&value), Some(value)));
+            }
+            ("POST", ["Groups"]) => {
+                let group = self.scim_create_group(tenant_id, actor, serde_json::from_value(body()?)?).await?;
+                let value = serde_json::to_value(group)?;
+                return Ok((201, scim_location(&value), Some(value)));
+            }
+            ("PUT", ["Users", id]) => {
+                serde_json::to_value(self.scim_replace_user(tenant_id, id, serde_json::from_value(body()?)?).await?)?
+            }
+            ("PATCH", ["Users", id]) => {
+                serde_json::to_value(self.scim_patch_user(tenant_id, id, serde_json::from_value(body()?)?).await?)?
+            }
+            ("PUT", ["Groups", id]) => serde_json::to_value(
+                self.scim_replace_group(tenant_id, id, actor, serde_json::from_value(body()?)?).await?,
+            )?,
+            ("PATCH", ["Groups", id]) => serde_json::to_value(
+                self.scim_patch_group(tenant_id, id, actor, serde_json::from_value(body()?)?).await?,
+            )?,
+            ("DELETE", ["Users", id]) => {
+                self.scim_delete_user(tenant_id, id).await?;
+                return Ok((204, None, None));
+            }
+            ("DELETE", ["Groups", id]) => {
+                self.scim_delete_group(tenant_id, id, actor).await?;
+                return Ok((204, None, None));
+            }
+            _ => return Err(AuthError::Validation(format!("Unsupported bulk operation {} {}", method, path))),
+        };
+
+        Ok((200, scim_location(&resource), Some(resource)))
+    }
+
+    /// Public verification keys served at `/.well-known/jwks.json`
+    pub fn jwks(&self) -> Result<jsonwebtoken::jwk::JwkSet> {
+        Ok(self.jwt.jwks()?)
+    }
+
+    pub async fn list_signing_keys(&self, user_id: Uuid) -> Result<Vec<SigningKeyInfo>> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_signing_keys(&user)?;
+
+        Ok(self.jwt.signing_keys()?)
+    }
+
+    /// Stage a new signing key; it is published in the JWKS but does not sign until promoted
+    pub async fn stage_signing_key(&self, user_id: Uuid, request: StageSigningKeyRequest) -> Result<SigningKeyInfo> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_signing_keys(&user)?;
+
+        let kid = request.kid.unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+        let key = match (request.algorithm.as_str(), request.private_key_pem) {
+            ("EdDSA", Some(pem)) => SigningKey::ed25519_pem(kid, &pem)?,
+            ("EdDSA", None) => SigningKey::generate_ed25519_with_kid(kid)?,
+            ("RS256", Some(pem)) => SigningKey::rsa_pem(kid, &pem)?,
+            ("RS256", None) => {
+                return Err(AuthError::Validation("RS256 keys require a private_key_pem".to_string()));
+            }
+            (other, _) => {
+                return Err(AuthError::Validation(format!("Unsupported signing algorithm: {}", other)));
+            }
+        };
+
+        self.load_signing_keys().await?;
+        let info = self.jwt.stage_signing_key(key)?;
+        self.save_signing_keys().await?;
+        info!("Staged JWT signing key {} ({})", info.kid, info.algorithm);
+        Ok(info)
+    }
+
+    /// Promote a staged key; the previous key keeps verifying for the refresh-token lifetime
+    pub async fn promote_signing_key(&self, user_id: Uuid, kid: &str) -> Result<()> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_signing_keys(&user)?;
+
+        self.load_signing_keys().await?;
+        self.jwt.promote_signing_key(kid)?;
+        self.save_signing_keys().await?;
+        info!("Promoted JWT signing key {}", kid);
         Ok(())
     }
 
-    pub async fn revoke_all_other_sessions(&self, user_id: Uuid, current_session_id: Option<Uuid>) -> Result<()> {
-        // Revoke all sessions except the current one
-        if let Some(current_id) = current_session_id {
-            self.user_repo.revoke_all_user_tokens_except(user_id, current_id).await?;
+    /// Drop retiring keys whose grace period has passed
+    pub async fn retire_signing_keys(&self, user_id: Uuid) -> Result<Vec<String>> {
+        let user = self.user_repo.find_user_by_id(user_id).await?;
+        self.ensure_can_manage_signing_keys(&user)?;
+
+        self.load_signing_keys().await?;
+        let retired = self.jwt.retire_expired_keys()?;
+        if !retired.is_empty() {
+            self.save_signing_keys().await?;
+            info!("Retired JWT signing keys {:?}", retired);
+        }
+        Ok(retired)
+    }
+
+    /// Replace the in-memory keyset with the stored one. The first instance to start with an
+    /// empty store seeds it with its own keyset.
+    pub async fn load_signing_keys(&self) -> Result<()> {
+        let stored = self.key_store.load().await?;
+        if stored.is_empty() {
+            self.save_signing_keys().await
         } else {
-            self.user_repo.revoke_all_user_tokens(user_id).await?;
+            Ok(self.jwt.restore_keys(stored)?)
+        }
+    }
+
+    /// Make a key from configuration the active signing key, unless the keyset already has it
+    pub async fn install_signing_key(&self, key: SigningKey) -> Result<()> {
+        self.load_signing_keys().await?;
+        if self.jwt.signing_keys()?.iter().any(|existing| existing.kid == key.kid) {
+            return Ok(());
         }
+
+        let kid = key.kid.clone();
+        self.jwt.stage_signing_key(key)?;
+        self.jwt.promote_signing_key(&kid)?;
+        self.save_signing_keys().await?;
+        info!("Installed configured JWT signing key {}", kid);
         Ok(())
     }
+
+    /// Periodically reload the keyset so keys staged or promoted on another instance are used here
+    pub fn spawn_key_sync(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.load_signing_keys().await {
+                    warn!("Failed to reload JWT signing keys: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn save_signing_keys(&self) -> Result<()> {
+        Ok(self.key_store.save(&self.jwt.stored_keys()?).await?)
+    }
+
+    /// Signing keys are platform-wide, so the tenant "admin" role alone is not enough
+    fn ensure_can_manage_signing_keys(&self, user: &User) -> Result<()> {
+        if user.permissions.iter().any(|p| p == permissions::ADMIN_SIGNING_KEYS) {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions(permissions::ADMIN_SIGNING_KEYS.to_string()))
+        }
+    }
+
+    fn ensure_can_manage_users(&self, user: &User) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin")
+            || user.permissions.iter().any(|p| p == permissions::ADMIN_USERS)
+        {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions(permissions::ADMIN_USERS.to_string()))
+        }
+    }
+
+    fn ensure_can_manage_terminals(&self, user: &User) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin")
+            || user.permissions.iter().any(|p| p == permissions::ADMIN_TERMINALS)
+        {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions(permissions::ADMIN_TERMINALS.to_string()))
+        }
+    }
+
+    fn ensure_can_manage_oauth_clients(&self, user: &User) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin")
+            || user.permissions.iter().any(|p| p == permissions::ADMIN_OAUTH_CLIENTS)
+        {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions(permissions::ADMIN_OAUTH_CLIENTS.to_string()))
+        }
+    }
+
+    fn ensure_can_manage_sso(&self, user: &User) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin")
+            || user.permissions.iter().any(|p| p == permissions::ADMIN_SETTINGS)
+        {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions(permissions::ADMIN_SETTINGS.to_string()))
+        }
+    }
+
+    fn ensure_can_manage_api_keys(&self, user: &User) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin")
+            || user.permissions.iter().any(|p| p == permissions::ADMIN_API_KEYS)
+        {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions(permissions::ADMIN_API_KEYS.to_string()))
+        }
+    }
+
+    /// Non-admins may only hand out roles they hold themselves
+    fn ensure_can_assign_roles(&self, user: &User, requested: &[String]) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin") {
+            return Ok(());
+        }
+
+        match requested.iter().find(|r| !user.roles.contains(r)) {
+            Some(missing) => Err(AuthError::InsufficientPermissions(format!("role {}", missing))),
+            None => Ok(()),
+        }
+    }
+
+    /// Non-admins may only delegate permissions they hold themselves
+    fn ensure_can_grant(&self, user: &User, requested: &[String]) -> Result<()> {
+        if user.roles.iter().any(|r| r == "admin") {
+            return Ok(());
+        }
+
+        match requested.iter().find(|p| !user.permissions.contains(p)) {
+            Some(missing) => Err(AuthError::InsufficientPermissions(missing.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+fn session_risk_event(
+    user: &User,
+    session_id: Option<Uuid>,
+    risk: &RiskAssessment,
+    device_id: Option<&str>,
+    ip_address: &str,
+    user_agent: &str,
+) -> SessionRiskDetectedEvent {
+    SessionRiskDetectedEvent {
+        user_id: user.id,
+        tenant_id: user.tenant_id,
+        session_id,
+        risk_score: risk.score,
+        signals: risk.signal_names(),
+        action: risk.action.as_str().to_string(),
+        country_code: risk.location.as_ref().and_then(|l| l.country_code.clone()),
+        city: risk.location.as_ref().and_then(|l| l.city.clone()),
+        travel_speed_kmh: risk.travel_speed_kmh,
+        device_id: device_id.map(str::to_string),
+        ip_address: ip_address.to_string(),
+        user_agent: user_agent.to_string(),
+    }
+}
+
+fn scim_location(resource: &serde_json::Value) -> Option<String> {
+    resource.pointer("/meta/location").and_then(|l| l.as_str()).map(str::to_string)
 }
 
 // Session information for display to users
@@ -383,4 +4000,10 @@ pub struct SessionInfo {
     pub created_at: DateTime<Utc>,
     pub last_used_at: DateTime<Utc>,
     pub is_current: bool,
+    pub impersonation: Option<SessionImpersonation>,
+    /// Where the session was signed in or last refreshed from, if GeoIP knew
+    pub location: Option<GeoLocation>,
+    /// Risk score (0-100) and signals found at sign-in or the last refresh
+    pub risk_score: i32,
+    pub risk_signals: Vec<String>,
 }
\ No newline at end of file
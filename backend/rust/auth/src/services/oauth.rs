@@ -0,0 +1,194 @@
+// ============================================================================
+// OLYMPUS CLOUD - OAUTH2 AUTHORIZATION SERVER HELPERS
+// ============================================================================
+// Module: auth/src/services/oauth.rs
+// Description: Client credentials, PKCE, redirect URI and scope checks for OAuth 2.1
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use crate::error::{AuthError, Result};
+
+/// Marker prepended to client ids so they are easy to tell apart from API keys
+pub const CLIENT_ID_MARKER: &str = "olc_";
+/// Marker prepended to client secrets so leaked secrets are easy to recognise in scanners
+pub const CLIENT_SECRET_MARKER: &str = "ols_";
+
+const CLIENT_ID_LEN: usize = 24;
+const CLIENT_SECRET_LEN: usize = 48;
+const AUTHORIZATION_CODE_LEN: usize = 48;
+
+/// Lifetime of an authorization code; codes are exchanged immediately by the client
+pub const AUTHORIZATION_CODE_DURATION_SECONDS: i64 = 60;
+
+pub const GRANT_AUTHORIZATION_CODE: &str = "authorization_code";
+pub const GRANT_CLIENT_CREDENTIALS: &str = "client_credentials";
+pub const GRANT_REFRESH_TOKEN: &str = "refresh_token";
+
+/// OAuth 2.1 only allows the S256 challenge method
+pub const PKCE_METHOD_S256: &str = "S256";
+
+fn random_alphanumeric(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// Generate a public client identifier
+pub fn generate_client_id() -> String {
+    format!("{}{}", CLIENT_ID_MARKER, random_alphanumeric(CLIENT_ID_LEN))
+}
+
+/// Generate a confidential client secret; only its hash is stored
+pub fn generate_client_secret() -> String {
+    format!("{}{}", CLIENT_SECRET_MARKER, random_alphanumeric(CLIENT_SECRET_LEN))
+}
+
+/// Generate a single-use authorization code
+pub fn generate_authorization_code() -> String {
+    random_alphanumeric(AUTHORIZATION_CODE_LEN)
+}
+
+/// Check a PKCE code verifier (RFC 7636) against the challenge sent to the authorize endpoint
+pub fn verify_pkce(code_verifier: &str, code_challenge: &str) -> bool {
+    // RFC 7636 section 4.1: 43-128 characters from the unreserved set
+    let well_formed = (43..=128).contains(&code_verifier.len())
+        && code_verifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'));
+    if !well_formed {
+        return false;
+    }
+
+    pkce_challenge(code_verifier) == code_challenge
+}
+
+/// S256 code challenge for a verifier, for flows where this service is the OAuth client
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Validate a redirect URI at registration: absolute, no fragment, and HTTPS except for loopback
+pub fn validate_redirect_uri(uri: &str) -> Result<()> {
+    let invalid = |reason: &str| AuthError::Validation(format!("Invalid redirect URI {}: {}", uri, reason));
+
+    if uri.contains('#') {
+        return Err(invalid("fragments are not allowed"));
+    }
+    if uri.contains('*') {
+        return Err(invalid("wildcards are not allowed"));
+    }
+
+    let Some(rest) = uri.strip_prefix("https://").or_else(|| uri.strip_prefix("http://")) else {
+        return Err(invalid("must be an http(s) URL"));
+    };
+    let host = rest.split(['/', '?']).next().unwrap_or_default();
+    let host = host.rsplit_once(':').map(|(host, _port)| host).unwrap_or(host);
+    if host.is_empty() {
+        return Err(invalid("missing host"));
+    }
+
+    let loopback = matches!(host, "localhost" | "127.0.0.1" | "[::1]");
+    if uri.starts_with("http://") && !loopback {
+        return Err(invalid("must use https"));
+    }
+
+    Ok(())
+}
+
+/// Redirect URIs are compared exactly, as OAuth 2.1 requires
+pub fn redirect_uri_registered(registered: &[String], redirect_uri: &str) -> bool {
+    registered.iter().any(|uri| uri == redirect_uri)
+}
+
+/// Split a space-delimited scope parameter, defaulting to everything the client is allowed
+pub fn parse_scope(scope: Option<&str>, allowed: &[String]) -> Result<Vec<String>> {
+    let mut scopes: Vec<String> = match scope.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(scope) => scope.split_whitespace().map(str::to_string).collect(),
+        None => allowed.to_vec(),
+    };
+    scopes.sort();
+    scopes.dedup();
+
+    if let Some(invalid) = scopes.iter().find(|s| !allowed.contains(s)) {
+        return Err(AuthError::InvalidScope(invalid.clone()));
+    }
+
+    Ok(scopes)
+}
+
+/// Format scopes back into the space-delimited form used on the wire
+pub fn format_scope(scopes: &[String]) -> String {
+    scopes.join(" ")
+}
+
+/// Append query parameters to a registered redirect URI
+pub fn build_redirect(redirect_uri: &str, params: &[(&str, &str)]) -> String {
+    let query = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+    let separator = if redirect_uri.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", redirect_uri, separator, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_s256() {
+        // Example from RFC 7636 appendix B
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+        assert!(verify_pkce(verifier, challenge));
+        assert!(!verify_pkce("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXx", challenge));
+        assert!(!verify_pkce("short", challenge));
+    }
+
+    #[test]
+    fn test_redirect_uri_validation() {
+        assert!(validate_redirect_uri("https://partner.example.com/callback").is_ok());
+        assert!(validate_redirect_uri("http://localhost:8080/callback").is_ok());
+        assert!(validate_redirect_uri("http://partner.example.com/callback").is_err());
+        assert!(validate_redirect_uri("https://partner.example.com/cb#frag").is_err());
+        assert!(validate_redirect_uri("https://*.example.com/cb").is_err());
+        assert!(validate_redirect_uri("partner://callback").is_err());
+
+        let registered = vec!["https://partner.example.com/callback".to_string()];
+        assert!(redirect_uri_registered(&registered, "https://partner.example.com/callback"));
+        assert!(!redirect_uri_registered(&registered, "https://partner.example.com/callback/"));
+    }
+
+    #[test]
+    fn test_scope_parsing() {
+        let allowed = vec!["read:orders".to_string(), "read:products".to_string()];
+
+        assert_eq!(parse_scope(None, &allowed).unwrap(), allowed);
+        assert_eq!(
+            parse_scope(Some("read:orders read:orders"), &allowed).unwrap(),
+            vec!["read:orders".to_string()]
+        );
+        assert!(matches!(
+            parse_scope(Some("write:orders"), &allowed),
+            Err(AuthError::InvalidScope(_))
+        ));
+    }
+
+    #[test]
+    fn test_build_redirect() {
+        assert_eq!(
+            build_redirect("https://partner.example.com/cb", &[("code", "abc"), ("state", "x y")]),
+            "https://partner.example.com/cb?code=abc&state=x+y"
+        );
+        assert_eq!(
+            build_redirect("https://partner.example.com/cb?tenant=1", &[("code", "abc")]),
+            "https://partner.example.com/cb?tenant=1&code=abc"
+        );
+    }
+}
@@ -1,25 +1,60 @@
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
+use olympus_shared::config::{Argon2Config, PasswordPolicy};
 use sha2::{Digest, Sha256};
 use crate::error::{AuthError, Result};
+use super::password_policy::{PasswordPolicyEvaluator, PasswordViolation};
 
 pub struct PasswordService {
     argon2: Argon2<'static>,
+    argon2_config: Argon2Config,
+    policy: PasswordPolicyEvaluator,
 }
 
 impl PasswordService {
     pub fn new() -> Self {
+        let argon2_config = Argon2Config::default();
         Self {
-            argon2: Argon2::default(),
+            argon2: Self::build_argon2(&argon2_config).expect("default Argon2 parameters are valid"),
+            argon2_config,
+            policy: PasswordPolicyEvaluator::new(PasswordPolicy::default()),
         }
     }
 
+    pub fn with_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.policy = PasswordPolicyEvaluator::new(policy);
+        self
+    }
+
+    /// Use different Argon2id cost parameters for new hashes
+    pub fn with_argon2(mut self, config: Argon2Config) -> Result<Self> {
+        self.argon2 = Self::build_argon2(&config)?;
+        self.argon2_config = config;
+        Ok(self)
+    }
+
+    fn build_argon2(config: &Argon2Config) -> Result<Argon2<'static>> {
+        let params = Params::new(config.memory_cost_kib, config.time_cost, config.parallelism, None)
+            .map_err(|e| AuthError::PasswordHashError(e.to_string()))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    pub fn policy(&self) -> &PasswordPolicy {
+        self.policy.policy()
+    }
+
     pub fn hash_password(&self, password: &str) -> Result<String> {
         // Validate password strength
         self.validate_password_strength(password)?;
 
+        self.rehash_password(password)
+    }
+
+    /// Hash an already verified password with the current parameters, skipping the
+    /// policy check so that upgrading a stored hash never blocks a login
+    pub fn rehash_password(&self, password: &str) -> Result<String> {
         let salt = SaltString::generate(&mut OsRng);
 
         let password_hash = self
@@ -31,7 +66,21 @@ impl PasswordService {
         Ok(password_hash)
     }
 
+    /// Hash a staff quick-switch PIN. PINs have their own format rules; no 4-6 digit
+    /// PIN could satisfy the password policy.
+    pub fn hash_pin(&self, pin: &str) -> Result<String> {
+        validate_pin(pin)?;
+        self.rehash_password(pin)
+    }
+
+    /// Verify against a PHC string, using the algorithm and parameters recorded in it,
+    /// or against a legacy bcrypt hash
     pub fn verify_password(&self, password: &str, hash: &str) -> Result<bool> {
+        if is_bcrypt_hash(hash) {
+            return bcrypt::verify(password, hash)
+                .map_err(|e| AuthError::PasswordHashError(e.to_string()));
+        }
+
         let parsed_hash = PasswordHash::new(hash)
             .map_err(|e| AuthError::PasswordHashError(e.to_string()))?;
 
@@ -42,6 +91,32 @@ impl PasswordService {
         }
     }
 
+    /// Whether a stored hash was made with anything other than the current Argon2id parameters
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        if is_bcrypt_hash(hash) {
+            return true;
+        }
+
+        let Ok(parsed_hash) = PasswordHash::new(hash) else {
+            return false;
+        };
+
+        if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13 as u32)
+        {
+            return true;
+        }
+
+        match Params::try_from(&parsed_hash) {
+            Ok(params) => {
+                params.m_cost() != self.argon2_config.memory_cost_kib
+                    || params.t_cost() != self.argon2_config.time_cost
+                    || params.p_cost() != self.argon2_config.parallelism
+            }
+            Err(_) => true,
+        }
+    }
+
     pub fn hash_token(&self, token: &str) -> Result<String> {
         let mut hasher = Sha256::new();
         hasher.update(token.as_bytes());
@@ -49,63 +124,85 @@ impl PasswordService {
         Ok(format!("{:x}", result))
     }
 
-    fn validate_password_strength(&self, password: &str) -> Result<()> {
-        if password.len() < 8 {
-            return Err(AuthError::WeakPassword("Password must be at least 8 characters long".to_string()));
-        }
+    /// Check a replacement password against the policy and the user's recent hashes,
+    /// newest first. Only the configured history depth is considered.
+    pub fn validate_new_password(&self, password: &str, previous_hashes: &[String]) -> Result<()> {
+        let mut violations = self.policy.evaluate(password);
 
-        if password.len() > 128 {
-            return Err(AuthError::WeakPassword("Password must be less than 128 characters".to_string()));
-        }
-
-        let has_uppercase = password.chars().any(|c| c.is_uppercase());
-        let has_lowercase = password.chars().any(|c| c.is_lowercase());
-        let has_digit = password.chars().any(|c| c.is_numeric());
-        let has_special = password.chars().any(|c| !c.is_alphanumeric());
-
-        let strength_score = [has_uppercase, has_lowercase, has_digit, has_special]
+        let history_count = self.policy().password_history_count;
+        let reused = previous_hashes
             .iter()
-            .filter(|&&x| x)
-            .count();
-
-        if strength_score < 3 {
-            return Err(AuthError::WeakPassword(
-                "Password must contain at least 3 of: uppercase, lowercase, digit, special character".to_string()
-            ));
+            .take(history_count as usize)
+            .any(|hash| self.verify_password(password, hash).unwrap_or(false));
+        if reused {
+            violations.push(PasswordViolation::RecentlyUsed { history_count });
         }
 
-        // Check for common passwords
-        let common_passwords = [
-            "password", "12345678", "qwerty", "abc123", "password123",
-            "admin", "letmein", "welcome", "monkey", "dragon",
-        ];
-
-        let password_lower = password.to_lowercase();
-        if common_passwords.iter().any(|&common| password_lower.contains(common)) {
-            return Err(AuthError::WeakPassword("Password is too common".to_string()));
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::PasswordPolicyViolation(violations))
         }
+    }
 
-        Ok(())
+    fn validate_password_strength(&self, password: &str) -> Result<()> {
+        let violations = self.policy.evaluate(password);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::PasswordPolicyViolation(violations))
+        }
     }
 
     pub fn generate_random_password(&self, length: usize) -> String {
+        use rand::seq::SliceRandom;
         use rand::Rng;
-        const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
-                                 abcdefghijklmnopqrstuvwxyz\
-                                 0123456789!@#$%^&*()_+-=[]{}|;:,.<>?";
+        const CLASSES: [&[u8]; 4] = [
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            b"abcdefghijklmnopqrstuvwxyz",
+            b"0123456789",
+            b"!@#$%^&*()_+-=[]{}|;:,.<>?",
+        ];
 
         let mut rng = rand::thread_rng();
-        let password: String = (0..length)
-            .map(|_| {
-                let idx = rng.gen_range(0..CHARSET.len());
-                CHARSET[idx] as char
+
+        // One character from every class so the result always satisfies the policy
+        let mut password: Vec<u8> = (0..length)
+            .map(|i| {
+                let charset = if i < CLASSES.len() {
+                    CLASSES[i]
+                } else {
+                    CLASSES[rng.gen_range(0..CLASSES.len())]
+                };
+                charset[rng.gen_range(0..charset.len())]
             })
             .collect();
+        password.shuffle(&mut rng);
 
-        password
+        password.into_iter().map(char::from).collect()
     }
 }
 
+/// PINs are 4 to 6 digits and may not be a repeated digit or a straight run like 1234 or 9876
+fn validate_pin(pin: &str) -> Result<()> {
+    if !(4..=6).contains(&pin.len()) || !pin.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(AuthError::WeakPin("PIN must be 4 to 6 digits".to_string()));
+    }
+
+    let digits: Vec<i8> = pin.bytes().map(|b| (b - b'0') as i8).collect();
+    let steps: Vec<i8> = digits.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    if steps.iter().all(|&step| step == steps[0]) && steps[0].abs() <= 1 {
+        return Err(AuthError::WeakPin("PIN is too easy to guess".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Hashes imported from the legacy POS use bcrypt's modular crypt format
+fn is_bcrypt_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix))
+}
+
 impl Default for PasswordService {
     fn default() -> Self {
         Self::new()
@@ -146,6 +243,19 @@ mod tests {
         assert!(service.hash_password("Str0ng&Secure!").is_ok());
     }
 
+    #[test]
+    fn test_pin_hashing() {
+        let service = PasswordService::new();
+
+        let hash = service.hash_pin("2580").unwrap();
+        assert!(service.verify_password("2580", &hash).unwrap());
+        assert!(!service.verify_password("2581", &hash).unwrap());
+
+        for pin in ["123", "1234567", "12a4", "0000", "1234", "987654"] {
+            assert!(matches!(service.hash_pin(pin), Err(AuthError::WeakPin(_))), "{}", pin);
+        }
+    }
+
     #[test]
     fn test_token_hashing() {
         let service = PasswordService::new();
@@ -166,4 +276,60 @@ mod tests {
         assert_eq!(password.len(), 16);
         assert!(service.hash_password(&password).is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_password_history_rejects_recent_reuse() {
+        let service = PasswordService::new().with_policy(PasswordPolicy {
+            password_history_count: 2,
+            ..PasswordPolicy::default()
+        });
+
+        let history = vec![
+            service.hash_password("Newest&Pass1").unwrap(),
+            service.hash_password("Middle&Pass1").unwrap(),
+            service.hash_password("Oldest&Pass1").unwrap(),
+        ];
+
+        match service.validate_new_password("Middle&Pass1", &history) {
+            Err(AuthError::PasswordPolicyViolation(violations)) => {
+                assert_eq!(violations, vec![PasswordViolation::RecentlyUsed { history_count: 2 }]);
+            }
+            other => panic!("expected history violation, got {:?}", other),
+        }
+
+        // Outside the configured history depth
+        assert!(service.validate_new_password("Oldest&Pass1", &history).is_ok());
+    }
+
+    #[test]
+    fn test_outdated_argon2_parameters_need_rehash() {
+        let weak = PasswordService::new()
+            .with_argon2(Argon2Config { memory_cost_kib: 1024, time_cost: 1, parallelism: 1 })
+            .unwrap();
+        let current = PasswordService::new()
+            .with_argon2(Argon2Config { memory_cost_kib: 4096, time_cost: 2, parallelism: 1 })
+            .unwrap();
+
+        let old_hash = weak.hash_password("UniqueTestPass123!").unwrap();
+        assert!(old_hash.contains("m=1024,t=1,p=1"));
+
+        // Old hashes still verify with their own parameters
+        assert!(current.verify_password("UniqueTestPass123!", &old_hash).unwrap());
+        assert!(current.needs_rehash(&old_hash));
+        assert!(!weak.needs_rehash(&old_hash));
+
+        let new_hash = current.rehash_password("UniqueTestPass123!").unwrap();
+        assert!(!current.needs_rehash(&new_hash));
+        assert!(current.verify_password("UniqueTestPass123!", &new_hash).unwrap());
+    }
+
+    #[test]
+    fn test_legacy_bcrypt_hash() {
+        let service = PasswordService::new();
+        let legacy_hash = "$2b$10$N9qo8uLOickgx2ZMRZoMyeWwaU9eumg46Bt4VNK3h4Zj6TIttLQ3G";
+
+        assert!(service.verify_password("LegacyPos#2019", legacy_hash).unwrap());
+        assert!(!service.verify_password("WrongPassword", legacy_hash).unwrap());
+        assert!(service.needs_rehash(legacy_hash));
+    }
+}
@@ -0,0 +1,237 @@
+// ============================================================================
+// OLYMPUS CLOUD - PASSWORD POLICY
+// ============================================================================
+// Module: auth/src/services/password_policy.rs
+// Description: Password policy evaluation and offline breached-password screening
+// Author: Claude Code Agent
+// Date: 2025-01-20
+// ============================================================================
+
+use olympus_shared::config::PasswordPolicy;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::fmt;
+use std::path::PathBuf;
+use tracing::warn;
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "12345678", "qwerty", "abc123", "password123",
+    "admin", "letmein", "welcome", "monkey", "dragon",
+];
+
+/// A single reason a password was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum PasswordViolation {
+    TooShort { min_length: usize },
+    TooLong { max_length: usize },
+    MissingUppercase,
+    MissingLowercase,
+    MissingDigit,
+    MissingSpecial,
+    CommonPassword,
+    RecentlyUsed { history_count: u32 },
+    Breached { occurrences: u64 },
+}
+
+impl fmt::Display for PasswordViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordViolation::TooShort { min_length } => {
+                write!(f, "must be at least {} characters long", min_length)
+            }
+            PasswordViolation::TooLong { max_length } => {
+                write!(f, "must be at most {} characters long", max_length)
+            }
+            PasswordViolation::MissingUppercase => write!(f, "must contain an uppercase letter"),
+            PasswordViolation::MissingLowercase => write!(f, "must contain a lowercase letter"),
+            PasswordViolation::MissingDigit => write!(f, "must contain a digit"),
+            PasswordViolation::MissingSpecial => write!(f, "must contain a special character"),
+            PasswordViolation::CommonPassword => write!(f, "is too common"),
+            PasswordViolation::RecentlyUsed { history_count } => {
+                write!(f, "must differ from the last {} passwords", history_count)
+            }
+            PasswordViolation::Breached { occurrences } => {
+                write!(f, "has appeared in {} known data breaches", occurrences)
+            }
+        }
+    }
+}
+
+/// Offline breached-password lookup over k-anonymity range files.
+///
+/// The directory holds one file per 5 character SHA-1 prefix (as served by the
+/// Pwned Passwords range API), each line being `SUFFIX:COUNT`. Only the prefix
+/// file for the candidate password is read.
+#[derive(Debug, Clone)]
+pub struct BreachedPasswordList {
+    dir: PathBuf,
+}
+
+impl BreachedPasswordList {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Number of breaches the password appears in; 0 when unknown or the range file is missing
+    pub fn occurrences(&self, password: &str) -> u64 {
+        let digest = Sha1::digest(password.as_bytes());
+        let hash = hex_upper(&digest);
+        let (prefix, suffix) = hash.split_at(5);
+
+        let contents = [self.dir.join(prefix), self.dir.join(format!("{}.txt", prefix))]
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok());
+
+        let Some(contents) = contents else {
+            warn!("No breached password range file for prefix {} in {}", prefix, self.dir.display());
+            return 0;
+        };
+
+        contents
+            .lines()
+            .filter_map(|line| line.trim().split_once(':'))
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+            .and_then(|(_, count)| count.trim().parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+/// Evaluates candidate passwords against the configured `PasswordPolicy`
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyEvaluator {
+    policy: PasswordPolicy,
+    breached: Option<BreachedPasswordList>,
+}
+
+impl PasswordPolicyEvaluator {
+    pub fn new(policy: PasswordPolicy) -> Self {
+        let breached = policy
+            .breached_password_path
+            .as_ref()
+            .map(BreachedPasswordList::new);
+
+        Self { policy, breached }
+    }
+
+    pub fn policy(&self) -> &PasswordPolicy {
+        &self.policy
+    }
+
+    /// Check length, character classes, common and breached passwords.
+    /// Password history needs the stored hashes and is checked by `PasswordService`.
+    pub fn evaluate(&self, password: &str) -> Vec<PasswordViolation> {
+        let policy = &self.policy;
+        let mut violations = Vec::new();
+
+        let length = password.chars().count();
+        if length < policy.min_length {
+            violations.push(PasswordViolation::TooShort { min_length: policy.min_length });
+        }
+        if length > policy.max_length {
+            violations.push(PasswordViolation::TooLong { max_length: policy.max_length });
+        }
+
+        if policy.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            violations.push(PasswordViolation::MissingUppercase);
+        }
+        if policy.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            violations.push(PasswordViolation::MissingLowercase);
+        }
+        if policy.require_digit && !password.chars().any(|c| c.is_numeric()) {
+            violations.push(PasswordViolation::MissingDigit);
+        }
+        if policy.require_special && !password.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PasswordViolation::MissingSpecial);
+        }
+
+        if policy.prevent_common_passwords {
+            let password_lower = password.to_lowercase();
+            if COMMON_PASSWORDS.iter().any(|&common| password_lower.contains(common)) {
+                violations.push(PasswordViolation::CommonPassword);
+            }
+        }
+
+        if let Some(breached) = &self.breached {
+            let occurrences = breached.occurrences(password);
+            if occurrences > 0 {
+                violations.push(PasswordViolation::Breached { occurrences });
+            }
+        }
+
+        violations
+    }
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict_policy() -> PasswordPolicy {
+        PasswordPolicy {
+            require_special: true,
+            ..PasswordPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_structured_violations() {
+        let evaluator = PasswordPolicyEvaluator::new(strict_policy());
+
+        assert_eq!(
+            evaluator.evaluate("qwerty"),
+            vec![
+                PasswordViolation::TooShort { min_length: 8 },
+                PasswordViolation::MissingUppercase,
+                PasswordViolation::MissingDigit,
+                PasswordViolation::MissingSpecial,
+                PasswordViolation::CommonPassword,
+            ]
+        );
+        assert!(evaluator.evaluate("Str0ng&Secure!").is_empty());
+    }
+
+    #[test]
+    fn test_policy_toggles() {
+        let evaluator = PasswordPolicyEvaluator::new(PasswordPolicy {
+            require_uppercase: false,
+            require_digit: false,
+            prevent_common_passwords: false,
+            ..PasswordPolicy::default()
+        });
+
+        assert!(evaluator.evaluate("letmein-please").is_empty());
+    }
+
+    #[test]
+    fn test_breached_password_range_file() {
+        let dir = std::env::temp_dir().join(format!("olympus-breached-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // SHA-1("P@ssw0rd") = 21BD12DC183F740EE76F27B78EB39C8AD972A757
+        std::fs::write(
+            dir.join("21BD1.txt"),
+            "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n2DC183F740EE76F27B78EB39C8AD972A757:52579\r\n",
+        )
+        .unwrap();
+
+        let list = BreachedPasswordList::new(&dir);
+        assert_eq!(list.occurrences("P@ssw0rd"), 52579);
+        assert_eq!(list.occurrences("Str0ng&Secure!"), 0);
+
+        let evaluator = PasswordPolicyEvaluator::new(PasswordPolicy {
+            breached_password_path: Some(dir.to_string_lossy().into_owned()),
+            ..PasswordPolicy::default()
+        });
+        assert_eq!(
+            evaluator.evaluate("P@ssw0rd"),
+            vec![PasswordViolation::Breached { occurrences: 52579 }]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,312 @@
+// ============================================================================
+// OLYMPUS CLOUD - ACCESS TOKEN REVOCATION
+// ============================================================================
+// Module: auth/src/services/revocation.rs
+// Description: Redis-backed jti/session denylist with an in-process bloom filter
+// Author: Claude Code Agent
+// Date: 2025-01-20
+// ============================================================================
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use crate::error::{AuthError, Result};
+
+const REDIS_KEY_PREFIX: &str = "auth:revoked:";
+
+/// What a denylist entry refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevocationKind {
+    /// A single access token, by `jti`
+    Token,
+    /// Every access token issued for a session, by `session_id`
+    Session,
+}
+
+impl RevocationKind {
+    fn key(&self, id: &str) -> String {
+        match self {
+            RevocationKind::Token => format!("jti:{}", id),
+            RevocationKind::Session => format!("session:{}", id),
+        }
+    }
+}
+
+/// Shared storage for revoked identifiers; entries expire on their own
+#[async_trait]
+pub trait RevocationStore: Send + Sync {
+    async fn insert(&self, key: &str, ttl: Duration) -> Result<()>;
+    async fn contains(&self, key: &str) -> Result<bool>;
+    /// All live entries, used to rebuild the local bloom filter
+    async fn entries(&self) -> Result<Vec<String>>;
+}
+
+/// Denylist shared by every auth instance through Redis
+pub struct RedisRevocationStore {
+    redis: ConnectionManager,
+}
+
+impl RedisRevocationStore {
+    pub async fn new(redis_url: &str) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let redis = ConnectionManager::new(client).await?;
+        Ok(Self { redis })
+    }
+}
+
+#[async_trait]
+impl RevocationStore for RedisRevocationStore {
+    async fn insert(&self, key: &str, ttl: Duration) -> Result<()> {
+        let mut conn = self.redis.clone();
+        conn.set_ex::<_, _, ()>(format!("{}{}", REDIS_KEY_PREFIX, key), 1, ttl.as_secs().max(1))
+            .await?;
+        Ok(())
+    }
+
+    async fn contains(&self, key: &str) -> Result<bool> {
+        let mut conn = self.redis.clone();
+        Ok(conn.exists(format!("{}{}", REDIS_KEY_PREFIX, key)).await?)
+    }
+
+    async fn entries(&self) -> Result<Vec<String>> {
+        let mut conn = self.redis.clone();
+        let mut iter = conn
+            .scan_match::<_, String>(format!("{}*", REDIS_KEY_PREFIX))
+            .await?;
+
+        let mut entries = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            if let Some(entry) = key.strip_prefix(REDIS_KEY_PREFIX) {
+                entries.push(entry.to_string());
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Single-process denylist for development and tests
+#[derive(Default)]
+pub struct InMemoryRevocationStore {
+    entries: RwLock<HashMap<String, Instant>>,
+}
+
+#[async_trait]
+impl RevocationStore for InMemoryRevocationStore {
+    async fn insert(&self, key: &str, ttl: Duration) -> Result<()> {
+        let mut entries = self.entries.write()
+            .map_err(|_| AuthError::Internal("Revocation store lock poisoned".to_string()))?;
+        let now = Instant::now();
+        entries.retain(|_, expires_at| *expires_at > now);
+        entries.insert(key.to_string(), now + ttl);
+        Ok(())
+    }
+
+    async fn contains(&self, key: &str) -> Result<bool> {
+        let entries = self.entries.read()
+            .map_err(|_| AuthError::Internal("Revocation store lock poisoned".to_string()))?;
+        Ok(entries.get(key).is_some_and(|expires_at| *expires_at > Instant::now()))
+    }
+
+    async fn entries(&self) -> Result<Vec<String>> {
+        let entries = self.entries.read()
+            .map_err(|_| AuthError::Internal("Revocation store lock poisoned".to_string()))?;
+        let now = Instant::now();
+        Ok(entries.iter().filter(|(_, at)| **at > now).map(|(key, _)| key.clone()).collect())
+    }
+}
+
+/// Fixed-size bloom filter: no false negatives, tunable false positive rate
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-(n * false_positive_rate.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 16.0) as u32;
+
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    pub fn insert(&mut self, item: &str) {
+        let indexes: Vec<u64> = self.indexes(item).collect();
+        for index in indexes {
+            self.bits[(index / 64) as usize] |= 1 << (index % 64);
+        }
+    }
+
+    pub fn might_contain(&self, item: &str) -> bool {
+        self.indexes(item)
+            .all(|index| self.bits[(index / 64) as usize] & (1 << (index % 64)) != 0)
+    }
+
+    /// Double hashing (Kirsch-Mitzenmacher) over a single SHA-256 digest
+    fn indexes(&self, item: &str) -> impl Iterator<Item = u64> + '_ {
+        let digest = Sha256::digest(item.as_bytes());
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().expect("digest is 32 bytes"));
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().expect("digest is 32 bytes")) | 1;
+
+        (0..self.num_hashes as u64)
+            .map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits)
+    }
+}
+
+/// Access token denylist consulted on every authenticated request.
+///
+/// The bloom filter answers "definitely not revoked" locally, so only tokens that
+/// might be revoked cost a store lookup. Revocations made by other instances reach
+/// the filter on the next `refresh`, which the sync task runs periodically.
+pub struct RevocationList {
+    store: Arc<dyn RevocationStore>,
+    filter: RwLock<BloomFilter>,
+    expected_items: usize,
+    false_positive_rate: f64,
+}
+
+impl RevocationList {
+    pub fn new(store: Arc<dyn RevocationStore>) -> Self {
+        Self::with_capacity(store, 100_000, 0.01)
+    }
+
+    pub fn with_capacity(store: Arc<dyn RevocationStore>, expected_items: usize, false_positive_rate: f64) -> Self {
+        Self {
+            store,
+            filter: RwLock::new(BloomFilter::new(expected_items, false_positive_rate)),
+            expected_items,
+            false_positive_rate,
+        }
+    }
+
+    /// Deny an identifier for `ttl`, which should cover the remaining token lifetime
+    pub async fn revoke(&self, kind: RevocationKind, id: &str, ttl: Duration) -> Result<()> {
+        let key = kind.key(id);
+        self.store.insert(&key, ttl).await?;
+        self.filter_mut()?.insert(&key);
+        Ok(())
+    }
+
+    /// Whether an access token has been revoked, either directly or through its session
+    pub async fn is_revoked(&self, jti: &str, session_id: &str) -> Result<bool> {
+        let token_key = RevocationKind::Token.key(jti);
+        let session_key = RevocationKind::Session.key(session_id);
+
+        let candidates: Vec<String> = {
+            let filter = self.filter()?;
+            [token_key, session_key]
+                .into_iter()
+                .filter(|key| filter.might_contain(key))
+                .collect()
+        };
+
+        for key in candidates {
+            if self.store.contains(&key).await? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Rebuild the filter from the store, dropping expired entries and picking up
+    /// revocations made by other instances
+    pub async fn refresh(&self) -> Result<usize> {
+        let entries = self.store.entries().await?;
+
+        let mut filter = BloomFilter::new(self.expected_items.max(entries.len()), self.false_positive_rate);
+        for entry in &entries {
+            filter.insert(entry);
+        }
+        *self.filter_mut()? = filter;
+
+        Ok(entries.len())
+    }
+
+    /// Periodically refresh the filter in the background
+    pub fn spawn_sync(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.refresh().await {
+                    warn!("Failed to refresh access token revocation filter: {}", e);
+                }
+            }
+        })
+    }
+
+    fn filter(&self) -> Result<std::sync::RwLockReadGuard<'_, BloomFilter>> {
+        self.filter.read()
+            .map_err(|_| AuthError::Internal("Revocation filter lock poisoned".to_string()))
+    }
+
+    fn filter_mut(&self) -> Result<std::sync::RwLockWriteGuard<'_, BloomFilter>> {
+        self.filter.write()
+            .map_err(|_| AuthError::Internal("Revocation filter lock poisoned".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_filter_has_no_false_negatives() {
+        let mut filter = BloomFilter::new(1_000, 0.01);
+        for i in 0..1_000 {
+            filter.insert(&format!("jti:{}", i));
+        }
+
+        assert!((0..1_000).all(|i| filter.might_contain(&format!("jti:{}", i))));
+
+        let false_positives = (1_000..11_000)
+            .filter(|i| filter.might_contain(&format!("jti:{}", i)))
+            .count();
+        assert!(false_positives < 300, "false positive rate too high: {}", false_positives);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_token_and_session() {
+        let list = RevocationList::new(Arc::new(InMemoryRevocationStore::default()));
+
+        assert!(!list.is_revoked("jti-1", "session-1").await.unwrap());
+
+        list.revoke(RevocationKind::Token, "jti-1", Duration::from_secs(60)).await.unwrap();
+        assert!(list.is_revoked("jti-1", "session-1").await.unwrap());
+        assert!(!list.is_revoked("jti-2", "session-1").await.unwrap());
+
+        list.revoke(RevocationKind::Session, "session-1", Duration::from_secs(60)).await.unwrap();
+        assert!(list.is_revoked("jti-2", "session-1").await.unwrap());
+        assert!(!list.is_revoked("jti-2", "session-2").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_picks_up_remote_revocations_and_expiry() {
+        let store = Arc::new(InMemoryRevocationStore::default());
+        let list = RevocationList::new(store.clone());
+
+        // Revoked by another instance directly in the shared store
+        store.insert("jti:remote", Duration::from_secs(60)).await.unwrap();
+        assert!(!list.is_revoked("remote", "session").await.unwrap());
+
+        assert_eq!(list.refresh().await.unwrap(), 1);
+        assert!(list.is_revoked("remote", "session").await.unwrap());
+
+        list.revoke(RevocationKind::Token, "short", Duration::from_millis(10)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!list.is_revoked("short", "session").await.unwrap());
+    }
+}
@@ -0,0 +1,463 @@
+// ============================================================================
+// OLYMPUS CLOUD - SESSION RISK ENGINE
+// ============================================================================
+// Module: auth/src/services/risk.rs
+// Description: GeoIP, impossible travel, device and anonymizer scoring for sign-ins and refreshes
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use ipnet::IpNet;
+use olympus_shared::config::RiskConfig;
+use serde::{Deserialize, Serialize};
+use crate::error::{AuthError, Result};
+use crate::models::RefreshToken;
+
+/// Sessions looked at when scoring a new one
+pub const RISK_HISTORY_LIMIT: i64 = 20;
+
+/// GeoIP accuracy is city level at best; shorter hops are never treated as travel
+const MIN_TRAVEL_DISTANCE_KM: f64 = 300.0;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Where an IP address was resolved to by the GeoIP database
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeoLocation {
+    #[serde(default)]
+    pub country_code: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+}
+
+impl GeoLocation {
+    fn coordinates(&self) -> Option<(f64, f64)> {
+        self.latitude.zip(self.longitude)
+    }
+
+    /// Great-circle distance in kilometres, when both locations have coordinates
+    pub fn distance_km(&self, other: &GeoLocation) -> Option<f64> {
+        let ((lat1, lon1), (lat2, lon2)) = (self.coordinates()?, other.coordinates()?);
+        let (dlat, dlon) = ((lat2 - lat1).to_radians(), (lon2 - lon1).to_radians());
+        let a = (dlat / 2.0).sin().powi(2)
+            + lat1.to_radians().cos() * lat2.to_radians().cos() * (dlon / 2.0).sin().powi(2);
+        Some(2.0 * EARTH_RADIUS_KM * a.sqrt().asin())
+    }
+}
+
+/// Resolves IP addresses to locations
+pub trait GeoIpResolver: Send + Sync {
+    fn lookup(&self, ip: IpAddr) -> Option<GeoLocation>;
+}
+
+/// A MaxMind City database read from local disk
+pub struct MaxMindGeoIp {
+    reader: maxminddb::Reader<Vec<u8>>,
+}
+
+impl MaxMindGeoIp {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let reader = maxminddb::Reader::open_readfile(path).map_err(|e| {
+            AuthError::Internal(format!("Failed to open GeoIP database {}: {}", path.display(), e))
+        })?;
+        Ok(Self { reader })
+    }
+}
+
+impl GeoIpResolver for MaxMindGeoIp {
+    fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        let city: maxminddb::geoip2::City = self.reader.lookup(ip).ok()?;
+        let location = city.location.as_ref();
+
+        Some(GeoLocation {
+            country_code: city.country.and_then(|c| c.iso_code).map(str::to_string),
+            city: city
+                .city
+                .and_then(|c| c.names)
+                .and_then(|names| names.get("en").map(|name| name.to_string())),
+            latitude: location.and_then(|l| l.latitude),
+            longitude: location.and_then(|l| l.longitude),
+        })
+    }
+}
+
+/// Addresses and ranges loaded from a plain text list, such as Tor exit nodes
+#[derive(Debug, Clone, Default)]
+pub struct IpList {
+    networks: Vec<IpNet>,
+}
+
+impl IpList {
+    /// One address or CIDR range per line; blank lines and `#` comments are skipped
+    pub fn parse(contents: &str) -> Self {
+        let networks = contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| {
+                line.parse::<IpNet>()
+                    .ok()
+                    .or_else(|| line.parse::<IpAddr>().ok().map(IpNet::from))
+            })
+            .collect();
+        Self { networks }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AuthError::Internal(format!("Failed to read IP list {}: {}", path.display(), e))
+        })?;
+        Ok(Self::parse(&contents))
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(&ip))
+    }
+
+    pub fn len(&self) -> usize {
+        self.networks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty()
+    }
+}
+
+/// Something unusual about a sign-in, with its contribution to the score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskSignal {
+    ImpossibleTravel,
+    NewDevice,
+    NewCountry,
+    TorExitNode,
+    VpnOrProxy,
+}
+
+impl RiskSignal {
+    pub fn weight(self) -> u32 {
+        match self {
+            RiskSignal::ImpossibleTravel => 60,
+            RiskSignal::TorExitNode => 50,
+            RiskSignal::NewCountry => 30,
+            RiskSignal::VpnOrProxy => 20,
+            RiskSignal::NewDevice => 15,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RiskSignal::ImpossibleTravel => "impossible_travel",
+            RiskSignal::NewDevice => "new_device",
+            RiskSignal::NewCountry => "new_country",
+            RiskSignal::TorExitNode => "tor_exit_node",
+            RiskSignal::VpnOrProxy => "vpn_or_proxy",
+        }
+    }
+}
+
+/// What to do about a sign-in, from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskAction {
+    Allow,
+    Notify,
+    StepUpMfa,
+    Block,
+}
+
+impl RiskAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RiskAction::Allow => "allow",
+            RiskAction::Notify => "notify",
+            RiskAction::StepUpMfa => "step_up_mfa",
+            RiskAction::Block => "block",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    /// 0 to 100
+    pub score: u32,
+    pub signals: Vec<RiskSignal>,
+    pub action: RiskAction,
+    pub location: Option<GeoLocation>,
+    /// Speed implied by the previous session, when it was impossible
+    pub travel_speed_kmh: Option<f64>,
+}
+
+impl RiskAssessment {
+    /// An unscored session, e.g. one issued to an OAuth client
+    pub fn none() -> Self {
+        Self {
+            score: 0,
+            signals: vec![],
+            action: RiskAction::Allow,
+            location: None,
+            travel_speed_kmh: None,
+        }
+    }
+
+    pub fn signal_names(&self) -> Vec<String> {
+        self.signals.iter().map(|signal| signal.as_str().to_string()).collect()
+    }
+}
+
+/// Scores sign-ins and refreshes against the user's recent sessions
+#[derive(Default)]
+pub struct RiskEngine {
+    config: RiskConfig,
+    geoip: Option<Arc<dyn GeoIpResolver>>,
+    tor_exits: IpList,
+    vpn_ranges: IpList,
+}
+
+impl RiskEngine {
+    /// Load the GeoIP database and IP lists named in the configuration
+    pub fn from_config(config: &RiskConfig) -> Result<Self> {
+        let geoip = match &config.geoip_database_path {
+            Some(path) => Some(Arc::new(MaxMindGeoIp::open(path)?) as Arc<dyn GeoIpResolver>),
+            None => None,
+        };
+        let load = |path: &Option<String>| path.as_deref().map(IpList::from_file).transpose();
+
+        Ok(Self {
+            config: config.clone(),
+            geoip,
+            tor_exits: load(&config.tor_exit_list_path)?.unwrap_or_default(),
+            vpn_ranges: load(&config.vpn_list_path)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn with_geoip(mut self, resolver: Arc<dyn GeoIpResolver>) -> Self {
+        self.geoip = Some(resolver);
+        self
+    }
+
+    pub fn with_ip_lists(mut self, tor_exits: IpList, vpn_ranges: IpList) -> Self {
+        self.tor_exits = tor_exits;
+        self.vpn_ranges = vpn_ranges;
+        self
+    }
+
+    /// Score an attempt from `ip_address` on `device_id` against recent sessions, newest first.
+    /// The address must be the client as `RequestContext` resolves it, not a forwarded header.
+    pub fn assess(
+        &self,
+        ip_address: &str,
+        device_id: Option<&str>,
+        history: &[RefreshToken],
+        now: DateTime<Utc>,
+    ) -> RiskAssessment {
+        let ip = ip_address.trim().parse::<IpAddr>().ok();
+        let location = ip.and_then(|ip| self.geoip.as_ref()?.lookup(ip));
+        let mut signals = Vec::new();
+        let mut travel_speed_kmh = None;
+
+        if let Some(ip) = ip {
+            if self.tor_exits.contains(ip) {
+                signals.push(RiskSignal::TorExitNode);
+            } else if self.vpn_ranges.contains(ip) {
+                signals.push(RiskSignal::VpnOrProxy);
+            }
+        }
+
+        if let Some(device_id) = device_id {
+            let known = history.iter().any(|session| session.device_id.as_deref() == Some(device_id));
+            if !history.is_empty() && !known {
+                signals.push(RiskSignal::NewDevice);
+            }
+        }
+
+        let previous: Vec<(DateTime<Utc>, GeoLocation)> = history
+            .iter()
+            .filter_map(|session| {
+                serde_json::from_value::<GeoLocation>(session.location.clone())
+                    .ok()
+                    .map(|location| (session.created_at, location))
+            })
+            .collect();
+
+        if let Some(location) = &location {
+            if let Some(country) = &location.country_code {
+                let countries: Vec<&String> = previous.iter().filter_map(|(_, l)| l.country_code.as_ref()).collect();
+                if !countries.is_empty() && !countries.contains(&country) {
+                    signals.push(RiskSignal::NewCountry);
+                }
+            }
+
+            let last = previous
+                .iter()
+                .filter(|(_, l)| l.coordinates().is_some())
+                .max_by_key(|(at, _)| *at);
+            if let Some((at, last_location)) = last {
+                let distance = location.distance_km(last_location).unwrap_or(0.0);
+                // Clamp to a minute so back-to-back sessions don't divide by zero
+                let hours = ((now - *at).num_seconds().max(60)) as f64 / 3600.0;
+                let speed = distance / hours;
+                if distance >= MIN_TRAVEL_DISTANCE_KM && speed > self.config.max_travel_speed_kmh {
+                    signals.push(RiskSignal::ImpossibleTravel);
+                    travel_speed_kmh = Some(speed.round());
+                }
+            }
+        }
+
+        let score = signals.iter().map(|signal| signal.weight()).sum::<u32>().min(100);
+        RiskAssessment {
+            score,
+            action: self.action_for(score),
+            signals,
+            location,
+            travel_speed_kmh,
+        }
+    }
+
+    fn action_for(&self, score: u32) -> RiskAction {
+        if score >= self.config.block_score {
+            RiskAction::Block
+        } else if score >= self.config.step_up_score {
+            RiskAction::StepUpMfa
+        } else if score >= self.config.notify_score {
+            RiskAction::Notify
+        } else {
+            RiskAction::Allow
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use axum::http::HeaderMap;
+    use chrono::Duration;
+    use olympus_shared::policy::{RequestContext, TrustedProxies};
+    use uuid::Uuid;
+
+    struct StaticGeoIp(HashMap<IpAddr, GeoLocation>);
+
+    impl GeoIpResolver for StaticGeoIp {
+        fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+            self.0.get(&ip).cloned()
+        }
+    }
+
+    fn place(country: &str, latitude: f64, longitude: f64) -> GeoLocation {
+        GeoLocation {
+            country_code: Some(country.to_string()),
+            city: None,
+            latitude: Some(latitude),
+            longitude: Some(longitude),
+        }
+    }
+
+    fn session(device_id: &str, location: &GeoLocation, created_at: DateTime<Utc>) -> RefreshToken {
+        RefreshToken {
+            id: Uuid::new_v4(),
+            token_hash: "hash".to_string(),
+            family_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            device_id: Some(device_id.to_string()),
+            device_name: None,
+            ip_address: "198.51.100.1".to_string(),
+            user_agent: "test".to_string(),
+            expires_at: created_at + Duration::days(30),
+            revoked_at: None,
+            replaced_by: None,
+            client_id: None,
+            scopes: vec![],
+            location: serde_json::to_value(location).unwrap(),
+            risk_score: 0,
+            risk_signals: vec![],
+            created_at,
+        }
+    }
+
+    fn engine() -> RiskEngine {
+        let geoip = StaticGeoIp(HashMap::from([
+            ("198.51.100.1".parse().unwrap(), place("US", 40.71, -74.01)),   // New York
+            ("198.51.100.2".parse().unwrap(), place("US", 40.73, -73.99)),   // New York
+            ("203.0.113.5".parse().unwrap(), place("JP", 35.68, 139.69)),    // Tokyo
+        ]));
+        RiskEngine::default()
+            .with_geoip(Arc::new(geoip))
+            .with_ip_lists(IpList::parse("# tor exits\n192.0.2.66\n"), IpList::parse("10.20.0.0/16\n"))
+    }
+
+    #[test]
+    fn test_familiar_session_is_allowed() {
+        let now = Utc::now();
+        let history = vec![session("phone", &place("US", 40.71, -74.01), now - Duration::hours(3))];
+
+        let assessment = engine().assess("198.51.100.2", Some("phone"), &history, now);
+        assert!(assessment.signals.is_empty());
+        assert_eq!(assessment.action, RiskAction::Allow);
+        assert_eq!(assessment.location.unwrap().country_code.as_deref(), Some("US"));
+    }
+
+    #[test]
+    fn test_impossible_travel_from_new_country() {
+        let now = Utc::now();
+        let history = vec![session("phone", &place("US", 40.71, -74.01), now - Duration::hours(1))];
+
+        let assessment = engine().assess("203.0.113.5", Some("phone"), &history, now);
+        assert_eq!(assessment.signals, vec![RiskSignal::NewCountry, RiskSignal::ImpossibleTravel]);
+        assert_eq!(assessment.action, RiskAction::Block);
+        assert!(assessment.travel_speed_kmh.unwrap() > 10_000.0);
+
+        // The same trip is plausible two days later, but the country is still new
+        let assessment = engine().assess("203.0.113.5", Some("phone"), &history, now + Duration::days(2));
+        assert_eq!(assessment.signals, vec![RiskSignal::NewCountry]);
+        assert_eq!(assessment.action, RiskAction::Notify);
+    }
+
+    #[test]
+    fn test_anonymizers_and_new_devices() {
+        let now = Utc::now();
+        let history = vec![session("phone", &GeoLocation::default(), now - Duration::days(1))];
+
+        let tor = engine().assess("192.0.2.66", Some("laptop"), &history, now);
+        assert_eq!(tor.signals, vec![RiskSignal::TorExitNode, RiskSignal::NewDevice]);
+        assert_eq!(tor.action, RiskAction::StepUpMfa);
+
+        let vpn = engine().assess("10.20.3.4", Some("phone"), &history, now);
+        assert_eq!(vpn.signals, vec![RiskSignal::VpnOrProxy]);
+        assert_eq!(vpn.action, RiskAction::Allow);
+
+        // A user's first session has nothing to compare against
+        let first = engine().assess("198.51.100.1", Some("laptop"), &[], now);
+        assert!(first.signals.is_empty());
+    }
+
+    #[test]
+    fn test_spoofed_forwarded_header_is_not_scored() {
+        let now = Utc::now();
+        let history = vec![session("phone", &place("US", 40.71, -74.01), now - Duration::hours(1))];
+
+        // The client claims the victim's usual address; the proxy appends the one it saw
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "198.51.100.1, 203.0.113.5".parse().unwrap());
+
+        let proxied = RequestContext::from_forwarded(&headers, "172.16.0.2".parse().ok(), TrustedProxies(1));
+        let ip_address = proxied.ip_address.unwrap().to_string();
+        assert_eq!(ip_address, "203.0.113.5");
+        let assessment = engine().assess(&ip_address, Some("phone"), &history, now);
+        assert_eq!(assessment.signals, vec![RiskSignal::NewCountry, RiskSignal::ImpossibleTravel]);
+
+        // Without trusted proxies the header is ignored and the peer is the client
+        let direct = RequestContext::from_forwarded(&headers, "203.0.113.5".parse().ok(), TrustedProxies(0));
+        assert_eq!(direct.ip_address.unwrap().to_string(), "203.0.113.5");
+    }
+}
@@ -0,0 +1,817 @@
+// ============================================================================
+// OLYMPUS CLOUD - SCIM 2.0 PROVISIONING HELPERS
+// ============================================================================
+// Module: auth/src/services/scim.rs
+// Description: SCIM resource mapping, filter evaluation and PATCH operations
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use chrono::Utc;
+use olympus_shared::models::permission::Role;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+use crate::models::{
+    ScimError, ScimGroup, ScimListResponse, ScimMember, ScimMeta, ScimMultiValue, ScimName,
+    ScimPatchOperation, ScimUser, User,
+};
+
+pub const SCHEMA_USER: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const SCHEMA_GROUP: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const SCHEMA_LIST_RESPONSE: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const SCHEMA_BULK_RESPONSE: &str = "urn:ietf:params:scim:api:messages:2.0:BulkResponse";
+pub const SCHEMA_ERROR: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+pub const SCHEMA_SERVICE_PROVIDER_CONFIG: &str = "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+
+/// Media type of every SCIM request and response body
+pub const CONTENT_TYPE: &str = "application/scim+json";
+
+pub const RESOURCE_USER: &str = "User";
+pub const RESOURCE_GROUP: &str = "Group";
+
+/// Base path the SCIM endpoints are mounted under, used for `meta.location`
+pub const BASE_PATH: &str = "/scim/v2";
+
+pub const MAX_BULK_OPERATIONS: usize = 100;
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// What this service provider supports, advertised to SCIM clients
+pub fn service_provider_config() -> Value {
+    serde_json::json!({
+        "schemas": [SCHEMA_SERVICE_PROVIDER_CONFIG],
+        "patch": { "supported": true },
+        "bulk": { "supported": true, "maxOperations": MAX_BULK_OPERATIONS, "maxPayloadSize": 1_048_576 },
+        "filter": { "supported": true, "maxResults": MAX_PAGE_SIZE },
+        "changePassword": { "supported": false },
+        "sort": { "supported": false },
+        "etag": { "supported": false },
+        "authenticationSchemes": [{
+            "type": "oauthbearertoken",
+            "name": "OAuth Bearer Token",
+            "description": "Tenant API key with the admin:scim permission",
+            "primary": true
+        }]
+    })
+}
+
+pub fn user_location(id: &str) -> String {
+    format!("{}/Users/{}", BASE_PATH, id)
+}
+
+pub fn group_location(id: &str) -> String {
+    format!("{}/Groups/{}", BASE_PATH, id)
+}
+
+/// SCIM error body for a failed request or bulk operation
+pub fn error_body(error: &AuthError) -> ScimError {
+    ScimError {
+        schemas: vec![SCHEMA_ERROR.to_string()],
+        status: error.status_code().to_string(),
+        scim_type: error.scim_type().map(str::to_string),
+        detail: error.to_string(),
+    }
+}
+
+/// Represent a user as a SCIM resource
+pub fn user_to_scim(user: &User, external_id: Option<String>, groups: Vec<ScimMember>) -> ScimUser {
+    let id = user.id.to_string();
+    ScimUser {
+        schemas: vec![SCHEMA_USER.to_string()],
+        external_id,
+        user_name: user.email.clone(),
+        name: Some(ScimName {
+            formatted: Some(format!("{} {}", user.first_name, user.last_name).trim().to_string()),
+            family_name: Some(user.last_name.clone()),
+            given_name: Some(user.first_name.clone()),
+        }),
+        display_name: user.display_name.clone(),
+        emails: vec![ScimMultiValue {
+            value: user.email.clone(),
+            value_type: Some("work".to_string()),
+            primary: Some(true),
+        }],
+        phone_numbers: user
+            .phone
+            .iter()
+            .map(|phone| ScimMultiValue {
+                value: phone.clone(),
+                value_type: Some("work".to_string()),
+                primary: Some(true),
+            })
+            .collect(),
+        active: user.is_active,
+        groups,
+        meta: Some(ScimMeta {
+            resource_type: RESOURCE_USER.to_string(),
+            created: user.created_at,
+            last_modified: user.updated_at,
+            location: user_location(&id),
+        }),
+        id: Some(id),
+    }
+}
+
+/// Copy the writable attributes of a SCIM user onto a user. Our users are keyed by email,
+/// so `userName` must be an email address unless a primary email is supplied.
+pub fn apply_scim_user(user: &mut User, scim: &ScimUser) -> Result<()> {
+    let primary = |values: &[ScimMultiValue]| {
+        values
+            .iter()
+            .find(|v| v.primary == Some(true))
+            .or_else(|| values.first())
+            .map(|v| v.value.trim().to_string())
+    };
+
+    let email = if scim.user_name.contains('@') {
+        scim.user_name.trim().to_lowercase()
+    } else {
+        primary(&scim.emails)
+            .filter(|email| email.contains('@'))
+            .map(|email| email.to_lowercase())
+            .ok_or_else(|| {
+                AuthError::Validation("userName must be an email address or a primary email is required".to_string())
+            })?
+    };
+
+    let name = scim.name.clone().unwrap_or_default();
+    let (first_name, last_name) = match (name.given_name, name.family_name) {
+        (Some(given), Some(family)) => (given, family),
+        (given, family) => {
+            let formatted = name.formatted.or_else(|| scim.display_name.clone()).unwrap_or_default();
+            let (first, last) = formatted.split_once(' ').unwrap_or((formatted.as_str(), ""));
+            (
+                given.unwrap_or_else(|| first.to_string()),
+                family.unwrap_or_else(|| last.to_string()),
+            )
+        }
+    };
+
+    user.email = email;
+    user.first_name = first_name;
+    user.last_name = last_name;
+    user.display_name = scim.display_name.clone();
+    user.phone = primary(&scim.phone_numbers);
+    user.is_active = scim.active;
+    user.updated_at = Utc::now();
+    Ok(())
+}
+
+/// Represent a tenant role as a SCIM group
+pub fn role_to_scim(role: &Role, external_id: Option<String>, members: Vec<ScimMember>) -> ScimGroup {
+    let id = role.id.to_string();
+    ScimGroup {
+        schemas: vec![SCHEMA_GROUP.to_string()],
+        external_id,
+        display_name: role.display_name.clone().unwrap_or_else(|| role.name.clone()),
+        members,
+        meta: Some(ScimMeta {
+            resource_type: RESOURCE_GROUP.to_string(),
+            created: role.created_at,
+            last_modified: role.updated_at,
+            location: group_location(&id),
+        }),
+        id: Some(id),
+    }
+}
+
+/// A user with their groups, taken from the tenant roles named in `User::roles`
+pub fn user_resource(user: &User, roles: &[Role], external_ids: &HashMap<Uuid, String>) -> ScimUser {
+    let groups = roles
+        .iter()
+        .filter(|role| user.roles.contains(&role.name))
+        .map(|role| ScimMember {
+            value: role.id.to_string(),
+            display: Some(role.display_name.clone().unwrap_or_else(|| role.name.clone())),
+            reference: Some(group_location(&role.id.to_string())),
+        })
+        .collect();
+
+    user_to_scim(user, external_ids.get(&user.id).cloned(), groups)
+}
+
+/// One page of a list response; SCIM's `startIndex` is 1-based
+pub fn page<T>(resources: Vec<T>, start_index: Option<usize>, count: Option<usize>) -> ScimListResponse<T> {
+    let start_index = start_index.unwrap_or(1).max(1);
+    let count = count.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let total_results = resources.len();
+    let resources: Vec<T> = resources.into_iter().skip(start_index - 1).take(count).collect();
+
+    ScimListResponse {
+        schemas: vec![SCHEMA_LIST_RESPONSE.to_string()],
+        total_results,
+        start_index,
+        items_per_page: resources.len(),
+        resources,
+    }
+}
+
+// ============================================================================
+// FILTERS (RFC 7644 section 3.4.2.2)
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Co,
+    Sw,
+    Ew,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CompareOp {
+    fn parse(op: &str) -> Option<Self> {
+        Some(match op.to_ascii_lowercase().as_str() {
+            "eq" => CompareOp::Eq,
+            "ne" => CompareOp::Ne,
+            "co" => CompareOp::Co,
+            "sw" => CompareOp::Sw,
+            "ew" => CompareOp::Ew,
+            "gt" => CompareOp::Gt,
+            "ge" => CompareOp::Ge,
+            "lt" => CompareOp::Lt,
+            "le" => CompareOp::Le,
+            _ => return None,
+        })
+    }
+}
+
+/// A parsed SCIM filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScimFilter {
+    Present(String),
+    Compare { path: String, op: CompareOp, value: Value },
+    /// `emails[type eq "work"]`: some element of a multi-valued attribute matches
+    ValuePath { path: String, filter: Box<ScimFilter> },
+    And(Box<ScimFilter>, Box<ScimFilter>),
+    Or(Box<ScimFilter>, Box<ScimFilter>),
+    Not(Box<ScimFilter>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Str(String),
+    Word(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '[' | ']' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    _ => Token::RBracket,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => value.push(escaped),
+                            None => return Err(AuthError::InvalidFilter("unterminated string".to_string())),
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err(AuthError::InvalidFilter("unterminated string".to_string())),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct FilterParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl FilterParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(AuthError::InvalidFilter(format!("expected {:?}, found {:?}", expected, other))),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<ScimFilter> {
+        let mut left = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.next();
+            left = ScimFilter::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<ScimFilter> {
+        let mut left = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.next();
+            left = ScimFilter::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<ScimFilter> {
+        if self.peek_keyword("not") {
+            self.next();
+            self.expect(Token::LParen)?;
+            let inner = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(ScimFilter::Not(Box::new(inner)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.next();
+            let inner = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(inner);
+        }
+        self.parse_attribute_expression()
+    }
+
+    fn parse_attribute_expression(&mut self) -> Result<ScimFilter> {
+        let path = match self.next() {
+            Some(Token::Word(path)) => normalize_path(&path),
+            other => return Err(AuthError::InvalidFilter(format!("expected attribute, found {:?}", other))),
+        };
+
+        if self.peek() == Some(&Token::LBracket) {
+            self.next();
+            let filter = self.parse_or()?;
+            self.expect(Token::RBracket)?;
+            return Ok(ScimFilter::ValuePath { path, filter: Box::new(filter) });
+        }
+
+        let op = match self.next() {
+            Some(Token::Word(op)) if op.eq_ignore_ascii_case("pr") => return Ok(ScimFilter::Present(path)),
+            Some(Token::Word(op)) => CompareOp::parse(&op)
+                .ok_or_else(|| AuthError::InvalidFilter(format!("unknown operator {}", op)))?,
+            other => return Err(AuthError::InvalidFilter(format!("expected operator, found {:?}", other))),
+        };
+
+        let value = match self.next() {
+            Some(Token::Str(value)) => Value::String(value),
+            Some(Token::Word(word)) => match word.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                number => serde_json::from_str::<serde_json::Number>(number)
+                    .map(Value::Number)
+                    .map_err(|_| AuthError::InvalidFilter(format!("invalid value {}", number)))?,
+            },
+            other => return Err(AuthError::InvalidFilter(format!("expected value, found {:?}", other))),
+        };
+
+        Ok(ScimFilter::Compare { path, op, value })
+    }
+}
+
+/// Drop the schema URN from fully qualified attribute names such as
+/// `urn:ietf:params:scim:schemas:core:2.0:User:userName`
+fn normalize_path(path: &str) -> String {
+    if path.to_ascii_lowercase().starts_with("urn:") {
+        path.rsplit(':').next().unwrap_or(path).to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+pub fn parse_filter(filter: &str) -> Result<ScimFilter> {
+    let mut parser = FilterParser {
+        tokens: tokenize(filter)?,
+        position: 0,
+    };
+    let parsed = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(AuthError::InvalidFilter(format!("unexpected {:?}", token)));
+    }
+    Ok(parsed)
+}
+
+/// Attribute names are case-insensitive in SCIM
+fn get_attribute<'a>(object: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    object
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// All values at a dotted attribute path, with multi-valued attributes flattened
+fn resolve<'a>(resource: &'a Value, path: &str) -> Vec<&'a Value> {
+    let mut current = vec![resource];
+    for segment in path.split('.') {
+        current = current
+            .into_iter()
+            .flat_map(|value| match value {
+                Value::Array(items) => items.iter().collect::<Vec<_>>(),
+                other => vec![other],
+            })
+            .filter_map(|value| value.as_object().and_then(|object| get_attribute(object, segment)))
+            .collect();
+    }
+
+    current
+        .into_iter()
+        .flat_map(|value| match value {
+            Value::Array(items) => items.iter().collect::<Vec<_>>(),
+            other => vec![other],
+        })
+        .collect()
+}
+
+fn compare(actual: &Value, op: CompareOp, expected: &Value) -> bool {
+    // Complex multi-valued entries (emails, members) compare on their `value`
+    let actual = match actual {
+        Value::Object(object) => match get_attribute(object, "value") {
+            Some(value) => value,
+            None => return false,
+        },
+        other => other,
+    };
+
+    match (actual, expected) {
+        (Value::String(actual), Value::String(expected)) => {
+            let (actual, expected) = (actual.to_lowercase(), expected.to_lowercase());
+            match op {
+                CompareOp::Eq => actual == expected,
+                CompareOp::Ne => actual != expected,
+                CompareOp::Co => actual.contains(&expected),
+                CompareOp::Sw => actual.starts_with(&expected),
+                CompareOp::Ew => actual.ends_with(&expected),
+                CompareOp::Gt => actual > expected,
+                CompareOp::Ge => actual >= expected,
+                CompareOp::Lt => actual < expected,
+                CompareOp::Le => actual <= expected,
+            }
+        }
+        (Value::Number(actual), Value::Number(expected)) => {
+            let (Some(actual), Some(expected)) = (actual.as_f64(), expected.as_f64()) else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => actual == expected,
+                CompareOp::Ne => actual != expected,
+                CompareOp::Gt => actual > expected,
+                CompareOp::Ge => actual >= expected,
+                CompareOp::Lt => actual < expected,
+                CompareOp::Le => actual <= expected,
+                _ => false,
+            }
+        }
+        (actual, expected) => match op {
+            CompareOp::Eq => actual == expected,
+            CompareOp::Ne => actual != expected,
+            _ => false,
+        },
+    }
+}
+
+impl ScimFilter {
+    /// Evaluate the filter against the JSON representation of a resource
+    pub fn matches(&self, resource: &Value) -> bool {
+        match self {
+            ScimFilter::Present(path) => resolve(resource, path).into_iter().any(|value| match value {
+                Value::Null => false,
+                Value::String(s) => !s.is_empty(),
+                _ => true,
+            }),
+            // A missing attribute is "not equal" to anything
+            ScimFilter::Compare { path, op: CompareOp::Ne, value } => {
+                !resolve(resource, path).into_iter().any(|actual| compare(actual, CompareOp::Eq, value))
+            }
+            ScimFilter::Compare { path, op: CompareOp::Eq, value: Value::Null } => {
+                resolve(resource, path).into_iter().all(Value::is_null)
+            }
+            ScimFilter::Compare { path, op, value } => {
+                resolve(resource, path).into_iter().any(|actual| compare(actual, *op, value))
+            }
+            ScimFilter::ValuePath { path, filter } => {
+                resolve(resource, path).into_iter().any(|element| filter.matches(element))
+            }
+            ScimFilter::And(left, right) => left.matches(resource) && right.matches(resource),
+            ScimFilter::Or(left, right) => left.matches(resource) || right.matches(resource),
+            ScimFilter::Not(inner) => !inner.matches(resource),
+        }
+    }
+}
+
+// ============================================================================
+// PATCH (RFC 7644 section 3.5.2)
+// ============================================================================
+
+/// A PATCH target such as `name.givenName` or `members[value eq "..."]`
+struct PatchPath {
+    attribute: String,
+    filter: Option<ScimFilter>,
+    sub_attribute: Option<String>,
+}
+
+fn parse_patch_path(path: &str) -> Result<PatchPath> {
+    let invalid = || AuthError::Validation(format!("Invalid patch path {}", path));
+    let path = normalize_path(path.trim());
+
+    if let Some((attribute, rest)) = path.split_once('[') {
+        let (filter, after) = rest.rsplit_once(']').ok_or_else(invalid)?;
+        let sub_attribute = match after {
+            "" => None,
+            sub => Some(sub.strip_prefix('.').ok_or_else(invalid)?.to_string()),
+        };
+        return Ok(PatchPath {
+            attribute: attribute.to_string(),
+            filter: Some(parse_filter(filter).map_err(|_| invalid())?),
+            sub_attribute,
+        });
+    }
+
+    let (attribute, sub_attribute) = match path.split_once('.') {
+        Some((attribute, sub)) => (attribute.to_string(), Some(sub.to_string())),
+        None => (path, None),
+    };
+    if attribute.is_empty() {
+        return Err(invalid());
+    }
+    Ok(PatchPath { attribute, filter: None, sub_attribute })
+}
+
+/// The attribute's value, created as `null` under the given name if missing
+fn attribute_mut<'a>(object: &'a mut Map<String, Value>, name: &str) -> &'a mut Value {
+    let key = object
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+        .unwrap_or_else(|| name.to_string());
+    object.entry(key).or_insert(Value::Null)
+}
+
+fn remove_attribute(object: &mut Map<String, Value>, name: &str) {
+    if let Some(key) = object.keys().find(|key| key.eq_ignore_ascii_case(name)).cloned() {
+        object.remove(&key);
+    }
+}
+
+fn as_object_mut(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value.as_object_mut().expect("just replaced with an object")
+}
+
+fn matching_elements<'a>(target: &'a mut Value, filter: &ScimFilter) -> Vec<&'a mut Value> {
+    match target {
+        Value::Array(items) => items.iter_mut().filter(|item| filter.matches(item)).collect(),
+        _ => vec![],
+    }
+}
+
+/// Apply SCIM PATCH operations to the JSON representation of a resource
+pub fn apply_patch(resource: &mut Value, operations: &[ScimPatchOperation]) -> Result<()> {
+    for operation in operations {
+        let op = operation.op.to_ascii_lowercase();
+        match (op.as_str(), &operation.path) {
+            ("add" | "replace", None) => {
+                // Without a path the value is an object of attributes to set
+                let Some(Value::Object(attributes)) = &operation.value else {
+                    return Err(AuthError::Validation(format!("{} without a path needs an object value", op)));
+                };
+                for (name, value) in attributes {
+                    apply_at_path(resource, &op, &parse_patch_path(name)?, Some(value))?;
+                }
+            }
+            ("add" | "replace", Some(path)) => {
+                let value = operation
+                    .value
+                    .as_ref()
+                    .ok_or_else(|| AuthError::Validation(format!("{} requires a value", op)))?;
+                apply_at_path(resource, &op, &parse_patch_path(path)?, Some(value))?;
+            }
+            ("remove", Some(path)) => {
+                apply_at_path(resource, &op, &parse_patch_path(path)?, operation.value.as_ref())?;
+            }
+            ("remove", None) => return Err(AuthError::Validation("remove requires a path".to_string())),
+            _ => return Err(AuthError::Validation(format!("Unsupported patch operation {}", operation.op))),
+        }
+    }
+    Ok(())
+}
+
+fn apply_at_path(resource: &mut Value, op: &str, path: &PatchPath, value: Option<&Value>) -> Result<()> {
+    let object = as_object_mut(resource);
+
+    if let Some(filter) = &path.filter {
+        let target = attribute_mut(object, &path.attribute);
+        if op == "remove" {
+            if let Value::Array(items) = target {
+                match &path.sub_attribute {
+                    Some(sub) => items
+                        .iter_mut()
+                        .filter(|item| filter.matches(item))
+                        .for_each(|item| remove_attribute(as_object_mut(item), sub)),
+                    None => items.retain(|item| !filter.matches(item)),
+                }
+            }
+            return Ok(());
+        }
+
+        let elements = matching_elements(target, filter);
+        if elements.is_empty() {
+            return Err(AuthError::Validation(format!("No values match {}", path.attribute)));
+        }
+
+        let value = value.cloned().unwrap_or(Value::Null);
+        for element in elements {
+            match (&path.sub_attribute, op, &value) {
+                (Some(sub), _, _) => *attribute_mut(as_object_mut(element), sub) = value.clone(),
+                (None, "add", Value::Object(fields)) => {
+                    let element = as_object_mut(element);
+                    for (name, field) in fields {
+                        *attribute_mut(element, name) = field.clone();
+                    }
+                }
+                (None, _, _) => *element = value.clone(),
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(sub) = &path.sub_attribute {
+        let parent = attribute_mut(object, &path.attribute);
+        match (op, value) {
+            ("remove", _) => match parent {
+                Value::Array(items) => items.iter_mut().for_each(|item| remove_attribute(as_object_mut(item), sub)),
+                Value::Object(parent) => remove_attribute(parent, sub),
+                _ => {}
+            },
+            (_, Some(value)) => *attribute_mut(as_object_mut(parent), sub) = value.clone(),
+            (_, None) => {}
+        }
+        return Ok(());
+    }
+
+    match (op, value) {
+        // Some clients remove multi-valued entries by listing them as the value
+        ("remove", Some(Value::Array(removed))) => {
+            if let Value::Array(items) = attribute_mut(object, &path.attribute) {
+                items.retain(|item| {
+                    !removed
+                        .iter()
+                        .any(|r| r.get("value").is_some() && r.get("value") == item.get("value"))
+                });
+            }
+        }
+        ("remove", _) => remove_attribute(object, &path.attribute),
+        ("add", Some(value)) => {
+            let target = attribute_mut(object, &path.attribute);
+            match (target, value) {
+                (Value::Array(items), Value::Array(added)) => {
+                    for item in added {
+                        if !items.contains(item) {
+                            items.push(item.clone());
+                        }
+                    }
+                }
+                (Value::Array(items), item) => {
+                    if !items.contains(item) {
+                        items.push(item.clone());
+                    }
+                }
+                (Value::Object(fields), Value::Object(added)) => {
+                    for (name, field) in added {
+                        *attribute_mut(fields, name) = field.clone();
+                    }
+                }
+                (target, value) => *target = value.clone(),
+            }
+        }
+        (_, Some(value)) => *attribute_mut(object, &path.attribute) = value.clone(),
+        (_, None) => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_user() -> Value {
+        serde_json::json!({
+            "userName": "jamie@example.com",
+            "name": { "givenName": "Jamie", "familyName": "Chef" },
+            "emails": [
+                { "value": "jamie@example.com", "type": "work", "primary": true },
+                { "value": "jamie@home.example", "type": "home" }
+            ],
+            "active": true,
+            "meta": { "lastModified": "2025-01-20T10:00:00Z" }
+        })
+    }
+
+    #[test]
+    fn test_filter_evaluation() {
+        let user = sample_user();
+        let matches = |filter: &str| parse_filter(filter).unwrap().matches(&user);
+
+        assert!(matches(r#"userName eq "JAMIE@example.com""#));
+        assert!(matches(r#"urn:ietf:params:scim:schemas:core:2.0:User:userName sw "jamie""#));
+        assert!(matches(r#"name.familyName co "hef" and active eq true"#));
+        assert!(matches(r#"emails[type eq "home" and value ew ".example"]"#));
+        assert!(matches(r#"emails.value eq "jamie@home.example""#));
+        assert!(matches(r#"meta.lastModified gt "2025-01-01T00:00:00Z""#));
+        assert!(matches(r#"externalId pr or (title pr or not (active eq false))"#));
+        assert!(!matches(r#"userName ne "jamie@example.com""#));
+        assert!(!matches("externalId pr"));
+
+        assert!(matches!(parse_filter("userName eq"), Err(AuthError::InvalidFilter(_))));
+        assert!(matches!(parse_filter(r#"userName like "x""#), Err(AuthError::InvalidFilter(_))));
+        assert!(matches!(parse_filter(r#"(userName eq "x""#), Err(AuthError::InvalidFilter(_))));
+    }
+
+    #[test]
+    fn test_patch_operations() {
+        let mut user = sample_user();
+        let ops: Vec<ScimPatchOperation> = serde_json::from_value(serde_json::json!([
+            { "op": "Replace", "value": { "active": false, "name.givenName": "Jay" } },
+            { "op": "replace", "path": "emails[type eq \"work\"].value", "value": "jay@example.com" },
+            { "op": "remove", "path": "emails[type eq \"home\"]" },
+            { "op": "add", "path": "phoneNumbers", "value": [{ "value": "+15550100", "type": "work" }] }
+        ]))
+        .unwrap();
+
+        apply_patch(&mut user, &ops).unwrap();
+        assert_eq!(user["active"], false);
+        assert_eq!(user["name"]["givenName"], "Jay");
+        assert_eq!(user["emails"].as_array().unwrap().len(), 1);
+        assert_eq!(user["emails"][0]["value"], "jay@example.com");
+        assert_eq!(user["phoneNumbers"][0]["value"], "+15550100");
+
+        let missing: Vec<ScimPatchOperation> = serde_json::from_value(serde_json::json!([
+            { "op": "remove" }
+        ]))
+        .unwrap();
+        assert!(apply_patch(&mut user, &missing).is_err());
+    }
+
+    #[test]
+    fn test_group_member_patch() {
+        let mut group = serde_json::json!({
+            "displayName": "Servers",
+            "members": [{ "value": "a" }, { "value": "b" }]
+        });
+        let ops: Vec<ScimPatchOperation> = serde_json::from_value(serde_json::json!([
+            { "op": "add", "path": "members", "value": [{ "value": "c" }, { "value": "a" }] },
+            { "op": "remove", "path": "members[value eq \"b\"]" },
+            { "op": "Remove", "path": "members", "value": [{ "value": "c" }] }
+        ]))
+        .unwrap();
+
+        apply_patch(&mut group, &ops).unwrap();
+        assert_eq!(group["members"], serde_json::json!([{ "value": "a" }]));
+    }
+}
@@ -0,0 +1,481 @@
+// ============================================================================
+// OLYMPUS CLOUD - OIDC SINGLE SIGN-ON
+// ============================================================================
+// Module: auth/src/services/sso.rs
+// Description: Per-tenant OIDC relying party configuration, discovery and ID token validation
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+use crate::models::SsoConfigResponse;
+
+/// How long a started SSO login may take to come back from the identity provider
+pub const SSO_STATE_DURATION_SECONDS: i64 = 600;
+
+/// Discovery documents and keys are refetched after this long, or when an unknown `kid` appears
+const PROVIDER_CACHE_TTL: Duration = Duration::from_secs(3600);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const ID_TOKEN_LEEWAY_SECONDS: u64 = 60;
+
+/// ID tokens must be signed with an asymmetric key published by the provider
+const ALLOWED_ID_TOKEN_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// A tenant's OIDC relying party settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcProviderConfig {
+    pub issuer: String,
+    pub client_id: String,
+    /// Kept out of the JSON configuration; stored separately as an encrypted value
+    #[serde(skip)]
+    pub client_secret: String,
+    pub redirect_uri: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// Disable password and magic link login for everyone in the tenant
+    #[serde(default)]
+    pub sso_required: bool,
+    /// Create users on their first SSO login instead of requiring an existing account
+    #[serde(default = "default_jit_provisioning")]
+    pub jit_provisioning: bool,
+    /// Only provision or link users with these email domains; empty allows any
+    #[serde(default)]
+    pub allowed_email_domains: Vec<String>,
+    #[serde(default = "default_roles")]
+    pub default_roles: Vec<String>,
+}
+
+pub fn default_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "email".to_string(), "profile".to_string()]
+}
+
+fn default_jit_provisioning() -> bool {
+    true
+}
+
+pub fn default_roles() -> Vec<String> {
+    vec!["user".to_string()]
+}
+
+impl OidcProviderConfig {
+    pub fn email_domain_allowed(&self, email: &str) -> bool {
+        if self.allowed_email_domains.is_empty() {
+            return true;
+        }
+
+        let Some((_, domain)) = email.rsplit_once('@') else {
+            return false;
+        };
+        self.allowed_email_domains
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(domain))
+    }
+}
+
+impl From<OidcProviderConfig> for SsoConfigResponse {
+    fn from(config: OidcProviderConfig) -> Self {
+        Self {
+            client_secret_set: !config.client_secret.is_empty(),
+            issuer: config.issuer,
+            client_id: config.client_id,
+            redirect_uri: config.redirect_uri,
+            scopes: config.scopes,
+            sso_required: config.sso_required,
+            jit_provisioning: config.jit_provisioning,
+            allowed_email_domains: config.allowed_email_domains,
+            default_roles: config.default_roles,
+        }
+    }
+}
+
+/// Storage for per-tenant SSO settings
+#[async_trait]
+pub trait SsoConfigStore: Send + Sync {
+    async fn load(&self, tenant_id: Uuid) -> Result<Option<OidcProviderConfig>>;
+    async fn save(&self, tenant_id: Uuid, config: &OidcProviderConfig, updated_by: Uuid) -> Result<()>;
+}
+
+/// Process-local store, used when no platform configuration service is wired in and in tests
+#[derive(Default)]
+pub struct InMemorySsoConfigStore {
+    configs: RwLock<HashMap<Uuid, OidcProviderConfig>>,
+}
+
+#[async_trait]
+impl SsoConfigStore for InMemorySsoConfigStore {
+    async fn load(&self, tenant_id: Uuid) -> Result<Option<OidcProviderConfig>> {
+        let configs = self
+            .configs
+            .read()
+            .map_err(|_| AuthError::Internal("SSO config lock poisoned".to_string()))?;
+        Ok(configs.get(&tenant_id).cloned())
+    }
+
+    async fn save(&self, tenant_id: Uuid, config: &OidcProviderConfig, _updated_by: Uuid) -> Result<()> {
+        self.configs
+            .write()
+            .map_err(|_| AuthError::Internal("SSO config lock poisoned".to_string()))?
+            .insert(tenant_id, config.clone());
+        Ok(())
+    }
+}
+
+/// The subset of the OpenID Provider metadata used by the relying party
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcDiscovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// Claims read from a validated ID token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Clone)]
+struct CachedProvider {
+    discovery: OidcDiscovery,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+/// HTTP client for identity providers, caching discovery documents and keys per issuer
+pub struct OidcClient {
+    http: reqwest::Client,
+    providers: RwLock<HashMap<String, CachedProvider>>,
+}
+
+impl Default for OidcClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OidcClient {
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(HTTP_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            providers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Fetch (or reuse) the provider's discovery document
+    pub async fn discover(&self, issuer: &str) -> Result<OidcDiscovery> {
+        Ok(self.provider(issuer, false).await?.discovery)
+    }
+
+    async fn provider(&self, issuer: &str, force_refresh: bool) -> Result<CachedProvider> {
+        if !force_refresh {
+            let cached = self
+                .providers
+                .read()
+                .map_err(|_| AuthError::Internal("OIDC provider cache lock poisoned".to_string()))?
+                .get(issuer)
+                .filter(|provider| provider.fetched_at.elapsed() < PROVIDER_CACHE_TTL)
+                .cloned();
+            if let Some(provider) = cached {
+                return Ok(provider);
+            }
+        }
+
+        let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+        let discovery: OidcDiscovery = self.get_json(&url).await?;
+
+        // OpenID Connect Discovery section 4.3: the document must be for the issuer we asked about
+        if discovery.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(AuthError::SsoProvider(format!(
+                "Discovery document issuer {} does not match {}",
+                discovery.issuer, issuer
+            )));
+        }
+
+        let jwks: JwkSet = self.get_json(&discovery.jwks_uri).await?;
+        let provider = CachedProvider {
+            discovery,
+            jwks,
+            fetched_at: Instant::now(),
+        };
+
+        self.providers
+            .write()
+            .map_err(|_| AuthError::Internal("OIDC provider cache lock poisoned".to_string()))?
+            .insert(issuer.to_string(), provider.clone());
+        Ok(provider)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AuthError::SsoProvider(format!("Failed to fetch {}: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| AuthError::SsoProvider(format!("Invalid response from {}: {}", url, e)))
+    }
+
+    /// URL to send the user to; `state`, `nonce` and the PKCE challenge come from the caller
+    pub async fn authorization_url(
+        &self,
+        config: &OidcProviderConfig,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String> {
+        let discovery = self.discover(&config.issuer).await?;
+        let scope = config.scopes.join(" ");
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &config.redirect_uri)
+            .append_pair("scope", &scope)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256")
+            .finish();
+
+        let separator = if discovery.authorization_endpoint.contains('?') { '&' } else { '?' };
+        Ok(format!("{}{}{}", discovery.authorization_endpoint, separator, query))
+    }
+
+    /// Redeem the authorization code at the provider's token endpoint and validate the ID token
+    pub async fn exchange_code(
+        &self,
+        config: &OidcProviderConfig,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims> {
+        let discovery = self.discover(&config.issuer).await?;
+
+        let response: TokenEndpointResponse = self
+            .http
+            .post(&discovery.token_endpoint)
+            .basic_auth(&config.client_id, Some(&config.client_secret))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", config.redirect_uri.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AuthError::SsoProvider(format!("Token exchange failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AuthError::SsoProvider(format!("Invalid token response: {}", e)))?;
+
+        let id_token = response
+            .id_token
+            .ok_or_else(|| AuthError::SsoProvider("Token response has no id_token".to_string()))?;
+        self.validate_id_token(config, &id_token, nonce).await
+    }
+
+    /// Validate signature, issuer, audience, expiry and nonce of an ID token
+    pub async fn validate_id_token(
+        &self,
+        config: &OidcProviderConfig,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims> {
+        let invalid = |reason: String| AuthError::InvalidToken(format!("Invalid ID token: {}", reason));
+
+        let header = decode_header(id_token).map_err(|e| invalid(e.to_string()))?;
+        if !ALLOWED_ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+            return Err(invalid(format!("algorithm {:?} is not allowed", header.alg)));
+        }
+        let kid = header.kid.ok_or_else(|| invalid("missing kid".to_string()))?;
+
+        let mut provider = self.provider(&config.issuer, false).await?;
+        if provider.jwks.find(&kid).is_none() {
+            // The provider may have rotated its keys since we cached them
+            provider = self.provider(&config.issuer, true).await?;
+        }
+        let jwk = provider
+            .jwks
+            .find(&kid)
+            .ok_or_else(|| invalid(format!("unknown kid {}", kid)))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| invalid(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.discovery.issuer]);
+        validation.set_audience(&[&config.client_id]);
+        validation.leeway = ID_TOKEN_LEEWAY_SECONDS;
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| invalid(e.to_string()))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid("nonce mismatch".to_string()));
+        }
+
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::keys::{KeySet, SigningKey};
+    use jsonwebtoken::{encode, Header};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A local identity provider serving discovery, keys and a token endpoint whose ID token carries `nonce`
+    async fn mock_idp(key: &SigningKey, nonce: &str) -> MockServer {
+        let server = MockServer::start().await;
+        let issuer = server.uri();
+
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "issuer": issuer,
+                "authorization_endpoint": format!("{}/authorize", issuer),
+                "token_endpoint": format!("{}/token", issuer),
+                "jwks_uri": format!("{}/jwks", issuer),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(KeySet::with_active(key.clone()).jwks()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "idp-access-token",
+                "token_type": "Bearer",
+                "id_token": sign_id_token(key, &issuer, "olympus-rp", nonce),
+            })))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn test_config(issuer: &str) -> OidcProviderConfig {
+        OidcProviderConfig {
+            issuer: issuer.to_string(),
+            client_id: "olympus-rp".to_string(),
+            client_secret: "rp-secret".to_string(),
+            redirect_uri: "https://app.olympuscloud.io/sso/callback".to_string(),
+            scopes: default_scopes(),
+            sso_required: false,
+            jit_provisioning: true,
+            allowed_email_domains: vec!["example.com".to_string()],
+            default_roles: default_roles(),
+        }
+    }
+
+    fn sign_id_token(key: &SigningKey, issuer: &str, audience: &str, nonce: &str) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+
+        encode(
+            &header,
+            &serde_json::json!({
+                "iss": issuer,
+                "aud": audience,
+                "sub": "idp-user-42",
+                "email": "chef@example.com",
+                "email_verified": true,
+                "given_name": "Jamie",
+                "family_name": "Chef",
+                "nonce": nonce,
+                "iat": now,
+                "exp": now + 300,
+            }),
+            key.encoding_key(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_code_exchange_validates_id_token() {
+        let key = SigningKey::generate_ed25519().unwrap();
+        let idp = mock_idp(&key, "nonce-1").await;
+        let config = test_config(&idp.uri());
+        let client = OidcClient::new();
+
+        let url = client.authorization_url(&config, "state-1", "nonce-1", "challenge").await.unwrap();
+        assert!(url.starts_with(&format!("{}/authorize?", idp.uri())));
+        assert!(url.contains("code_challenge_method=S256"));
+
+        let claims = client.exchange_code(&config, "idp-code", "verifier", "nonce-1").await.unwrap();
+        assert_eq!(claims.sub, "idp-user-42");
+        assert_eq!(claims.email.as_deref(), Some("chef@example.com"));
+
+        // A replayed ID token from another login attempt carries a different nonce
+        assert!(client.exchange_code(&config, "idp-code", "verifier", "nonce-2").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_id_token_rejects_wrong_nonce_and_audience() {
+        let key = SigningKey::generate_ed25519().unwrap();
+        let idp = mock_idp(&key, "nonce-1").await;
+        let config = test_config(&idp.uri());
+        let client = OidcClient::new();
+
+        let token = sign_id_token(&key, &idp.uri(), "olympus-rp", "nonce-1");
+        assert!(client.validate_id_token(&config, &token, "nonce-1").await.is_ok());
+        assert!(client.validate_id_token(&config, &token, "nonce-2").await.is_err());
+
+        let other_audience = sign_id_token(&key, &idp.uri(), "someone-else", "nonce-1");
+        assert!(client.validate_id_token(&config, &other_audience, "nonce-1").await.is_err());
+
+        // Keys the provider does not publish are never trusted
+        let forged = sign_id_token(&SigningKey::generate_ed25519().unwrap(), &idp.uri(), "olympus-rp", "nonce-1");
+        assert!(client.validate_id_token(&config, &forged, "nonce-1").await.is_err());
+    }
+
+    #[test]
+    fn test_email_domain_allowlist() {
+        let mut config = test_config("https://idp.example.com");
+        assert!(config.email_domain_allowed("chef@example.com"));
+        assert!(config.email_domain_allowed("chef@EXAMPLE.com"));
+        assert!(!config.email_domain_allowed("chef@evil.com"));
+
+        config.allowed_email_domains.clear();
+        assert!(config.email_domain_allowed("chef@evil.com"));
+    }
+}
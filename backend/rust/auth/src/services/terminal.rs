@@ -0,0 +1,137 @@
+// ============================================================================
+// OLYMPUS CLOUD - POS TERMINAL QUICK-SWITCH HELPERS
+// ============================================================================
+// Module: auth/src/services/terminal.rs
+// Description: Location scoping, lockout and audit events for PIN switches on shared terminals
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use chrono::{Duration, Utc};
+use olympus_shared::events::{
+    AuthenticationFailureReason, AuthenticationResult, ClientInfo, SecurityContext,
+    UserAuthenticationAttemptEvent,
+};
+use olympus_shared::models::permission::{PermissionEffect, PermissionSet};
+use olympus_shared::types::PermissionScope;
+use uuid::Uuid;
+use crate::models::PosTerminal;
+
+/// Header carrying the terminal session token on PIN switches
+pub const TERMINAL_TOKEN_HEADER: &str = "x-terminal-token";
+/// Header carrying the device the terminal session was registered to
+pub const DEVICE_ID_HEADER: &str = "x-device-id";
+
+/// Wrong PINs a terminal accepts, across all users, before it locks
+pub const MAX_PIN_ATTEMPTS: i32 = 5;
+/// How long a terminal stays locked after too many wrong PINs
+pub const PIN_LOCKOUT_MINUTES: i64 = 5;
+
+/// Narrow a user's grants to a terminal's location. Tenant-wide and global allows become
+/// location grants, grants for other locations are dropped, and denies are kept as they are.
+pub fn location_scoped_permissions(permissions: &[String], location_id: Uuid) -> Vec<String> {
+    PermissionSet::from_strings(permissions)
+        .grants()
+        .iter()
+        .filter(|grant| grant.effect == PermissionEffect::Deny || grant.applies_at(Some(location_id)))
+        .map(|grant| {
+            let mut grant = grant.clone();
+            if grant.effect == PermissionEffect::Allow
+                && matches!(grant.scope, PermissionScope::Tenant | PermissionScope::Global)
+            {
+                grant.scope = PermissionScope::Location;
+                grant.location_id = Some(location_id);
+            }
+            grant.to_string()
+        })
+        .collect()
+}
+
+/// Count a wrong PIN against the terminal; returns true if this attempt locked it
+pub fn record_failed_pin(terminal: &mut PosTerminal) -> bool {
+    terminal.failed_pin_attempts += 1;
+    if terminal.failed_pin_attempts >= MAX_PIN_ATTEMPTS {
+        terminal.failed_pin_attempts = 0;
+        terminal.locked_until = Some(Utc::now() + Duration::minutes(PIN_LOCKOUT_MINUTES));
+        return true;
+    }
+    false
+}
+
+/// Audit record for a PIN switch attempt on a terminal
+pub fn pin_switch_attempt(
+    terminal: &PosTerminal,
+    user_id: Option<Uuid>,
+    email: String,
+    result: AuthenticationResult,
+    failure_reason: Option<AuthenticationFailureReason>,
+    ip_address: String,
+    user_agent: String,
+) -> UserAuthenticationAttemptEvent {
+    let threat_indicators = match &failure_reason {
+        Some(AuthenticationFailureReason::TooManyAttempts) => vec!["pin_lockout".to_string()],
+        Some(_) => vec!["pin_failure".to_string()],
+        None => vec![],
+    };
+
+    UserAuthenticationAttemptEvent {
+        user_id,
+        tenant_id: terminal.tenant_id,
+        email,
+        attempt_result: result,
+        client_info: ClientInfo {
+            ip_address,
+            user_agent: Some(user_agent),
+            device_id: Some(terminal.device_id.clone()),
+            platform: Some("pos".to_string()),
+            app_version: None,
+            geolocation: None,
+        },
+        security_context: SecurityContext {
+            risk_score: if failure_reason.is_some() { 0.5 } else { 0.0 },
+            is_suspicious: matches!(failure_reason, Some(AuthenticationFailureReason::TooManyAttempts)),
+            threat_indicators,
+            previous_login_location: None,
+            device_fingerprint: Some(terminal.device_id.clone()),
+        },
+        failure_reason,
+        mfa_required: false,
+        mfa_completed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_scoped_permissions() {
+        let store = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let grants = vec![
+            "commerce.orders.*".to_string(),
+            "commerce.refunds.create@global".to_string(),
+            format!("commerce.inventory.read@location:{}", other),
+            format!("commerce.restaurant.*@location:{}", store),
+            "commerce.products.read@own".to_string(),
+            "!commerce.orders.export".to_string(),
+        ];
+
+        let scoped = location_scoped_permissions(&grants, store);
+        assert_eq!(
+            scoped,
+            vec![
+                format!("commerce.orders.*@location:{}", store),
+                format!("commerce.refunds.create@location:{}", store),
+                format!("commerce.restaurant.*@location:{}", store),
+                "commerce.products.read@own".to_string(),
+                "!commerce.orders.export".to_string(),
+            ]
+        );
+
+        let set = PermissionSet::from_strings(&scoped);
+        assert!(set.evaluate("commerce.orders.write", Some(store)).is_allowed());
+        assert!(!set.evaluate("commerce.orders.write", Some(other)).is_allowed());
+        assert!(!set.evaluate("commerce.orders.export", Some(store)).is_allowed());
+    }
+}
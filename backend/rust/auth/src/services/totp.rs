@@ -0,0 +1,208 @@
+// ============================================================================
+// OLYMPUS CLOUD - TOTP SERVICE
+// ============================================================================
+// Module: auth/src/services/totp.rs
+// Description: RFC 6238 time-based one-time passwords for multi-factor login
+// Author: Claude Code Agent
+// Date: 2025-01-20
+// ============================================================================
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::error::{AuthError, Result};
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// TOTP generator and verifier (HMAC-SHA1, as supported by common authenticator apps)
+#[derive(Debug, Clone)]
+pub struct TotpService {
+    issuer: String,
+    digits: u32,
+    step: u64,   // seconds
+    skew: u64,   // number of steps accepted on either side of the current one
+}
+
+impl TotpService {
+    /// Create a new TOTP service with the standard 6 digit / 30 second profile
+    pub fn new(issuer: String) -> Self {
+        Self {
+            issuer,
+            digits: 6,
+            step: 30,
+            skew: 1,
+        }
+    }
+
+    /// Generate a new random 160-bit secret, base32 encoded without padding
+    pub fn generate_secret(&self) -> String {
+        let mut bytes = [0u8; 20];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        base32::encode(base32::Alphabet::RFC4648 { padding: false }, &bytes)
+    }
+
+    /// Build the otpauth:// provisioning URI rendered as a QR code by the client
+    pub fn provisioning_uri(&self, secret: &str, account_name: &str) -> String {
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            encode_uri_component(&self.issuer),
+            encode_uri_component(account_name),
+            secret,
+            encode_uri_component(&self.issuer),
+            self.digits,
+            self.step,
+        )
+    }
+
+    /// Verify a code against the current time, allowing for clock skew
+    pub fn verify(&self, secret: &str, code: &str) -> Result<bool> {
+        Ok(self.verify_step(secret, code)?.is_some())
+    }
+
+    /// Verify a code against the current time and return the time step it was generated for,
+    /// so a caller can refuse a code that was already accepted within its window
+    pub fn verify_step(&self, secret: &str, code: &str) -> Result<Option<u64>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| AuthError::Internal(format!("System time error: {}", e)))?
+            .as_secs();
+
+        self.matching_step(secret, code, now)
+    }
+
+    /// Verify a code against an explicit unix timestamp
+    pub fn verify_at(&self, secret: &str, code: &str, timestamp: u64) -> Result<bool> {
+        Ok(self.matching_step(secret, code, timestamp)?.is_some())
+    }
+
+    /// The time step within the skew window whose code matches, if any
+    pub fn matching_step(&self, secret: &str, code: &str, timestamp: u64) -> Result<Option<u64>> {
+        let code = code.trim();
+        if code.len() != self.digits as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let key = decode_secret(secret)?;
+        let counter = timestamp / self.step;
+        let first = counter.saturating_sub(self.skew);
+
+        for candidate in first..=counter + self.skew {
+            let expected = self.generate_code(&key, candidate)?;
+            if constant_time_eq(expected.as_bytes(), code.as_bytes()) {
+                return Ok(Some(candidate));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Generate the code for a base32 secret at a unix timestamp
+    pub fn code_at(&self, secret: &str, timestamp: u64) -> Result<String> {
+        let key = decode_secret(secret)?;
+        self.generate_code(&key, timestamp / self.step)
+    }
+
+    fn generate_code(&self, key: &[u8], counter: u64) -> Result<String> {
+        let mut mac = HmacSha1::new_from_slice(key)
+            .map_err(|e| AuthError::Internal(format!("Invalid TOTP key: {}", e)))?;
+        mac.update(&counter.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        // Dynamic truncation (RFC 4226 section 5.3)
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = ((digest[offset] as u32 & 0x7f) << 24)
+            | ((digest[offset + 1] as u32) << 16)
+            | ((digest[offset + 2] as u32) << 8)
+            | (digest[offset + 3] as u32);
+
+        let value = binary % 10u32.pow(self.digits);
+        Ok(format!("{:0width$}", value, width = self.digits as usize))
+    }
+}
+
+fn decode_secret(secret: &str) -> Result<Vec<u8>> {
+    let normalized: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .collect::<String>()
+        .to_uppercase();
+
+    base32::decode(base32::Alphabet::RFC4648 { padding: false }, &normalized)
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| AuthError::Internal("Invalid TOTP secret encoding".to_string()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn encode_uri_component(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 Appendix B secret "12345678901234567890" in base32
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    fn rfc_service() -> TotpService {
+        TotpService {
+            issuer: "Olympus Cloud".to_string(),
+            digits: 8,
+            step: 30,
+            skew: 1,
+        }
+    }
+
+    #[test]
+    fn test_rfc6238_sha1_vectors() {
+        let service = rfc_service();
+        assert_eq!(service.code_at(RFC_SECRET, 59).unwrap(), "94287082");
+        assert_eq!(service.code_at(RFC_SECRET, 1111111109).unwrap(), "07081804");
+        assert_eq!(service.code_at(RFC_SECRET, 1234567890).unwrap(), "89005924");
+        assert_eq!(service.code_at(RFC_SECRET, 2000000000).unwrap(), "69279037");
+    }
+
+    #[test]
+    fn test_verify_with_skew() {
+        let service = TotpService::new("Olympus Cloud".to_string());
+        let secret = service.generate_secret();
+        let now = 1_700_000_000;
+
+        let code = service.code_at(&secret, now).unwrap();
+        assert!(service.verify_at(&secret, &code, now).unwrap());
+        assert!(service.verify_at(&secret, &code, now + 30).unwrap());
+        assert!(!service.verify_at(&secret, &code, now + 90).unwrap());
+        assert!(!service.verify_at(&secret, "12345", now).unwrap());
+    }
+
+    #[test]
+    fn test_matching_step_is_the_generating_step() {
+        let service = TotpService::new("Olympus Cloud".to_string());
+        let secret = service.generate_secret();
+        let now = 1_700_000_000;
+
+        let code = service.code_at(&secret, now).unwrap();
+        assert_eq!(service.matching_step(&secret, &code, now).unwrap(), Some(now / 30));
+        // Still accepted a step later through the skew window, but reported as the same step
+        assert_eq!(service.matching_step(&secret, &code, now + 30).unwrap(), Some(now / 30));
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        let service = TotpService::new("Olympus Cloud".to_string());
+        let uri = service.provisioning_uri("JBSWY3DPEHPK3PXP", "manager@example.com");
+
+        assert!(uri.starts_with("otpauth://totp/Olympus%20Cloud:manager%40example.com?"));
+        assert!(uri.contains("secret=JBSWY3DPEHPK3PXP"));
+        assert!(uri.contains("issuer=Olympus%20Cloud"));
+    }
+}
@@ -0,0 +1,191 @@
+// ============================================================================
+// OLYMPUS CLOUD - SOFTWARE AUTHENTICATOR
+// ============================================================================
+// Module: auth/src/services/webauthn/authenticator.rs
+// Description: A passkey held in memory, for driving WebAuthn ceremonies in tests
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, Ed25519KeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::models::{AssertionResponse, AttestationResponse, AuthenticationCredential, RegistrationCredential};
+use super::cbor::{self, Value};
+use super::{user_handle, COSE_EDDSA, COSE_ES256};
+
+#[derive(Clone)]
+enum Key {
+    Es256(Vec<u8>),
+    Ed25519(Vec<u8>),
+}
+
+/// A synced passkey for one user, signing with a key generated in memory. Cloning it
+/// copies the key and counter, as cloning a hardware authenticator would.
+#[derive(Clone)]
+pub struct SoftwareAuthenticator {
+    rp_id: String,
+    key: Key,
+    credential_id: Vec<u8>,
+    user_id: Uuid,
+    sign_count: u32,
+    user_verification: bool,
+}
+
+impl SoftwareAuthenticator {
+    pub fn es256(rp_id: &str) -> Self {
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &SystemRandom::new())
+            .expect("key generation");
+        Self::new(rp_id, Key::Es256(pkcs8.as_ref().to_vec()))
+    }
+
+    pub fn ed25519(rp_id: &str) -> Self {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("key generation");
+        Self::new(rp_id, Key::Ed25519(pkcs8.as_ref().to_vec()))
+    }
+
+    fn new(rp_id: &str, key: Key) -> Self {
+        let mut credential_id = vec![0u8; 16];
+        SystemRandom::new().fill(&mut credential_id).expect("random credential id");
+        Self {
+            rp_id: rp_id.to_string(),
+            key,
+            credential_id,
+            user_id: Uuid::new_v4(),
+            sign_count: 0,
+            user_verification: true,
+        }
+    }
+
+    /// Confirm presence only, as a security key without a PIN does
+    pub fn without_user_verification(mut self) -> Self {
+        self.user_verification = false;
+        self
+    }
+
+    pub fn user_id(&self) -> Uuid {
+        self.user_id
+    }
+
+    pub fn credential_id(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.credential_id)
+    }
+
+    /// Answer `navigator.credentials.create()`
+    pub fn register(&self, challenge: &str, origin: &str) -> RegistrationCredential {
+        let mut auth_data = self.authenticator_data(0x40, self.sign_count);
+        auth_data.extend([0u8; 16]);
+        auth_data.extend((self.credential_id.len() as u16).to_be_bytes());
+        auth_data.extend(&self.credential_id);
+        auth_data.extend(cbor::encode(&self.cose_key()));
+
+        let attestation = Value::Map(vec![
+            (Value::Text("fmt".to_string()), Value::Text("none".to_string())),
+            (Value::Text("attStmt".to_string()), Value::Map(vec![])),
+            (Value::Text("authData".to_string()), Value::Bytes(auth_data)),
+        ]);
+
+        RegistrationCredential {
+            id: self.credential_id(),
+            raw_id: self.credential_id(),
+            credential_type: "public-key".to_string(),
+            response: AttestationResponse {
+                client_data_json: client_data("webauthn.create", challenge, origin),
+                attestation_object: URL_SAFE_NO_PAD.encode(cbor::encode(&attestation)),
+                transports: vec!["internal".to_string(), "hybrid".to_string()],
+            },
+        }
+    }
+
+    /// Answer `navigator.credentials.get()`, counting the signature
+    pub fn authenticate(&mut self, challenge: &str, origin: &str) -> AuthenticationCredential {
+        self.sign_count += 1;
+        let auth_data = self.authenticator_data(0, self.sign_count);
+        let client_data_json = client_data("webauthn.get", challenge, origin);
+
+        let client_data_hash = Sha256::digest(URL_SAFE_NO_PAD.decode(&client_data_json).expect("own encoding"));
+        let signature = self.sign(&[auth_data.as_slice(), client_data_hash.as_slice()].concat());
+
+        AuthenticationCredential {
+            id: self.credential_id(),
+            raw_id: self.credential_id(),
+            credential_type: "public-key".to_string(),
+            response: AssertionResponse {
+                client_data_json,
+                authenticator_data: URL_SAFE_NO_PAD.encode(auth_data),
+                signature: URL_SAFE_NO_PAD.encode(signature),
+                user_handle: Some(user_handle(self.user_id)),
+            },
+        }
+    }
+
+    fn authenticator_data(&self, extra_flags: u8, sign_count: u32) -> Vec<u8> {
+        // User present and backup eligible, plus verified when a PIN or biometric is used
+        let mut flags = 0x01 | 0x08 | extra_flags;
+        if self.user_verification {
+            flags |= 0x04;
+        }
+
+        let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend(sign_count.to_be_bytes());
+        data
+    }
+
+    fn cose_key(&self) -> Value {
+        let int = Value::Integer;
+        match &self.key {
+            Key::Es256(pkcs8) => {
+                let point = self.es256_key_pair(pkcs8).public_key().as_ref().to_vec();
+                Value::Map(vec![
+                    (int(1), int(2)),
+                    (int(3), int(COSE_ES256 as i128)),
+                    (int(-1), int(1)),
+                    (int(-2), Value::Bytes(point[1..33].to_vec())),
+                    (int(-3), Value::Bytes(point[33..65].to_vec())),
+                ])
+            }
+            Key::Ed25519(pkcs8) => {
+                let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8).expect("own key");
+                Value::Map(vec![
+                    (int(1), int(1)),
+                    (int(3), int(COSE_EDDSA as i128)),
+                    (int(-1), int(6)),
+                    (int(-2), Value::Bytes(key_pair.public_key().as_ref().to_vec())),
+                ])
+            }
+        }
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match &self.key {
+            Key::Es256(pkcs8) => self
+                .es256_key_pair(pkcs8)
+                .sign(&SystemRandom::new(), message)
+                .expect("signing")
+                .as_ref()
+                .to_vec(),
+            Key::Ed25519(pkcs8) => Ed25519KeyPair::from_pkcs8(pkcs8)
+                .expect("own key")
+                .sign(message)
+                .as_ref()
+                .to_vec(),
+        }
+    }
+
+    fn es256_key_pair(&self, pkcs8: &[u8]) -> EcdsaKeyPair {
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8, &SystemRandom::new()).expect("own key")
+    }
+}
+
+fn client_data(ceremony_type: &str, challenge: &str, origin: &str) -> String {
+    let json = serde_json::json!({
+        "type": ceremony_type,
+        "challenge": challenge,
+        "origin": origin,
+        "crossOrigin": false,
+    });
+    URL_SAFE_NO_PAD.encode(json.to_string())
+}
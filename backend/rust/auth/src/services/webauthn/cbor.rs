@@ -0,0 +1,268 @@
+// ============================================================================
+// OLYMPUS CLOUD - CBOR DECODING FOR WEBAUTHN
+// ============================================================================
+// Module: auth/src/services/webauthn/cbor.rs
+// Description: The subset of CBOR (RFC 8949) used by attestation objects and COSE keys
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use crate::error::{AuthError, Result};
+
+/// Deeper nesting than this never appears in WebAuthn structures
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Major types 0 and 1
+    Integer(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Look up a map entry by text key
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k.as_text() == Some(key)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Look up a map entry by integer key, as COSE keys use
+    pub fn get_int(&self, key: i128) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k.as_integer() == Some(key)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Decode one item from the start of `input`, returning it and the number of bytes it
+/// used; authenticator data has a COSE key followed by other fields
+pub fn decode_prefix(input: &[u8]) -> Result<(Value, usize)> {
+    let mut decoder = Decoder { input, pos: 0 };
+    let value = decoder.value(0)?;
+    Ok((value, decoder.pos))
+}
+
+/// Decode exactly one item
+pub fn decode(input: &[u8]) -> Result<Value> {
+    let (value, used) = decode_prefix(input)?;
+    if used != input.len() {
+        return Err(malformed("trailing bytes"));
+    }
+    Ok(value)
+}
+
+struct Decoder<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.input.len())
+            .ok_or_else(|| malformed("unexpected end of input"))?;
+        let bytes = &self.input[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// The argument that follows an initial byte's additional information
+    fn argument(&mut self, info: u8) -> Result<u64> {
+        Ok(match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().expect("length checked")) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().expect("length checked")) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().expect("length checked")),
+            // Indefinite lengths are not allowed in WebAuthn's canonical CBOR
+            _ => return Err(malformed("unsupported length encoding")),
+        })
+    }
+
+    fn length(&mut self, info: u8) -> Result<usize> {
+        let len = self.argument(info)?;
+        // Every item takes at least one byte, so a longer count cannot be valid
+        if len > (self.input.len() - self.pos) as u64 {
+            return Err(malformed("length exceeds input"));
+        }
+        Ok(len as usize)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(malformed("nesting too deep"));
+        }
+
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        match major {
+            0 => Ok(Value::Integer(self.argument(info)? as i128)),
+            1 => Ok(Value::Integer(-1 - self.argument(info)? as i128)),
+            2 => {
+                let len = self.length(info)?;
+                Ok(Value::Bytes(self.take(len)?.to_vec()))
+            }
+            3 => {
+                let len = self.length(info)?;
+                let text = std::str::from_utf8(self.take(len)?).map_err(|_| malformed("invalid UTF-8"))?;
+                Ok(Value::Text(text.to_string()))
+            }
+            4 => {
+                let len = self.length(info)?;
+                let items = (0..len).map(|_| self.value(depth + 1)).collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(items))
+            }
+            5 => {
+                let len = self.length(info)?;
+                let mut entries = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.value(depth + 1)?;
+                    let value = self.value(depth + 1)?;
+                    entries.push((key, value));
+                }
+                Ok(Value::Map(entries))
+            }
+            7 => match info {
+                20 => Ok(Value::Bool(false)),
+                21 => Ok(Value::Bool(true)),
+                22 => Ok(Value::Null),
+                _ => Err(malformed("unsupported simple value")),
+            },
+            _ => Err(malformed("unsupported major type")),
+        }
+    }
+}
+
+fn malformed(reason: &str) -> AuthError {
+    AuthError::InvalidPasskey(format!("Malformed CBOR: {}", reason))
+}
+
+/// Encoding, for the software authenticator in tests
+#[cfg(test)]
+pub fn encode(value: &Value) -> Vec<u8> {
+    fn head(out: &mut Vec<u8>, major: u8, n: u64) {
+        let major = major << 5;
+        match n {
+            0..=23 => out.push(major | n as u8),
+            24..=0xff => out.extend([major | 24, n as u8]),
+            0x100..=0xffff => {
+                out.push(major | 25);
+                out.extend((n as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(major | 26);
+                out.extend((n as u32).to_be_bytes());
+            }
+            _ => {
+                out.push(major | 27);
+                out.extend(n.to_be_bytes());
+            }
+        }
+    }
+
+    fn write(out: &mut Vec<u8>, value: &Value) {
+        match value {
+            Value::Integer(n) if *n >= 0 => head(out, 0, *n as u64),
+            Value::Integer(n) => head(out, 1, (-1 - *n) as u64),
+            Value::Bytes(bytes) => {
+                head(out, 2, bytes.len() as u64);
+                out.extend(bytes);
+            }
+            Value::Text(text) => {
+                head(out, 3, text.len() as u64);
+                out.extend(text.as_bytes());
+            }
+            Value::Array(items) => {
+                head(out, 4, items.len() as u64);
+                items.iter().for_each(|item| write(out, item));
+            }
+            Value::Map(entries) => {
+                head(out, 5, entries.len() as u64);
+                for (key, value) in entries {
+                    write(out, key);
+                    write(out, value);
+                }
+            }
+            Value::Bool(b) => out.push(0xf4 + *b as u8),
+            Value::Null => out.push(0xf6),
+        }
+    }
+
+    let mut out = Vec::new();
+    write(&mut out, value);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_known_bytes() {
+        // {1: 2, 3: -7, -1: h'0102', "fmt": "none", "ok": true, "x": [null]}
+        let bytes = [
+            0xa6, 0x01, 0x02, 0x03, 0x26, 0x20, 0x42, 0x01, 0x02, 0x63, b'f', b'm', b't', 0x64, b'n', b'o',
+            b'n', b'e', 0x62, b'o', b'k', 0xf5, 0x61, b'x', 0x81, 0xf6,
+        ];
+        let value = decode(&bytes).unwrap();
+
+        assert_eq!(value.get_int(1).and_then(Value::as_integer), Some(2));
+        assert_eq!(value.get_int(3).and_then(Value::as_integer), Some(-7));
+        assert_eq!(value.get_int(-1).and_then(Value::as_bytes), Some(&[1u8, 2][..]));
+        assert_eq!(value.get("fmt").and_then(Value::as_text), Some("none"));
+        assert_eq!(value.get("ok"), Some(&Value::Bool(true)));
+        assert_eq!(value.get("x"), Some(&Value::Array(vec![Value::Null])));
+        assert_eq!(encode(&value), bytes);
+    }
+
+    #[test]
+    fn test_prefix_reports_length() {
+        let mut bytes = encode(&Value::Map(vec![(Value::Integer(1), Value::Integer(-257))]));
+        let len = bytes.len();
+        bytes.extend([0xde, 0xad]);
+
+        let (_, used) = decode_prefix(&bytes).unwrap();
+        assert_eq!(used, len);
+        assert!(decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_rejects_truncated_and_oversized() {
+        assert!(decode(&[0x42, 0x01]).is_err());
+        // Byte string claiming 2^32 bytes
+        assert!(decode(&[0x5a, 0xff, 0xff, 0xff, 0xff]).is_err());
+        // Indefinite-length map
+        assert!(decode(&[0xbf, 0xff]).is_err());
+        // Deep nesting
+        assert!(decode(&[0x81; 64]).is_err());
+    }
+}
@@ -0,0 +1,693 @@
+// ============================================================================
+// OLYMPUS CLOUD - WEBAUTHN RELYING PARTY
+// ============================================================================
+// Module: auth/src/services/webauthn/mod.rs
+// Description: Passkey registration and assertion ceremonies (WebAuthn Level 2)
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+pub mod cbor;
+#[cfg(test)]
+pub(crate) mod authenticator;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use olympus_shared::config::WebAuthnConfig;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{
+    RsaPublicKeyComponents, UnparsedPublicKey, ECDSA_P256_SHA256_ASN1, ED25519, RSA_PKCS1_2048_8192_SHA256,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::error::{AuthError, Result};
+use crate::models::{
+    AuthenticationCredential, PasskeyAuthenticatorSelection, PasskeyCreationOptions,
+    PasskeyCredentialDescriptor, PasskeyCredentialParameters, PasskeyRelyingParty, PasskeyRequestOptions,
+    PasskeyUser, RegistrationCredential, WebAuthnCredential,
+};
+use cbor::Value;
+
+/// COSE algorithm identifiers accepted for new passkeys, in order of preference
+pub const COSE_ES256: i64 = -7;
+pub const COSE_EDDSA: i64 = -8;
+pub const COSE_RS256: i64 = -257;
+const SUPPORTED_ALGORITHMS: [i64; 3] = [COSE_ES256, COSE_EDDSA, COSE_RS256];
+
+/// Authenticator data flags
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_BACKUP_ELIGIBLE: u8 = 0x08;
+const FLAG_BACKED_UP: u8 = 0x10;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+const FLAG_EXTENSION_DATA: u8 = 0x80;
+
+const CHALLENGE_BYTES: usize = 32;
+const MAX_CREDENTIAL_ID_BYTES: usize = 1023;
+
+pub const CEREMONY_REGISTRATION: &str = "registration";
+pub const CEREMONY_AUTHENTICATION: &str = "authentication";
+pub const CEREMONY_MFA: &str = "mfa";
+
+/// The parsed `authenticatorData` structure
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+    /// Present on registration only
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestedCredential {
+    pub aaguid: Uuid,
+    pub credential_id: Vec<u8>,
+    /// The COSE_Key, kept as encoded for storage
+    pub public_key: Vec<u8>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < 37 {
+            return Err(invalid("authenticator data is too short"));
+        }
+
+        let rp_id_hash: [u8; 32] = data[..32].try_into().expect("length checked");
+        let flags = data[32];
+        let sign_count = u32::from_be_bytes(data[33..37].try_into().expect("length checked"));
+        let mut rest = &data[37..];
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+            if rest.len() < 18 {
+                return Err(invalid("attested credential data is too short"));
+            }
+            let aaguid = Uuid::from_bytes(rest[..16].try_into().expect("length checked"));
+            let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            rest = &rest[18..];
+            if id_len > MAX_CREDENTIAL_ID_BYTES || rest.len() < id_len {
+                return Err(invalid("credential id length is invalid"));
+            }
+            let credential_id = rest[..id_len].to_vec();
+            rest = &rest[id_len..];
+
+            let (_, used) = cbor::decode_prefix(rest)?;
+            let public_key = rest[..used].to_vec();
+            rest = &rest[used..];
+
+            Some(AttestedCredential { aaguid, credential_id, public_key })
+        } else {
+            None
+        };
+
+        // Only extension outputs may follow, and only when flagged
+        if !rest.is_empty() && flags & FLAG_EXTENSION_DATA == 0 {
+            return Err(invalid("unexpected trailing authenticator data"));
+        }
+
+        Ok(Self { rp_id_hash, flags, sign_count, attested_credential })
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_USER_PRESENT != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_USER_VERIFIED != 0
+    }
+
+    pub fn backup_eligible(&self) -> bool {
+        self.flags & FLAG_BACKUP_ELIGIBLE != 0
+    }
+
+    pub fn backed_up(&self) -> bool {
+        self.flags & FLAG_BACKED_UP != 0
+    }
+}
+
+/// The fields of `clientDataJSON` that are checked
+#[derive(Debug, Deserialize)]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    ceremony_type: String,
+    challenge: String,
+    origin: String,
+    #[serde(default, rename = "crossOrigin")]
+    cross_origin: bool,
+}
+
+/// A public key from a COSE_Key map
+#[derive(Debug, Clone, PartialEq)]
+pub enum CosePublicKey {
+    /// Uncompressed P-256 point, `0x04 || x || y`
+    Es256(Vec<u8>),
+    Ed25519(Vec<u8>),
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl CosePublicKey {
+    pub fn parse(cose_key: &[u8]) -> Result<Self> {
+        let key = cbor::decode(cose_key)?;
+        let int = |label| key.get_int(label).and_then(Value::as_integer);
+        let bytes = |label| {
+            key.get_int(label)
+                .and_then(Value::as_bytes)
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| invalid("public key is missing a parameter"))
+        };
+
+        // kty (1), alg (3), then type-specific parameters at negative labels
+        match (int(1), int(3).map(|alg| alg as i64)) {
+            (Some(2), Some(COSE_ES256)) => {
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                if int(-1) != Some(1) || x.len() != 32 || y.len() != 32 {
+                    return Err(invalid("only uncompressed P-256 keys are supported for ES256"));
+                }
+                Ok(CosePublicKey::Es256([&[0x04][..], &x, &y].concat()))
+            }
+            (Some(1), Some(COSE_EDDSA)) => {
+                let x = bytes(-2)?;
+                if int(-1) != Some(6) || x.len() != 32 {
+                    return Err(invalid("only Ed25519 keys are supported for EdDSA"));
+                }
+                Ok(CosePublicKey::Ed25519(x))
+            }
+            (Some(3), Some(COSE_RS256)) => Ok(CosePublicKey::Rs256 { n: bytes(-1)?, e: bytes(-2)? }),
+            _ => Err(invalid("unsupported public key algorithm")),
+        }
+    }
+
+    pub fn algorithm(&self) -> i64 {
+        match self {
+            CosePublicKey::Es256(_) => COSE_ES256,
+            CosePublicKey::Ed25519(_) => COSE_EDDSA,
+            CosePublicKey::Rs256 { .. } => COSE_RS256,
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        let verified = match self {
+            CosePublicKey::Es256(point) => {
+                UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, point).verify(message, signature)
+            }
+            CosePublicKey::Ed25519(key) => {
+                UnparsedPublicKey::new(&ED25519, key).verify(message, signature)
+            }
+            CosePublicKey::Rs256 { n, e } => RsaPublicKeyComponents { n, e }
+                .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature),
+        };
+        verified.map_err(|_| invalid("signature verification failed"))
+    }
+}
+
+/// What a successful registration ceremony yields, ready to be stored
+#[derive(Debug, Clone)]
+pub struct VerifiedRegistration {
+    /// Base64url
+    pub credential_id: String,
+    pub public_key: Vec<u8>,
+    pub algorithm: i64,
+    pub sign_count: u32,
+    pub aaguid: Uuid,
+    pub user_verified: bool,
+    pub backup_eligible: bool,
+    pub backed_up: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VerifiedAssertion {
+    pub sign_count: u32,
+    pub user_verified: bool,
+    pub backed_up: bool,
+}
+
+/// The relying party: builds ceremony options and verifies what the browser sends back
+pub struct RelyingParty {
+    config: WebAuthnConfig,
+    rp_id_hash: [u8; 32],
+    rng: SystemRandom,
+}
+
+impl Default for RelyingParty {
+    fn default() -> Self {
+        Self::new(WebAuthnConfig::default())
+    }
+}
+
+impl RelyingParty {
+    fn new(config: WebAuthnConfig) -> Self {
+        Self {
+            rp_id_hash: Sha256::digest(config.rp_id.as_bytes()).into(),
+            config,
+            rng: SystemRandom::new(),
+        }
+    }
+
+    pub fn from_config(config: &WebAuthnConfig) -> Result<Self> {
+        if !matches!(config.user_verification.as_str(), "required" | "preferred" | "discouraged") {
+            return Err(AuthError::Validation(format!(
+                "Unknown WebAuthn user verification requirement {}",
+                config.user_verification
+            )));
+        }
+        if config.origins.is_empty() {
+            return Err(AuthError::Validation("At least one WebAuthn origin is required".to_string()));
+        }
+        Ok(Self::new(config.clone()))
+    }
+
+    pub fn challenge_timeout_seconds(&self) -> i64 {
+        self.config.challenge_timeout_seconds
+    }
+
+    pub fn rejects_sign_count_regression(&self) -> bool {
+        self.config.reject_sign_count_regression
+    }
+
+    pub fn generate_challenge(&self) -> Result<String> {
+        let mut challenge = [0u8; CHALLENGE_BYTES];
+        self.rng
+            .fill(&mut challenge)
+            .map_err(|_| AuthError::Internal("Failed to generate WebAuthn challenge".to_string()))?;
+        Ok(URL_SAFE_NO_PAD.encode(challenge))
+    }
+
+    pub fn creation_options(
+        &self,
+        challenge: String,
+        user_id: Uuid,
+        email: &str,
+        display_name: &str,
+        existing: &[WebAuthnCredential],
+    ) -> PasskeyCreationOptions {
+        PasskeyCreationOptions {
+            challenge,
+            rp: PasskeyRelyingParty {
+                id: self.config.rp_id.clone(),
+                name: self.config.rp_name.clone(),
+            },
+            user: PasskeyUser {
+                id: user_handle(user_id),
+                name: email.to_string(),
+                display_name: display_name.to_string(),
+            },
+            pub_key_cred_params: SUPPORTED_ALGORITHMS
+                .iter()
+                .map(|alg| PasskeyCredentialParameters {
+                    credential_type: "public-key".to_string(),
+                    alg: *alg,
+                })
+                .collect(),
+            timeout: self.timeout_millis(),
+            // Stops the same authenticator from being registered twice
+            exclude_credentials: existing.iter().map(descriptor).collect(),
+            authenticator_selection: PasskeyAuthenticatorSelection {
+                resident_key: "preferred".to_string(),
+                require_resident_key: false,
+                user_verification: self.config.user_verification.clone(),
+            },
+            attestation: "none".to_string(),
+        }
+    }
+
+    /// Options for an assertion; with no credentials listed the browser offers any
+    /// discoverable passkey it holds for the relying party
+    pub fn request_options(&self, challenge: String, allowed: &[WebAuthnCredential]) -> PasskeyRequestOptions {
+        PasskeyRequestOptions {
+            challenge,
+            timeout: self.timeout_millis(),
+            rp_id: self.config.rp_id.clone(),
+            allow_credentials: allowed.iter().map(descriptor).collect(),
+            user_verification: self.config.user_verification.clone(),
+        }
+    }
+
+    /// The challenge a response claims to answer, for finding the stored ceremony; the
+    /// response is only trusted once `verify_registration` or `verify_assertion` passes
+    pub fn client_challenge(&self, client_data_json: &str) -> Result<String> {
+        Ok(parse_client_data(&decode_field("clientDataJSON", client_data_json)?)?.challenge)
+    }
+
+    pub fn verify_registration(&self, challenge: &str, credential: &RegistrationCredential) -> Result<VerifiedRegistration> {
+        check_credential_type(&credential.credential_type)?;
+
+        let client_data_json = decode_field("clientDataJSON", &credential.response.client_data_json)?;
+        self.check_client_data(&client_data_json, "webauthn.create", challenge)?;
+
+        let attestation = cbor::decode(&decode_field("attestationObject", &credential.response.attestation_object)?)?;
+        let auth_data_bytes = attestation
+            .get("authData")
+            .and_then(Value::as_bytes)
+            .ok_or_else(|| invalid("attestation object has no authenticator data"))?;
+        // Attestation is not requested, so a statement in any other format carries nothing
+        // that would be acted on and is not checked
+        if attestation.get("fmt").and_then(Value::as_text).is_none() {
+            return Err(invalid("attestation object has no format"));
+        }
+
+        let auth_data = AuthenticatorData::parse(auth_data_bytes)?;
+        self.check_authenticator_data(&auth_data)?;
+
+        let attested = auth_data
+            .attested_credential
+            .as_ref()
+            .ok_or_else(|| invalid("no credential was created"))?;
+        let credential_id = URL_SAFE_NO_PAD.encode(&attested.credential_id);
+        if decode_field("rawId", &credential.raw_id)? != attested.credential_id {
+            return Err(invalid("credential id does not match the authenticator data"));
+        }
+
+        let public_key = CosePublicKey::parse(&attested.public_key)?;
+
+        Ok(VerifiedRegistration {
+            credential_id,
+            public_key: attested.public_key.clone(),
+            algorithm: public_key.algorithm(),
+            sign_count: auth_data.sign_count,
+            aaguid: attested.aaguid,
+            user_verified: auth_data.user_verified(),
+            backup_eligible: auth_data.backup_eligible(),
+            backed_up: auth_data.backed_up(),
+        })
+    }
+
+    /// Verify an assertion made with `stored`. The caller compares the returned signature
+    /// counter with the stored one.
+    pub fn verify_assertion(
+        &self,
+        challenge: &str,
+        credential: &AuthenticationCredential,
+        stored: &WebAuthnCredential,
+    ) -> Result<VerifiedAssertion> {
+        check_credential_type(&credential.credential_type)?;
+
+        if decode_field("rawId", &credential.raw_id)? != decode_field("credentialId", &stored.credential_id)? {
+            return Err(invalid("assertion was made with a different passkey"));
+        }
+        if let Some(handle) = &credential.response.user_handle {
+            if decode_field("userHandle", handle)? != stored.user_id.as_bytes() {
+                return Err(invalid("passkey belongs to a different user"));
+            }
+        }
+
+        let client_data_json = decode_field("clientDataJSON", &credential.response.client_data_json)?;
+        self.check_client_data(&client_data_json, "webauthn.get", challenge)?;
+
+        let auth_data_bytes = decode_field("authenticatorData", &credential.response.authenticator_data)?;
+        let auth_data = AuthenticatorData::parse(&auth_data_bytes)?;
+        self.check_authenticator_data(&auth_data)?;
+
+        // The signature covers the authenticator data and a hash of the client data
+        let signed = [auth_data_bytes.as_slice(), Sha256::digest(&client_data_json).as_slice()].concat();
+        let signature = decode_field("signature", &credential.response.signature)?;
+        CosePublicKey::parse(&stored.public_key)?.verify(&signed, &signature)?;
+
+        Ok(VerifiedAssertion {
+            sign_count: auth_data.sign_count,
+            user_verified: auth_data.user_verified(),
+            backed_up: auth_data.backed_up(),
+        })
+    }
+
+    fn check_client_data(&self, client_data_json: &[u8], ceremony_type: &str, challenge: &str) -> Result<()> {
+        let client_data = parse_client_data(client_data_json)?;
+
+        if client_data.ceremony_type != ceremony_type {
+            return Err(invalid("wrong ceremony type"));
+        }
+        if client_data.challenge != challenge {
+            return Err(invalid("challenge does not match"));
+        }
+        if !self.config.origins.contains(&client_data.origin) {
+            return Err(invalid("origin is not allowed"));
+        }
+        // Passkeys are only used from our own pages, never from inside another site's frame
+        if client_data.cross_origin {
+            return Err(invalid("cross-origin requests are not allowed"));
+        }
+        Ok(())
+    }
+
+    fn check_authenticator_data(&self, auth_data: &AuthenticatorData) -> Result<()> {
+        if auth_data.rp_id_hash != self.rp_id_hash {
+            return Err(invalid("passkey is for a different relying party"));
+        }
+        if !auth_data.user_present() {
+            return Err(invalid("user presence was not confirmed"));
+        }
+        if self.config.user_verification == "required" && !auth_data.user_verified() {
+            return Err(invalid("user verification is required"));
+        }
+        Ok(())
+    }
+
+    fn timeout_millis(&self) -> u64 {
+        self.config.challenge_timeout_seconds.max(0) as u64 * 1000
+    }
+}
+
+/// A signature counter that failed to increase. Authenticators without a counter always
+/// report zero, which is not a regression.
+pub fn sign_count_regressed(stored: u32, received: u32) -> bool {
+    (stored != 0 || received != 0) && received <= stored
+}
+
+/// The WebAuthn user handle for a user: their id's bytes, base64url
+pub fn user_handle(user_id: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(user_id.as_bytes())
+}
+
+fn descriptor(credential: &WebAuthnCredential) -> PasskeyCredentialDescriptor {
+    PasskeyCredentialDescriptor {
+        credential_type: "public-key".to_string(),
+        id: credential.credential_id.clone(),
+        transports: credential.transports.clone(),
+    }
+}
+
+fn parse_client_data(client_data_json: &[u8]) -> Result<CollectedClientData> {
+    serde_json::from_slice(client_data_json).map_err(|_| invalid("client data is not valid JSON"))
+}
+
+fn check_credential_type(credential_type: &str) -> Result<()> {
+    if credential_type != "public-key" {
+        return Err(invalid("credential type must be public-key"));
+    }
+    Ok(())
+}
+
+/// Browsers send base64url without padding; some libraries add it
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| invalid(&format!("{} is not base64url", name)))
+}
+
+fn invalid(reason: &str) -> AuthError {
+    AuthError::InvalidPasskey(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::authenticator::SoftwareAuthenticator;
+    use chrono::Utc;
+
+    const ORIGIN: &str = "https://app.olympuscloud.io";
+
+    fn relying_party() -> RelyingParty {
+        RelyingParty::default()
+    }
+
+    fn register(rp: &RelyingParty, authenticator: &SoftwareAuthenticator) -> WebAuthnCredential {
+        let challenge = rp.generate_challenge().unwrap();
+        let credential = authenticator.register(&challenge, ORIGIN);
+        assert_eq!(rp.client_challenge(&credential.response.client_data_json).unwrap(), challenge);
+
+        let verified = rp.verify_registration(&challenge, &credential).unwrap();
+        WebAuthnCredential {
+            id: Uuid::new_v4(),
+            user_id: authenticator.user_id(),
+            tenant_id: Uuid::new_v4(),
+            credential_id: verified.credential_id,
+            public_key: verified.public_key,
+            algorithm: verified.algorithm as i32,
+            sign_count: verified.sign_count as i64,
+            transports: credential.response.transports,
+            aaguid: verified.aaguid,
+            name: "Test key".to_string(),
+            backup_eligible: verified.backup_eligible,
+            backed_up: verified.backed_up,
+            created_at: Utc::now(),
+            last_used_at: None,
+        }
+    }
+
+    #[test]
+    fn test_registration_and_assertion_es256() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::es256("olympuscloud.io");
+        let stored = register(&rp, &authenticator);
+        assert_eq!(stored.algorithm as i64, COSE_ES256);
+        assert_eq!(stored.credential_id, authenticator.credential_id());
+        assert!(stored.backup_eligible);
+
+        let challenge = rp.generate_challenge().unwrap();
+        let assertion = authenticator.authenticate(&challenge, ORIGIN);
+        let verified = rp.verify_assertion(&challenge, &assertion, &stored).unwrap();
+        assert_eq!(verified.sign_count, 1);
+        assert!(verified.user_verified);
+    }
+
+    #[test]
+    fn test_registration_and_assertion_ed25519() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::ed25519("olympuscloud.io");
+        let stored = register(&rp, &authenticator);
+        assert_eq!(stored.algorithm as i64, COSE_EDDSA);
+
+        let challenge = rp.generate_challenge().unwrap();
+        let assertion = authenticator.authenticate(&challenge, ORIGIN);
+        assert!(rp.verify_assertion(&challenge, &assertion, &stored).is_ok());
+    }
+
+    #[test]
+    fn test_registration_rejects_wrong_origin_challenge_and_rp() {
+        let rp = relying_party();
+        let authenticator = SoftwareAuthenticator::es256("olympuscloud.io");
+        let challenge = rp.generate_challenge().unwrap();
+
+        let phished = authenticator.register(&challenge, "https://olympuscloud.io.evil.example");
+        assert!(rp.verify_registration(&challenge, &phished).is_err());
+
+        let replayed = authenticator.register(&rp.generate_challenge().unwrap(), ORIGIN);
+        assert!(rp.verify_registration(&challenge, &replayed).is_err());
+
+        let other_rp = SoftwareAuthenticator::es256("evil.example").register(&challenge, ORIGIN);
+        assert!(rp.verify_registration(&challenge, &other_rp).is_err());
+    }
+
+    #[test]
+    fn test_assertion_rejects_tampering() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::es256("olympuscloud.io");
+        let stored = register(&rp, &authenticator);
+        let challenge = rp.generate_challenge().unwrap();
+
+        let mut forged = authenticator.authenticate(&challenge, ORIGIN);
+        let mut signature = URL_SAFE_NO_PAD.decode(&forged.response.signature).unwrap();
+        let last = signature.len() - 1;
+        signature[last] ^= 0x01;
+        forged.response.signature = URL_SAFE_NO_PAD.encode(signature);
+        assert!(rp.verify_assertion(&challenge, &forged, &stored).is_err());
+
+        // A genuine signature, but for a different challenge
+        let stale = authenticator.authenticate(&rp.generate_challenge().unwrap(), ORIGIN);
+        assert!(rp.verify_assertion(&challenge, &stale, &stored).is_err());
+
+        let mut other_user = authenticator.authenticate(&challenge, ORIGIN);
+        other_user.response.user_handle = Some(user_handle(Uuid::new_v4()));
+        assert!(rp.verify_assertion(&challenge, &other_user, &stored).is_err());
+
+        let mut other_key = SoftwareAuthenticator::es256("olympuscloud.io");
+        let wrong_key = other_key.authenticate(&challenge, ORIGIN);
+        assert!(rp.verify_assertion(&challenge, &wrong_key, &stored).is_err());
+    }
+
+    #[test]
+    fn test_user_verification_required() {
+        let rp = RelyingParty::from_config(&WebAuthnConfig {
+            user_verification: "required".to_string(),
+            ..WebAuthnConfig::default()
+        })
+        .unwrap();
+        let authenticator = SoftwareAuthenticator::es256("olympuscloud.io").without_user_verification();
+        let challenge = rp.generate_challenge().unwrap();
+
+        let credential = authenticator.register(&challenge, ORIGIN);
+        assert!(matches!(
+            rp.verify_registration(&challenge, &credential),
+            Err(AuthError::InvalidPasskey(_))
+        ));
+        assert!(relying_party().verify_registration(&challenge, &credential).is_ok());
+    }
+
+    #[test]
+    fn test_cloned_authenticator_counter() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::es256("olympuscloud.io");
+        let mut stored = register(&rp, &authenticator);
+        let mut clone = authenticator.clone();
+
+        for _ in 0..3 {
+            let challenge = rp.generate_challenge().unwrap();
+            let verified = rp
+                .verify_assertion(&challenge, &authenticator.authenticate(&challenge, ORIGIN), &stored)
+                .unwrap();
+            assert!(!sign_count_regressed(stored.sign_count as u32, verified.sign_count));
+            stored.sign_count = verified.sign_count as i64;
+        }
+
+        // The clone's signature is valid, but its counter lags behind
+        let challenge = rp.generate_challenge().unwrap();
+        let verified = rp.verify_assertion(&challenge, &clone.authenticate(&challenge, ORIGIN), &stored).unwrap();
+        assert!(sign_count_regressed(stored.sign_count as u32, verified.sign_count));
+    }
+
+    #[test]
+    fn test_sign_count_regressed() {
+        assert!(!sign_count_regressed(0, 0));
+        assert!(!sign_count_regressed(0, 1));
+        assert!(!sign_count_regressed(7, 8));
+        assert!(sign_count_regressed(7, 7));
+        assert!(sign_count_regressed(7, 0));
+    }
+
+    #[test]
+    fn test_authenticator_data_parse() {
+        assert!(AuthenticatorData::parse(&[0u8; 36]).is_err());
+
+        let mut data = vec![0u8; 37];
+        data[32] = FLAG_USER_PRESENT | FLAG_BACKED_UP;
+        data[36] = 9;
+        let parsed = AuthenticatorData::parse(&data).unwrap();
+        assert_eq!(parsed.sign_count, 9);
+        assert!(parsed.user_present() && parsed.backed_up() && !parsed.user_verified());
+        assert!(parsed.attested_credential.is_none());
+
+        data.push(0);
+        assert!(AuthenticatorData::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_creation_options_exclude_existing() {
+        let rp = relying_party();
+        let authenticator = SoftwareAuthenticator::es256("olympuscloud.io");
+        let stored = register(&rp, &authenticator);
+
+        let options = rp.creation_options("c".to_string(), stored.user_id, "ana@example.com", "Ana", &[stored.clone()]);
+        assert_eq!(options.rp.id, "olympuscloud.io");
+        assert_eq!(options.user.id, user_handle(stored.user_id));
+        assert_eq!(options.exclude_credentials[0].id, stored.credential_id);
+        assert_eq!(options.pub_key_cred_params[0].alg, COSE_ES256);
+        assert_eq!(options.timeout, 300_000);
+
+        let json = serde_json::to_value(&options).unwrap();
+        assert!(json.get("pubKeyCredParams").is_some());
+        assert_eq!(json["authenticatorSelection"]["residentKey"], "preferred");
+    }
+
+    #[test]
+    fn test_from_config_validates() {
+        assert!(RelyingParty::from_config(&WebAuthnConfig {
+            user_verification: "sometimes".to_string(),
+            ..WebAuthnConfig::default()
+        })
+        .is_err());
+        assert!(RelyingParty::from_config(&WebAuthnConfig {
+            origins: vec![],
+            ..WebAuthnConfig::default()
+        })
+        .is_err());
+    }
+}
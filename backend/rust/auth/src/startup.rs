@@ -0,0 +1,106 @@
+// ============================================================================
+// OLYMPUS CLOUD - AUTH SERVICE STARTUP
+// ============================================================================
+// Module: auth/src/startup.rs
+// Description: Build the auth service from configuration and start its background tasks
+// Author: Claude Code Agent
+// Date: 2025-01-23
+// ============================================================================
+
+use std::sync::Arc;
+use std::time::Duration;
+use olympus_shared::config::AppConfig;
+use olympus_shared::database::DbPool;
+use olympus_shared::events::{EventPublisher, EventSubscriber, SubscriptionConfig};
+use olympus_shared::Error;
+use crate::error::Result;
+use crate::event_handlers::AuthEventHandlerFactory;
+use crate::services::impersonation::PostgresImpersonationAuditStore;
+use crate::services::keys::{PostgresKeyStore, SigningKey};
+use crate::services::mailer::Mailer;
+use crate::services::revocation::RedisRevocationStore;
+use crate::services::risk::RiskEngine;
+use crate::services::sso::SsoConfigStore;
+use crate::services::AuthService;
+
+/// How often each instance reloads the persisted keyset to pick up rotations made elsewhere
+const KEY_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often each instance rebuilds its revocation filter from the shared denylist
+const REVOCATION_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Build the auth service with its persistent stores and spawn its background tasks.
+///
+/// Tenant SSO settings live outside the auth crate, so the caller passes in their store.
+///
+/// The returned subscriber runs the auth event handlers, which send the emails the service
+/// queues; it stops when dropped, so the caller holds it for as long as the service runs.
+pub async fn start_auth_service(
+    config: &AppConfig,
+    db: Arc<DbPool>,
+    sso_configs: Arc<dyn SsoConfigStore>,
+    event_publisher: Option<Arc<tokio::sync::Mutex<EventPublisher>>>,
+) -> Result<(Arc<AuthService>, EventSubscriber)> {
+    let mailer = Arc::new(Mailer::from_config(&config.email, db.clone())?);
+    mailer.clone().spawn_worker();
+
+    let auth_service = AuthService::new(db.clone(), &config.jwt.secret, event_publisher)?
+        .with_key_store(Arc::new(PostgresKeyStore::new(db.clone(), &config.jwt.secret)))
+        .with_impersonation_audit_store(Arc::new(PostgresImpersonationAuditStore::new(db)))
+        .with_mailer(mailer.clone())
+        .with_risk_engine(RiskEngine::from_config(&config.security.risk)?)
+        .with_sso_config_store(sso_configs)
+        .with_revocation_store(Arc::new(RedisRevocationStore::new(&config.redis.url).await?));
+    let auth_service = Arc::new(auth_service);
+
+    auth_service.load_signing_keys().await?;
+    if let Some(key) = configured_signing_key(config)? {
+        auth_service.install_signing_key(key).await?;
+    }
+    auth_service.clone().spawn_key_sync(KEY_SYNC_INTERVAL);
+    auth_service.revocation_list().spawn_sync(REVOCATION_SYNC_INTERVAL);
+
+    let subscriber = start_event_handlers(config, mailer).await?;
+
+    Ok((auth_service, subscriber))
+}
+
+/// Subscribe the auth event handlers, including email delivery, to the event bus
+async fn start_event_handlers(config: &AppConfig, mailer: Arc<Mailer>) -> Result<EventSubscriber> {
+    let mut subscriber = EventSubscriber::new(
+        &config.redis.url,
+        SubscriptionConfig {
+            name: "auth_service".to_string(),
+            consumer_group: Some("auth".to_string()),
+            ..SubscriptionConfig::default()
+        },
+    )
+    .await?;
+
+    for handler in AuthEventHandlerFactory::create_handlers_with_mailer(mailer) {
+        subscriber.register_handler(handler).await?;
+    }
+    subscriber.start().await?;
+
+    Ok(subscriber)
+}
+
+/// The RS256 or EdDSA key at `jwt.signing_key_path`, if one is configured
+fn configured_signing_key(config: &AppConfig) -> Result<Option<SigningKey>> {
+    let (Some(path), Some(kid)) = (&config.jwt.signing_key_path, &config.jwt.signing_key_id) else {
+        return Ok(None);
+    };
+
+    let pem = std::fs::read_to_string(path)
+        .map_err(|e| Error::Configuration(format!("Cannot read JWT signing key {}: {}", path, e)))?;
+
+    let key = match config.jwt.algorithm.as_str() {
+        "RS256" => SigningKey::rsa_pem(kid.clone(), &pem)?,
+        "EdDSA" => SigningKey::ed25519_pem(kid.clone(), &pem)?,
+        other => {
+            return Err(Error::Configuration(format!("Unsupported JWT signing algorithm for a key file: {}", other)).into());
+        }
+    };
+
+    Ok(Some(key))
+}
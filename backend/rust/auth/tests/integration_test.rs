@@ -22,6 +22,7 @@ mod database_tests {
             last_name: "User".to_string(),
             phone: None,
             tenant_slug: "test-tenant".to_string(),
+            locale: None,
         };
 
         let user_response = auth_service.register(register_req).await.unwrap();
@@ -72,6 +73,7 @@ fn test_jwt_service() {
         failed_login_attempts: 0,
         locked_until: None,
         password_changed_at: None,
+        force_password_change: false,
         created_at: Utc::now(),
         updated_at: Utc::now(),
         deleted_at: None,
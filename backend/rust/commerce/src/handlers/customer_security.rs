@@ -21,7 +21,7 @@ use std::net::IpAddr;
 
 use crate::models::customer_security::*;
 use crate::services::CustomerSecurityService;
-use olympus_shared::integration::AuthContext;
+use olympus_shared::integration::go_gateway::AuthContext;
 
 /// Response wrapper for customer security API endpoints
 #[derive(Debug, Serialize)]
@@ -18,6 +18,8 @@ use serde::Serialize;
 use uuid::Uuid;
 
 use crate::services::inventory::InventoryService;
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::models::permission::permissions;
 
 // ============================================================================
 // RESPONSE MODELS
@@ -77,5 +79,5 @@ pub async fn get_stock_level(
 
 pub fn inventory_routes() -> Router<InventoryService> {
     Router::new()
-        .route("/tenants/:tenant_id/stock/:product_id", get(get_stock_level))
+        .route("/tenants/:tenant_id/stock/:product_id", get(get_stock_level).route_layer(RequirePermission(permissions::COMMERCE_INVENTORY_READ)))
 }
\ No newline at end of file
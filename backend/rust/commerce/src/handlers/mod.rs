@@ -13,10 +13,12 @@ pub mod payments;
 pub mod restaurant;
 pub mod websocket;
 pub mod customer_security;
+pub mod staff;
 
 pub use products::*;
 pub use orders::*;
 pub use payments::*;
 pub use restaurant::*;
 pub use websocket::*;
-pub use customer_security::*;
\ No newline at end of file
+pub use customer_security::*;
+pub use staff::*;
\ No newline at end of file
@@ -9,7 +9,7 @@
 
 use std::sync::Arc;
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     response::Json,
     routing::{get, post, put, delete},
@@ -20,6 +20,10 @@ use uuid::Uuid;
 use validator::Validate;
 
 use olympus_shared::error::{Result, OlympusError};
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::integration::go_gateway::AuthContext;
+use olympus_shared::models::permission::permissions;
+use olympus_shared::policy::RequestContext;
 use crate::models::{
     Order, OrderStatus, PaymentStatus, FulfillmentStatus,
     CreateOrderRequest, UpdateOrderRequest, OrderSearchRequest,
@@ -35,30 +39,30 @@ use crate::services::OrderService;
 pub fn create_order_router(order_service: Arc<OrderService>) -> Router {
     Router::new()
         // Order CRUD operations
-        .route("/orders", post(create_order))
-        .route("/orders", get(list_orders))
-        .route("/orders/search", post(search_orders))
-        .route("/orders/:order_id", get(get_order))
-        .route("/orders/:order_id", put(update_order))
-        .route("/orders/:order_id", delete(cancel_order))
+        .route("/orders", post(create_order).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_WRITE)))
+        .route("/orders", get(list_orders).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/search", post(search_orders).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/:order_id", get(get_order).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/:order_id", put(update_order).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_WRITE)))
+        .route("/orders/:order_id", delete(cancel_order).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_CANCEL)))
 
         // Order status management
-        .route("/orders/:order_id/confirm", post(confirm_order))
-        .route("/orders/:order_id/cancel", post(cancel_order_with_reason))
-        .route("/orders/:order_id/status", put(update_order_status))
+        .route("/orders/:order_id/confirm", post(confirm_order).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_WRITE)))
+        .route("/orders/:order_id/cancel", post(cancel_order_with_reason).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_CANCEL)))
+        .route("/orders/:order_id/status", put(update_order_status).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_WRITE)))
 
         // Order lifecycle operations
-        .route("/orders/:order_id/events", get(get_order_events))
-        .route("/orders/:order_id/modifications", get(get_order_modifications))
-        .route("/orders/:order_id/fulfillments", get(get_order_fulfillments))
-        .route("/orders/:order_id/fulfillments", post(create_fulfillment))
+        .route("/orders/:order_id/events", get(get_order_events).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/:order_id/modifications", get(get_order_modifications).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/:order_id/fulfillments", get(get_order_fulfillments).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/:order_id/fulfillments", post(create_fulfillment).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_WRITE)))
 
         // Bulk operations
-        .route("/orders/bulk-update", post(bulk_update_orders))
+        .route("/orders/bulk-update", post(bulk_update_orders).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_WRITE)))
 
         // Analytics and reporting
-        .route("/orders/analytics/summary", get(get_order_analytics))
-        .route("/orders/export", post(export_orders))
+        .route("/orders/analytics/summary", get(get_order_analytics).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)))
+        .route("/orders/export", post(export_orders).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_EXPORT)))
 
         .with_state(order_service)
 }
@@ -326,13 +330,12 @@ pub async fn update_order(
 
 pub async fn cancel_order(
     State(order_service): State<Arc<OrderService>>,
+    Extension(auth): Extension<AuthContext>,
+    context: RequestContext,
     Path(order_id): Path<Uuid>,
 ) -> Result<StatusCode> {
-    let tenant_id = Uuid::new_v4(); // Mock tenant ID
-    let cancelled_by = Uuid::new_v4(); // Mock user ID
-
     let cancelled = order_service
-        .cancel_order(tenant_id, order_id, "Order cancelled via API".to_string(), cancelled_by)
+        .cancel_order(auth.tenant_id, order_id, "Order cancelled via API".to_string(), &auth, &context)
         .await?;
 
     if cancelled.is_some() {
@@ -367,6 +370,8 @@ pub async fn confirm_order(
 
 pub async fn cancel_order_with_reason(
     State(order_service): State<Arc<OrderService>>,
+    Extension(auth): Extension<AuthContext>,
+    context: RequestContext,
     Path(order_id): Path<Uuid>,
     Json(request): Json<CancelOrderRequest>,
 ) -> Result<Json<OrderResponse>> {
@@ -374,11 +379,8 @@ pub async fn cancel_order_with_reason(
     request.validate()
         .map_err(|e| OlympusError::Validation(format!("Invalid request: {}", e)))?;
 
-    let tenant_id = Uuid::new_v4(); // Mock tenant ID
-    let cancelled_by = Uuid::new_v4(); // Mock user ID
-
     let order = order_service
-        .cancel_order(tenant_id, order_id, request.reason, cancelled_by)
+        .cancel_order(auth.tenant_id, order_id, request.reason, &auth, &context)
         .await?
         .ok_or_else(|| OlympusError::NotFound("Order not found".to_string()))?;
 
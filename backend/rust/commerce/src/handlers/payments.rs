@@ -24,8 +24,9 @@ use crate::{
     services::payment_service::PaymentService,
 };
 use olympus_shared::{
-    auth::Claims,
     error::{ApiError, ApiResult},
+    integration::go_gateway::AuthContext,
+    policy::RequestContext,
 };
 
 #[derive(Debug, Deserialize)]
@@ -43,7 +44,7 @@ pub struct PaymentQuery {
 // ============================================================================
 
 pub async fn create_payment_method(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Json(request): Json<CreatePaymentMethodRequest>,
 ) -> ApiResult<Json<StoredPaymentMethod>> {
@@ -53,23 +54,23 @@ pub async fn create_payment_method(
     })?;
 
     let payment_method = service
-        .create_payment_method(claims.tenant_id, request)
+        .create_payment_method(auth.tenant_id, request)
         .await?;
 
     Ok(Json(payment_method))
 }
 
 pub async fn get_payment_method(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<StoredPaymentMethod>> {
-    let payment_method = service.get_payment_method(claims.tenant_id, id).await?;
+    let payment_method = service.get_payment_method(auth.tenant_id, id).await?;
     Ok(Json(payment_method))
 }
 
 pub async fn list_payment_methods(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Query(query): Query<PaymentQuery>,
 ) -> ApiResult<Json<Vec<StoredPaymentMethod>>> {
@@ -77,14 +78,14 @@ pub async fn list_payment_methods(
     let offset = query.offset.unwrap_or(0);
 
     let methods = service
-        .list_payment_methods(claims.tenant_id, limit, offset)
+        .list_payment_methods(auth.tenant_id, limit, offset)
         .await?;
 
     Ok(Json(methods))
 }
 
 pub async fn update_payment_method(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
     Json(request): Json<CreatePaymentMethodRequest>,
@@ -95,18 +96,18 @@ pub async fn update_payment_method(
     })?;
 
     let payment_method = service
-        .update_payment_method(claims.tenant_id, id, request)
+        .update_payment_method(auth.tenant_id, id, request)
         .await?;
 
     Ok(Json(payment_method))
 }
 
 pub async fn delete_payment_method(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<StatusCode> {
-    service.delete_payment_method(claims.tenant_id, id).await?;
+    service.delete_payment_method(auth.tenant_id, id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -115,7 +116,7 @@ pub async fn delete_payment_method(
 // ============================================================================
 
 pub async fn create_payment(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Json(request): Json<CreatePaymentRequest>,
 ) -> ApiResult<Json<PaymentResponse>> {
@@ -124,33 +125,33 @@ pub async fn create_payment(
         message: e.to_string(),
     })?;
 
-    let response = service.create_payment(claims.tenant_id, request).await?;
+    let response = service.create_payment(auth.tenant_id, request).await?;
     Ok(Json(response))
 }
 
 pub async fn process_payment(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
     Json(request): Json<ProcessPaymentRequest>,
 ) -> ApiResult<Json<PaymentResponse>> {
     let response = service
-        .process_payment(claims.tenant_id, id, request)
+        .process_payment(auth.tenant_id, id, request)
         .await?;
     Ok(Json(response))
 }
 
 pub async fn get_payment(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<PaymentTransaction>> {
-    let payment = service.get_payment(claims.tenant_id, id).await?;
+    let payment = service.get_payment(auth.tenant_id, id).await?;
     Ok(Json(payment))
 }
 
 pub async fn list_payments(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Query(query): Query<PaymentQuery>,
 ) -> ApiResult<Json<Vec<PaymentTransaction>>> {
@@ -159,7 +160,7 @@ pub async fn list_payments(
 
     let payments = service
         .list_payments(
-            claims.tenant_id,
+            auth.tenant_id,
             query.status,
             query.gateway,
             query.from_date,
@@ -173,20 +174,20 @@ pub async fn list_payments(
 }
 
 pub async fn capture_payment(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<PaymentResponse>> {
-    let response = service.capture_payment(claims.tenant_id, id).await?;
+    let response = service.capture_payment(auth.tenant_id, id).await?;
     Ok(Json(response))
 }
 
 pub async fn void_payment(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<PaymentResponse>> {
-    let response = service.void_payment(claims.tenant_id, id).await?;
+    let response = service.void_payment(auth.tenant_id, id).await?;
     Ok(Json(response))
 }
 
@@ -195,8 +196,9 @@ pub async fn void_payment(
 // ============================================================================
 
 pub async fn create_refund(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
+    context: RequestContext,
     Json(request): Json<RefundRequest>,
 ) -> ApiResult<Json<Refund>> {
     request.validate().map_err(|e| ApiError::ValidationError {
@@ -204,21 +206,21 @@ pub async fn create_refund(
         message: e.to_string(),
     })?;
 
-    let refund = service.create_refund(claims.tenant_id, request).await?;
+    let refund = service.create_refund(auth.tenant_id, request, &auth, &context).await?;
     Ok(Json(refund))
 }
 
 pub async fn get_refund(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<Refund>> {
-    let refund = service.get_refund(claims.tenant_id, id).await?;
+    let refund = service.get_refund(auth.tenant_id, id).await?;
     Ok(Json(refund))
 }
 
 pub async fn list_refunds(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Query(query): Query<PaymentQuery>,
 ) -> ApiResult<Json<Vec<Refund>>> {
@@ -226,18 +228,18 @@ pub async fn list_refunds(
     let offset = query.offset.unwrap_or(0);
 
     let refunds = service
-        .list_refunds(claims.tenant_id, limit, offset)
+        .list_refunds(auth.tenant_id, limit, offset)
         .await?;
 
     Ok(Json(refunds))
 }
 
 pub async fn process_refund(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<Refund>> {
-    let refund = service.process_refund(claims.tenant_id, id).await?;
+    let refund = service.process_refund(auth.tenant_id, id).await?;
     Ok(Json(refund))
 }
 
@@ -246,13 +248,13 @@ pub async fn process_refund(
 // ============================================================================
 
 pub async fn get_payment_summary(
-    Extension(claims): Extension<Claims>,
+    Extension(auth): Extension<AuthContext>,
     State(service): State<PaymentService>,
     Query(query): Query<PaymentQuery>,
 ) -> ApiResult<Json<PaymentSummary>> {
     let summary = service
         .get_payment_summary(
-            claims.tenant_id,
+            auth.tenant_id,
             query.from_date,
             query.to_date,
         )
@@ -267,27 +269,29 @@ pub async fn get_payment_summary(
 
 use axum::routing::{delete, get, post, put};
 use axum::Router;
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::models::permission::permissions;
 
 pub fn payment_routes() -> Router<PaymentService> {
     Router::new()
         // Payment Methods
-        .route("/payment-methods", post(create_payment_method))
-        .route("/payment-methods", get(list_payment_methods))
-        .route("/payment-methods/:id", get(get_payment_method))
-        .route("/payment-methods/:id", put(update_payment_method))
-        .route("/payment-methods/:id", delete(delete_payment_method))
+        .route("/payment-methods", post(create_payment_method).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
+        .route("/payment-methods", get(list_payment_methods).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_READ)))
+        .route("/payment-methods/:id", get(get_payment_method).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_READ)))
+        .route("/payment-methods/:id", put(update_payment_method).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
+        .route("/payment-methods/:id", delete(delete_payment_method).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
         // Payments
-        .route("/payments", post(create_payment))
-        .route("/payments", get(list_payments))
-        .route("/payments/:id", get(get_payment))
-        .route("/payments/:id/process", post(process_payment))
-        .route("/payments/:id/capture", post(capture_payment))
-        .route("/payments/:id/void", post(void_payment))
+        .route("/payments", post(create_payment).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
+        .route("/payments", get(list_payments).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_READ)))
+        .route("/payments/:id", get(get_payment).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_READ)))
+        .route("/payments/:id/process", post(process_payment).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
+        .route("/payments/:id/capture", post(capture_payment).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
+        .route("/payments/:id/void", post(void_payment).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_WRITE)))
         // Refunds
-        .route("/refunds", post(create_refund))
-        .route("/refunds", get(list_refunds))
-        .route("/refunds/:id", get(get_refund))
-        .route("/refunds/:id/process", post(process_refund))
+        .route("/refunds", post(create_refund).route_layer(RequirePermission(permissions::COMMERCE_REFUNDS_CREATE)))
+        .route("/refunds", get(list_refunds).route_layer(RequirePermission(permissions::COMMERCE_REFUNDS_READ)))
+        .route("/refunds/:id", get(get_refund).route_layer(RequirePermission(permissions::COMMERCE_REFUNDS_READ)))
+        .route("/refunds/:id/process", post(process_refund).route_layer(RequirePermission(permissions::COMMERCE_REFUNDS_PROCESS)))
         // Analytics
-        .route("/payments/summary", get(get_payment_summary))
+        .route("/payments/summary", get(get_payment_summary).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_READ)))
 }
\ No newline at end of file
@@ -20,6 +20,8 @@ use uuid::Uuid;
 use validator::Validate;
 
 use olympus_shared::error::{Result, OlympusError};
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::models::permission::permissions;
 use crate::models::{
     Product, ProductCategory, ProductSearchRequest, ProductSearchResponse,
     CreateProductRequest, UpdateProductRequest, ProductSortBy, SortOrder,
@@ -34,21 +36,21 @@ use crate::services::CatalogService;
 pub fn create_product_router(catalog_service: Arc<CatalogService>) -> Router {
     Router::new()
         // Product CRUD operations
-        .route("/products", post(create_product))
-        .route("/products", get(list_products))
-        .route("/products/search", post(search_products))
-        .route("/products/:product_id", get(get_product))
-        .route("/products/:product_id", put(update_product))
-        .route("/products/:product_id", delete(delete_product))
+        .route("/products", post(create_product).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_WRITE)))
+        .route("/products", get(list_products).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_READ)))
+        .route("/products/search", post(search_products).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_READ)))
+        .route("/products/:product_id", get(get_product).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_READ)))
+        .route("/products/:product_id", put(update_product).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_WRITE)))
+        .route("/products/:product_id", delete(delete_product).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_DELETE)))
 
         // Product categories
-        .route("/categories", post(create_category))
-        .route("/categories", get(list_categories))
-        .route("/categories/:category_id", get(get_category))
+        .route("/categories", post(create_category).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_WRITE)))
+        .route("/categories", get(list_categories).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_READ)))
+        .route("/categories/:category_id", get(get_category).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_READ)))
 
         // Product variants (placeholder for future implementation)
-        .route("/products/:product_id/variants", get(list_product_variants))
-        .route("/products/:product_id/variants", post(create_product_variant))
+        .route("/products/:product_id/variants", get(list_product_variants).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_READ)))
+        .route("/products/:product_id/variants", post(create_product_variant).route_layer(RequirePermission(permissions::COMMERCE_PRODUCTS_WRITE)))
 
         .with_state(catalog_service)
 }
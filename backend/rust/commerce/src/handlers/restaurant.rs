@@ -21,6 +21,8 @@ use std::collections::HashMap;
 use crate::models::restaurant::*;
 use crate::services::RestaurantService;
 use olympus_shared::integration::AuthContext;
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::models::permission::permissions;
 
 /// Query parameters for filtering orders
 #[derive(Debug, Deserialize)]
@@ -69,23 +71,23 @@ impl<T> ApiResponse<T> {
 pub fn restaurant_routes() -> Router<RestaurantService> {
     Router::new()
         // Dashboard endpoints
-        .route("/dashboard", get(get_dashboard_metrics))
+        .route("/dashboard", get(get_dashboard_metrics).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
 
         // Table management endpoints
-        .route("/tables", get(get_tables))
-        .route("/tables/:table_id", get(get_table))
-        .route("/tables/:table_id/status", put(update_table_status))
-        .route("/tables/analytics", get(get_table_analytics))
+        .route("/tables", get(get_tables).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
+        .route("/tables/:table_id", get(get_table).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
+        .route("/tables/:table_id/status", put(update_table_status).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_WRITE)))
+        .route("/tables/analytics", get(get_table_analytics).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
 
         // Order management endpoints
-        .route("/orders", get(get_orders))
-        .route("/orders", post(create_order))
-        .route("/orders/:order_id", get(get_order))
-        .route("/orders/:order_id/status", put(update_order_status))
+        .route("/orders", get(get_orders).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
+        .route("/orders", post(create_order).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_WRITE)))
+        .route("/orders/:order_id", get(get_order).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
+        .route("/orders/:order_id/status", put(update_order_status).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_WRITE)))
 
         // Kitchen display endpoints
-        .route("/kitchen/display", get(get_kitchen_display))
-        .route("/kitchen/items/:item_id/status", put(update_kitchen_item_status))
+        .route("/kitchen/display", get(get_kitchen_display).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_READ)))
+        .route("/kitchen/items/:item_id/status", put(update_kitchen_item_status).route_layer(RequirePermission(permissions::COMMERCE_RESTAURANT_WRITE)))
 }
 
 // ============================================================================
@@ -0,0 +1,60 @@
+// ============================================================================
+// OLYMPUS CLOUD - STAFF HANDLERS
+// ============================================================================
+// Module: commerce/src/handlers/staff.rs
+// Description: HTTP handlers for scheduling staff shifts
+// Author: Claude Code Agent
+// Date: 2025-01-23
+// ============================================================================
+
+use std::sync::Arc;
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use uuid::Uuid;
+
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::error::Result;
+use olympus_shared::integration::go_gateway::AuthContext;
+use olympus_shared::models::permission::permissions;
+use crate::models::staff::{ScheduleShiftRequest, ShiftQuery, StaffShift};
+use crate::services::StaffService;
+
+pub fn create_staff_router(staff_service: Arc<StaffService>) -> Router {
+    Router::new()
+        .route("/shifts", post(schedule_shift).route_layer(RequirePermission(permissions::COMMERCE_SHIFTS_WRITE)))
+        .route("/shifts", get(list_shifts).route_layer(RequirePermission(permissions::COMMERCE_SHIFTS_READ)))
+        .route("/shifts/:shift_id", delete(delete_shift).route_layer(RequirePermission(permissions::COMMERCE_SHIFTS_WRITE)))
+        .with_state(staff_service)
+}
+
+pub async fn schedule_shift(
+    State(staff_service): State<Arc<StaffService>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(request): Json<ScheduleShiftRequest>,
+) -> Result<(StatusCode, Json<StaffShift>)> {
+    let shift = staff_service.schedule_shift(auth.tenant_id, request, auth.user_id).await?;
+    Ok((StatusCode::CREATED, Json(shift)))
+}
+
+pub async fn list_shifts(
+    State(staff_service): State<Arc<StaffService>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ShiftQuery>,
+) -> Result<Json<Vec<StaffShift>>> {
+    let shifts = staff_service.list_shifts(auth.tenant_id, query).await?;
+    Ok(Json(shifts))
+}
+
+pub async fn delete_shift(
+    State(staff_service): State<Arc<StaffService>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(shift_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    staff_service.delete_shift(auth.tenant_id, shift_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -31,10 +31,12 @@ use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use sqlx::PgPool;
 
+use olympus_shared::authorization::{Authenticate, Authenticator};
 use olympus_shared::database::DbPool;
 use olympus_shared::events::EventPublisher;
-use crate::handlers::{create_product_router, create_order_router, inventory_routes, restaurant_routes, create_websocket_manager};
-use crate::services::{CatalogService, InventoryService, OrderService, RestaurantService};
+use olympus_shared::policy::PolicyEngine;
+use crate::handlers::{create_product_router, create_order_router, create_staff_router, customer_security_routes, inventory_routes, payment_routes, restaurant_routes, create_websocket_manager};
+use crate::services::{CatalogService, CustomerSecurityService, InventoryService, OrderService, PaymentService, RestaurantService, StaffService};
 use simple_service::SimpleCommerceService;
 use simple_handlers::*;
 
@@ -43,6 +45,11 @@ use simple_handlers::*;
 pub struct CommerceConfig {
     pub db: Arc<DbPool>,
     pub event_publisher: Arc<EventPublisher>,
+    /// Tenant access policies, e.g. backed by `olympus_platform::services::PlatformPolicyStore`.
+    /// Subjects' locations and shifts are read from the staff schedule.
+    pub policy_engine: PolicyEngine,
+    /// Verifies callers' tokens and API keys, normally the auth service
+    pub authenticator: Arc<dyn Authenticator>,
 }
 
 /// Create commerce router with all endpoints and middleware
@@ -53,10 +60,16 @@ pub fn create_router(config: CommerceConfig) -> Router {
         config.event_publisher.clone(),
     ));
 
-    let order_service = Arc::new(OrderService::new(
-        config.db.clone(),
-        config.event_publisher.clone(),
-    ));
+    let staff_service = Arc::new(StaffService::new(config.db.clone()));
+    let policy_engine = config.policy_engine.clone().with_subject_directory(staff_service.clone());
+
+    let order_service = Arc::new(
+        OrderService::new(config.db.clone(), config.event_publisher.clone())
+            .with_policy_engine(policy_engine.clone()),
+    );
+
+    let payment_service = PaymentService::new((*config.db).clone(), config.event_publisher.clone())
+        .with_policy_engine(policy_engine);
 
     let inventory_service = Arc::new(InventoryService::new(
         (*config.db).clone(),
@@ -64,18 +77,22 @@ pub fn create_router(config: CommerceConfig) -> Router {
     ));
 
     let restaurant_service = RestaurantService::new((*config.db).clone());
+    let customer_security_service = CustomerSecurityService::new((*config.db).clone());
     let ws_manager = create_websocket_manager();
 
-    Router::new()
-        // Health check
-        .route("/health", get(health_check))
-
+    let api = Router::new()
         // Product catalog routes
         .nest("/api/v1/commerce", create_product_router(catalog_service.clone()))
 
         // Order management routes
         .nest("/api/v1/commerce", create_order_router(order_service.clone()))
 
+        // Payment and refund routes
+        .nest("/api/v1/commerce", payment_routes().with_state(payment_service))
+
+        // Staff scheduling routes
+        .nest("/api/v1/commerce/staff", create_staff_router(staff_service))
+
         // Inventory management routes
         .nest("/api/v1/commerce/inventory", inventory_routes().with_state((*inventory_service).clone()))
 
@@ -85,6 +102,21 @@ pub fn create_router(config: CommerceConfig) -> Router {
         // WebSocket route for real-time updates
         .route("/api/v1/restaurants/:tenant_id/ws", axum::routing::get(crate::handlers::websocket_handler).with_state((restaurant_service, ws_manager)))
 
+        // Customer security, consent and GDPR routes
+        .nest("/api/v1", customer_security_routes().with_state(customer_security_service));
+
+    with_authentication(api, config.authenticator)
+}
+
+/// Serve `api` behind `authenticator`, which runs ahead of the routes' permission checks,
+/// next to the public health check. It is a route layer so unmatched requests still fall
+/// through to the next service's router.
+fn with_authentication(api: Router, authenticator: Arc<dyn Authenticator>) -> Router {
+    Router::new()
+        // Health check
+        .route("/health", get(health_check))
+        .merge(api.route_layer(Authenticate(authenticator)))
+
         // Middleware stack
         .layer(
             ServiceBuilder::new()
@@ -94,12 +126,10 @@ pub fn create_router(config: CommerceConfig) -> Router {
 }
 
 /// Create simple router for backward compatibility with demo
-pub fn create_simple_router(db: Arc<PgPool>) -> Router {
+pub fn create_simple_router(db: Arc<PgPool>, authenticator: Arc<dyn Authenticator>) -> Router {
     let service = Arc::new(SimpleCommerceService::new(db));
 
-    Router::new()
-        // Health check
-        .route("/health", get(health_check))
+    let api = Router::new()
         // Product routes
         .route("/tenants/:tenant_id/products", post(create_product))
         .route("/tenants/:tenant_id/products", get(list_products))
@@ -111,13 +141,9 @@ pub fn create_simple_router(db: Arc<PgPool>) -> Router {
         .route("/tenants/:tenant_id/orders/:order_id", get(get_order))
         .route("/tenants/:tenant_id/orders/:order_id", put(update_order))
         // Add service state
-        .with_state(service)
-        // Middleware
-        .layer(
-            ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive()),
-        )
+        .with_state(service);
+
+    with_authentication(api, authenticator)
 }
 
 /// Create router for testing without dependencies
@@ -10,7 +10,9 @@
 pub mod restaurant;
 pub mod customer_security;
 pub mod inventory_transactions;
+pub mod staff;
 
 pub use restaurant::*;
 pub use customer_security::*;
-pub use inventory_transactions::*;
\ No newline at end of file
+pub use inventory_transactions::*;
+pub use staff::*;
\ No newline at end of file
@@ -0,0 +1,42 @@
+// ============================================================================
+// OLYMPUS CLOUD - STAFF MODELS
+// ============================================================================
+// Module: commerce/src/models/staff.rs
+// Description: Scheduled staff shifts
+// Author: Claude Code Agent
+// Date: 2025-01-23
+// ============================================================================
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A shift a staff member is scheduled to work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaffShift {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub location_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub scheduled_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleShiftRequest {
+    pub user_id: Uuid,
+    pub location_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShiftQuery {
+    pub user_id: Option<Uuid>,
+    /// Shifts ending after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Shifts starting before this time
+    pub to: Option<DateTime<Utc>>,
+}
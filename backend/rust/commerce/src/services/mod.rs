@@ -13,6 +13,7 @@ pub mod order;
 pub mod payment_service;
 pub mod restaurant_service;
 pub mod customer_security_service;
+pub mod staff;
 pub mod gateways;
 
 pub use analytics::AnalyticsService;
@@ -20,4 +21,5 @@ pub use catalog::CatalogService;
 pub use order::OrderService;
 pub use payment_service::PaymentService;
 pub use restaurant_service::RestaurantService;
-pub use customer_security_service::CustomerSecurityService;
\ No newline at end of file
+pub use customer_security_service::CustomerSecurityService;
+pub use staff::StaffService;
\ No newline at end of file
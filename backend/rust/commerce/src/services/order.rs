@@ -17,6 +17,9 @@ use olympus_shared::{
     database::DbPool,
     events::EventPublisher,
     error::{Result, OlympusError},
+    models::permission::permissions,
+    integration::go_gateway::AuthContext,
+    policy::{PolicyEngine, PolicyInput, RequestContext},
 };
 
 use crate::models::{
@@ -34,11 +37,22 @@ use crate::models::{
 pub struct OrderService {
     db: Arc<DbPool>,
     event_publisher: Arc<EventPublisher>,
+    policies: PolicyEngine,
 }
 
 impl OrderService {
     pub fn new(db: Arc<DbPool>, event_publisher: Arc<EventPublisher>) -> Self {
-        Self { db, event_publisher }
+        Self {
+            db,
+            event_publisher,
+            policies: PolicyEngine::default(),
+        }
+    }
+
+    /// Evaluate cancellations against tenants' access policies
+    pub fn with_policy_engine(mut self, policies: PolicyEngine) -> Self {
+        self.policies = policies;
+        self
     }
 
     // ========================================================================
@@ -464,8 +478,10 @@ impl OrderService {
         tenant_id: Uuid,
         order_id: Uuid,
         reason: String,
-        cancelled_by: Uuid,
+        auth: &AuthContext,
+        context: &RequestContext,
     ) -> Result<Option<Order>> {
+        let cancelled_by = auth.user_id;
         let mut tx = self.db.begin().await?;
 
         // Verify order can be cancelled
@@ -485,6 +501,21 @@ impl OrderService {
             _ => {}
         }
 
+        // Tenant policies may limit voids, e.g. by order total, location or shift
+        let resource = serde_json::json!({
+            "id": current_order.id,
+            "total": current_order.total,
+            "currency": current_order.currency,
+            "status": current_order.status,
+            "customer_id": current_order.customer_id,
+            "location_id": current_order.metadata.get("location_id"),
+        });
+        let actor = self.policies.subject(auth, context).await?;
+        self.policies
+            .evaluate(tenant_id, permissions::COMMERCE_ORDERS_CANCEL, &PolicyInput::new(&actor, resource, context))
+            .await?
+            .ensure_permitted()?;
+
         // Update order status
         let now = Utc::now();
         sqlx::query!(
@@ -22,15 +22,20 @@ use crate::models::{
 use olympus_shared::{
     error::{ApiError, ApiResult},
     events::{Event, EventPublisher},
+    models::permission::permissions,
+    integration::go_gateway::AuthContext,
+    policy::{PolicyEngine, PolicyInput, RequestContext},
 };
 
 use super::gateways::{PaymentGateway, StripeGateway, SquareGateway};
 
+#[derive(Clone)]
 pub struct PaymentService {
     pool: PgPool,
     event_publisher: Arc<EventPublisher>,
     stripe_gateway: Arc<StripeGateway>,
     square_gateway: Arc<SquareGateway>,
+    policies: PolicyEngine,
 }
 
 impl PaymentService {
@@ -40,9 +45,16 @@ impl PaymentService {
             event_publisher,
             stripe_gateway: Arc::new(StripeGateway::new()),
             square_gateway: Arc::new(SquareGateway::new()),
+            policies: PolicyEngine::default(),
         }
     }
 
+    /// Evaluate refunds against tenants' access policies
+    pub fn with_policy_engine(mut self, policies: PolicyEngine) -> Self {
+        self.policies = policies;
+        self
+    }
+
     // ========================================================================
     // PAYMENT METHODS
     // ========================================================================
@@ -564,6 +576,8 @@ impl PaymentService {
         &self,
         tenant_id: Uuid,
         request: RefundRequest,
+        auth: &AuthContext,
+        context: &RequestContext,
     ) -> ApiResult<Refund> {
         let id = Uuid::new_v4();
         let now = Utc::now();
@@ -578,6 +592,27 @@ impl PaymentService {
             });
         }
 
+        // Tenant policies may require a manager above an amount, restrict locations, etc.
+        let resource = serde_json::json!({
+            "payment_id": payment.id,
+            "order_id": payment.order_id,
+            "amount": request.amount,
+            "payment_amount": payment.amount,
+            "currency": payment.currency,
+            "location_id": payment.metadata.as_ref().and_then(|m| m.get("location_id")),
+        });
+        let actor = self
+            .policies
+            .subject(auth, context)
+            .await
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+        self.policies
+            .evaluate(tenant_id, permissions::COMMERCE_REFUNDS_CREATE, &PolicyInput::new(&actor, resource, context))
+            .await
+            .map_err(|e| ApiError::InternalError(e.to_string()))?
+            .ensure_permitted()
+            .map_err(|e| ApiError::PolicyDenied(e.to_string()))?;
+
         // Create refund record
         let refund = sqlx::query_as!(
             Refund,
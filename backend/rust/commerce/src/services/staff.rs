@@ -0,0 +1,158 @@
+// ============================================================================
+// OLYMPUS CLOUD - STAFF SERVICE
+// ============================================================================
+// Module: commerce/src/services/staff.rs
+// Description: Staff shift scheduling, and the subject attributes access policies
+//              read from it and from tenant memberships
+// Author: Claude Code Agent
+// Date: 2025-01-23
+// ============================================================================
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use olympus_shared::{
+    database::DbPool,
+    error::{OlympusError, Result},
+    policy::{Shift, SubjectDirectory},
+};
+
+use crate::models::staff::{ScheduleShiftRequest, ShiftQuery, StaffShift};
+
+#[derive(Clone)]
+pub struct StaffService {
+    db: Arc<DbPool>,
+}
+
+impl StaffService {
+    pub fn new(db: Arc<DbPool>) -> Self {
+        Self { db }
+    }
+
+    /// Schedule a shift; a user's shifts may not overlap, so at most one is current
+    pub async fn schedule_shift(
+        &self,
+        tenant_id: Uuid,
+        request: ScheduleShiftRequest,
+        scheduled_by: Uuid,
+    ) -> Result<StaffShift> {
+        if request.starts_at >= request.ends_at {
+            return Err(OlympusError::Validation("Shift must end after it starts".to_string()));
+        }
+
+        let overlapping = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM commerce.staff_shifts
+                WHERE tenant_id = $1 AND user_id = $2 AND starts_at < $4 AND ends_at > $3
+            ) AS "exists!"
+            "#,
+            tenant_id,
+            request.user_id,
+            request.starts_at,
+            request.ends_at
+        )
+        .fetch_one(self.db.as_ref())
+        .await?;
+        if overlapping {
+            return Err(OlympusError::AlreadyExists("Shift overlaps another shift for this user".to_string()));
+        }
+
+        let shift = sqlx::query_as!(
+            StaffShift,
+            r#"
+            INSERT INTO commerce.staff_shifts (id, tenant_id, user_id, location_id, starts_at, ends_at, scheduled_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, tenant_id, user_id, location_id, starts_at, ends_at, scheduled_by, created_at
+            "#,
+            Uuid::new_v4(),
+            tenant_id,
+            request.user_id,
+            request.location_id,
+            request.starts_at,
+            request.ends_at,
+            scheduled_by
+        )
+        .fetch_one(self.db.as_ref())
+        .await?;
+
+        Ok(shift)
+    }
+
+    pub async fn list_shifts(&self, tenant_id: Uuid, query: ShiftQuery) -> Result<Vec<StaffShift>> {
+        let shifts = sqlx::query_as!(
+            StaffShift,
+            r#"
+            SELECT id, tenant_id, user_id, location_id, starts_at, ends_at, scheduled_by, created_at
+            FROM commerce.staff_shifts
+            WHERE tenant_id = $1
+              AND ($2::uuid IS NULL OR user_id = $2)
+              AND ($3::timestamptz IS NULL OR ends_at > $3)
+              AND ($4::timestamptz IS NULL OR starts_at < $4)
+            ORDER BY starts_at
+            "#,
+            tenant_id,
+            query.user_id,
+            query.from,
+            query.to
+        )
+        .fetch_all(self.db.as_ref())
+        .await?;
+
+        Ok(shifts)
+    }
+
+    pub async fn delete_shift(&self, tenant_id: Uuid, shift_id: Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM commerce.staff_shifts WHERE id = $1 AND tenant_id = $2",
+            shift_id,
+            tenant_id
+        )
+        .execute(self.db.as_ref())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(OlympusError::NotFound("Shift not found".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SubjectDirectory for StaffService {
+    async fn assigned_locations(&self, tenant_id: Uuid, user_id: Uuid) -> Result<Vec<Uuid>> {
+        let locations = sqlx::query_scalar!(
+            r#"
+            SELECT location_ids FROM auth.tenant_memberships
+            WHERE user_id = $1 AND tenant_id = $2 AND is_active
+            "#,
+            user_id,
+            tenant_id
+        )
+        .fetch_optional(self.db.as_ref())
+        .await?;
+
+        Ok(locations.unwrap_or_default())
+    }
+
+    async fn current_shift(&self, tenant_id: Uuid, user_id: Uuid, at: DateTime<Utc>) -> Result<Option<Shift>> {
+        let shift = sqlx::query_as!(
+            Shift,
+            r#"
+            SELECT location_id, starts_at, ends_at FROM commerce.staff_shifts
+            WHERE tenant_id = $1 AND user_id = $2 AND starts_at <= $3 AND ends_at > $3
+            ORDER BY starts_at DESC
+            LIMIT 1
+            "#,
+            tenant_id,
+            user_id,
+            at
+        )
+        .fetch_optional(self.db.as_ref())
+        .await?;
+
+        Ok(shift)
+    }
+}
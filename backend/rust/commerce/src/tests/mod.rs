@@ -7,4 +7,5 @@
 // Date: 2025-01-19
 // ============================================================================
 
-pub mod payment_tests;
\ No newline at end of file
+pub mod payment_tests;
+pub mod router_tests;
\ No newline at end of file
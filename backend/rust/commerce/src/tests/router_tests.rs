@@ -0,0 +1,112 @@
+// ============================================================================
+// OLYMPUS CLOUD - ROUTER TESTS
+// ============================================================================
+// Module: commerce/src/tests/router_tests.rs
+// Description: Authentication and permission layering of the commerce routers
+// Author: Claude Code Agent
+// Date: 2025-01-23
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use async_trait::async_trait;
+    use axum::{
+        body::Body,
+        extract::Request,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::get,
+        Router,
+    };
+    use olympus_shared::authorization::{Authenticator, RequirePermission};
+    use olympus_shared::integration::go_gateway::AuthContext;
+    use olympus_shared::models::permission::permissions;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use crate::handlers::customer_security_routes;
+    use crate::services::CustomerSecurityService;
+
+    /// Accepts `Bearer valid` with read access to orders
+    struct StaticAuthenticator;
+
+    #[async_trait]
+    impl Authenticator for StaticAuthenticator {
+        async fn authenticate(&self, request: &mut Request) -> std::result::Result<AuthContext, Response> {
+            if request.headers().get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer valid") {
+                return Err(StatusCode::UNAUTHORIZED.into_response());
+            }
+            Ok(AuthContext {
+                user_id: Uuid::new_v4(),
+                tenant_id: Uuid::new_v4(),
+                roles: vec!["cashier".to_string()],
+                permissions: vec![permissions::COMMERCE_ORDERS_READ.to_string()],
+                session_id: Uuid::new_v4().to_string(),
+                expires_at: chrono::Utc::now() + chrono::Duration::minutes(15),
+                location_id: None,
+            })
+        }
+    }
+
+    async fn status(app: &Router, uri: &str, token: Option<&str>) -> StatusCode {
+        let mut request = axum::http::Request::builder().uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", token);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    fn api() -> Router {
+        // Never connected: only handlers that skip the database are called
+        let db = sqlx::PgPool::connect_lazy("postgres://localhost/olympus").unwrap();
+
+        // Same nesting and permission layers as `create_order_router`
+        let orders = Router::new()
+            .route(
+                "/orders",
+                get(|| async { "[]" }).route_layer(RequirePermission(permissions::COMMERCE_ORDERS_READ)),
+            )
+            .route(
+                "/payments",
+                get(|| async { "[]" }).route_layer(RequirePermission(permissions::COMMERCE_PAYMENTS_READ)),
+            );
+
+        Router::new()
+            .nest("/api/v1/commerce", orders)
+            .nest("/api/v1", customer_security_routes().with_state(CustomerSecurityService::new(db)))
+    }
+
+    #[tokio::test]
+    async fn test_api_routes_require_authentication() {
+        let app = crate::with_authentication(api(), Arc::new(StaticAuthenticator));
+
+        assert_eq!(status(&app, "/health", None).await, StatusCode::OK);
+        assert_eq!(status(&app, "/api/v1/commerce/orders", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, "/api/v1/commerce/orders", Some("Bearer forged")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, "/api/v1/commerce/orders", Some("Bearer valid")).await, StatusCode::OK);
+        assert_eq!(status(&app, "/api/v1/commerce/payments", Some("Bearer valid")).await, StatusCode::FORBIDDEN);
+        // Left for the next router rather than rejected
+        assert_eq!(status(&app, "/api/v1/platform/feature-flags", None).await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_customer_security_routes_take_the_authenticated_caller() {
+        let app = crate::with_authentication(api(), Arc::new(StaticAuthenticator));
+
+        assert_eq!(status(&app, "/api/v1/security/incidents", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, "/api/v1/security/incidents", Some("Bearer valid")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_simple_router_requires_authentication() {
+        let db = Arc::new(sqlx::PgPool::connect_lazy("postgres://localhost/olympus").unwrap());
+        let app = crate::create_simple_router(db, Arc::new(StaticAuthenticator));
+        let tenant_id = Uuid::new_v4();
+
+        assert_eq!(status(&app, "/health", None).await, StatusCode::OK);
+        assert_eq!(
+            status(&app, &format!("/tenants/{}/products", tenant_id), None).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -0,0 +1,16 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - REFRESH TOKEN ROTATION
+-- ============================================================================
+-- Migration: 011_refresh_token_families.sql
+-- Description: Refresh token families for rotation and reuse detection
+-- Author: Claude Code Agent
+-- Date: 2025-01-20
+-- ============================================================================
+
+-- Every refresh token rotated from the same login shares a family id
+ALTER TABLE auth.user_sessions
+ADD COLUMN IF NOT EXISTS family_id UUID NOT NULL DEFAULT uuid_generate_v4(),
+ADD COLUMN IF NOT EXISTS replaced_by UUID REFERENCES auth.user_sessions(id) ON DELETE SET NULL;
+
+-- Reuse handling revokes a whole family at once
+CREATE INDEX IF NOT EXISTS idx_user_sessions_family ON auth.user_sessions(family_id);
@@ -0,0 +1,22 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - PASSWORD POLICY
+-- ============================================================================
+-- Migration: 012_password_history.sql
+-- Description: Password history for reuse checks and forced password rotation
+-- Author: Claude Code Agent
+-- Date: 2025-01-20
+-- ============================================================================
+
+-- Set by an administrator to make the user pick a new password at next login
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS force_password_change BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Previous password hashes, checked against the policy's history depth
+CREATE TABLE IF NOT EXISTS auth.password_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    password_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_history_user ON auth.password_history(user_id, created_at DESC);
@@ -0,0 +1,25 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - MAGIC LINK LOGIN
+-- ============================================================================
+-- Migration: 013_magic_links.sql
+-- Description: Single-use passwordless login links
+-- Author: Claude Code Agent
+-- Date: 2025-01-20
+-- ============================================================================
+
+-- Only the SHA-256 hash of the emailed token is stored
+CREATE TABLE IF NOT EXISTS auth.magic_link_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    ip_address TEXT NOT NULL,
+    user_agent TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_magic_link_tokens_user ON auth.magic_link_tokens(user_id);
+CREATE INDEX IF NOT EXISTS idx_magic_link_tokens_expiry ON auth.magic_link_tokens(expires_at)
+    WHERE consumed_at IS NULL;
@@ -0,0 +1,28 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - SUPPORT IMPERSONATION
+-- ============================================================================
+-- Migration: 014_impersonation_sessions.sql
+-- Description: Time-limited support sessions acting as tenant users
+-- Author: Claude Code Agent
+-- Date: 2025-01-20
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS auth.impersonation_sessions (
+    id UUID PRIMARY KEY,
+    impersonator_id UUID NOT NULL REFERENCES users(id),
+    impersonator_email VARCHAR(255) NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    read_only BOOLEAN NOT NULL DEFAULT TRUE,
+    ip_address TEXT NOT NULL,
+    user_agent TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ
+);
+
+-- get_sessions shows users every active session acting as them
+CREATE INDEX IF NOT EXISTS idx_impersonation_sessions_user ON auth.impersonation_sessions(user_id, expires_at)
+    WHERE ended_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_impersonation_sessions_impersonator ON auth.impersonation_sessions(impersonator_id);
@@ -0,0 +1,62 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - OAUTH2 AUTHORIZATION SERVER
+-- ============================================================================
+-- Migration: 015_oauth_authorization_server.sql
+-- Description: Partner OAuth clients, authorization codes and user consent
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+-- Third-party applications registered per tenant; public clients have no secret
+CREATE TABLE IF NOT EXISTS auth.oauth_clients (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    client_id VARCHAR(64) NOT NULL UNIQUE,
+    client_secret_hash VARCHAR(64),
+    name VARCHAR(255) NOT NULL,
+    redirect_uris TEXT[] NOT NULL DEFAULT '{}',
+    allowed_scopes TEXT[] NOT NULL DEFAULT '{}',
+    grant_types TEXT[] NOT NULL DEFAULT '{}',
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_clients_tenant ON auth.oauth_clients(tenant_id);
+
+-- Single-use codes; only the SHA-256 hash is stored, together with the PKCE challenge
+CREATE TABLE IF NOT EXISTS auth.oauth_authorization_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    code_hash VARCHAR(64) NOT NULL UNIQUE,
+    client_id UUID NOT NULL REFERENCES auth.oauth_clients(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    redirect_uri TEXT NOT NULL,
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    code_challenge TEXT NOT NULL,
+    code_challenge_method VARCHAR(10) NOT NULL DEFAULT 'S256',
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_codes_expiry ON auth.oauth_authorization_codes(expires_at)
+    WHERE consumed_at IS NULL;
+
+-- Scopes each user has granted each client
+CREATE TABLE IF NOT EXISTS auth.oauth_consents (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    client_id UUID NOT NULL REFERENCES auth.oauth_clients(id) ON DELETE CASCADE,
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, client_id)
+);
+
+-- OAuth grants reuse refresh token storage, tied to the client they were issued to
+ALTER TABLE auth.user_sessions
+ADD COLUMN IF NOT EXISTS client_id UUID REFERENCES auth.oauth_clients(id) ON DELETE CASCADE,
+ADD COLUMN IF NOT EXISTS scopes TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_user_sessions_client ON auth.user_sessions(client_id)
+    WHERE client_id IS NOT NULL;
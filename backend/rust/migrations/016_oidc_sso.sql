@@ -0,0 +1,41 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - OIDC SINGLE SIGN-ON
+-- ============================================================================
+-- Migration: 016_oidc_sso.sql
+-- Description: SSO login state and links between users and identity provider subjects
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+-- Relying party settings live in platform configurations under auth.sso.oidc,
+-- with the client secret stored separately as an encrypted value.
+
+-- Logins in flight; only the SHA-256 hash of the state parameter is stored
+CREATE TABLE IF NOT EXISTS auth.sso_login_states (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    state_hash VARCHAR(64) NOT NULL UNIQUE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sso_login_states_expires ON auth.sso_login_states(expires_at);
+
+-- A user's subject at their tenant's identity provider; the issuer and subject pair is stable
+-- even when the email address changes
+CREATE TABLE IF NOT EXISTS auth.user_identities (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    email VARCHAR(255),
+    last_login TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, issuer, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user ON auth.user_identities(user_id);
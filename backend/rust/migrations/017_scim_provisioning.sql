@@ -0,0 +1,21 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - SCIM 2.0 PROVISIONING
+-- ============================================================================
+-- Migration: 017_scim_provisioning.sql
+-- Description: External identifiers assigned by tenants' SCIM clients
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+-- SCIM users map onto users and SCIM groups onto roles / user_roles; only the
+-- client's externalId needs a home of its own
+CREATE TABLE IF NOT EXISTS auth.scim_external_ids (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    resource_type VARCHAR(10) NOT NULL CHECK (resource_type IN ('User', 'Group')),
+    resource_id UUID NOT NULL,
+    external_id TEXT NOT NULL,
+    PRIMARY KEY (tenant_id, resource_type, resource_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scim_external_ids_external
+    ON auth.scim_external_ids(tenant_id, resource_type, external_id);
@@ -0,0 +1,15 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - ROLE INHERITANCE
+-- ============================================================================
+-- Migration: 018_role_inheritance.sql
+-- Description: Parent roles for hierarchical RBAC
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+-- Names of roles whose grants a role inherits. Grants themselves stay in
+-- roles.permissions and may use wildcards (commerce.orders.*), explicit denies
+-- (!commerce.refunds.create) and scopes (commerce.refunds.create@location:<id>).
+ALTER TABLE roles ADD COLUMN IF NOT EXISTS parent_roles TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_roles_parent_roles ON roles USING GIN (parent_roles);
@@ -0,0 +1,34 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - POS TERMINAL QUICK-SWITCH
+-- ============================================================================
+-- Migration: 019_pos_terminals.sql
+-- Description: Device-bound shared terminals and staff PINs for quick user switching
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS auth.pos_terminals (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    location_id UUID NOT NULL REFERENCES locations(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    device_id VARCHAR(255) NOT NULL,
+    -- Wrong PINs count against the terminal, whichever user they were entered for
+    failed_pin_attempts INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMPTZ,
+    registered_by UUID NOT NULL REFERENCES users(id),
+    last_switch_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_pos_terminals_tenant ON auth.pos_terminals(tenant_id, location_id)
+    WHERE revoked_at IS NULL;
+
+-- Argon2 hashes only; a PIN is never stored or logged in clear
+CREATE TABLE IF NOT EXISTS auth.user_pins (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    pin_hash TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
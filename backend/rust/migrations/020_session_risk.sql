@@ -0,0 +1,23 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - SESSION RISK SCORING
+-- ============================================================================
+-- Migration: 020_session_risk.sql
+-- Description: GeoIP location and risk engine results on each session
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+-- The GeoIP resolver fills location ({country_code, city, latitude, longitude});
+-- sessions it could not place keep an empty object
+UPDATE auth.user_sessions SET location = '{}' WHERE location IS NULL;
+ALTER TABLE auth.user_sessions
+ALTER COLUMN location SET DEFAULT '{}',
+ALTER COLUMN location SET NOT NULL;
+
+-- Score and signals the risk engine gave the sign-in or refresh
+ALTER TABLE auth.user_sessions
+ADD COLUMN IF NOT EXISTS risk_score INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS risk_signals TEXT[] NOT NULL DEFAULT '{}';
+
+-- Impossible travel compares each login with the user's most recent sessions
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_recent ON auth.user_sessions(user_id, created_at DESC);
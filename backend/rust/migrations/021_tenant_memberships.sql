@@ -0,0 +1,48 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - TENANT INVITATIONS AND MEMBERSHIPS
+-- ============================================================================
+-- Migration: 021_tenant_memberships.sql
+-- Description: Invitations by email and memberships linking one identity to many tenants
+-- Author: Claude Code Agent
+-- Date: 2025-01-21
+-- ============================================================================
+
+-- A user's home tenant stays on users.tenant_id; rows here grant access to other tenants,
+-- or carry location limits for an account created from an invitation
+CREATE TABLE IF NOT EXISTS auth.tenant_memberships (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    roles TEXT[] NOT NULL DEFAULT '{}',
+    permissions TEXT[] NOT NULL DEFAULT '{}',
+    -- Empty for the whole tenant
+    location_ids UUID[] NOT NULL DEFAULT '{}',
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, tenant_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_memberships_tenant ON auth.tenant_memberships(tenant_id)
+    WHERE is_active;
+
+-- Only the hash of the emailed token is stored
+CREATE TABLE IF NOT EXISTS auth.invitations (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    roles TEXT[] NOT NULL DEFAULT '{}',
+    location_ids UUID[] NOT NULL DEFAULT '{}',
+    invited_by UUID NOT NULL REFERENCES users(id),
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    declined_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_invitations_tenant ON auth.invitations(tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_invitations_email ON auth.invitations(lower(email))
+    WHERE accepted_at IS NULL AND declined_at IS NULL AND revoked_at IS NULL;
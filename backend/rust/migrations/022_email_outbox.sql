@@ -0,0 +1,36 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - EMAIL OUTBOX
+-- ============================================================================
+-- Migration: 022_email_outbox.sql
+-- Description: Rendered transactional emails awaiting delivery, retried with backoff
+-- Author: Claude Code Agent
+-- Date: 2025-01-22
+-- ============================================================================
+
+-- Bodies contain single-use tokens, so sent rows are purged after a retention period
+CREATE TABLE IF NOT EXISTS auth.email_outbox (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Template plus the id of the event that caused the send; makes enqueueing idempotent
+    dedupe_key TEXT NOT NULL UNIQUE,
+    template VARCHAR(100) NOT NULL,
+    locale VARCHAR(16) NOT NULL,
+    recipient VARCHAR(255) NOT NULL,
+    from_email VARCHAR(255) NOT NULL,
+    from_name VARCHAR(255) NOT NULL,
+    subject TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- For claimed rows, the end of the worker's lease
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_outbox_due ON auth.email_outbox(next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_email_outbox_tenant ON auth.email_outbox(tenant_id, created_at DESC);
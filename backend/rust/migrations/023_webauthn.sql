@@ -0,0 +1,49 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - WEBAUTHN PASSKEYS
+-- ============================================================================
+-- Migration: 023_webauthn.sql
+-- Description: Passkey credentials per user and the challenges of ceremonies in flight
+-- Author: Claude Code Agent
+-- Date: 2025-01-22
+-- ============================================================================
+
+-- Passkeys enrolled as a second factor; backup codes for users whose first factor
+-- is a passkey are kept on this row
+ALTER TYPE auth.mfa_type ADD VALUE IF NOT EXISTS 'webauthn';
+
+CREATE TABLE IF NOT EXISTS auth.webauthn_credentials (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Base64url; generated by the authenticator, so unique across tenants
+    credential_id TEXT NOT NULL UNIQUE,
+    -- COSE_Key as the authenticator encoded it
+    public_key BYTEA NOT NULL,
+    algorithm INTEGER NOT NULL,
+    -- Compared on every assertion; a counter going backwards suggests a cloned authenticator
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    transports TEXT[] NOT NULL DEFAULT '{}',
+    aaguid UUID NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    backup_eligible BOOLEAN NOT NULL DEFAULT FALSE,
+    backed_up BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user ON auth.webauthn_credentials(user_id);
+
+-- Registrations and logins in flight; only the SHA-256 hash of the challenge is stored
+CREATE TABLE IF NOT EXISTS auth.webauthn_challenges (
+    id UUID PRIMARY KEY,
+    challenge_hash VARCHAR(64) NOT NULL UNIQUE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Unset for logins with discoverable credentials, where the passkey names the user
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    ceremony VARCHAR(20) NOT NULL CHECK (ceremony IN ('registration', 'authentication', 'mfa')),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_challenges_expires ON auth.webauthn_challenges(expires_at);
@@ -0,0 +1,23 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - FEATURE FLAG BUCKETING
+-- ============================================================================
+-- Migration: 024_feature_flag_bucketing.sql
+-- Description: Per-flag bucketing unit and hash salt for stable rollout assignment
+-- Author: Claude Code Agent
+-- Date: 2025-01-22
+-- ============================================================================
+
+DO $$ BEGIN
+    CREATE TYPE platform.flag_bucket_by AS ENUM ('user', 'tenant', 'location', 'device');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+ALTER TABLE platform.feature_flags
+    ADD COLUMN IF NOT EXISTS bucket_by platform.flag_bucket_by NOT NULL DEFAULT 'user',
+    -- Hashed together with the unit id; existing flags take their id so each gets its own
+    ADD COLUMN IF NOT EXISTS salt VARCHAR(255);
+
+UPDATE platform.feature_flags SET salt = id::text WHERE salt IS NULL;
+
+ALTER TABLE platform.feature_flags ALTER COLUMN salt SET NOT NULL;
@@ -0,0 +1,36 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - FEATURE FLAG ROLLOUT SCHEDULES
+-- ============================================================================
+-- Migration: 025_feature_flag_rollout_schedules.sql
+-- Description: Stepped rollout plans for gradual flags, advanced by the rollout scheduler
+-- Author: Claude Code Agent
+-- Date: 2025-01-22
+-- ============================================================================
+
+DO $$ BEGIN
+    CREATE TYPE platform.rollout_schedule_status AS ENUM ('active', 'paused', 'rolled_back', 'completed');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+-- One schedule per flag; creating a new one restarts it from the first step
+CREATE TABLE IF NOT EXISTS platform.feature_flag_rollout_schedules (
+    id UUID PRIMARY KEY,
+    flag_id UUID NOT NULL UNIQUE REFERENCES platform.feature_flags(id) ON DELETE CASCADE,
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    -- [{"percentage": 1, "dwell_minutes": 60}, ...]
+    steps JSONB NOT NULL,
+    -- [{"metric_name": "error_rate", "action": "rollback"}, ...]
+    guards JSONB NOT NULL DEFAULT '[]',
+    current_step INTEGER NOT NULL DEFAULT 0,
+    status platform.rollout_schedule_status NOT NULL DEFAULT 'active',
+    -- Dwell time of the current step counts from here; reset on resume
+    step_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    status_reason TEXT,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_feature_flag_rollout_schedules_active
+    ON platform.feature_flag_rollout_schedules(status) WHERE status = 'active';
@@ -0,0 +1,52 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - FEATURE FLAG DEPENDENCIES
+-- ============================================================================
+-- Migration: 026_feature_flag_dependencies.sql
+-- Description: Flag prerequisites, mutually exclusive experiment groups, the global
+--              kill switch and an audit trail of changes to them
+-- Author: Claude Code Agent
+-- Date: 2025-01-22
+-- ============================================================================
+
+ALTER TABLE platform.feature_flags
+    -- [{"key": "kds_enabled", "variant": null}, ...]
+    ADD COLUMN IF NOT EXISTS prerequisites JSONB NOT NULL DEFAULT '[]',
+    -- Members of a group take disjoint percentage ranges of the same user buckets
+    ADD COLUMN IF NOT EXISTS exclusion_group VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS exclusion_start DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS exclusion_end DOUBLE PRECISION,
+    -- Set by the kill switch; the flag serves default_value until revived
+    ADD COLUMN IF NOT EXISTS killed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS killed_by UUID,
+    ADD COLUMN IF NOT EXISTS kill_reason TEXT;
+
+DO $$ BEGIN
+    ALTER TABLE platform.feature_flags ADD CONSTRAINT feature_flags_exclusion_range CHECK (
+        (exclusion_group IS NULL AND exclusion_start IS NULL AND exclusion_end IS NULL)
+        OR (exclusion_group IS NOT NULL AND 0 <= exclusion_start AND exclusion_start < exclusion_end AND exclusion_end <= 100)
+    );
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_feature_flags_exclusion_group
+    ON platform.feature_flags(exclusion_group) WHERE exclusion_group IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_feature_flags_key
+    ON platform.feature_flags(key) WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS platform.feature_flag_audits (
+    id UUID PRIMARY KEY,
+    flag_id UUID NOT NULL REFERENCES platform.feature_flags(id) ON DELETE CASCADE,
+    flag_key VARCHAR(100) NOT NULL,
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    -- created, prerequisites_changed, exclusion_changed, killed, revived
+    action VARCHAR(50) NOT NULL,
+    old_value JSONB,
+    new_value JSONB,
+    changed_by UUID NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_feature_flag_audits_flag
+    ON platform.feature_flag_audits(flag_id, changed_at DESC);
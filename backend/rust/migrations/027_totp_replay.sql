@@ -0,0 +1,12 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - TOTP REPLAY PROTECTION
+-- ============================================================================
+-- Migration: 027_totp_replay.sql
+-- Description: Remember the last accepted TOTP time step so a code can't be reused
+-- Author: Claude Code Agent
+-- Date: 2025-01-22
+-- ============================================================================
+
+-- unix time / 30 of the last accepted code; codes from this or an earlier step are refused
+ALTER TABLE auth.user_mfa
+ADD COLUMN IF NOT EXISTS last_used_step BIGINT;
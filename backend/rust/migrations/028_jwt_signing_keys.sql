@@ -0,0 +1,28 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - JWT SIGNING KEYS
+-- ============================================================================
+-- Migration: 028_jwt_signing_keys.sql
+-- Description: Persisted JWT keyset, so staged and rotated keys survive restarts
+--              and are shared by every auth replica
+-- Author: Claude Code Agent
+-- Date: 2025-01-23
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS auth.jwt_signing_keys (
+    kid VARCHAR(100) PRIMARY KEY,
+    -- HS256, RS256, EdDSA
+    algorithm VARCHAR(20) NOT NULL,
+    -- AES-256-GCM sealed PEM (nonce || ciphertext || tag); NULL for the shared-secret key,
+    -- which every replica derives from its configured JWT secret
+    sealed_private_key BYTEA,
+    -- staged, active, retiring
+    status VARCHAR(20) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    promoted_at TIMESTAMPTZ,
+    retire_after TIMESTAMPTZ,
+    CONSTRAINT jwt_signing_keys_status CHECK (status IN ('staged', 'active', 'retiring'))
+);
+
+-- Exactly one key signs at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_jwt_signing_keys_active
+    ON auth.jwt_signing_keys(status) WHERE status = 'active';
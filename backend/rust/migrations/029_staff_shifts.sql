@@ -0,0 +1,25 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - STAFF SHIFTS
+-- ============================================================================
+-- Migration: 029_staff_shifts.sql
+-- Description: Scheduled staff shifts, read by access policies as the subject's
+--              shift window and working location
+-- Author: Claude Code Agent
+-- Date: 2025-01-23
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS commerce.staff_shifts (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Null for shifts not tied to one location
+    location_id UUID REFERENCES locations(id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    scheduled_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT staff_shifts_range CHECK (starts_at < ends_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_staff_shifts_user
+    ON commerce.staff_shifts(tenant_id, user_id, starts_at);
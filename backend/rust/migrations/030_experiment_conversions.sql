@@ -0,0 +1,38 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - EXPERIMENT CONVERSIONS
+-- ============================================================================
+-- Migration: 030_experiment_conversions.sql
+-- Description: Conversions reported for experiment analysis, and the exposure
+--              columns the feature flag service records evaluations with
+-- Author: Claude Code Agent
+-- Date: 2025-01-23
+-- ============================================================================
+
+-- Evaluations are recorded by flag key, with the variant served and when
+ALTER TABLE platform.feature_flag_evaluations
+    ALTER COLUMN feature_flag_id DROP NOT NULL,
+    ALTER COLUMN tenant_id DROP NOT NULL,
+    ALTER COLUMN evaluated_value DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS flag_key VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS is_enabled BOOLEAN,
+    ADD COLUMN IF NOT EXISTS value JSONB,
+    ADD COLUMN IF NOT EXISTS variant VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS reason TEXT,
+    ADD COLUMN IF NOT EXISTS evaluated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS idx_flag_evaluations_exposures
+    ON platform.feature_flag_evaluations(flag_key, user_id, evaluated_at);
+
+-- One row per conversion, e.g. an order placed; experiments count a user's first
+-- conversion after their first exposure
+CREATE TABLE IF NOT EXISTS platform.experiment_conversions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_experiment_conversions_user
+    ON platform.experiment_conversions(user_id, event_type, occurred_at);
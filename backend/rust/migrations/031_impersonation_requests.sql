@@ -0,0 +1,27 @@
+-- ============================================================================
+-- OLYMPUS CLOUD - IMPERSONATION AUDIT TRAIL
+-- ============================================================================
+-- Migration: 031_impersonation_requests.sql
+-- Description: Every request made with an impersonation token, including refused writes
+-- Author: Claude Code Agent
+-- Date: 2025-01-23
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS auth.impersonation_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    session_id UUID NOT NULL REFERENCES auth.impersonation_sessions(id) ON DELETE CASCADE,
+    impersonator_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    method VARCHAR(10) NOT NULL,
+    path TEXT NOT NULL,
+    -- HTTP status of the response, 403 for writes refused on a read-only session
+    status SMALLINT NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Reviewed per session, and per impersonator across sessions
+CREATE INDEX IF NOT EXISTS idx_impersonation_requests_session
+    ON auth.impersonation_requests(session_id, requested_at);
+CREATE INDEX IF NOT EXISTS idx_impersonation_requests_impersonator
+    ON auth.impersonation_requests(impersonator_id, requested_at);
@@ -22,15 +22,24 @@ use validator::Validate;
 use chrono::{DateTime, Utc};
 
 use olympus_shared::error::{Result, Error};
+use olympus_shared::authorization::RequirePermission;
+use olympus_shared::models::permission::permissions;
+use olympus_shared::events::commerce_events;
+use olympus_shared::flags::FlagRuleset;
 use crate::models::{
     FeatureFlag, FeatureFlagEvaluation, FeatureFlagUsage, FeatureFlagEvaluationRequest,
     CreateFeatureFlagRequest, UpdateFeatureFlagRequest,
+    RolloutSchedule, CreateRolloutScheduleRequest, ExperimentResults, ExperimentConversion,
+    KillFeatureFlagRequest, FeatureFlagAudit,
     Configuration, ConfigurationAudit, ConfigScope, ConfigType,
     ConfigurationSearchRequest, ConfigurationSearchResponse,
     CreateConfigurationRequest, UpdateConfigurationRequest,
 };
 use crate::services::{FeatureFlagsService, ConfigurationService};
 
+/// Largest exposure or conversion batch a flag client may post
+const MAX_EXPOSURE_BATCH: usize = 5_000;
+
 // ============================================================================
 // ROUTER CONFIGURATION
 // ============================================================================
@@ -41,24 +50,35 @@ pub fn create_configuration_router(
 ) -> Router {
     Router::new()
         // Feature Flag endpoints
-        .route("/feature-flags", post(create_feature_flag))
-        .route("/feature-flags", get(list_feature_flags))
-        .route("/feature-flags/:flag_id", get(get_feature_flag))
-        .route("/feature-flags/:flag_id", put(update_feature_flag))
-        .route("/feature-flags/:flag_id", delete(delete_feature_flag))
-        .route("/feature-flags/key/:flag_key", get(get_feature_flag_by_key))
-        .route("/feature-flags/evaluate", post(evaluate_feature_flag))
-        .route("/feature-flags/:flag_key/usage", get(get_feature_flag_usage))
+        .route("/feature-flags", post(create_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_WRITE)))
+        .route("/feature-flags", get(list_feature_flags).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/:flag_id", get(get_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/:flag_id", put(update_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_WRITE)))
+        .route("/feature-flags/:flag_id", delete(delete_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_WRITE)))
+        .route("/feature-flags/key/:flag_key", get(get_feature_flag_by_key).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/key/:flag_key/kill", post(kill_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_KILL)))
+        .route("/feature-flags/key/:flag_key/revive", post(revive_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_KILL)))
+        .route("/feature-flags/evaluate", post(evaluate_feature_flag).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/ruleset", get(get_flag_ruleset).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/exposures", post(record_flag_exposures).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/conversions", post(record_experiment_conversions).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/:flag_key/usage", get(get_feature_flag_usage).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/:flag_key/experiment", get(get_experiment_results).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/:flag_id/rollout", post(create_rollout_schedule).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_WRITE)))
+        .route("/feature-flags/:flag_id/rollout", get(get_rollout_schedule).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
+        .route("/feature-flags/:flag_id/rollout/pause", post(pause_rollout).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_WRITE)))
+        .route("/feature-flags/:flag_id/rollout/resume", post(resume_rollout).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_WRITE)))
+        .route("/feature-flags/:flag_id/audit", get(get_feature_flag_audit).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)))
 
         // System Configuration endpoints
-        .route("/configurations", post(create_configuration))
-        .route("/configurations", get(search_configurations))
-        .route("/configurations/:config_id", get(get_configuration))
-        .route("/configurations/:config_id", put(update_configuration))
-        .route("/configurations/:config_id", delete(delete_configuration))
-        .route("/configurations/:config_id/audit", get(get_configuration_audit))
-        .route("/configurations/scope/:scope", get(get_configurations_by_scope))
-        .route("/configurations/key/:scope/:key", get(get_configuration_by_key))
+        .route("/configurations", post(create_configuration).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_WRITE)))
+        .route("/configurations", get(search_configurations).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_READ)))
+        .route("/configurations/:config_id", get(get_configuration).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_READ)))
+        .route("/configurations/:config_id", put(update_configuration).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_WRITE)))
+        .route("/configurations/:config_id", delete(delete_configuration).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_WRITE)))
+        .route("/configurations/:config_id/audit", get(get_configuration_audit).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_READ)))
+        .route("/configurations/scope/:scope", get(get_configurations_by_scope).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_READ)))
+        .route("/configurations/key/:scope/:key", get(get_configuration_by_key).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_READ)))
 
         .with_state((feature_flags_service, config_service))
 }
@@ -95,6 +115,50 @@ pub struct FeatureFlagUsageResponse {
     pub message: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlagRulesetResponse {
+    pub success: bool,
+    pub data: FlagRuleset,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlagExposuresResponse {
+    pub success: bool,
+    /// Exposures recorded
+    pub data: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExperimentConversionsResponse {
+    pub success: bool,
+    /// Conversions recorded
+    pub data: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExperimentResultsResponse {
+    pub success: bool,
+    pub data: ExperimentResults,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RolloutScheduleResponse {
+    pub success: bool,
+    pub data: RolloutSchedule,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeatureFlagAuditResponse {
+    pub success: bool,
+    pub data: Vec<FeatureFlagAudit>,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigurationResponse {
     pub success: bool,
@@ -128,6 +192,15 @@ pub struct UsageQuery {
     pub period_end: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ExperimentQuery {
+    /// Event type counted as a conversion; defaults to `OrderCreated`
+    pub conversion_event: Option<String>,
+    pub control_variant: Option<String>,
+    pub period_start: Option<DateTime<Utc>>,
+    pub period_end: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ConfigScopeQuery {
     pub scope_id: Option<Uuid>,
@@ -306,6 +379,230 @@ pub async fn get_feature_flag_usage(
     }))
 }
 
+pub async fn get_flag_ruleset(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+) -> Result<Json<FlagRulesetResponse>> {
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+
+    let ruleset = feature_flags_service.get_flag_ruleset(tenant_id).await?;
+
+    Ok(Json(FlagRulesetResponse {
+        success: true,
+        data: ruleset,
+        message: "Feature flag ruleset retrieved successfully".to_string(),
+    }))
+}
+
+pub async fn record_flag_exposures(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Json(exposures): Json<Vec<FeatureFlagEvaluation>>,
+) -> Result<Json<FlagExposuresResponse>> {
+    if exposures.len() > MAX_EXPOSURE_BATCH {
+        return Err(Error::Validation(format!(
+            "At most {} exposures may be sent at once",
+            MAX_EXPOSURE_BATCH
+        )));
+    }
+
+    let recorded = feature_flags_service.record_flag_exposures(&exposures).await?;
+
+    Ok(Json(FlagExposuresResponse {
+        success: true,
+        data: recorded,
+        message: "Feature flag exposures recorded successfully".to_string(),
+    }))
+}
+
+/// Report conversions, e.g. orders placed, for experiment results to count
+pub async fn record_experiment_conversions(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Json(conversions): Json<Vec<ExperimentConversion>>,
+) -> Result<Json<ExperimentConversionsResponse>> {
+    if conversions.len() > MAX_EXPOSURE_BATCH {
+        return Err(Error::Validation(format!(
+            "At most {} conversions may be sent at once",
+            MAX_EXPOSURE_BATCH
+        )));
+    }
+
+    let recorded = feature_flags_service.record_experiment_conversions(&conversions).await?;
+
+    Ok(Json(ExperimentConversionsResponse {
+        success: true,
+        data: recorded,
+        message: "Experiment conversions recorded successfully".to_string(),
+    }))
+}
+
+pub async fn get_experiment_results(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_key): Path<String>,
+    Query(query): Query<ExperimentQuery>,
+) -> Result<Json<ExperimentResultsResponse>> {
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+    let now = Utc::now();
+
+    let period_start = query.period_start.unwrap_or_else(|| now - chrono::Duration::days(30));
+    let period_end = query.period_end.unwrap_or(now);
+    let conversion_event = query
+        .conversion_event
+        .unwrap_or_else(|| commerce_events::ORDER_CREATED.to_string());
+
+    let results = feature_flags_service
+        .get_experiment_results(
+            tenant_id,
+            &flag_key,
+            &conversion_event,
+            query.control_variant.as_deref(),
+            period_start,
+            period_end,
+        )
+        .await?
+        .ok_or_else(|| Error::NotFound("Feature flag not found".to_string()))?;
+
+    Ok(Json(ExperimentResultsResponse {
+        success: true,
+        data: results,
+        message: "Experiment results retrieved successfully".to_string(),
+    }))
+}
+
+pub async fn create_rollout_schedule(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_id): Path<Uuid>,
+    Json(request): Json<CreateRolloutScheduleRequest>,
+) -> Result<Json<RolloutScheduleResponse>> {
+    // Validate request
+    request.validate()
+        .map_err(|e| Error::Validation(format!("Invalid request: {}", e)))?;
+
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+    let created_by = Uuid::new_v4(); // Mock user ID
+
+    let schedule = feature_flags_service
+        .create_rollout_schedule(tenant_id, flag_id, request, created_by)
+        .await?;
+
+    Ok(Json(RolloutScheduleResponse {
+        success: true,
+        data: schedule,
+        message: "Rollout schedule created successfully".to_string(),
+    }))
+}
+
+pub async fn get_rollout_schedule(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_id): Path<Uuid>,
+) -> Result<Json<RolloutScheduleResponse>> {
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+
+    let schedule = feature_flags_service
+        .get_rollout_schedule(tenant_id, flag_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Rollout schedule not found".to_string()))?;
+
+    Ok(Json(RolloutScheduleResponse {
+        success: true,
+        data: schedule,
+        message: "Rollout schedule retrieved successfully".to_string(),
+    }))
+}
+
+pub async fn pause_rollout(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_id): Path<Uuid>,
+) -> Result<Json<RolloutScheduleResponse>> {
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+    let paused_by = Uuid::new_v4(); // Mock user ID
+
+    let schedule = feature_flags_service
+        .pause_rollout(tenant_id, flag_id, paused_by)
+        .await?;
+
+    Ok(Json(RolloutScheduleResponse {
+        success: true,
+        data: schedule,
+        message: "Rollout paused successfully".to_string(),
+    }))
+}
+
+pub async fn resume_rollout(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_id): Path<Uuid>,
+) -> Result<Json<RolloutScheduleResponse>> {
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+
+    let schedule = feature_flags_service
+        .resume_rollout(tenant_id, flag_id)
+        .await?;
+
+    Ok(Json(RolloutScheduleResponse {
+        success: true,
+        data: schedule,
+        message: "Rollout resumed successfully".to_string(),
+    }))
+}
+
+/// Serve the flag's default value in every tenant until it is revived
+pub async fn kill_feature_flag(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_key): Path<String>,
+    Json(request): Json<KillFeatureFlagRequest>,
+) -> Result<Json<FeatureFlagListResponse>> {
+    // Validate request
+    request.validate()
+        .map_err(|e| Error::Validation(format!("Invalid request: {}", e)))?;
+
+    let killed_by = Uuid::new_v4(); // Mock user ID
+
+    let flags = feature_flags_service
+        .kill_feature_flag(&flag_key, &request.reason, killed_by)
+        .await?;
+
+    let message = format!("Feature flag killed in {} tenants", flags.len());
+    Ok(Json(FeatureFlagListResponse {
+        success: true,
+        data: flags,
+        message,
+    }))
+}
+
+pub async fn revive_feature_flag(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_key): Path<String>,
+) -> Result<Json<FeatureFlagListResponse>> {
+    let revived_by = Uuid::new_v4(); // Mock user ID
+
+    let flags = feature_flags_service
+        .revive_feature_flag(&flag_key, revived_by)
+        .await?;
+
+    let message = format!("Feature flag revived in {} tenants", flags.len());
+    Ok(Json(FeatureFlagListResponse {
+        success: true,
+        data: flags,
+        message,
+    }))
+}
+
+pub async fn get_feature_flag_audit(
+    State((feature_flags_service, _)): State<(Arc<FeatureFlagsService>, Arc<ConfigurationService>)>,
+    Path(flag_id): Path<Uuid>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<FeatureFlagAuditResponse>> {
+    let tenant_id = Some(Uuid::new_v4()); // Mock tenant ID
+
+    let audits = feature_flags_service
+        .get_feature_flag_audit_history(tenant_id, flag_id, query.limit, query.offset)
+        .await?;
+
+    Ok(Json(FeatureFlagAuditResponse {
+        success: true,
+        data: audits,
+        message: "Feature flag audit history retrieved successfully".to_string(),
+    }))
+}
+
 // ============================================================================
 // SYSTEM CONFIGURATION HANDLERS
 // ============================================================================
@@ -21,6 +21,7 @@ use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 
+use olympus_shared::authorization::{Authenticate, Authenticator};
 use olympus_shared::database::DbPool;
 use olympus_shared::events::EventPublisher;
 use crate::handlers::create_configuration_router;
@@ -31,30 +32,41 @@ use crate::services::{FeatureFlagsService, ConfigurationService};
 pub struct PlatformConfig {
     pub db: Arc<DbPool>,
     pub event_publisher: Arc<EventPublisher>,
+    /// Verifies callers' tokens and API keys, normally the auth service
+    pub authenticator: Arc<dyn Authenticator>,
 }
 
 /// Create platform router with all endpoints and middleware
 pub fn create_router(config: PlatformConfig) -> Router {
     // Create services
-    let feature_flags_service = Arc::new(FeatureFlagsService::new(
-        config.db.clone(),
-        config.event_publisher.clone(),
-    ));
-
     let configuration_service = Arc::new(ConfigurationService::new(
         config.db.clone(),
         config.event_publisher.clone(),
     ));
 
-    Router::new()
-        // Health check
-        .route("/health", get(health_check))
+    let feature_flags_service = Arc::new(
+        FeatureFlagsService::new(config.db.clone(), config.event_publisher.clone())
+            .with_configuration(configuration_service.clone()),
+    );
 
+    let api = Router::new()
         // Configuration management routes (feature flags & system config)
         .nest("/api/v1/platform", create_configuration_router(
             feature_flags_service.clone(),
             configuration_service.clone(),
-        ))
+        ));
+
+    with_authentication(api, config.authenticator)
+}
+
+/// Serve `api` behind `authenticator`, which runs ahead of the routes' permission checks,
+/// next to the public health check. It is a route layer so unmatched requests still fall
+/// through to the next service's router.
+fn with_authentication(api: Router, authenticator: Arc<dyn Authenticator>) -> Router {
+    Router::new()
+        // Health check
+        .route("/health", get(health_check))
+        .merge(api.route_layer(Authenticate(authenticator)))
 
         // Middleware stack
         .layer(
@@ -89,4 +101,74 @@ pub use event_handlers::{
 };
 pub use handlers::*;
 pub use models::*;
-pub use services::*;
\ No newline at end of file
+pub use services::*;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        extract::Request,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    };
+    use olympus_shared::authorization::RequirePermission;
+    use olympus_shared::integration::go_gateway::AuthContext;
+    use olympus_shared::models::permission::permissions;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    /// Accepts `Bearer valid` with read access to feature flags
+    struct StaticAuthenticator;
+
+    #[async_trait::async_trait]
+    impl Authenticator for StaticAuthenticator {
+        async fn authenticate(&self, request: &mut Request) -> std::result::Result<AuthContext, Response> {
+            if request.headers().get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer valid") {
+                return Err(StatusCode::UNAUTHORIZED.into_response());
+            }
+            Ok(AuthContext {
+                user_id: Uuid::new_v4(),
+                tenant_id: Uuid::new_v4(),
+                roles: vec!["manager".to_string()],
+                permissions: vec![permissions::PLATFORM_FEATURE_FLAGS_READ.to_string()],
+                session_id: Uuid::new_v4().to_string(),
+                expires_at: chrono::Utc::now() + chrono::Duration::minutes(15),
+                location_id: None,
+            })
+        }
+    }
+
+    async fn status(app: &Router, uri: &str, token: Option<&str>) -> StatusCode {
+        let mut request = axum::http::Request::builder().uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", token);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_api_routes_require_authentication() {
+        // Same nesting and permission layers as `create_configuration_router`
+        let flags = Router::new()
+            .route(
+                "/feature-flags",
+                get(|| async { "[]" }).route_layer(RequirePermission(permissions::PLATFORM_FEATURE_FLAGS_READ)),
+            )
+            .route(
+                "/configurations",
+                get(|| async { "[]" }).route_layer(RequirePermission(permissions::PLATFORM_CONFIG_READ)),
+            );
+        let app = with_authentication(
+            Router::new().nest("/api/v1/platform", flags),
+            Arc::new(StaticAuthenticator),
+        );
+
+        assert_eq!(status(&app, "/health", None).await, StatusCode::OK);
+        assert_eq!(status(&app, "/api/v1/platform/feature-flags", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, "/api/v1/platform/feature-flags", Some("Bearer forged")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&app, "/api/v1/platform/feature-flags", Some("Bearer valid")).await, StatusCode::OK);
+        assert_eq!(status(&app, "/api/v1/platform/configurations", Some("Bearer valid")).await, StatusCode::FORBIDDEN);
+        // Left for the next router rather than rejected
+        assert_eq!(status(&app, "/api/v1/commerce/products", None).await, StatusCode::NOT_FOUND);
+    }
+}
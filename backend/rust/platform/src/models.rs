@@ -3,6 +3,10 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use validator::Validate;
 
+use olympus_shared::flags::{
+    BucketBy, EvaluationContext, ExclusionSlot, FlagDefinition, FlagPrerequisite, TargetingRules,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tenant {
     pub id: Uuid,
@@ -46,26 +50,8 @@ pub enum FeatureFlagType {
     JsonObject,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "feature_flag_status", rename_all = "lowercase")]
-pub enum FeatureFlagStatus {
-    Active,
-    Inactive,
-    Scheduled,
-    Expired,
-    Testing,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "rollout_strategy", rename_all = "snake_case")]
-pub enum RolloutStrategy {
-    AllUsers,
-    PercentageUsers,
-    SpecificUsers,
-    UserGroups,
-    GradualRollout,
-    ABTest,
-}
+// Evaluation lives in olympus-shared so local-evaluation clients share it
+pub use olympus_shared::flags::{FeatureFlagStatus, RolloutStrategy};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureFlag {
@@ -91,6 +77,48 @@ pub struct FeatureFlag {
     pub updated_by: Uuid,
     pub starts_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
+    /// Unit percentages and variants are assigned by
+    pub bucket_by: BucketBy,
+    /// Hashed with the unit id; changing it reshuffles the flag's assignments
+    pub salt: String,
+    /// Flags that must be on before this one evaluates
+    pub prerequisites: Vec<FlagPrerequisite>,
+    /// Share of an exclusion group, keeping users in at most one of its experiments
+    pub exclusion: Option<ExclusionSlot>,
+    /// Set by the kill switch, which serves `default_value` in every tenant
+    pub killed_at: Option<DateTime<Utc>>,
+    pub killed_by: Option<Uuid>,
+    pub kill_reason: Option<String>,
+}
+
+impl FeatureFlag {
+    /// The flag as evaluation sees it. Rules are validated when saved; rules written
+    /// before that are skipped rather than failing evaluation.
+    pub fn definition(&self) -> FlagDefinition {
+        let rules = TargetingRules::from_conditions(&self.conditions).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring targeting rules of flag {}: {}", self.key, e);
+            TargetingRules::default()
+        });
+
+        FlagDefinition {
+            key: self.key.clone(),
+            status: self.status,
+            default_value: self.default_value.clone(),
+            rollout_strategy: self.rollout_strategy,
+            rollout_percentage: self.rollout_percentage,
+            target_users: self.target_users.clone(),
+            target_groups: self.target_groups.clone(),
+            rules,
+            variants: self.variants.clone(),
+            bucket_by: self.bucket_by,
+            salt: self.salt.clone(),
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            prerequisites: self.prerequisites.clone(),
+            exclusion: self.exclusion.clone(),
+            killed: self.killed_at.is_some(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -114,6 +142,11 @@ pub struct CreateFeatureFlagRequest {
     pub is_global: bool,
     pub starts_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
+    pub bucket_by: Option<BucketBy>,
+    #[validate(length(min = 1, max = 255))]
+    pub salt: Option<String>,
+    pub prerequisites: Option<Vec<FlagPrerequisite>>,
+    pub exclusion: Option<ExclusionSlot>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -134,6 +167,33 @@ pub struct UpdateFeatureFlagRequest {
     pub tags: Option<Vec<String>>,
     pub starts_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
+    /// Replaces the flag's prerequisites; an empty list removes them
+    pub prerequisites: Option<Vec<FlagPrerequisite>>,
+    /// Moves the flag into, or within, an exclusion group
+    pub exclusion: Option<ExclusionSlot>,
+    /// Takes the flag out of its exclusion group
+    pub clear_exclusion: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct KillFeatureFlagRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+/// A change to a flag's dependencies or kill state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagAudit {
+    pub id: Uuid,
+    pub flag_id: Uuid,
+    pub flag_key: String,
+    pub tenant_id: Option<Uuid>,
+    pub action: String, // created, prerequisites_changed, exclusion_changed, killed, revived
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+    pub changed_by: Uuid,
+    pub changed_at: DateTime<Utc>,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -148,6 +208,76 @@ pub struct FeatureFlagEvaluation {
     pub evaluated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "rollout_schedule_status", rename_all = "snake_case")]
+pub enum RolloutScheduleStatus {
+    Active,
+    /// Held at the current step after a guard breach or by hand
+    Paused,
+    /// Flag set back to 0% after a guard breach
+    RolledBack,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardAction {
+    Pause,
+    Rollback,
+}
+
+/// One stage of a progressive rollout: the percentage served and how long to hold it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutStep {
+    pub percentage: f64,
+    pub dwell_minutes: i64,
+}
+
+/// A monitoring metric whose alert rule stops the rollout while it fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutGuardMetric {
+    pub metric_name: String,
+    pub action: GuardAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutSchedule {
+    pub id: Uuid,
+    pub flag_id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    pub steps: Vec<RolloutStep>,
+    pub guards: Vec<RolloutGuardMetric>,
+    /// Index into `steps` of the percentage currently served
+    pub current_step: i32,
+    pub status: RolloutScheduleStatus,
+    pub step_started_at: DateTime<Utc>,
+    /// Why the schedule was last paused or rolled back
+    pub status_reason: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RolloutSchedule {
+    pub fn step(&self) -> Option<&RolloutStep> {
+        usize::try_from(self.current_step).ok().and_then(|step| self.steps.get(step))
+    }
+
+    /// When the current step's dwell time ends
+    pub fn step_ends_at(&self) -> DateTime<Utc> {
+        let dwell = self.step().map_or(0, |step| step.dwell_minutes);
+        self.step_started_at + chrono::Duration::minutes(dwell)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateRolloutScheduleRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub steps: Vec<RolloutStep>,
+    #[serde(default)]
+    pub guards: Vec<RolloutGuardMetric>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureFlagUsage {
     pub flag_key: String,
@@ -160,6 +290,72 @@ pub struct FeatureFlagUsage {
     pub period_end: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Exposures and conversions of one variant, compared against the control
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantResults {
+    pub variant: String,
+    /// Share of exposures the variant weights call for
+    pub expected_share: f64,
+    pub exposures: i64,
+    pub conversions: i64,
+    pub conversion_rate: f64,
+    pub conversion_rate_interval: ConfidenceInterval,
+    /// Relative change in conversion rate over the control; unset for the control itself
+    pub lift: Option<f64>,
+    /// Absolute difference in conversion rate over the control, valid at any sample size
+    pub difference_interval: Option<ConfidenceInterval>,
+    /// Always-valid p-value, safe to check while the experiment runs
+    pub p_value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleRatioCheck {
+    pub chi_squared: f64,
+    pub p_value: f64,
+    pub mismatch: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentVerdict {
+    InsufficientData,
+    SampleRatioMismatch,
+    Inconclusive,
+    Significant,
+}
+
+/// A conversion reported for a user, e.g. an order placed; experiment results count it
+/// against the variant the user was first exposed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentConversion {
+    pub user_id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    /// Matched against the `conversion_event` of an experiment, e.g. `OrderCreated`
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentResults {
+    pub flag_key: String,
+    pub conversion_event: String,
+    pub control_variant: String,
+    pub variants: Vec<VariantResults>,
+    pub sample_ratio: SampleRatioCheck,
+    pub verdict: ExperimentVerdict,
+    /// Set when the verdict is significant; the control wins when every treatment loses
+    pub winner: Option<String>,
+    pub summary: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "config_scope", rename_all = "lowercase")]
 pub enum ConfigScope {
@@ -278,4 +474,32 @@ pub struct FeatureFlagEvaluationRequest {
     pub user_attributes: Option<serde_json::Value>,
     pub location_id: Option<Uuid>,
     pub custom_attributes: Option<serde_json::Value>,
+    /// Attributes targeting rules can reference, e.g. `tenant.tier` or `app_version`
+    #[serde(default)]
+    pub context: serde_json::Map<String, serde_json::Value>,
+}
+
+impl FeatureFlagEvaluationRequest {
+    /// The context targeting rules see: `context` plus the user, location and custom
+    /// attributes, with `user.id`, `tenant.id` and `location.id` always set
+    pub fn evaluation_context(&self, tenant_id: Option<Uuid>) -> EvaluationContext {
+        let mut context = EvaluationContext::new(self.context.clone());
+
+        for (prefix, attributes) in [("user.", &self.user_attributes), ("", &self.custom_attributes)] {
+            if let Some(serde_json::Value::Object(attributes)) = attributes {
+                for (name, value) in attributes {
+                    context = context.with_attribute(&format!("{}{}", prefix, name), value.clone());
+                }
+            }
+        }
+
+        context = context.with_attribute("user.id", serde_json::json!(self.user_id));
+        if let Some(tenant_id) = tenant_id {
+            context = context.with_attribute("tenant.id", serde_json::json!(tenant_id));
+        }
+        if let Some(location_id) = self.location_id {
+            context = context.with_attribute("location.id", serde_json::json!(location_id));
+        }
+        context
+    }
 }
\ No newline at end of file
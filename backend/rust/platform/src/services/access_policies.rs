@@ -0,0 +1,48 @@
+// ============================================================================
+// OLYMPUS CLOUD - ACCESS POLICY STORE
+// ============================================================================
+// Module: platform/src/services/access_policies.rs
+// Description: Loads tenants' attribute-based access policies from configuration
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use olympus_shared::{
+    error::{Error, Result},
+    policy::{Policy, PolicyStore, POLICY_CONFIG_KEY},
+};
+
+use crate::models::ConfigScope;
+use crate::services::ConfigurationService;
+
+/// Reads the `access_policies` tenant configuration, a JSON array of policies
+pub struct PlatformPolicyStore {
+    configuration: Arc<ConfigurationService>,
+}
+
+impl PlatformPolicyStore {
+    pub fn new(configuration: Arc<ConfigurationService>) -> Self {
+        Self { configuration }
+    }
+}
+
+#[async_trait]
+impl PolicyStore for PlatformPolicyStore {
+    async fn load(&self, tenant_id: Uuid) -> Result<Vec<Policy>> {
+        let Some(settings) = self
+            .configuration
+            .get_configuration_by_key(ConfigScope::Tenant, Some(tenant_id), POLICY_CONFIG_KEY, false)
+            .await?
+        else {
+            return Ok(vec![]);
+        };
+
+        // A malformed policy document must fail closed rather than silently drop rules
+        serde_json::from_value(settings.value)
+            .map_err(|e| Error::Configuration(format!("Invalid access policies for tenant {}: {}", tenant_id, e)))
+    }
+}
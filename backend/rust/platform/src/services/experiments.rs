@@ -0,0 +1,661 @@
+// ============================================================================
+// OLYMPUS CLOUD - EXPERIMENT ANALYSIS
+// ============================================================================
+// Module: platform/src/services/experiments.rs
+// Description: Conversion statistics for A/B test flags: lift, confidence
+//              intervals, sequential p-values and sample-ratio mismatch, from
+//              recorded exposures and reported conversions
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+//
+// Experiments are looked at while they run, so a fixed-horizon test would
+// declare winners far more often than its significance level promises. The
+// p-values here come from a mixture sequential probability ratio test (mSPRT,
+// Johari et al. 2017) evaluated after every day of data and carried as a
+// running minimum: they stay valid however often the dashboard is refreshed.
+// The difference intervals are the matching always-valid intervals.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::consts::PI;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value;
+use sqlx::PgExecutor;
+
+use olympus_shared::error::{Error, Result};
+
+use crate::models::{ConfidenceInterval, ExperimentVerdict, SampleRatioCheck, VariantResults};
+
+/// Family-wise significance level; split evenly across treatments
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Exposures every variant needs before a verdict is given
+pub const MIN_EXPOSURES: i64 = 100;
+
+/// Below this, the exposure split is treated as broken assignment or logging
+/// rather than chance
+pub const SRM_P_VALUE: f64 = 0.001;
+
+/// Variance of the mSPRT's normal prior on the difference in conversion rates;
+/// tuned to differences of around one percentage point
+const MIXING_VARIANCE: f64 = 1e-4;
+
+const Z_95: f64 = 1.959_964;
+
+/// Exposures first seen and conversions made on one day, per variant
+#[derive(Debug, Clone)]
+pub struct DailyCounts {
+    pub variant: String,
+    pub day: DateTime<Utc>,
+    pub exposures: i64,
+    pub conversions: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExperimentAnalysis {
+    pub control_variant: String,
+    pub variants: Vec<VariantResults>,
+    pub sample_ratio: SampleRatioCheck,
+    pub verdict: ExperimentVerdict,
+    pub winner: Option<String>,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    exposures: i64,
+    conversions: i64,
+}
+
+impl Totals {
+    fn rate(&self) -> f64 {
+        if self.exposures == 0 {
+            0.0
+        } else {
+            self.conversions as f64 / self.exposures as f64
+        }
+    }
+
+    fn variance(&self) -> f64 {
+        if self.exposures == 0 {
+            0.0
+        } else {
+            let rate = self.rate();
+            rate * (1.0 - rate) / self.exposures as f64
+        }
+    }
+}
+
+/// Count a flag's exposures and conversions per variant and day, from the evaluations
+/// recorded for it and the conversions reported to `platform.experiment_conversions`
+pub async fn daily_counts<'e, E: PgExecutor<'e>>(
+    executor: E,
+    flag_key: &str,
+    tenant_id: Option<Uuid>,
+    conversion_event: &str,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+) -> Result<Vec<DailyCounts>> {
+    let rows = sqlx::query!(
+        r#"
+        WITH exposures AS (
+            SELECT DISTINCT ON (user_id) user_id, variant, evaluated_at AS exposed_at
+            FROM platform.feature_flag_evaluations
+            WHERE flag_key = $1
+                AND (tenant_id = $2 OR tenant_id IS NULL)
+                AND user_id IS NOT NULL
+                AND variant IS NOT NULL
+                AND evaluated_at BETWEEN $3 AND $4
+            ORDER BY user_id, evaluated_at
+        ),
+        conversions AS (
+            SELECT x.user_id, x.variant, MIN(c.occurred_at) AS converted_at
+            FROM exposures x
+            JOIN platform.experiment_conversions c
+                ON c.user_id = x.user_id
+                AND c.event_type = $5
+                AND ($2::uuid IS NULL OR c.tenant_id = $2)
+                AND c.occurred_at >= x.exposed_at
+                AND c.occurred_at <= $4
+            GROUP BY x.user_id, x.variant
+        ),
+        daily AS (
+            SELECT variant, date_trunc('day', exposed_at) AS day, 1 AS exposed, 0 AS converted
+            FROM exposures
+            UNION ALL
+            SELECT variant, date_trunc('day', converted_at) AS day, 0 AS exposed, 1 AS converted
+            FROM conversions
+        )
+        SELECT
+            variant as "variant!",
+            day as "day!",
+            SUM(exposed)::bigint as "exposures!",
+            SUM(converted)::bigint as "conversions!"
+        FROM daily
+        GROUP BY variant, day
+        ORDER BY day, variant
+        "#,
+        flag_key,
+        tenant_id,
+        period_start,
+        period_end,
+        conversion_event
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| DailyCounts {
+            variant: row.variant,
+            day: row.day,
+            exposures: row.exposures,
+            conversions: row.conversions,
+        })
+        .collect())
+}
+
+/// Analyze an experiment from its daily counts and the flag's `{"variant": weight}`
+/// map. Without `control`, a variant named "control" is used, else the first by name.
+pub fn analyze(daily: &[DailyCounts], weights: &Value, control: Option<&str>) -> Result<ExperimentAnalysis> {
+    let weights: BTreeMap<String, f64> = serde_json::from_value(weights.clone()).unwrap_or_default();
+    let names: BTreeSet<String> = weights
+        .keys()
+        .cloned()
+        .chain(daily.iter().map(|counts| counts.variant.clone()))
+        .collect();
+    if names.is_empty() {
+        return Err(Error::Validation("Experiment has no variants".to_string()));
+    }
+
+    let control = match control {
+        Some(control) if names.contains(control) => control.to_string(),
+        Some(control) => return Err(Error::Validation(format!("Unknown control variant: {}", control))),
+        None if names.contains("control") => "control".to_string(),
+        None => names.iter().next().cloned().unwrap_or_default(),
+    };
+
+    let total_weight: f64 = weights.values().sum();
+    let expected_share = |variant: &str| {
+        if total_weight > 0.0 {
+            weights.get(variant).copied().unwrap_or(0.0) / total_weight
+        } else {
+            1.0 / names.len() as f64
+        }
+    };
+
+    // Replay the experiment day by day, keeping each treatment's smallest p-value
+    let days: BTreeSet<DateTime<Utc>> = daily.iter().map(|counts| counts.day).collect();
+    let mut totals: BTreeMap<&str, Totals> = names.iter().map(|name| (name.as_str(), Totals::default())).collect();
+    let mut p_values: BTreeMap<&str, f64> = BTreeMap::new();
+    for day in days {
+        for counts in daily.iter().filter(|counts| counts.day == day) {
+            if let Some(totals) = totals.get_mut(counts.variant.as_str()) {
+                totals.exposures += counts.exposures;
+                totals.conversions += counts.conversions;
+            }
+        }
+        let baseline = totals[control.as_str()];
+        for (&name, treatment) in totals.iter().filter(|(&name, _)| name != control) {
+            let p_value = sequential_p_value(treatment, &baseline);
+            p_values
+                .entry(name)
+                .and_modify(|min| *min = min.min(p_value))
+                .or_insert(p_value);
+        }
+    }
+
+    let baseline = totals[control.as_str()];
+    let variants: Vec<VariantResults> = totals
+        .iter()
+        .map(|(&name, counts)| {
+            let is_control = name == control;
+            VariantResults {
+                variant: name.to_string(),
+                expected_share: expected_share(name),
+                exposures: counts.exposures,
+                conversions: counts.conversions,
+                conversion_rate: counts.rate(),
+                conversion_rate_interval: wilson_interval(counts),
+                lift: (!is_control && baseline.rate() > 0.0)
+                    .then(|| (counts.rate() - baseline.rate()) / baseline.rate()),
+                difference_interval: if is_control { None } else { difference_interval(counts, &baseline) },
+                p_value: if is_control { None } else { Some(p_values.get(name).copied().unwrap_or(1.0)) },
+            }
+        })
+        .collect();
+
+    let sample_ratio = sample_ratio_check(&variants);
+    let (verdict, winner) = verdict(&variants, &control, &sample_ratio);
+    let summary = summarize(&variants, &control, verdict, winner.as_deref());
+
+    Ok(ExperimentAnalysis {
+        control_variant: control,
+        variants,
+        sample_ratio,
+        verdict,
+        winner,
+        summary,
+    })
+}
+
+fn verdict(
+    variants: &[VariantResults],
+    control: &str,
+    sample_ratio: &SampleRatioCheck,
+) -> (ExperimentVerdict, Option<String>) {
+    if variants.len() < 2 || variants.iter().any(|variant| variant.exposures < MIN_EXPOSURES) {
+        return (ExperimentVerdict::InsufficientData, None);
+    }
+    if sample_ratio.mismatch {
+        return (ExperimentVerdict::SampleRatioMismatch, None);
+    }
+
+    let control_rate = variants
+        .iter()
+        .find(|variant| variant.variant == control)
+        .map(|variant| variant.conversion_rate)
+        .unwrap_or_default();
+    // Bonferroni across treatments
+    let alpha = SIGNIFICANCE_LEVEL / (variants.len() - 1) as f64;
+    let significant: Vec<&VariantResults> = variants
+        .iter()
+        .filter(|variant| variant.p_value.is_some_and(|p| p < alpha))
+        .collect();
+    if significant.is_empty() {
+        return (ExperimentVerdict::Inconclusive, None);
+    }
+
+    let best = significant
+        .iter()
+        .filter(|variant| variant.conversion_rate > control_rate)
+        .max_by(|a, b| a.conversion_rate.total_cmp(&b.conversion_rate));
+    let winner = best.map_or_else(|| control.to_string(), |variant| variant.variant.clone());
+    (ExperimentVerdict::Significant, Some(winner))
+}
+
+fn summarize(variants: &[VariantResults], control: &str, verdict: ExperimentVerdict, winner: Option<&str>) -> String {
+    match verdict {
+        ExperimentVerdict::InsufficientData => format!(
+            "Not enough data yet: every variant needs at least {} exposures",
+            MIN_EXPOSURES
+        ),
+        ExperimentVerdict::SampleRatioMismatch => {
+            "Exposures do not match the variant weights; check assignment and exposure logging before trusting these results"
+                .to_string()
+        }
+        ExperimentVerdict::Inconclusive => {
+            format!("No variant differs significantly from {} yet", control)
+        }
+        ExperimentVerdict::Significant => match variants.iter().find(|variant| Some(variant.variant.as_str()) == winner) {
+            Some(VariantResults { variant, lift: Some(lift), .. }) => {
+                format!("{} beats {} with a lift of {:+.1}%", variant, control, lift * 100.0)
+            }
+            _ => format!("{} outperforms every other variant", control),
+        },
+    }
+}
+
+/// Always-valid p-value of the difference between two conversion rates at the
+/// current sample size
+fn sequential_p_value(treatment: &Totals, control: &Totals) -> f64 {
+    let variance = treatment.variance() + control.variance();
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    let difference = treatment.rate() - control.rate();
+    let mixed = variance + MIXING_VARIANCE;
+    let log_likelihood_ratio =
+        0.5 * (variance / mixed).ln() + MIXING_VARIANCE * difference.powi(2) / (2.0 * variance * mixed);
+    (-log_likelihood_ratio).exp().min(1.0)
+}
+
+/// The differences the mSPRT would not reject at the significance level
+fn difference_interval(treatment: &Totals, control: &Totals) -> Option<ConfidenceInterval> {
+    let variance = treatment.variance() + control.variance();
+    if variance <= 0.0 {
+        return None;
+    }
+    let difference = treatment.rate() - control.rate();
+    let mixed = variance + MIXING_VARIANCE;
+    let half_width = (variance * mixed / MIXING_VARIANCE
+        * (2.0 * (1.0 / SIGNIFICANCE_LEVEL).ln() + (mixed / variance).ln()))
+    .sqrt();
+    Some(ConfidenceInterval {
+        lower: difference - half_width,
+        upper: difference + half_width,
+    })
+}
+
+/// 95% Wilson score interval, which stays inside [0, 1] for small samples
+fn wilson_interval(counts: &Totals) -> ConfidenceInterval {
+    if counts.exposures == 0 {
+        return ConfidenceInterval { lower: 0.0, upper: 1.0 };
+    }
+    let n = counts.exposures as f64;
+    let rate = counts.rate();
+    let z2 = Z_95 * Z_95;
+    let denominator = 1.0 + z2 / n;
+    let center = (rate + z2 / (2.0 * n)) / denominator;
+    let half_width = Z_95 * (rate * (1.0 - rate) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    ConfidenceInterval {
+        lower: (center - half_width).max(0.0),
+        upper: (center + half_width).min(1.0),
+    }
+}
+
+/// Chi-squared goodness of fit of the exposures against the variant weights
+fn sample_ratio_check(variants: &[VariantResults]) -> SampleRatioCheck {
+    let expected: Vec<&VariantResults> = variants.iter().filter(|variant| variant.expected_share > 0.0).collect();
+    let total: i64 = expected.iter().map(|variant| variant.exposures).sum();
+    if expected.len() < 2 || total == 0 {
+        return SampleRatioCheck { chi_squared: 0.0, p_value: 1.0, mismatch: false };
+    }
+
+    let chi_squared: f64 = expected
+        .iter()
+        .map(|variant| {
+            let expected = total as f64 * variant.expected_share;
+            (variant.exposures as f64 - expected).powi(2) / expected
+        })
+        .sum();
+    let p_value = chi_squared_survival(chi_squared, (expected.len() - 1) as f64);
+    SampleRatioCheck {
+        chi_squared,
+        p_value,
+        mismatch: p_value < SRM_P_VALUE,
+    }
+}
+
+fn chi_squared_survival(chi_squared: f64, degrees_of_freedom: f64) -> f64 {
+    regularized_gamma_q(degrees_of_freedom / 2.0, chi_squared / 2.0)
+}
+
+/// Upper regularized incomplete gamma function Q(a, x), by series below a + 1
+/// and by continued fraction above
+fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefactor = (a * x.ln() - x - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..1000 {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        return (1.0 - sum * prefactor).clamp(0.0, 1.0);
+    }
+
+    // Modified Lentz
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut fraction = d;
+    for i in 1..1000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        fraction *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    (prefactor * fraction).clamp(0.0, 1.0)
+}
+
+/// Lanczos approximation, g = 7
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_403,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use serde_json::json;
+
+    /// Spread totals evenly over `days` days
+    fn counts(variant: &str, exposures: i64, conversions: i64, days: i64) -> Vec<DailyCounts> {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        (0..days)
+            .map(|day| DailyCounts {
+                variant: variant.to_string(),
+                day: start + Duration::days(day),
+                exposures: exposures / days + i64::from(day < exposures % days),
+                conversions: conversions / days + i64::from(day < conversions % days),
+            })
+            .collect()
+    }
+
+    fn result<'a>(analysis: &'a ExperimentAnalysis, variant: &str) -> &'a VariantResults {
+        analysis.variants.iter().find(|result| result.variant == variant).unwrap()
+    }
+
+    #[test]
+    fn test_chi_squared_survival() {
+        // Critical values at the 5% and 0.1% levels
+        assert!((chi_squared_survival(3.841, 1.0) - 0.05).abs() < 1e-3);
+        assert!((chi_squared_survival(5.991, 2.0) - 0.05).abs() < 1e-3);
+        assert!((chi_squared_survival(10.828, 1.0) - 0.001).abs() < 1e-4);
+        assert!((chi_squared_survival(148.2, 99.0) - 0.001).abs() < 1e-4);
+        assert_eq!(chi_squared_survival(0.0, 3.0), 1.0);
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_significant_winner() {
+        let weights = json!({ "control": 50, "grid": 50 });
+        let daily = [counts("control", 10_000, 1_000, 14), counts("grid", 10_000, 1_200, 14)].concat();
+
+        let analysis = analyze(&daily, &weights, None).unwrap();
+        assert_eq!(analysis.control_variant, "control");
+        assert_eq!(analysis.verdict, ExperimentVerdict::Significant);
+        assert_eq!(analysis.winner.as_deref(), Some("grid"));
+
+        let grid = result(&analysis, "grid");
+        assert!((grid.lift.unwrap() - 0.2).abs() < 1e-9);
+        assert!(grid.p_value.unwrap() < 0.01);
+        let interval = grid.difference_interval.unwrap();
+        assert!(interval.lower > 0.0 && interval.upper > 0.02);
+        let rate = grid.conversion_rate_interval;
+        assert!(rate.lower < 0.12 && rate.upper > 0.12);
+        assert!(result(&analysis, "control").p_value.is_none());
+    }
+
+    #[test]
+    fn test_equal_variants_are_inconclusive() {
+        let weights = json!({ "control": 50, "list": 50 });
+        let daily = [counts("control", 5_000, 500, 7), counts("list", 5_000, 505, 7)].concat();
+
+        let analysis = analyze(&daily, &weights, None).unwrap();
+        assert_eq!(analysis.verdict, ExperimentVerdict::Inconclusive);
+        assert!(analysis.winner.is_none());
+        let interval = result(&analysis, "list").difference_interval.unwrap();
+        assert!(interval.lower < 0.0 && interval.upper > 0.0);
+    }
+
+    #[test]
+    fn test_sequential_p_value_never_rises() {
+        // Treatment far ahead early, then the gap closes: the early evidence stands
+        let weights = json!({ "a": 50, "b": 50 });
+        let early = [counts("a", 2_000, 200, 2), counts("b", 2_000, 320, 2)].concat();
+        let mut late = early.clone();
+        late.extend(counts("a", 8_000, 900, 8).into_iter().map(|mut c| {
+            c.day += Duration::days(2);
+            c
+        }));
+        late.extend(counts("b", 8_000, 780, 8).into_iter().map(|mut c| {
+            c.day += Duration::days(2);
+            c
+        }));
+
+        let p_early = result(&analyze(&early, &weights, Some("a")).unwrap(), "b").p_value.unwrap();
+        let p_late = result(&analyze(&late, &weights, Some("a")).unwrap(), "b").p_value.unwrap();
+        assert!(p_late <= p_early);
+        assert!(p_early < 0.01);
+    }
+
+    #[test]
+    fn test_sample_ratio_mismatch() {
+        let weights = json!({ "control": 50, "grid": 30, "list": 20 });
+        let balanced = [
+            counts("control", 5_020, 500, 7),
+            counts("grid", 2_990, 300, 7),
+            counts("list", 1_990, 200, 7),
+        ]
+        .concat();
+        let skewed = [
+            counts("control", 5_000, 500, 7),
+            counts("grid", 3_300, 330, 7),
+            counts("list", 1_700, 170, 7),
+        ]
+        .concat();
+
+        assert!(!analyze(&balanced, &weights, None).unwrap().sample_ratio.mismatch);
+        let analysis = analyze(&skewed, &weights, None).unwrap();
+        assert!(analysis.sample_ratio.mismatch);
+        assert_eq!(analysis.verdict, ExperimentVerdict::SampleRatioMismatch);
+    }
+
+    #[test]
+    fn test_insufficient_data_and_unknown_control() {
+        let weights = json!({ "control": 50, "grid": 50 });
+        let daily = [counts("control", 80, 40, 2), counts("grid", 80, 10, 2)].concat();
+
+        let analysis = analyze(&daily, &weights, None).unwrap();
+        assert_eq!(analysis.verdict, ExperimentVerdict::InsufficientData);
+        assert!(analyze(&daily, &weights, Some("tiles")).is_err());
+        assert!(analyze(&[], &json!({}), None).is_err());
+    }
+
+    /// Run against a migrated database: `DATABASE_URL=... cargo test -p olympus-platform -- --ignored`
+    #[tokio::test]
+    #[ignore] // Requires Postgres
+    async fn test_daily_counts_from_recorded_rows() {
+        let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "postgres://localhost/olympus".to_string());
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        // Rolled back when dropped, so nothing is left behind
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SET LOCAL TIME ZONE 'UTC'").execute(&mut *tx).await.unwrap();
+
+        let tenant_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, slug, name, display_name) VALUES ($1, $2, 'Bistro', 'Bistro')")
+            .bind(tenant_id)
+            .bind(format!("bistro-{}", tenant_id.simple()))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let users: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        for user_id in &users {
+            sqlx::query("INSERT INTO users (id, tenant_id, email, password_hash) VALUES ($1, $2, $3, 'hash')")
+                .bind(user_id)
+                .bind(tenant_id)
+                .bind(format!("{}@example.com", user_id.simple()))
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+        }
+
+        let flag_key = format!("menu-layout-{}", tenant_id.simple());
+        let start = (Utc::now() - Duration::days(3)).date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let at = |hours: i64| start + Duration::hours(hours);
+
+        // The second exposure of user 0 is to another variant and doesn't count
+        let exposures = [(0, "control", 1), (0, "grid", 30), (1, "grid", 2), (2, "grid", 26), (3, "control", 27)];
+        for (user, variant, hours) in exposures {
+            sqlx::query(
+                "INSERT INTO platform.feature_flag_evaluations \
+                 (flag_key, user_id, tenant_id, is_enabled, value, variant, reason, evaluated_at) \
+                 VALUES ($1, $2, $3, true, '{}', $4, 'variant', $5)",
+            )
+            .bind(&flag_key)
+            .bind(users[user])
+            .bind(tenant_id)
+            .bind(variant)
+            .bind(at(hours))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        // User 0 converts before and after exposure, user 1 twice, user 2 only with another event
+        let conversions = [(0, "OrderCreated", 0), (0, "OrderCreated", 40), (1, "OrderCreated", 3), (1, "OrderCreated", 50), (2, "PaymentProcessed", 28)];
+        for (user, event_type, hours) in conversions {
+            sqlx::query(
+                "INSERT INTO platform.experiment_conversions (user_id, tenant_id, event_type, occurred_at) \
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(users[user])
+            .bind(tenant_id)
+            .bind(event_type)
+            .bind(at(hours))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let daily = daily_counts(&mut *tx, &flag_key, Some(tenant_id), "OrderCreated", start, Utc::now())
+            .await
+            .unwrap();
+        let totals = |variant: &str| {
+            daily
+                .iter()
+                .filter(|counts| counts.variant == variant)
+                .fold((0, 0), |(exposures, conversions), counts| {
+                    (exposures + counts.exposures, conversions + counts.conversions)
+                })
+        };
+
+        assert_eq!(totals("control"), (2, 1));
+        assert_eq!(totals("grid"), (2, 1));
+        // Conversions land on the day they were made, not the day of exposure
+        let converted_on: Vec<i64> = daily
+            .iter()
+            .filter(|counts| counts.conversions > 0)
+            .map(|counts| (counts.day - start).num_days())
+            .collect();
+        assert_eq!(converted_on, vec![0, 1]);
+    }
+}
@@ -8,7 +8,6 @@
 // ============================================================================
 
 use std::sync::Arc;
-use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use sqlx::{Row, query, query_as};
@@ -16,25 +15,41 @@ use rand::Rng;
 
 use olympus_shared::{
     database::DbPool,
-    events::{EventPublisher, DomainEvent},
+    events::{EventPublisher, DomainEvent, EventContainer, FeatureFlagChangedEvent, platform_events},
     error::{Result, Error},
+    flags::{
+        prerequisite_cycle, BucketBy, ExclusionSlot, FlagEvaluation, FlagPrerequisite, FlagRuleset, Flags,
+        Segments, TargetingRules, MAX_PREREQUISITE_DEPTH, SEGMENTS_CONFIG_KEY,
+    },
 };
 
 use crate::models::{
     FeatureFlag, FeatureFlagType, FeatureFlagStatus, RolloutStrategy,
     FeatureFlagEvaluation, FeatureFlagUsage, FeatureFlagEvaluationRequest,
-    CreateFeatureFlagRequest, UpdateFeatureFlagRequest,
+    CreateFeatureFlagRequest, UpdateFeatureFlagRequest, ConfigScope,
+    RolloutSchedule, RolloutScheduleStatus, CreateRolloutScheduleRequest, ExperimentResults,
+    ExperimentConversion,
+    FeatureFlagAudit,
 };
+use crate::services::ConfigurationService;
+use crate::services::experiments;
 
 #[derive(Clone)]
 pub struct FeatureFlagsService {
     db: Arc<DbPool>,
     event_publisher: Arc<EventPublisher>,
+    /// Source of tenants' targeting segments; without it segment references never match
+    configuration: Option<Arc<ConfigurationService>>,
 }
 
 impl FeatureFlagsService {
     pub fn new(db: Arc<DbPool>, event_publisher: Arc<EventPublisher>) -> Self {
-        Self { db, event_publisher }
+        Self { db, event_publisher, configuration: None }
+    }
+
+    pub fn with_configuration(mut self, configuration: Arc<ConfigurationService>) -> Self {
+        self.configuration = Some(configuration);
+        self
     }
 
     // ============================================================================
@@ -65,9 +80,19 @@ impl FeatureFlagsService {
         // Serialize JSON fields
         let conditions_json = request.conditions.unwrap_or_else(|| serde_json::json!({}));
         let variants_json = request.variants.unwrap_or_else(|| serde_json::json!({}));
+
+        // Targeting rules are evaluated on every request, so reject malformed ones up front
+        TargetingRules::from_conditions(&conditions_json)?.validate(&variants_json)?;
         let target_users = request.target_users.unwrap_or_default();
         let target_groups = request.target_groups.unwrap_or_default();
         let tags = request.tags.unwrap_or_default();
+        let bucket_by = request.bucket_by.unwrap_or_default();
+        // A random salt keeps this flag's buckets independent of every other flag's
+        let salt = request.salt.unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+        let prerequisites = request.prerequisites.unwrap_or_default();
+        self.validate_flag_dependencies(tenant_id, &request.key, None, &prerequisites, request.exclusion.as_ref())
+            .await?;
+        let exclusion = request.exclusion.as_ref();
 
         // Insert feature flag
         let flag_row = query_as!(
@@ -78,9 +103,10 @@ impl FeatureFlagsService {
                 default_value, rollout_strategy, rollout_percentage,
                 target_users, target_groups, conditions, variants, tags,
                 is_global, created_at, updated_at, created_by, updated_by,
-                starts_at, ends_at
+                starts_at, ends_at, bucket_by, salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28)
             RETURNING
                 id, tenant_id, key, name, description,
                 flag_type as "flag_type: FeatureFlagType",
@@ -88,7 +114,10 @@ impl FeatureFlagsService {
                 default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
                 rollout_percentage, target_users, target_groups, conditions,
                 variants, tags, is_global, created_at, updated_at,
-                created_by, updated_by, starts_at, ends_at
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
             "#,
             flag_id,
             tenant_id,
@@ -111,7 +140,13 @@ impl FeatureFlagsService {
             created_by,
             created_by,
             request.starts_at,
-            request.ends_at
+            request.ends_at,
+            bucket_by as BucketBy,
+            salt,
+            serde_json::to_value(&prerequisites)?,
+            exclusion.map(|slot| slot.group.clone()),
+            exclusion.map(|slot| slot.start),
+            exclusion.map(|slot| slot.end)
         )
         .fetch_one(self.db.as_ref())
         .await
@@ -119,6 +154,19 @@ impl FeatureFlagsService {
 
         let flag = self.flag_row_to_model(flag_row)?;
 
+        self.create_flag_audit_record(
+            &flag,
+            "created",
+            None,
+            Some(serde_json::json!({
+                "prerequisites": flag.prerequisites,
+                "exclusion": flag.exclusion,
+            })),
+            created_by,
+            None,
+        )
+        .await?;
+
         // Publish domain event
         let event = DomainEvent::builder()
             .data(serde_json::json!({
@@ -156,7 +204,10 @@ impl FeatureFlagsService {
                 default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
                 rollout_percentage, target_users, target_groups, conditions,
                 variants, tags, is_global, created_at, updated_at,
-                created_by, updated_by, starts_at, ends_at
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
             FROM platform.feature_flags
             WHERE id = $1 AND (tenant_id = $2 OR tenant_id IS NULL) AND deleted_at IS NULL
             "#,
@@ -188,7 +239,10 @@ impl FeatureFlagsService {
                 default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
                 rollout_percentage, target_users, target_groups, conditions,
                 variants, tags, is_global, created_at, updated_at,
-                created_by, updated_by, starts_at, ends_at
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
             FROM platform.feature_flags
             WHERE key = $1 AND (tenant_id = $2 OR tenant_id IS NULL) AND deleted_at IS NULL
             ORDER BY tenant_id NULLS LAST
@@ -216,6 +270,16 @@ impl FeatureFlagsService {
     ) -> Result<Option<FeatureFlag>> {
         let now = Utc::now();
 
+        let changes_dependencies = request.prerequisites.is_some()
+            || request.exclusion.is_some()
+            || request.clear_exclusion.unwrap_or(false);
+        if changes_dependencies {
+            let Some(flag) = self.get_feature_flag(tenant_id, flag_id).await? else {
+                return Ok(None);
+            };
+            self.update_flag_dependencies(&flag, &request, updated_by).await?;
+        }
+
         // Simplified update - in production, would build dynamic query
         if let Some(status) = request.status {
             let flag_row = query_as!(
@@ -231,7 +295,10 @@ impl FeatureFlagsService {
                     default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
                     rollout_percentage, target_users, target_groups, conditions,
                     variants, tags, is_global, created_at, updated_at,
-                    created_by, updated_by, starts_at, ends_at
+                    created_by, updated_by, starts_at, ends_at,
+                    bucket_by as "bucket_by: BucketBy", salt,
+                    prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                    killed_at, killed_by, kill_reason
                 "#,
                 flag_id,
                 tenant_id,
@@ -331,38 +398,28 @@ impl FeatureFlagsService {
         // Get the feature flag
         let flag = self.get_feature_flag_by_key(tenant_id, &request.flag_key).await?;
 
-        let (is_enabled, value, variant, reason) = match flag {
+        let result = match flag {
             Some(flag) => {
-                // Check if flag is active and within time bounds
-                if flag.status != FeatureFlagStatus::Active {
-                    (false, flag.default_value, None, "Flag is not active".to_string())
-                } else if let Some(starts_at) = flag.starts_at {
-                    if now < starts_at {
-                        (false, flag.default_value, None, "Flag has not started yet".to_string())
-                    } else {
-                        self.evaluate_flag_logic(&flag, &request).await?
-                    }
-                } else if let Some(ends_at) = flag.ends_at {
-                    if now > ends_at {
-                        (false, flag.default_value, None, "Flag has expired".to_string())
-                    } else {
-                        self.evaluate_flag_logic(&flag, &request).await?
-                    }
+                let definition = flag.definition();
+                let flags = self.load_prerequisites(tenant_id, &definition.prerequisites).await?;
+                let segments = if definition.needs_segments() || flags.values().any(|flag| flag.needs_segments()) {
+                    self.load_segments(tenant_id).await
                 } else {
-                    self.evaluate_flag_logic(&flag, &request).await?
-                }
+                    Segments::new()
+                };
+                definition.evaluate(&flags, &request.evaluation_context(tenant_id), &segments, now)
             }
-            None => (false, serde_json::Value::Bool(false), None, "Flag not found".to_string()),
+            None => FlagEvaluation::not_found(),
         };
 
         let evaluation = FeatureFlagEvaluation {
             flag_key: request.flag_key.clone(),
             user_id: request.user_id,
             tenant_id,
-            is_enabled,
-            value,
-            variant,
-            reason,
+            is_enabled: result.is_enabled,
+            value: result.value,
+            variant: result.variant,
+            reason: result.reason,
             evaluated_at: now,
         };
 
@@ -372,102 +429,102 @@ impl FeatureFlagsService {
         Ok(evaluation)
     }
 
-    async fn evaluate_flag_logic(
-        &self,
-        flag: &FeatureFlag,
-        request: &FeatureFlagEvaluationRequest,
-    ) -> Result<(bool, serde_json::Value, Option<String>, String)> {
-        match flag.rollout_strategy {
-            RolloutStrategy::AllUsers => {
-                Ok((true, flag.default_value.clone(), None, "All users enabled".to_string()))
-            }
+    /// The flags `prerequisites` name, and theirs in turn, as far as evaluation follows them
+    async fn load_prerequisites(&self, tenant_id: Option<Uuid>, prerequisites: &[FlagPrerequisite]) -> Result<Flags> {
+        let mut flags = Flags::new();
+        let mut pending: Vec<String> = prerequisites.iter().map(|prerequisite| prerequisite.key.clone()).collect();
 
-            RolloutStrategy::PercentageUsers => {
-                let percentage = flag.rollout_percentage.unwrap_or(0.0);
-                let user_hash = self.hash_user_for_percentage(request.user_id, &flag.key);
-                let is_enabled = user_hash < percentage;
-                Ok((
-                    is_enabled,
-                    flag.default_value.clone(),
-                    None,
-                    format!("Percentage rollout: {}%", percentage),
-                ))
+        for _ in 0..MAX_PREREQUISITE_DEPTH {
+            let mut next = Vec::new();
+            for key in pending {
+                if flags.contains_key(&key) {
+                    continue;
+                }
+                if let Some(flag) = self.get_feature_flag_by_key(tenant_id, &key).await? {
+                    let definition = flag.definition();
+                    next.extend(definition.prerequisites.iter().map(|prerequisite| prerequisite.key.clone()));
+                    flags.insert(key, definition);
+                }
             }
-
-            RolloutStrategy::SpecificUsers => {
-                let is_enabled = flag.target_users.contains(&request.user_id);
-                Ok((
-                    is_enabled,
-                    flag.default_value.clone(),
-                    None,
-                    if is_enabled { "User in target list" } else { "User not in target list" }.to_string(),
-                ))
+            if next.is_empty() {
+                break;
             }
+            pending = next;
+        }
 
-            RolloutStrategy::UserGroups => {
-                // Simplified - would check user's groups against target_groups
-                Ok((false, flag.default_value.clone(), None, "User groups not implemented".to_string()))
-            }
+        Ok(flags)
+    }
 
-            RolloutStrategy::GradualRollout => {
-                let percentage = flag.rollout_percentage.unwrap_or(0.0);
-                let user_hash = self.hash_user_for_percentage(request.user_id, &flag.key);
-                let is_enabled = user_hash < percentage;
-                Ok((
-                    is_enabled,
-                    flag.default_value.clone(),
-                    None,
-                    format!("Gradual rollout: {}%", percentage),
-                ))
-            }
+    /// Every flag a tenant sees, global flags included, with its segments, for
+    /// clients that evaluate locally
+    pub async fn get_flag_ruleset(&self, tenant_id: Option<Uuid>) -> Result<FlagRuleset> {
+        let flag_rows = query_as!(
+            FeatureFlagRow,
+            r#"
+            SELECT
+                id, tenant_id, key, name, description,
+                flag_type as "flag_type: FeatureFlagType",
+                status as "status: FeatureFlagStatus",
+                default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
+                rollout_percentage, target_users, target_groups, conditions,
+                variants, tags, is_global, created_at, updated_at,
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
+            FROM platform.feature_flags
+            WHERE (tenant_id = $1 OR tenant_id IS NULL) AND deleted_at IS NULL
+            ORDER BY key
+            "#,
+            tenant_id
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to get flag ruleset: {}", e)))?;
 
-            RolloutStrategy::ABTest => {
-                // A/B test logic - assign users to variants
-                let variant = self.assign_ab_test_variant(request.user_id, &flag.key, &flag.variants);
-                let is_enabled = variant.is_some();
-                Ok((
-                    is_enabled,
-                    flag.default_value.clone(),
-                    variant,
-                    "A/B test variant assignment".to_string(),
-                ))
-            }
+        let mut flags = Vec::with_capacity(flag_rows.len());
+        for row in flag_rows {
+            flags.push(self.flag_row_to_model(row)?.definition());
         }
-    }
 
-    fn hash_user_for_percentage(&self, user_id: Uuid, flag_key: &str) -> f64 {
-        // Simple hash-based percentage calculation for consistent user assignment
-        let combined = format!("{}{}", user_id, flag_key);
-        let hash = combined.chars().map(|c| c as u32).sum::<u32>();
-        (hash % 100) as f64
+        Ok(FlagRuleset {
+            tenant_id,
+            flags,
+            segments: self.load_segments(tenant_id).await,
+            generated_at: Utc::now(),
+        })
     }
 
-    fn assign_ab_test_variant(
-        &self,
-        user_id: Uuid,
-        flag_key: &str,
-        variants: &serde_json::Value,
-    ) -> Option<String> {
-        // Simplified A/B test variant assignment
-        if let Ok(variant_map) = serde_json::from_value::<HashMap<String, f64>>(variants.clone()) {
-            let user_hash = self.hash_user_for_percentage(user_id, flag_key);
-            let mut cumulative = 0.0;
-
-            for (variant_name, weight) in variant_map {
-                cumulative += weight;
-                if user_hash < cumulative {
-                    return Some(variant_name);
-                }
+    /// A tenant's segments; a malformed document is logged and treated as empty so
+    /// that segment rules fall through instead of failing every evaluation
+    async fn load_segments(&self, tenant_id: Option<Uuid>) -> Segments {
+        let (Some(configuration), Some(tenant_id)) = (&self.configuration, tenant_id) else {
+            return Segments::new();
+        };
+
+        let settings = match configuration
+            .get_configuration_by_key(ConfigScope::Tenant, Some(tenant_id), SEGMENTS_CONFIG_KEY, false)
+            .await
+        {
+            Ok(Some(settings)) => settings,
+            Ok(None) => return Segments::new(),
+            Err(e) => {
+                tracing::warn!("Failed to load flag segments for tenant {}: {}", tenant_id, e);
+                return Segments::new();
             }
-        }
-        None
+        };
+
+        serde_json::from_value(settings.value).unwrap_or_else(|e| {
+            tracing::warn!("Invalid flag segments for tenant {}: {}", tenant_id, e);
+            Segments::new()
+        })
     }
 
     async fn record_flag_evaluation(&self, evaluation: &FeatureFlagEvaluation) -> Result<()> {
         // Record evaluation for analytics
         query!(
             r#"
-            INSERT INTO feature_flag_evaluations (
+            INSERT INTO platform.feature_flag_evaluations (
                 flag_key, user_id, tenant_id, is_enabled, value, variant,
                 reason, evaluated_at
             )
@@ -489,6 +546,715 @@ impl FeatureFlagsService {
         Ok(())
     }
 
+    /// Record evaluations made locally by flag clients, in one statement per batch
+    pub async fn record_flag_exposures(&self, exposures: &[FeatureFlagEvaluation]) -> Result<u64> {
+        if exposures.is_empty() {
+            return Ok(0);
+        }
+
+        let flag_keys: Vec<String> = exposures.iter().map(|e| e.flag_key.clone()).collect();
+        let user_ids: Vec<Uuid> = exposures.iter().map(|e| e.user_id).collect();
+        let tenant_ids: Vec<Option<Uuid>> = exposures.iter().map(|e| e.tenant_id).collect();
+        let enabled: Vec<bool> = exposures.iter().map(|e| e.is_enabled).collect();
+        let values: Vec<serde_json::Value> = exposures.iter().map(|e| e.value.clone()).collect();
+        let variants: Vec<Option<String>> = exposures.iter().map(|e| e.variant.clone()).collect();
+        let reasons: Vec<String> = exposures.iter().map(|e| e.reason.clone()).collect();
+        let evaluated_at: Vec<DateTime<Utc>> = exposures.iter().map(|e| e.evaluated_at).collect();
+
+        let recorded = query!(
+            r#"
+            INSERT INTO platform.feature_flag_evaluations (
+                flag_key, user_id, tenant_id, is_enabled, value, variant,
+                reason, evaluated_at
+            )
+            SELECT * FROM UNNEST(
+                $1::text[], $2::uuid[], $3::uuid[], $4::bool[], $5::jsonb[], $6::text[],
+                $7::text[], $8::timestamptz[]
+            )
+            "#,
+            &flag_keys,
+            &user_ids,
+            &tenant_ids as &[Option<Uuid>],
+            &enabled,
+            &values,
+            &variants as &[Option<String>],
+            &reasons,
+            &evaluated_at
+        )
+        .execute(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record flag exposures: {}", e)))?
+        .rows_affected();
+
+        Ok(recorded)
+    }
+
+    /// Record conversions for experiment analysis, in one statement per batch
+    pub async fn record_experiment_conversions(&self, conversions: &[ExperimentConversion]) -> Result<u64> {
+        if conversions.is_empty() {
+            return Ok(0);
+        }
+
+        let user_ids: Vec<Uuid> = conversions.iter().map(|c| c.user_id).collect();
+        let tenant_ids: Vec<Option<Uuid>> = conversions.iter().map(|c| c.tenant_id).collect();
+        let event_types: Vec<String> = conversions.iter().map(|c| c.event_type.clone()).collect();
+        let occurred_at: Vec<DateTime<Utc>> = conversions.iter().map(|c| c.occurred_at).collect();
+
+        let recorded = query!(
+            r#"
+            INSERT INTO platform.experiment_conversions (user_id, tenant_id, event_type, occurred_at)
+            SELECT * FROM UNNEST($1::uuid[], $2::uuid[], $3::text[], $4::timestamptz[])
+            "#,
+            &user_ids,
+            &tenant_ids as &[Option<Uuid>],
+            &event_types,
+            &occurred_at
+        )
+        .execute(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record experiment conversions: {}", e)))?
+        .rows_affected();
+
+        Ok(recorded)
+    }
+
+    // ============================================================================
+    // PROGRESSIVE ROLLOUT SCHEDULES
+    // ============================================================================
+
+    /// Attach a schedule to a gradual rollout flag, replacing any earlier one, and
+    /// serve its first step straight away
+    pub async fn create_rollout_schedule(
+        &self,
+        tenant_id: Option<Uuid>,
+        flag_id: Uuid,
+        request: CreateRolloutScheduleRequest,
+        created_by: Uuid,
+    ) -> Result<RolloutSchedule> {
+        let flag = self
+            .get_feature_flag(tenant_id, flag_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Feature flag not found".to_string()))?;
+
+        if flag.rollout_strategy != RolloutStrategy::GradualRollout {
+            return Err(Error::Validation(
+                "Rollout schedules require the gradual rollout strategy".to_string()
+            ));
+        }
+        validate_rollout_schedule(&request)?;
+
+        let now = Utc::now();
+        let row = query_as!(
+            RolloutScheduleRow,
+            r#"
+            INSERT INTO platform.feature_flag_rollout_schedules (
+                id, flag_id, tenant_id, steps, guards, current_step, status,
+                step_started_at, status_reason, created_by, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, 0, 'active', $6, NULL, $7, $6, $6)
+            ON CONFLICT (flag_id) DO UPDATE SET
+                steps = EXCLUDED.steps,
+                guards = EXCLUDED.guards,
+                current_step = 0,
+                status = 'active',
+                step_started_at = EXCLUDED.step_started_at,
+                status_reason = NULL,
+                created_by = EXCLUDED.created_by,
+                updated_at = EXCLUDED.updated_at
+            RETURNING
+                id, flag_id, tenant_id, steps, guards, current_step,
+                status as "status: RolloutScheduleStatus",
+                step_started_at, status_reason, created_by, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            flag_id,
+            flag.tenant_id,
+            serde_json::to_value(&request.steps)?,
+            serde_json::to_value(&request.guards)?,
+            now,
+            created_by
+        )
+        .fetch_one(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to create rollout schedule: {}", e)))?;
+
+        let schedule = self.rollout_row_to_model(row)?;
+        self.set_rollout_percentage(flag_id, request.steps[0].percentage, created_by).await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn get_rollout_schedule(
+        &self,
+        tenant_id: Option<Uuid>,
+        flag_id: Uuid,
+    ) -> Result<Option<RolloutSchedule>> {
+        let row = query_as!(
+            RolloutScheduleRow,
+            r#"
+            SELECT
+                id, flag_id, tenant_id, steps, guards, current_step,
+                status as "status: RolloutScheduleStatus",
+                step_started_at, status_reason, created_by, created_at, updated_at
+            FROM platform.feature_flag_rollout_schedules
+            WHERE flag_id = $1 AND (tenant_id = $2 OR tenant_id IS NULL)
+            "#,
+            flag_id,
+            tenant_id
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to get rollout schedule: {}", e)))?;
+
+        row.map(|row| self.rollout_row_to_model(row)).transpose()
+    }
+
+    /// Schedules of every tenant that the scheduler should look at
+    pub async fn list_active_rollout_schedules(&self) -> Result<Vec<RolloutSchedule>> {
+        let rows = query_as!(
+            RolloutScheduleRow,
+            r#"
+            SELECT
+                s.id, s.flag_id, s.tenant_id, s.steps, s.guards, s.current_step,
+                s.status as "status: RolloutScheduleStatus",
+                s.step_started_at, s.status_reason, s.created_by, s.created_at, s.updated_at
+            FROM platform.feature_flag_rollout_schedules s
+            JOIN platform.feature_flags f ON f.id = s.flag_id
+            WHERE s.status = 'active' AND f.deleted_at IS NULL
+            ORDER BY s.step_started_at
+            "#
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list rollout schedules: {}", e)))?;
+
+        rows.into_iter().map(|row| self.rollout_row_to_model(row)).collect()
+    }
+
+    /// Move an active schedule on to `step`, serving its percentage and restarting the dwell
+    /// time. Returns false, changing nothing, if the schedule moved since it was read.
+    pub async fn advance_rollout(&self, schedule: &RolloutSchedule, step: usize) -> Result<bool> {
+        let percentage = schedule
+            .steps
+            .get(step)
+            .map(|step| step.percentage)
+            .ok_or_else(|| Error::Validation(format!("Rollout schedule has no step {}", step)))?;
+
+        if !self
+            .update_rollout_state(schedule, step as i32, RolloutScheduleStatus::Active, None, true)
+            .await?
+        {
+            return Ok(false);
+        }
+        self.set_rollout_percentage(schedule.flag_id, percentage, schedule.created_by).await?;
+
+        tracing::info!("Rollout of flag {} advanced to step {} ({}%)", schedule.flag_id, step, percentage);
+        Ok(true)
+    }
+
+    /// Returns false if the schedule moved since it was read
+    pub async fn complete_rollout(&self, schedule: &RolloutSchedule) -> Result<bool> {
+        self.update_rollout_state(
+            schedule,
+            schedule.current_step,
+            RolloutScheduleStatus::Completed,
+            None,
+            false,
+        )
+        .await
+    }
+
+    /// Stop a schedule at its current step; a rollback also turns the flag off for everyone.
+    /// Returns false, changing nothing, if the schedule moved since it was read.
+    pub async fn halt_rollout(
+        &self,
+        schedule: &RolloutSchedule,
+        status: RolloutScheduleStatus,
+        reason: &str,
+    ) -> Result<bool> {
+        if !self
+            .update_rollout_state(schedule, schedule.current_step, status, Some(reason), false)
+            .await?
+        {
+            return Ok(false);
+        }
+
+        if status == RolloutScheduleStatus::RolledBack {
+            self.set_rollout_percentage(schedule.flag_id, 0.0, schedule.created_by).await?;
+        }
+
+        tracing::warn!("Rollout of flag {} set to {:?}: {}", schedule.flag_id, status, reason);
+        Ok(true)
+    }
+
+    pub async fn pause_rollout(
+        &self,
+        tenant_id: Option<Uuid>,
+        flag_id: Uuid,
+        paused_by: Uuid,
+    ) -> Result<RolloutSchedule> {
+        let schedule = self.rollout_in_status(tenant_id, flag_id, RolloutScheduleStatus::Active).await?;
+        if !self
+            .halt_rollout(&schedule, RolloutScheduleStatus::Paused, &format!("Paused by {}", paused_by))
+            .await?
+        {
+            return Err(Error::Validation("Rollout schedule changed while pausing; try again".to_string()));
+        }
+        self.get_rollout_schedule(tenant_id, flag_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Rollout schedule not found".to_string()))
+    }
+
+    /// Continue a paused schedule; the current step's dwell time starts over
+    pub async fn resume_rollout(&self, tenant_id: Option<Uuid>, flag_id: Uuid) -> Result<RolloutSchedule> {
+        let schedule = self.rollout_in_status(tenant_id, flag_id, RolloutScheduleStatus::Paused).await?;
+        if !self
+            .update_rollout_state(&schedule, schedule.current_step, RolloutScheduleStatus::Active, None, true)
+            .await?
+        {
+            return Err(Error::Validation("Rollout schedule changed while resuming; try again".to_string()));
+        }
+        self.get_rollout_schedule(tenant_id, flag_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Rollout schedule not found".to_string()))
+    }
+
+    async fn rollout_in_status(
+        &self,
+        tenant_id: Option<Uuid>,
+        flag_id: Uuid,
+        status: RolloutScheduleStatus,
+    ) -> Result<RolloutSchedule> {
+        let schedule = self
+            .get_rollout_schedule(tenant_id, flag_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Rollout schedule not found".to_string()))?;
+
+        if schedule.status != status {
+            return Err(Error::Validation(format!(
+                "Rollout schedule is {:?}, expected {:?}",
+                schedule.status, status
+            )));
+        }
+        Ok(schedule)
+    }
+
+    /// Move `schedule` on from the step and status it was read with. Concurrent schedulers
+    /// race on the same rows, so only the one whose update matches applies it; returns
+    /// false when the row had already moved.
+    async fn update_rollout_state(
+        &self,
+        schedule: &RolloutSchedule,
+        current_step: i32,
+        status: RolloutScheduleStatus,
+        status_reason: Option<&str>,
+        restart_dwell: bool,
+    ) -> Result<bool> {
+        let now = Utc::now();
+
+        let updated = query!(
+            r#"
+            UPDATE platform.feature_flag_rollout_schedules
+            SET current_step = $2,
+                status = $3,
+                status_reason = $4,
+                step_started_at = CASE WHEN $5 THEN $6 ELSE step_started_at END,
+                updated_at = $6
+            WHERE id = $1 AND status = $7 AND current_step = $8
+            "#,
+            schedule.id,
+            current_step,
+            status as RolloutScheduleStatus,
+            status_reason,
+            restart_dwell,
+            now,
+            schedule.status as RolloutScheduleStatus,
+            schedule.current_step
+        )
+        .execute(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update rollout schedule: {}", e)))?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    /// Serve a new rollout percentage and announce it with `FeatureFlagChanged`
+    async fn set_rollout_percentage(&self, flag_id: Uuid, percentage: f64, changed_by: Uuid) -> Result<()> {
+        let now = Utc::now();
+
+        // Joining the row to itself exposes the percentage from before the update
+        let changed = query!(
+            r#"
+            UPDATE platform.feature_flags flag
+            SET rollout_percentage = $2, updated_at = $3, updated_by = $4
+            FROM platform.feature_flags previous
+            WHERE flag.id = $1 AND previous.id = flag.id AND flag.deleted_at IS NULL
+            RETURNING flag.key, flag.tenant_id, previous.rollout_percentage as previous_percentage
+            "#,
+            flag_id,
+            percentage,
+            now,
+            changed_by
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update rollout percentage: {}", e)))?
+        .ok_or_else(|| Error::NotFound("Feature flag not found".to_string()))?;
+
+        let previous = changed.previous_percentage.unwrap_or(0.0);
+        self.publish_flag_changed(
+            flag_id,
+            changed.tenant_id,
+            changed.key,
+            previous > 0.0,
+            percentage > 0.0,
+            Some(percentage),
+            changed_by,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn publish_flag_changed(
+        &self,
+        flag_id: Uuid,
+        tenant_id: Option<Uuid>,
+        key: String,
+        old_value: bool,
+        new_value: bool,
+        rollout_percentage: Option<f64>,
+        changed_by: Uuid,
+    ) {
+        // Global flags have no tenant; they are announced under the nil tenant
+        let tenant_id = tenant_id.unwrap_or_else(Uuid::nil);
+        let data = FeatureFlagChangedEvent {
+            tenant_id,
+            feature_name: key,
+            old_value,
+            new_value,
+            changed_by,
+            rollout_percentage: rollout_percentage.map(|percentage| percentage as f32),
+        };
+
+        let event = match DomainEvent::builder(
+            platform_events::FEATURE_FLAG_CHANGED.to_string(),
+            flag_id,
+            "FeatureFlag".to_string(),
+            tenant_id,
+        )
+        .data(data)
+        {
+            Ok(builder) => builder
+                .user_id(changed_by)
+                .source_service("platform".to_string())
+                .build(),
+            Err(e) => {
+                tracing::warn!("Failed to serialize FeatureFlagChanged event: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = self.event_publisher.publish_async(EventContainer::Legacy(event)).await {
+            tracing::warn!("Failed to publish FeatureFlagChanged event: {}", e);
+        }
+    }
+
+    // ============================================================================
+    // PREREQUISITES, EXCLUSION GROUPS AND KILL SWITCH
+    // ============================================================================
+
+    async fn update_flag_dependencies(
+        &self,
+        flag: &FeatureFlag,
+        request: &UpdateFeatureFlagRequest,
+        updated_by: Uuid,
+    ) -> Result<()> {
+        let prerequisites = request.prerequisites.clone().unwrap_or_else(|| flag.prerequisites.clone());
+        let exclusion = if request.clear_exclusion.unwrap_or(false) {
+            None
+        } else {
+            request.exclusion.clone().or_else(|| flag.exclusion.clone())
+        };
+        self.validate_flag_dependencies(flag.tenant_id, &flag.key, Some(flag.id), &prerequisites, exclusion.as_ref())
+            .await?;
+
+        query!(
+            r#"
+            UPDATE platform.feature_flags
+            SET prerequisites = $2, exclusion_group = $3, exclusion_start = $4, exclusion_end = $5,
+                updated_at = $6, updated_by = $7
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+            flag.id,
+            serde_json::to_value(&prerequisites)?,
+            exclusion.as_ref().map(|slot| slot.group.clone()),
+            exclusion.as_ref().map(|slot| slot.start),
+            exclusion.as_ref().map(|slot| slot.end),
+            Utc::now(),
+            updated_by
+        )
+        .execute(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update flag dependencies: {}", e)))?;
+
+        if prerequisites != flag.prerequisites {
+            self.create_flag_audit_record(
+                flag,
+                "prerequisites_changed",
+                Some(serde_json::to_value(&flag.prerequisites)?),
+                Some(serde_json::to_value(&prerequisites)?),
+                updated_by,
+                None,
+            )
+            .await?;
+        }
+        if exclusion != flag.exclusion {
+            self.create_flag_audit_record(
+                flag,
+                "exclusion_changed",
+                Some(serde_json::to_value(&flag.exclusion)?),
+                Some(serde_json::to_value(&exclusion)?),
+                updated_by,
+                None,
+            )
+            .await?;
+        }
+
+        // Local evaluators refresh on any change, so announce it even though the
+        // flag's own state is unchanged
+        let is_on = flag.status == FeatureFlagStatus::Active && flag.killed_at.is_none();
+        self.publish_flag_changed(
+            flag.id,
+            flag.tenant_id,
+            flag.key.clone(),
+            is_on,
+            is_on,
+            flag.rollout_percentage,
+            updated_by,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    /// Force every tenant's flag with this key, global flag included, to serve its
+    /// default value until revived. Returns the flags that were killed.
+    pub async fn kill_feature_flag(
+        &self,
+        flag_key: &str,
+        reason: &str,
+        killed_by: Uuid,
+    ) -> Result<Vec<FeatureFlag>> {
+        let now = Utc::now();
+
+        let flag_rows = query_as!(
+            FeatureFlagRow,
+            r#"
+            UPDATE platform.feature_flags
+            SET killed_at = $2, killed_by = $3, kill_reason = $4, updated_at = $2, updated_by = $3
+            WHERE key = $1 AND killed_at IS NULL AND deleted_at IS NULL
+            RETURNING
+                id, tenant_id, key, name, description,
+                flag_type as "flag_type: FeatureFlagType",
+                status as "status: FeatureFlagStatus",
+                default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
+                rollout_percentage, target_users, target_groups, conditions,
+                variants, tags, is_global, created_at, updated_at,
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
+            "#,
+            flag_key,
+            now,
+            killed_by,
+            reason
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to kill feature flag: {}", e)))?;
+
+        if flag_rows.is_empty() {
+            self.ensure_flag_key_exists(flag_key).await?;
+        }
+
+        let mut flags = Vec::with_capacity(flag_rows.len());
+        for row in flag_rows {
+            let flag = self.flag_row_to_model(row)?;
+            self.create_flag_audit_record(
+                &flag,
+                "killed",
+                Some(serde_json::json!({ "status": flag.status })),
+                Some(serde_json::json!({ "value": flag.default_value })),
+                killed_by,
+                Some(reason.to_string()),
+            )
+            .await?;
+            self.publish_flag_changed(
+                flag.id,
+                flag.tenant_id,
+                flag.key.clone(),
+                flag.status == FeatureFlagStatus::Active,
+                false,
+                None,
+                killed_by,
+            )
+            .await;
+            flags.push(flag);
+        }
+
+        tracing::warn!("Feature flag {} killed in {} tenants: {}", flag_key, flags.len(), reason);
+        Ok(flags)
+    }
+
+    /// Lift the kill switch on every flag with this key. Returns the flags revived.
+    pub async fn revive_feature_flag(&self, flag_key: &str, revived_by: Uuid) -> Result<Vec<FeatureFlag>> {
+        let now = Utc::now();
+
+        // Joining the row to itself exposes the kill being lifted for the audit trail
+        let revived = query!(
+            r#"
+            UPDATE platform.feature_flags flag
+            SET killed_at = NULL, killed_by = NULL, kill_reason = NULL, updated_at = $2, updated_by = $3
+            FROM platform.feature_flags previous
+            WHERE flag.key = $1 AND previous.id = flag.id
+              AND flag.killed_at IS NOT NULL AND flag.deleted_at IS NULL
+            RETURNING flag.id, previous.killed_at, previous.kill_reason
+            "#,
+            flag_key,
+            now,
+            revived_by
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to revive feature flag: {}", e)))?;
+
+        if revived.is_empty() {
+            self.ensure_flag_key_exists(flag_key).await?;
+        }
+
+        let flag_ids: Vec<Uuid> = revived.iter().map(|row| row.id).collect();
+        let flag_rows = query_as!(
+            FeatureFlagRow,
+            r#"
+            SELECT
+                id, tenant_id, key, name, description,
+                flag_type as "flag_type: FeatureFlagType",
+                status as "status: FeatureFlagStatus",
+                default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
+                rollout_percentage, target_users, target_groups, conditions,
+                variants, tags, is_global, created_at, updated_at,
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
+            FROM platform.feature_flags
+            WHERE id = ANY($1)
+            "#,
+            &flag_ids
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load revived feature flags: {}", e)))?;
+
+        let mut flags = Vec::with_capacity(flag_rows.len());
+        for row in flag_rows {
+            let Some(kill) = revived.iter().find(|kill| kill.id == row.id) else {
+                continue;
+            };
+            let flag = self.flag_row_to_model(row)?;
+            self.create_flag_audit_record(
+                &flag,
+                "revived",
+                Some(serde_json::json!({ "killed_at": kill.killed_at, "kill_reason": kill.kill_reason })),
+                None,
+                revived_by,
+                None,
+            )
+            .await?;
+            self.publish_flag_changed(
+                flag.id,
+                flag.tenant_id,
+                flag.key.clone(),
+                false,
+                flag.status == FeatureFlagStatus::Active,
+                flag.rollout_percentage,
+                revived_by,
+            )
+            .await;
+            flags.push(flag);
+        }
+
+        Ok(flags)
+    }
+
+    pub async fn get_feature_flag_audit_history(
+        &self,
+        tenant_id: Option<Uuid>,
+        flag_id: Uuid,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<FeatureFlagAudit>> {
+        let limit = limit.unwrap_or(50).min(100);
+        let offset = offset.unwrap_or(0);
+
+        query_as!(
+            FeatureFlagAudit,
+            r#"
+            SELECT
+                id, flag_id, flag_key, tenant_id, action, old_value, new_value,
+                changed_by, changed_at, reason
+            FROM platform.feature_flag_audits
+            WHERE flag_id = $1 AND (tenant_id = $2 OR tenant_id IS NULL)
+            ORDER BY changed_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            flag_id,
+            tenant_id,
+            limit as i64,
+            offset as i64
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to get flag audit history: {}", e)))
+    }
+
+    async fn create_flag_audit_record(
+        &self,
+        flag: &FeatureFlag,
+        action: &str,
+        old_value: Option<serde_json::Value>,
+        new_value: Option<serde_json::Value>,
+        changed_by: Uuid,
+        reason: Option<String>,
+    ) -> Result<()> {
+        query!(
+            r#"
+            INSERT INTO platform.feature_flag_audits (
+                id, flag_id, flag_key, tenant_id, action, old_value, new_value,
+                changed_by, changed_at, reason
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            Uuid::new_v4(),
+            flag.id,
+            flag.key,
+            flag.tenant_id,
+            action,
+            old_value,
+            new_value,
+            changed_by,
+            Utc::now(),
+            reason
+        )
+        .execute(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to create flag audit record: {}", e)))?;
+
+        Ok(())
+    }
+
     // ============================================================================
     // ANALYTICS AND REPORTING
     // ============================================================================
@@ -509,7 +1275,7 @@ impl FeatureFlagsService {
                 COUNT(*) FILTER (WHERE is_enabled = true) as enabled_evaluations,
                 COUNT(DISTINCT user_id) as unique_users,
                 MAX(evaluated_at) as last_evaluated
-            FROM feature_flag_evaluations
+            FROM platform.feature_flag_evaluations
             WHERE flag_key = $1
                 AND (tenant_id = $2 OR tenant_id IS NULL)
                 AND evaluated_at BETWEEN $3 AND $4
@@ -540,6 +1306,55 @@ impl FeatureFlagsService {
         }
     }
 
+    /// Compare conversion across the variants of an A/B test flag. Each user counts
+    /// once, in the variant of their first exposure in the period, and converts with
+    /// their first reported `conversion_event` after that exposure.
+    pub async fn get_experiment_results(
+        &self,
+        tenant_id: Option<Uuid>,
+        flag_key: &str,
+        conversion_event: &str,
+        control_variant: Option<&str>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Option<ExperimentResults>> {
+        let flag = match self.get_feature_flag_by_key(tenant_id, flag_key).await? {
+            Some(flag) => flag,
+            None => return Ok(None),
+        };
+
+        if flag.rollout_strategy != RolloutStrategy::ABTest {
+            return Err(Error::Validation(
+                "Experiment results are only available for A/B test flags".to_string()
+            ));
+        }
+
+        let daily = experiments::daily_counts(
+            self.db.as_ref(),
+            flag_key,
+            tenant_id,
+            conversion_event,
+            period_start,
+            period_end,
+        )
+        .await?;
+
+        let analysis = experiments::analyze(&daily, &flag.variants, control_variant)?;
+
+        Ok(Some(ExperimentResults {
+            flag_key: flag.key,
+            conversion_event: conversion_event.to_string(),
+            control_variant: analysis.control_variant,
+            variants: analysis.variants,
+            sample_ratio: analysis.sample_ratio,
+            verdict: analysis.verdict,
+            winner: analysis.winner,
+            summary: analysis.summary,
+            period_start,
+            period_end,
+        }))
+    }
+
     pub async fn list_feature_flags(
         &self,
         tenant_id: Option<Uuid>,
@@ -559,7 +1374,10 @@ impl FeatureFlagsService {
                 default_value, rollout_strategy as "rollout_strategy: RolloutStrategy",
                 rollout_percentage, target_users, target_groups, conditions,
                 variants, tags, is_global, created_at, updated_at,
-                created_by, updated_by, starts_at, ends_at
+                created_by, updated_by, starts_at, ends_at,
+                bucket_by as "bucket_by: BucketBy", salt,
+                prerequisites, exclusion_group, exclusion_start, exclusion_end,
+                killed_at, killed_by, kill_reason
             FROM platform.feature_flags
             WHERE (tenant_id = $1 OR tenant_id IS NULL) AND deleted_at IS NULL
             ORDER BY created_at DESC
@@ -613,6 +1431,125 @@ impl FeatureFlagsService {
         Ok(())
     }
 
+    /// Prerequisites must name flags visible to this one without leading back to
+    /// it, and an exclusion slot must not overlap another member of its group
+    async fn validate_flag_dependencies(
+        &self,
+        tenant_id: Option<Uuid>,
+        key: &str,
+        flag_id: Option<Uuid>,
+        prerequisites: &[FlagPrerequisite],
+        exclusion: Option<&ExclusionSlot>,
+    ) -> Result<()> {
+        if !prerequisites.is_empty() {
+            // A tenant's own flag shadows a global one with the same key
+            let rows = query!(
+                r#"
+                SELECT key, prerequisites
+                FROM platform.feature_flags
+                WHERE (tenant_id = $1 OR tenant_id IS NULL) AND key <> $2 AND deleted_at IS NULL
+                ORDER BY tenant_id NULLS FIRST
+                "#,
+                tenant_id,
+                key
+            )
+            .fetch_all(self.db.as_ref())
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load flag prerequisites: {}", e)))?;
+
+            let mut graph = std::collections::HashMap::new();
+            for row in rows {
+                let requires: Vec<FlagPrerequisite> = serde_json::from_value(row.prerequisites)?;
+                graph.insert(row.key, requires.into_iter().map(|prerequisite| prerequisite.key).collect());
+            }
+
+            if let Some(cycle) = prerequisite_cycle(key, prerequisites, &graph) {
+                return Err(Error::Validation(format!(
+                    "Prerequisites would form a cycle: {}",
+                    cycle.join(" -> ")
+                )));
+            }
+            if let Some(missing) = prerequisites.iter().find(|prerequisite| !graph.contains_key(&prerequisite.key)) {
+                return Err(Error::Validation(format!("Prerequisite flag {} does not exist", missing.key)));
+            }
+        }
+
+        let Some(slot) = exclusion else {
+            return Ok(());
+        };
+        if slot.group.trim().is_empty() || slot.group.len() > 100 {
+            return Err(Error::Validation("Exclusion group name must be 1-100 characters".to_string()));
+        }
+        if !(0.0 <= slot.start && slot.start < slot.end && slot.end <= 100.0) {
+            return Err(Error::Validation("Exclusion range must be within 0-100 with start before end".to_string()));
+        }
+
+        // Global flags share buckets with every tenant, so they are checked against all members
+        let members = query!(
+            r#"
+            SELECT key, exclusion_start as "exclusion_start!", exclusion_end as "exclusion_end!"
+            FROM platform.feature_flags
+            WHERE exclusion_group = $1 AND deleted_at IS NULL
+              AND (tenant_id = $2 OR tenant_id IS NULL OR $2::uuid IS NULL)
+              AND ($3::uuid IS NULL OR id <> $3)
+            "#,
+            slot.group,
+            tenant_id,
+            flag_id
+        )
+        .fetch_all(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load exclusion group: {}", e)))?;
+
+        let taken = members.into_iter().find(|member| {
+            slot.overlaps(&ExclusionSlot {
+                group: slot.group.clone(),
+                start: member.exclusion_start,
+                end: member.exclusion_end,
+            })
+        });
+        if let Some(member) = taken {
+            return Err(Error::Validation(format!(
+                "Exclusion range overlaps flag {} ({}-{}%) in group {}",
+                member.key, member.exclusion_start, member.exclusion_end, slot.group
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_flag_key_exists(&self, key: &str) -> Result<()> {
+        let exists = query!(
+            "SELECT id FROM platform.feature_flags WHERE key = $1 AND deleted_at IS NULL LIMIT 1",
+            key
+        )
+        .fetch_optional(self.db.as_ref())
+        .await
+        .map_err(|e| Error::Database(format!("Failed to look up feature flag: {}", e)))?;
+
+        match exists {
+            Some(_) => Ok(()),
+            None => Err(Error::NotFound("Feature flag not found".to_string())),
+        }
+    }
+
+    fn rollout_row_to_model(&self, row: RolloutScheduleRow) -> Result<RolloutSchedule> {
+        Ok(RolloutSchedule {
+            id: row.id,
+            flag_id: row.flag_id,
+            tenant_id: row.tenant_id,
+            steps: serde_json::from_value(row.steps)?,
+            guards: serde_json::from_value(row.guards)?,
+            current_step: row.current_step,
+            status: row.status,
+            step_started_at: row.step_started_at,
+            status_reason: row.status_reason,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+
     fn flag_row_to_model(&self, row: FeatureFlagRow) -> Result<FeatureFlag> {
         Ok(FeatureFlag {
             id: row.id,
@@ -637,10 +1574,42 @@ impl FeatureFlagsService {
             updated_by: row.updated_by,
             starts_at: row.starts_at,
             ends_at: row.ends_at,
+            bucket_by: row.bucket_by,
+            salt: row.salt,
+            prerequisites: serde_json::from_value(row.prerequisites)?,
+            exclusion: match (row.exclusion_group, row.exclusion_start, row.exclusion_end) {
+                (Some(group), Some(start), Some(end)) => Some(ExclusionSlot { group, start, end }),
+                _ => None,
+            },
+            killed_at: row.killed_at,
+            killed_by: row.killed_by,
+            kill_reason: row.kill_reason,
         })
     }
 }
 
+/// Steps must raise the percentage each time, ending at most at 100%
+fn validate_rollout_schedule(request: &CreateRolloutScheduleRequest) -> Result<()> {
+    let mut previous = 0.0;
+    for step in &request.steps {
+        if step.percentage <= previous || step.percentage > 100.0 {
+            return Err(Error::Validation(
+                "Rollout step percentages must increase and stay within 0-100".to_string()
+            ));
+        }
+        if step.dwell_minutes < 0 {
+            return Err(Error::Validation("Rollout step dwell time cannot be negative".to_string()));
+        }
+        previous = step.percentage;
+    }
+
+    if request.guards.iter().any(|guard| guard.metric_name.trim().is_empty()) {
+        return Err(Error::Validation("Rollout guard metric name must not be empty".to_string()));
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // DATABASE ROW TYPES
 // ============================================================================
@@ -669,4 +1638,29 @@ struct FeatureFlagRow {
     pub updated_by: Uuid,
     pub starts_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
-}
\ No newline at end of file
+    pub bucket_by: BucketBy,
+    pub salt: String,
+    pub prerequisites: serde_json::Value,
+    pub exclusion_group: Option<String>,
+    pub exclusion_start: Option<f64>,
+    pub exclusion_end: Option<f64>,
+    pub killed_at: Option<DateTime<Utc>>,
+    pub killed_by: Option<Uuid>,
+    pub kill_reason: Option<String>,
+}
+
+#[derive(Debug)]
+struct RolloutScheduleRow {
+    pub id: Uuid,
+    pub flag_id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    pub steps: serde_json::Value,
+    pub guards: serde_json::Value,
+    pub current_step: i32,
+    pub status: RolloutScheduleStatus,
+    pub step_started_at: DateTime<Utc>,
+    pub status_reason: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,7 +1,12 @@
 pub mod tenant_service;
 pub mod feature_flags;
 pub mod config;
+pub mod access_policies;
+pub mod rollout_scheduler;
+pub mod experiments;
 
 pub use tenant_service::TenantService;
 pub use feature_flags::FeatureFlagsService;
-pub use config::ConfigurationService;
\ No newline at end of file
+pub use config::ConfigurationService;
+pub use access_policies::PlatformPolicyStore;
+pub use rollout_scheduler::{RolloutScheduler, GuardMetricSource, GuardBreach};
\ No newline at end of file
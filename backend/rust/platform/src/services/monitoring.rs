@@ -22,6 +22,7 @@ use olympus_shared::error::Error as OlympusError;
 use crate::models::{
     TenantHealthCheck, TenantAnalytics, FeatureFlagAnalytics, FeatureFlagUsage
 };
+use crate::services::rollout_scheduler::{GuardBreach, GuardMetricSource};
 
 #[derive(Clone)]
 pub struct PlatformMonitoringService {
@@ -360,6 +361,18 @@ impl PlatformMonitoringService {
     // ========================================================================
 
     pub async fn evaluate_alert_rules(&self) -> Result<Vec<Alert>> {
+        let triggered_alerts = self.firing_alerts().await?;
+
+        // Store triggered alerts
+        for alert in &triggered_alerts {
+            self.store_alert(alert).await?;
+        }
+
+        Ok(triggered_alerts)
+    }
+
+    /// Alerts whose rules currently fire, without storing them
+    pub async fn firing_alerts(&self) -> Result<Vec<Alert>> {
         let mut triggered_alerts = Vec::new();
 
         let alert_rules = self.get_active_alert_rules().await?;
@@ -371,11 +384,6 @@ impl PlatformMonitoringService {
             }
         }
 
-        // Store triggered alerts
-        for alert in &triggered_alerts {
-            self.store_alert(alert).await?;
-        }
-
         Ok(triggered_alerts)
     }
 
@@ -547,6 +555,27 @@ impl PlatformMonitoringService {
     }
 }
 
+/// Firing alerts guard progressive rollouts of feature flags. Reading them stores nothing,
+/// so a scheduler tick never records or notifies alerts of its own.
+#[async_trait::async_trait]
+impl GuardMetricSource for PlatformMonitoringService {
+    async fn breaches(&self) -> olympus_shared::error::Result<Vec<GuardBreach>> {
+        let alerts = self
+            .firing_alerts()
+            .await
+            .map_err(|e| OlympusError::Internal(format!("Failed to evaluate alert rules: {}", e)))?;
+
+        Ok(alerts
+            .into_iter()
+            .map(|alert| GuardBreach {
+                metric_name: alert.metric_name,
+                current_value: alert.current_value,
+                threshold: alert.threshold,
+            })
+            .collect())
+    }
+}
+
 // Helper structs for metrics
 #[derive(Debug)]
 struct RequestMetrics {
@@ -0,0 +1,253 @@
+// ============================================================================
+// OLYMPUS CLOUD - PROGRESSIVE ROLLOUT SCHEDULER
+// ============================================================================
+// Module: platform/src/services/rollout_scheduler.rs
+// Description: Advances gradual rollout schedules step by step, pausing or
+//              rolling back when a guard metric's alert fires
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use olympus_shared::error::Result;
+
+use crate::models::{GuardAction, RolloutSchedule, RolloutScheduleStatus};
+use crate::services::FeatureFlagsService;
+
+/// A metric currently past its alert threshold
+#[derive(Debug, Clone)]
+pub struct GuardBreach {
+    pub metric_name: String,
+    pub current_value: f64,
+    pub threshold: f64,
+}
+
+/// Source of firing alerts, e.g. `PlatformMonitoringService::firing_alerts`. Reading
+/// breaches must not record or notify anything, as the scheduler reads them every tick.
+#[async_trait]
+pub trait GuardMetricSource: Send + Sync {
+    async fn breaches(&self) -> Result<Vec<GuardBreach>>;
+}
+
+/// What a schedule should do on this tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum RolloutAction {
+    Hold,
+    Advance { step: usize },
+    Complete,
+    Pause { reason: String },
+    Rollback { reason: String },
+}
+
+/// Decide a schedule's next move. Guards are checked before the dwell time, so a
+/// breach stops a rollout even when its next step is due; rollback guards win
+/// over pause guards.
+pub fn next_action(schedule: &RolloutSchedule, now: DateTime<Utc>, breaches: &[GuardBreach]) -> RolloutAction {
+    if schedule.status != RolloutScheduleStatus::Active {
+        return RolloutAction::Hold;
+    }
+
+    let breached = |action: GuardAction| {
+        schedule
+            .guards
+            .iter()
+            .filter(|guard| guard.action == action)
+            .find_map(|guard| breaches.iter().find(|breach| breach.metric_name == guard.metric_name))
+            .map(|breach| {
+                format!(
+                    "{} at {} breached threshold {}",
+                    breach.metric_name, breach.current_value, breach.threshold
+                )
+            })
+    };
+    if let Some(reason) = breached(GuardAction::Rollback) {
+        return RolloutAction::Rollback { reason };
+    }
+    if let Some(reason) = breached(GuardAction::Pause) {
+        return RolloutAction::Pause { reason };
+    }
+
+    if now < schedule.step_ends_at() {
+        return RolloutAction::Hold;
+    }
+
+    let next = schedule.current_step.max(0) as usize + 1;
+    if next < schedule.steps.len() {
+        RolloutAction::Advance { step: next }
+    } else {
+        RolloutAction::Complete
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RolloutReport {
+    pub advanced: usize,
+    pub completed: usize,
+    pub paused: usize,
+    pub rolled_back: usize,
+}
+
+pub struct RolloutScheduler {
+    flags: Arc<FeatureFlagsService>,
+    guards: Option<Arc<dyn GuardMetricSource>>,
+}
+
+impl RolloutScheduler {
+    pub fn new(flags: Arc<FeatureFlagsService>) -> Self {
+        Self { flags, guards: None }
+    }
+
+    pub fn with_guards(mut self, guards: Arc<dyn GuardMetricSource>) -> Self {
+        self.guards = Some(guards);
+        self
+    }
+
+    /// Apply one tick to every active schedule
+    pub async fn run_once(&self) -> Result<RolloutReport> {
+        let mut report = RolloutReport::default();
+
+        let schedules = self.flags.list_active_rollout_schedules().await?;
+        if schedules.is_empty() {
+            return Ok(report);
+        }
+
+        // Without a reading of the guards nothing may advance; wait for the next tick
+        let breaches = match &self.guards {
+            Some(guards) => match guards.breaches().await {
+                Ok(breaches) => Some(breaches),
+                Err(e) => {
+                    tracing::warn!("Failed to evaluate rollout guards, holding all rollouts: {}", e);
+                    return Ok(report);
+                }
+            },
+            None => None,
+        };
+
+        let now = Utc::now();
+        for schedule in &schedules {
+            let breaches: &[GuardBreach] = match &breaches {
+                Some(breaches) => breaches,
+                None if schedule.guards.is_empty() => &[],
+                // Guards that cannot be read hold their schedule rather than pass
+                None => {
+                    tracing::debug!("No guard source configured, holding guarded rollout of flag {}", schedule.flag_id);
+                    continue;
+                }
+            };
+
+            let (result, count) = match next_action(schedule, now, breaches) {
+                RolloutAction::Hold => continue,
+                RolloutAction::Advance { step } => {
+                    (self.flags.advance_rollout(schedule, step).await, &mut report.advanced)
+                }
+                RolloutAction::Complete => (self.flags.complete_rollout(schedule).await, &mut report.completed),
+                RolloutAction::Pause { reason } => (
+                    self.flags.halt_rollout(schedule, RolloutScheduleStatus::Paused, &reason).await,
+                    &mut report.paused,
+                ),
+                RolloutAction::Rollback { reason } => (
+                    self.flags.halt_rollout(schedule, RolloutScheduleStatus::RolledBack, &reason).await,
+                    &mut report.rolled_back,
+                ),
+            };
+
+            // Another instance may have moved the schedule since it was listed
+            match result {
+                Ok(true) => *count += 1,
+                Ok(false) => tracing::debug!("Rollout of flag {} was already updated elsewhere", schedule.flag_id),
+                Err(e) => tracing::warn!("Failed to update rollout of flag {}: {}", schedule.flag_id, e),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Tick in the background
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_once().await {
+                    tracing::warn!("Failed to run rollout scheduler: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use uuid::Uuid;
+    use crate::models::{RolloutGuardMetric, RolloutStep};
+
+    fn schedule(current_step: i32, started_minutes_ago: i64) -> RolloutSchedule {
+        let now = Utc::now();
+        let step = |percentage, dwell_minutes| RolloutStep { percentage, dwell_minutes };
+        RolloutSchedule {
+            id: Uuid::new_v4(),
+            flag_id: Uuid::new_v4(),
+            tenant_id: Some(Uuid::new_v4()),
+            steps: vec![step(1.0, 60), step(5.0, 120), step(25.0, 240), step(100.0, 0)],
+            guards: vec![
+                RolloutGuardMetric { metric_name: "avg_response_time_ms".to_string(), action: GuardAction::Pause },
+                RolloutGuardMetric { metric_name: "error_rate".to_string(), action: GuardAction::Rollback },
+            ],
+            current_step,
+            status: RolloutScheduleStatus::Active,
+            step_started_at: now - Duration::minutes(started_minutes_ago),
+            status_reason: None,
+            created_by: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn breach(metric_name: &str) -> GuardBreach {
+        GuardBreach { metric_name: metric_name.to_string(), current_value: 0.09, threshold: 0.05 }
+    }
+
+    #[test]
+    fn test_steps_advance_after_dwell() {
+        let now = Utc::now();
+
+        assert_eq!(next_action(&schedule(0, 30), now, &[]), RolloutAction::Hold);
+        assert_eq!(next_action(&schedule(0, 61), now, &[]), RolloutAction::Advance { step: 1 });
+        assert_eq!(next_action(&schedule(1, 61), now, &[]), RolloutAction::Hold);
+        assert_eq!(next_action(&schedule(2, 241), now, &[]), RolloutAction::Advance { step: 3 });
+        assert_eq!(next_action(&schedule(3, 1), now, &[]), RolloutAction::Complete);
+    }
+
+    #[test]
+    fn test_guard_breaches_stop_rollout() {
+        let now = Utc::now();
+        let due = schedule(1, 500);
+
+        assert_eq!(
+            next_action(&due, now, &[breach("avg_response_time_ms")]),
+            RolloutAction::Pause { reason: "avg_response_time_ms at 0.09 breached threshold 0.05".to_string() }
+        );
+        assert!(matches!(
+            next_action(&due, now, &[breach("avg_response_time_ms"), breach("error_rate")]),
+            RolloutAction::Rollback { .. }
+        ));
+        // Alerts on metrics the schedule does not guard are ignored
+        assert_eq!(next_action(&due, now, &[breach("cpu_usage_percent")]), RolloutAction::Advance { step: 2 });
+    }
+
+    #[test]
+    fn test_inactive_schedules_hold() {
+        let now = Utc::now();
+        for status in [RolloutScheduleStatus::Paused, RolloutScheduleStatus::RolledBack, RolloutScheduleStatus::Completed] {
+            let mut stopped = schedule(1, 500);
+            stopped.status = status;
+            assert_eq!(next_action(&stopped, now, &[breach("error_rate")]), RolloutAction::Hold);
+        }
+    }
+}
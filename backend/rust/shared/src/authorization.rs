@@ -0,0 +1,468 @@
+// ============================================================================
+// OLYMPUS CLOUD - ROUTE AUTHORIZATION
+// ============================================================================
+// Module: shared/src/authorization.rs
+// Description: Tower layers authenticating requests and enforcing permissions on axum routes
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, Extensions, Method, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures::future::{ready, BoxFuture, Either, Ready};
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use crate::error::{Error, ErrorResponse};
+use crate::integration::go_gateway::AuthContext;
+use crate::models::permission::{PermissionDecision, PermissionSet};
+use crate::types::PermissionScope;
+
+/// The caller's permissions, from the set inserted by authentication middleware
+/// or, for requests proxied by the Go gateway, its `AuthContext`
+fn permission_set(extensions: &axum::http::Extensions) -> Option<PermissionSet> {
+    if let Some(set) = extensions.get::<PermissionSet>() {
+        return Some(set.clone());
+    }
+    extensions
+        .get::<AuthContext>()
+        .map(|auth| PermissionSet::from_strings(&auth.permissions))
+}
+
+fn error_response(error: Error, permission: &str) -> Response {
+    let status = StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::FORBIDDEN);
+    let body = ErrorResponse::new(&error).with_details(serde_json::json!({ "permission": permission }));
+    (status, Json(body)).into_response()
+}
+
+/// Verifies the credentials on incoming requests. The auth service implements this and
+/// is handed to the other services' routers at startup, so every service accepts the
+/// same tokens and API keys.
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// Identify the caller of `request`, or return the response rejecting it. Implementations
+    /// may insert further extensions of their own; they are passed back to `completed`.
+    async fn authenticate(&self, request: &mut Request) -> std::result::Result<AuthContext, Response>;
+
+    /// Called once an authenticated request has been handled, with the request's extensions
+    /// and the status returned to the caller
+    async fn completed(&self, _extensions: &Extensions, _method: &Method, _path: &str, _status: StatusCode) {}
+}
+
+/// Authenticate every request before it reaches the routes, exposing the caller as an
+/// `AuthContext` and a `PermissionSet` for `RequirePermission`. Apply it with `.layer` so
+/// it runs ahead of the routes' permission checks:
+///
+/// ```ignore
+/// Router::new()
+///     .route("/refunds", post(create_refund).route_layer(RequirePermission("commerce.refunds.create")))
+///     .layer(Authenticate(authenticator))
+/// ```
+#[derive(Clone)]
+pub struct Authenticate(pub Arc<dyn Authenticator>);
+
+impl<S> Layer<S> for Authenticate {
+    type Service = AuthenticateService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthenticateService {
+            inner,
+            authenticator: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuthenticateService<S> {
+    inner: S,
+    authenticator: Arc<dyn Authenticator>,
+}
+
+impl<S> Service<Request> for AuthenticateService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, std::result::Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        // The clone has not been polled ready; keep it and call the instance that was
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let authenticator = self.authenticator.clone();
+
+        Box::pin(async move {
+            let auth = match authenticator.authenticate(&mut request).await {
+                Ok(auth) => auth,
+                Err(response) => return Ok(response),
+            };
+            request
+                .extensions_mut()
+                .insert(PermissionSet::from_strings(&auth.permissions));
+            request.extensions_mut().insert(auth);
+
+            let extensions = request.extensions().clone();
+            let method = request.method().clone();
+            let path = request.uri().path().to_string();
+
+            let response = inner.call(request).await?;
+            authenticator
+                .completed(&extensions, &method, &path, response.status())
+                .await;
+            Ok(response)
+        })
+    }
+}
+
+/// Reject requests whose caller does not hold `permission` tenant-wide:
+///
+/// ```ignore
+/// .route("/refunds", post(create_refund).route_layer(RequirePermission("commerce.refunds.create")))
+/// ```
+///
+/// Unauthenticated requests get 401; missing grants and explicit denies get 403. Grants
+/// limited to a location or to the caller's own records are refused here, since the layer
+/// cannot see the resource; routes that accept them use `RequireScopedPermission`.
+#[derive(Debug, Clone, Copy)]
+pub struct RequirePermission(pub &'static str);
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+            scoped: false,
+        }
+    }
+}
+
+/// Like `RequirePermission`, but also lets through callers whose grants are limited to
+/// locations or their own records. The handler must check the resource it loads against
+/// the `GrantedScope` extension before acting on it:
+///
+/// ```ignore
+/// async fn get_order(Extension(scope): Extension<GrantedScope>, ...) -> Result<Json<Order>> {
+///     let order = load_order(order_id).await?;
+///     scope.check(order.location_id, order.created_by)?;
+///     ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RequireScopedPermission(pub &'static str);
+
+impl<S> Layer<S> for RequireScopedPermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+            scoped: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: &'static str,
+    /// Whether the handler checks location and own grants against the resource
+    scoped: bool,
+}
+
+impl<S> Service<Request> for RequirePermissionService<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Response, S::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let Some(set) = permission_set(request.extensions()) else {
+            return Either::Left(ready(Ok(error_response(Error::Unauthorized, self.permission))));
+        };
+
+        let granted = match set.evaluate(self.permission, None) {
+            PermissionDecision::Allowed(PermissionScope::Tenant | PermissionScope::Global) => true,
+            _ => self.scoped && set.may_allow(self.permission),
+        };
+        if !granted {
+            tracing::debug!("Permission {} not granted for {}", self.permission, request.uri().path());
+            return Either::Left(ready(Ok(error_response(Error::InsufficientPermissions, self.permission))));
+        }
+
+        let user_id = request.extensions().get::<AuthContext>().map(|auth| auth.user_id);
+        request.extensions_mut().insert(GrantedScope {
+            permission: self.permission,
+            grants: set,
+            user_id,
+        });
+        Either::Right(self.inner.call(request))
+    }
+}
+
+/// The permission a request was let through for, with the caller's grants, so handlers
+/// can check the resource they load against location and own grants
+#[derive(Debug, Clone)]
+pub struct GrantedScope {
+    permission: &'static str,
+    grants: PermissionSet,
+    user_id: Option<Uuid>,
+}
+
+impl GrantedScope {
+    /// Check the caller may act on a resource at `location_id` owned by `owner_id`; pass
+    /// `None` for resources that belong to no location or no user
+    pub fn check(&self, location_id: Option<Uuid>, owner_id: Option<Uuid>) -> crate::error::Result<()> {
+        let permitted = match self.grants.evaluate(self.permission, location_id) {
+            PermissionDecision::Allowed(PermissionScope::Own) => owner_id.is_some() && owner_id == self.user_id,
+            decision => decision.is_allowed(),
+        };
+        if permitted {
+            Ok(())
+        } else {
+            Err(Error::InsufficientPermissions)
+        }
+    }
+
+    /// Whether the caller holds the permission across the tenant, so results need no narrowing
+    pub fn is_tenant_wide(&self) -> bool {
+        matches!(
+            self.grants.evaluate(self.permission, None),
+            PermissionDecision::Allowed(PermissionScope::Tenant | PermissionScope::Global)
+        )
+    }
+}
+
+/// Extract the caller's permission set for checks that depend on the request body
+#[async_trait]
+impl<S> FromRequestParts<S> for PermissionSet
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        permission_set(&parts.extensions).ok_or_else(|| {
+            (StatusCode::UNAUTHORIZED, Json(ErrorResponse::new(&Error::Unauthorized))).into_response()
+        })
+    }
+}
+
+/// Extract the caller identified by `Authenticate` or the Go gateway
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<AuthContext>().cloned().ok_or_else(|| {
+            (StatusCode::UNAUTHORIZED, Json(ErrorResponse::new(&Error::Unauthorized))).into_response()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        extract::{Path, State},
+        routing::get,
+        Extension, Router,
+    };
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new().route(
+            "/refunds",
+            get(|| async { "ok" }).route_layer(RequirePermission("commerce.refunds.create")),
+        )
+    }
+
+    async fn status(grants: Option<&[&str]>) -> StatusCode {
+        let mut request = axum::http::Request::builder().uri("/refunds").body(Body::empty()).unwrap();
+        if let Some(grants) = grants {
+            request.extensions_mut().insert(PermissionSet::from_strings(grants));
+        }
+        app().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_require_permission() {
+        let store = Uuid::new_v4();
+        let scoped = format!("commerce.refunds.create@location:{}", store);
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some(&["commerce.*"])).await, StatusCode::OK);
+        assert_eq!(
+            status(Some(&["commerce.*", "!commerce.refunds.create"])).await,
+            StatusCode::FORBIDDEN
+        );
+        // The layer cannot see which location a refund belongs to, so scoped grants are refused
+        assert_eq!(status(Some(&[scoped.as_str()])).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Some(&["commerce.refunds.create@own"])).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_scoped_permission_checks_the_resource_location() {
+        let store = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let order_at_store = Uuid::new_v4();
+        let order_elsewhere = Uuid::new_v4();
+        let locations: HashMap<Uuid, Uuid> = [(order_at_store, store), (order_elsewhere, other)].into_iter().collect();
+
+        // The handler looks the order up and checks its location, never a client-supplied one
+        let app = Router::new()
+            .route(
+                "/orders/:order_id",
+                get(
+                    |State(locations): State<HashMap<Uuid, Uuid>>,
+                     Path(order_id): Path<Uuid>,
+                     Extension(scope): Extension<GrantedScope>| async move {
+                        match scope.check(locations.get(&order_id).copied(), None) {
+                            Ok(()) => StatusCode::OK,
+                            Err(e) => StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::FORBIDDEN),
+                        }
+                    },
+                )
+                .route_layer(RequireScopedPermission("commerce.orders.read")),
+            )
+            .with_state(locations);
+
+        let call = |grants: Vec<String>, order_id: Uuid| {
+            let app = app.clone();
+            async move {
+                let mut request = axum::http::Request::builder()
+                    .uri(format!("/orders/{}", order_id))
+                    .body(Body::empty())
+                    .unwrap();
+                request.extensions_mut().insert(PermissionSet::from_strings(&grants));
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        let at_store = vec![format!("commerce.orders.read@location:{}", store)];
+        assert_eq!(call(at_store.clone(), order_at_store).await, StatusCode::OK);
+        assert_eq!(call(at_store, order_elsewhere).await, StatusCode::FORBIDDEN);
+
+        let tenant_wide = vec!["commerce.*".to_string()];
+        assert_eq!(call(tenant_wide.clone(), order_elsewhere).await, StatusCode::OK);
+
+        let denied_at_store = vec![
+            "commerce.*".to_string(),
+            format!("!commerce.orders.read@location:{}", store),
+        ];
+        assert_eq!(call(denied_at_store.clone(), order_at_store).await, StatusCode::FORBIDDEN);
+        assert_eq!(call(denied_at_store, order_elsewhere).await, StatusCode::OK);
+
+        assert_eq!(call(vec![], order_at_store).await, StatusCode::FORBIDDEN);
+    }
+
+    /// Accepts `Bearer valid` with refund grants and counts completed requests
+    #[derive(Default)]
+    struct StaticAuthenticator {
+        completed: std::sync::Mutex<Vec<(Method, StatusCode)>>,
+    }
+
+    #[async_trait]
+    impl Authenticator for StaticAuthenticator {
+        async fn authenticate(&self, request: &mut Request) -> std::result::Result<AuthContext, Response> {
+            if request.headers().get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer valid") {
+                return Err(StatusCode::UNAUTHORIZED.into_response());
+            }
+            Ok(AuthContext {
+                user_id: Uuid::new_v4(),
+                tenant_id: Uuid::new_v4(),
+                roles: vec!["manager".to_string()],
+                permissions: vec!["commerce.refunds.*".to_string()],
+                session_id: Uuid::new_v4().to_string(),
+                expires_at: chrono::Utc::now() + chrono::Duration::minutes(15),
+                location_id: None,
+            })
+        }
+
+        async fn completed(&self, extensions: &Extensions, method: &Method, _path: &str, status: StatusCode) {
+            assert!(extensions.get::<AuthContext>().is_some());
+            self.completed.lock().unwrap().push((method.clone(), status));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_runs_before_permission_checks() {
+        let authenticator = Arc::new(StaticAuthenticator::default());
+        let app = app()
+            .route(
+                "/voids",
+                get(|| async { "ok" }).route_layer(RequirePermission("commerce.voids.create")),
+            )
+            .layer(Authenticate(authenticator.clone()));
+
+        let call = |uri: &'static str, token: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = axum::http::Request::builder().uri(uri);
+                if let Some(token) = token {
+                    request = request.header("authorization", token);
+                }
+                app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(call("/refunds", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(call("/refunds", Some("Bearer forged")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(call("/refunds", Some("Bearer valid")).await, StatusCode::OK);
+        assert_eq!(call("/voids", Some("Bearer valid")).await, StatusCode::FORBIDDEN);
+
+        // Only requests that got past authentication are reported as completed
+        assert_eq!(
+            *authenticator.completed.lock().unwrap(),
+            vec![(Method::GET, StatusCode::OK), (Method::GET, StatusCode::FORBIDDEN)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth_context_extractor() {
+        let whoami = Router::new().route("/me", get(|auth: AuthContext| async move { auth.roles.join(",") }));
+        let request = |token: Option<&str>| {
+            let mut request = axum::http::Request::builder().uri("/me");
+            if let Some(token) = token {
+                request = request.header("authorization", token);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        // Without the layer there is no caller to extract
+        let response = whoami.clone().oneshot(request(Some("Bearer valid"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let app = whoami.layer(Authenticate(Arc::new(StaticAuthenticator::default())));
+        let response = app.oneshot(request(Some("Bearer valid"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"manager");
+    }
+}
@@ -146,8 +146,6 @@ impl GrpcRetry for GrpcClient {
                     ) {
                         return Err(last_error.unwrap());
                     }
-
-                    last_error = Some(status);
                 }
             }
 
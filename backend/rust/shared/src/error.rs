@@ -66,6 +66,9 @@ pub enum Error {
     #[error("Insufficient permissions")]
     InsufficientPermissions,
 
+    #[error("Denied by policy: {0}")]
+    PolicyDenied(String),
+
     #[error("Tenant not found")]
     TenantNotFound,
 
@@ -98,6 +101,7 @@ impl Error {
             | Error::SessionExpired => 401,
             Error::Forbidden
             | Error::InsufficientPermissions
+            | Error::PolicyDenied(_)
             | Error::AccountLocked
             | Error::AccountSuspended => 403,
             Error::Validation(_)
@@ -133,6 +137,7 @@ impl Error {
             Error::SessionExpired => "SESSION_EXPIRED",
             Error::RateLimitExceeded => "RATE_LIMIT_EXCEEDED",
             Error::InsufficientPermissions => "INSUFFICIENT_PERMISSIONS",
+            Error::PolicyDenied(_) => "POLICY_DENIED",
             Error::TenantNotFound => "TENANT_NOT_FOUND",
             Error::Migration(_) => "MIGRATION_ERROR",
             Error::Configuration(_) => "CONFIGURATION_ERROR",
@@ -171,6 +176,7 @@ impl Error {
             | Error::SessionExpired
             | Error::RateLimitExceeded
             | Error::InsufficientPermissions
+            | Error::PolicyDenied(_)
             | Error::TenantNotFound
         )
     }
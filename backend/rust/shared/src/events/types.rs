@@ -26,6 +26,12 @@ pub struct UserRegisteredEvent {
     pub last_name: Option<String>,
     pub email_verification_required: bool,
     pub registration_source: String, // "web", "mobile", "api", "invite"
+    /// Outbox row of the verification email, if one was queued; the token itself is never published
+    #[serde(default)]
+    pub email_id: Option<Uuid>,
+    /// Preferred language for emails, e.g. `fr-CA`
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 /// User login event
@@ -51,15 +57,21 @@ pub struct UserLoggedOutEvent {
     pub logout_reason: LogoutReason,
 }
 
-/// Password reset requested event
+/// Password reset requested event. The token itself is never published; the email carrying
+/// it is already in the outbox as `email_id`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PasswordResetRequestedEvent {
     pub user_id: Uuid,
     pub tenant_id: Uuid,
     pub email: String,
     pub reset_token_id: Uuid,
+    /// Outbox row of the reset email, if one was queued
+    #[serde(default)]
+    pub email_id: Option<Uuid>,
     pub expires_at: DateTime<Utc>,
     pub request_source: String, // "web", "mobile", "api"
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 /// Password changed event
@@ -100,6 +112,135 @@ pub struct MfaEnrolledEvent {
     pub backup_codes_generated: u32,
 }
 
+/// A refresh token that had already been rotated was presented again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenReuseDetectedEvent {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub family_id: Uuid,
+    pub token_id: Uuid,
+    pub device_id: Option<String>,
+    pub ip_address: String,
+    pub user_agent: String,
+}
+
+/// A passkey presented a signature counter at or below the stored one, which suggests the
+/// authenticator was cloned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeySignCountRegressedEvent {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub passkey_id: Uuid,
+    pub credential_id: String,
+    pub stored_sign_count: u32,
+    pub received_sign_count: u32,
+    /// Whether the assertion was refused or allowed through
+    pub rejected: bool,
+    pub ip_address: String,
+    pub user_agent: String,
+}
+
+/// A passwordless login link was requested. The link is never published; the email carrying
+/// it is already in the outbox as `email_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MagicLinkRequestedEvent {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub token_id: Uuid,
+    /// Outbox row of the login link email, if one was queued
+    #[serde(default)]
+    pub email_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Support staff started acting as a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationStartedEvent {
+    pub session_id: Uuid,
+    pub impersonator_id: Uuid,
+    pub impersonator_email: String,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub reason: String,
+    pub read_only: bool,
+    pub expires_at: DateTime<Utc>,
+    pub ip_address: String,
+}
+
+/// An impersonation session was ended before its token expired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationEndedEvent {
+    pub session_id: Uuid,
+    pub impersonator_id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// A request made with an impersonation token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonatedRequestEvent {
+    pub session_id: Uuid,
+    pub impersonator_id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+}
+
+/// A sign-in or token refresh scored high enough to notify, step up or block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRiskDetectedEvent {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    /// The session (refresh token family), unless the attempt was blocked before one existed
+    pub session_id: Option<Uuid>,
+    pub risk_score: u32,
+    pub signals: Vec<String>,
+    /// `notify`, `step_up_mfa` or `block`
+    pub action: String,
+    pub country_code: Option<String>,
+    pub city: Option<String>,
+    /// Speed implied by travel from the previous session, when that was implausible
+    pub travel_speed_kmh: Option<f64>,
+    pub device_id: Option<String>,
+    pub ip_address: String,
+    pub user_agent: String,
+}
+
+/// Someone was invited to join a tenant; the mailer delivers `token` to `email`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInvitedEvent {
+    pub invitation_id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub roles: Vec<String>,
+    /// Empty when the membership covers every location
+    pub location_ids: Vec<Uuid>,
+    pub invited_by: Uuid,
+    /// Outbox row of the invitation email, if one was queued; the token itself is never published
+    #[serde(default)]
+    pub email_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// An invitation was accepted or declined by the person it was sent to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitationRespondedEvent {
+    pub invitation_id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    /// The identity that joined the tenant; `None` when declined
+    pub user_id: Option<Uuid>,
+    pub accepted: bool,
+}
+
 // ============================================================================
 // PLATFORM EVENTS
 // ============================================================================
@@ -368,6 +509,7 @@ pub enum MfaType {
     Email,
     Hardware,
     BackupCodes,
+    Passkey,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -454,6 +596,17 @@ pub mod auth_events {
     pub const EMAIL_VERIFIED: &str = "EmailVerified";
     pub const ACCOUNT_LOCKED: &str = "AccountLocked";
     pub const MFA_ENROLLED: &str = "MfaEnrolled";
+    pub const REFRESH_TOKEN_REUSE_DETECTED: &str = "RefreshTokenReuseDetected";
+    pub const PASSKEY_SIGN_COUNT_REGRESSED: &str = "PasskeySignCountRegressed";
+    pub const MAGIC_LINK_REQUESTED: &str = "MagicLinkRequested";
+    pub const IMPERSONATION_STARTED: &str = "ImpersonationStarted";
+    pub const IMPERSONATION_ENDED: &str = "ImpersonationEnded";
+    pub const IMPERSONATED_REQUEST: &str = "ImpersonatedRequest";
+    pub const USER_AUTHENTICATION_ATTEMPT: &str = "UserAuthenticationAttempt";
+    pub const SESSION_RISK_DETECTED: &str = "SessionRiskDetected";
+    pub const USER_INVITED: &str = "UserInvited";
+    pub const INVITATION_ACCEPTED: &str = "InvitationAccepted";
+    pub const INVITATION_DECLINED: &str = "InvitationDeclined";
 }
 
 /// Platform event types
@@ -495,6 +648,8 @@ mod tests {
             last_name: Some("Doe".to_string()),
             email_verification_required: true,
             registration_source: "web".to_string(),
+            email_id: None,
+            locale: None,
         };
 
         let serialized = serde_json::to_string(&event).unwrap();
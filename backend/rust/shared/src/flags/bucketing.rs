@@ -0,0 +1,234 @@
+// ============================================================================
+// OLYMPUS CLOUD - FEATURE FLAG BUCKETING
+// ============================================================================
+// Module: shared/src/flags/bucketing.rs
+// Description: Stable assignment of users, tenants, locations or devices to
+//              rollout buckets and experiment variants
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+//
+// A unit is hashed with MurmurHash3 (x86, 32-bit) over `"<salt>.<unit id>"` into
+// one of 10,000 buckets, so rollouts have 0.01% granularity. The hash has no
+// per-process seed: a unit lands in the same bucket on every instance and
+// after every restart. Raising a percentage only adds buckets, so units already
+// enabled stay enabled. Each flag has its own salt, keeping assignments of
+// different flags independent; changing the salt reshuffles a flag.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::targeting::EvaluationContext;
+
+/// Number of buckets; a percentage of 12.34 covers buckets 0..1234
+pub const BUCKETS: u32 = 10_000;
+
+/// The unit a flag is rolled out by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "flag_bucket_by", rename_all = "snake_case")]
+pub enum BucketBy {
+    #[default]
+    User,
+    Tenant,
+    Location,
+    Device,
+}
+
+impl BucketBy {
+    /// Context attribute identifying the unit
+    pub fn attribute(self) -> &'static str {
+        match self {
+            BucketBy::User => "user.id",
+            BucketBy::Tenant => "tenant.id",
+            BucketBy::Location => "location.id",
+            BucketBy::Device => "device.id",
+        }
+    }
+}
+
+/// The bucket of the unit `bucket_by` names in `context`. Without that attribute,
+/// e.g. no device id on a server-side call, the user is bucketed instead.
+pub fn context_bucket(salt: &str, bucket_by: BucketBy, context: &EvaluationContext) -> u32 {
+    let unit = [bucket_by.attribute(), BucketBy::User.attribute()]
+        .iter()
+        .find_map(|attribute| context.attribute(attribute).and_then(unit_id))
+        .unwrap_or_default();
+    bucket(salt, &unit)
+}
+
+fn unit_id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+pub fn bucket(salt: &str, unit: &str) -> u32 {
+    murmur3_32(format!("{}.{}", salt, unit).as_bytes(), 0) % BUCKETS
+}
+
+/// Whether a bucket falls within a rollout percentage
+pub fn in_rollout(bucket: u32, percentage: f64) -> bool {
+    (bucket as f64) < percentage * (BUCKETS as f64 / 100.0)
+}
+
+/// Pick a variant from a `{"name": weight_percent}` map. Variants are laid out in
+/// name order, so the assignment does not depend on JSON key order; buckets past
+/// the total weight are in no variant.
+pub fn assign_variant(bucket: u32, variants: &Value) -> Option<String> {
+    let weights: BTreeMap<String, f64> = serde_json::from_value(variants.clone()).ok()?;
+
+    let mut cumulative = 0.0;
+    for (variant, weight) in weights {
+        cumulative += weight;
+        if in_rollout(bucket, cumulative) {
+            return Some(variant);
+        }
+    }
+    None
+}
+
+/// MurmurHash3_x86_32
+pub fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mix = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+
+    let mut hash = seed;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        let k = u32::from_le_bytes(block.try_into().expect("chunk of 4"));
+        hash = (hash ^ mix(k)).rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        let k = tail.iter().rev().fold(0u32, |k, byte| (k << 8) | *byte as u32);
+        hash ^= mix(k);
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    const SAMPLE: u128 = 100_000;
+
+    /// Deterministic ids, as sequential as real v7/serial ids can be
+    fn units() -> impl Iterator<Item = String> {
+        (0..SAMPLE).map(|n| Uuid::from_u128(0x0192_0000_0000_7000_8000_0000_0000_0000 + n).to_string())
+    }
+
+    #[test]
+    fn test_murmur3_reference_values() {
+        assert_eq!(murmur3_32(b"", 0), 0);
+        assert_eq!(murmur3_32(b"", 1), 0x514e_28b7);
+        assert_eq!(murmur3_32(b"", 0xffff_ffff), 0x81f1_6f39);
+        assert_eq!(murmur3_32(b"test", 0), 0xba6b_d213);
+        assert_eq!(murmur3_32(b"Hello, world!", 0), 0xc036_3e43);
+        assert_eq!(murmur3_32(b"The quick brown fox jumps over the lazy dog", 0), 0x2e4f_f723);
+    }
+
+    #[test]
+    fn test_buckets_are_sticky() {
+        // Pinned values: any change to the hash or key format would silently move
+        // users between variants of running experiments
+        assert_eq!(bucket("new_menu_layout", "6f1c1a2e-8f5b-4c1e-9a7d-3b2f0c9d4e11"), 145);
+        assert_eq!(bucket("checkout_v2", "user-42"), 7838);
+    }
+
+    #[test]
+    fn test_distribution_is_uniform() {
+        // Chi-squared over 100 equal ranges of buckets; the 99.9th percentile with 99
+        // degrees of freedom is 148.2
+        let mut counts = [0u32; 100];
+        for unit in units() {
+            counts[(bucket("uniformity", &unit) / 100) as usize] += 1;
+        }
+        let expected = SAMPLE as f64 / 100.0;
+        let chi_squared: f64 = counts.iter().map(|&c| (c as f64 - expected).powi(2) / expected).sum();
+        assert!(chi_squared < 148.2, "chi-squared {}", chi_squared);
+
+        // Fine-grained percentages land close to their target
+        for percentage in [0.5, 1.0, 12.34, 50.0, 99.9] {
+            let enabled = units().filter(|unit| in_rollout(bucket("granularity", unit), percentage)).count();
+            let share = enabled as f64 * 100.0 / SAMPLE as f64;
+            assert!((share - percentage).abs() < 0.5, "{}% rollout enabled {}%", percentage, share);
+        }
+    }
+
+    #[test]
+    fn test_rollout_bounds_and_growth() {
+        assert!(!in_rollout(0, 0.0));
+        assert!(in_rollout(0, 0.01));
+        assert!(!in_rollout(1, 0.01));
+        assert!(in_rollout(BUCKETS - 1, 100.0));
+
+        // Raising the percentage keeps everyone already enabled
+        for unit in units().take(10_000) {
+            let bucket = bucket("growth", &unit);
+            if in_rollout(bucket, 5.0) {
+                assert!(in_rollout(bucket, 25.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_salts_are_independent() {
+        // With independent salts, being in 50% of one flag says nothing about another
+        let both = units()
+            .filter(|unit| in_rollout(bucket("flag_a", unit), 50.0) && in_rollout(bucket("flag_b", unit), 50.0))
+            .count();
+        let share = both as f64 / SAMPLE as f64;
+        assert!((share - 0.25).abs() < 0.01, "overlap {}", share);
+    }
+
+    #[test]
+    fn test_variants_are_deterministic_and_weighted() {
+        let forwards = json!({ "control": 50, "grid": 30, "list": 20 });
+        let backwards: Value = serde_json::from_str(r#"{ "list": 20, "grid": 30, "control": 50 }"#).unwrap();
+
+        let mut counts = BTreeMap::new();
+        for unit in units() {
+            let bucket = bucket("menu_layout", &unit);
+            let variant = assign_variant(bucket, &forwards);
+            assert_eq!(variant, assign_variant(bucket, &backwards));
+            *counts.entry(variant.unwrap()).or_insert(0u32) += 1;
+        }
+        for (variant, weight) in [("control", 50.0), ("grid", 30.0), ("list", 20.0)] {
+            // Sampling noise alone is about 0.15 points at this sample size
+            let share = counts[variant] as f64 * 100.0 / SAMPLE as f64;
+            assert!((share - weight).abs() < 1.0, "{} got {}%", variant, share);
+        }
+
+        // Weights short of 100% leave the rest out of the experiment
+        assert_eq!(assign_variant(9_999, &json!({ "a": 10, "b": 10 })), None);
+        assert_eq!(assign_variant(0, &json!({})), None);
+    }
+
+    #[test]
+    fn test_context_bucket_falls_back_to_user() {
+        let user = Uuid::new_v4();
+        let device = "ipad-7";
+        let context = EvaluationContext::default().with_attribute("user.id", json!(user));
+        let with_device = context.clone().with_attribute("device.id", json!(device));
+
+        assert_eq!(context_bucket("s", BucketBy::Device, &with_device), bucket("s", device));
+        assert_eq!(context_bucket("s", BucketBy::Device, &context), bucket("s", &user.to_string()));
+        assert_eq!(context_bucket("s", BucketBy::User, &with_device), bucket("s", &user.to_string()));
+    }
+}
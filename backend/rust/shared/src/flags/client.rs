@@ -0,0 +1,377 @@
+// ============================================================================
+// OLYMPUS CLOUD - LOCAL-EVALUATION FEATURE FLAG CLIENT
+// ============================================================================
+// Module: shared/src/flags/client.rs
+// Description: Evaluates a tenant's feature flags in process from a downloaded
+//              ruleset kept current over Redis pub/sub
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+//
+// `evaluate` never leaves the process: it reads the last ruleset downloaded from
+// the platform service and applies the same `FlagDefinition::evaluate` the
+// service uses. The ruleset is downloaded again whenever a feature flag event
+// for the tenant or for global flags is published, and on a polling interval in
+// case a message was missed or Redis is unavailable. Exposures are queued
+// without blocking and sent to the platform in batches; when the queue is full
+// they are dropped rather than slowing the caller down.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::evaluation::{FlagDefinition, FlagEvaluation, FlagRuleset};
+use super::targeting::{EvaluationContext, Segments};
+use crate::clients::{ClientConfig, HttpClient, HttpClientError};
+
+const RULESET_PATH: &str = "/api/v1/platform/feature-flags/ruleset";
+const EXPOSURES_PATH: &str = "/api/v1/platform/feature-flags/exposures";
+
+#[derive(Debug, Clone)]
+pub struct FlagClientConfig {
+    pub tenant_id: Uuid,
+    /// Platform service; a JWT here is sent with every request
+    pub platform: ClientConfig,
+    /// Without Redis, changes arrive only by polling
+    pub redis_url: Option<String>,
+    pub refresh_interval: Duration,
+    pub exposure_batch_size: usize,
+    pub exposure_flush_interval: Duration,
+    /// Exposures waiting to be sent; further ones are dropped
+    pub exposure_queue_size: usize,
+}
+
+impl FlagClientConfig {
+    pub fn new(tenant_id: Uuid, platform: ClientConfig) -> Self {
+        Self {
+            tenant_id,
+            platform,
+            redis_url: None,
+            refresh_interval: Duration::from_secs(60),
+            exposure_batch_size: 500,
+            exposure_flush_interval: Duration::from_secs(5),
+            exposure_queue_size: 10_000,
+        }
+    }
+
+    pub fn with_redis(mut self, redis_url: String) -> Self {
+        self.redis_url = Some(redis_url);
+        self
+    }
+}
+
+/// An evaluation as the platform records it in `feature_flag_evaluations`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagExposure {
+    pub flag_key: String,
+    pub user_id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    pub is_enabled: bool,
+    pub value: Value,
+    pub variant: Option<String>,
+    pub reason: String,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    data: T,
+}
+
+#[derive(Debug, Default)]
+struct LoadedRuleset {
+    flags: HashMap<String, FlagDefinition>,
+    segments: Segments,
+}
+
+pub struct FlagClient {
+    config: FlagClientConfig,
+    http: HttpClient,
+    ruleset: RwLock<LoadedRuleset>,
+    exposures: mpsc::Sender<FlagExposure>,
+    pending_exposures: Mutex<Option<mpsc::Receiver<FlagExposure>>>,
+}
+
+impl FlagClient {
+    pub fn new(config: FlagClientConfig) -> Result<Self, HttpClientError> {
+        let jwt_token = config.platform.jwt_token.clone();
+        let mut http = HttpClient::new(config.platform.clone().into())?;
+        if let Some(token) = jwt_token {
+            http = http.with_token(token);
+        }
+
+        let (exposures, pending_exposures) = mpsc::channel(config.exposure_queue_size.max(1));
+        Ok(Self {
+            config,
+            http,
+            ruleset: RwLock::new(LoadedRuleset::default()),
+            exposures,
+            pending_exposures: Mutex::new(Some(pending_exposures)),
+        })
+    }
+
+    /// Evaluate a flag from the local ruleset and queue the exposure. The tenant is
+    /// added to the context when the caller did not set one.
+    pub fn evaluate(&self, flag_key: &str, context: &EvaluationContext) -> FlagEvaluation {
+        let context = match context.attribute("tenant.id") {
+            Some(_) => context.clone(),
+            None => context.clone().with_attribute("tenant.id", serde_json::json!(self.config.tenant_id)),
+        };
+
+        let now = Utc::now();
+        let evaluation = {
+            let ruleset = self.ruleset.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+            match ruleset.flags.get(flag_key) {
+                Some(flag) => flag.evaluate(&ruleset.flags, &context, &ruleset.segments, now),
+                None => FlagEvaluation::not_found(),
+            }
+        };
+
+        self.record_exposure(flag_key, &context, &evaluation);
+        evaluation
+    }
+
+    pub fn is_enabled(&self, flag_key: &str, context: &EvaluationContext) -> bool {
+        self.evaluate(flag_key, context).is_enabled
+    }
+
+    pub fn variant(&self, flag_key: &str, context: &EvaluationContext) -> Option<String> {
+        self.evaluate(flag_key, context).variant
+    }
+
+    /// Download the tenant's ruleset and swap it in
+    pub async fn refresh(&self) -> Result<(), HttpClientError> {
+        let response: Envelope<FlagRuleset> = self.http.get(RULESET_PATH).await?;
+        let count = response.data.flags.len();
+        self.load(response.data);
+        debug!("Loaded {} feature flags for tenant {}", count, self.config.tenant_id);
+        Ok(())
+    }
+
+    fn load(&self, ruleset: FlagRuleset) {
+        let loaded = LoadedRuleset {
+            flags: ruleset.flags.into_iter().map(|flag| (flag.key.clone(), flag)).collect(),
+            segments: ruleset.segments,
+        };
+        *self.ruleset.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = loaded;
+    }
+
+    /// Only evaluations of identified users are exposures worth analyzing
+    fn record_exposure(&self, flag_key: &str, context: &EvaluationContext, evaluation: &FlagEvaluation) {
+        let Some(user_id) = context
+            .attribute("user.id")
+            .and_then(Value::as_str)
+            .and_then(|user_id| Uuid::parse_str(user_id).ok())
+        else {
+            return;
+        };
+
+        let exposure = FlagExposure {
+            flag_key: flag_key.to_string(),
+            user_id,
+            tenant_id: Some(self.config.tenant_id),
+            is_enabled: evaluation.is_enabled,
+            value: evaluation.value.clone(),
+            variant: evaluation.variant.clone(),
+            reason: evaluation.reason.clone(),
+            evaluated_at: Utc::now(),
+        };
+        if self.exposures.try_send(exposure).is_err() {
+            debug!("Flag exposure queue full, dropping exposure of {}", flag_key);
+        }
+    }
+
+    /// Keep the ruleset current and send exposures in the background
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            tokio::join!(self.watch_updates(), self.send_exposures());
+        })
+    }
+
+    async fn watch_updates(&self) {
+        let mut poll = tokio::time::interval(self.config.refresh_interval);
+        loop {
+            let mut messages = self.subscribe().await;
+            loop {
+                tokio::select! {
+                    _ = poll.tick() => {
+                        self.refresh_logged().await;
+                        // A failed subscription is retried on every poll
+                        if messages.is_none() && self.config.redis_url.is_some() {
+                            break;
+                        }
+                    }
+                    message = next_message(&mut messages) => {
+                        if message.is_none() {
+                            warn!("Lost feature flag updates subscription, resubscribing");
+                            break;
+                        }
+                        self.refresh_logged().await;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn refresh_logged(&self) {
+        if let Err(e) = self.refresh().await {
+            warn!("Failed to refresh feature flags, keeping the last ruleset: {}", e);
+        }
+    }
+
+    /// Flag events for the tenant, and for global flags, which are published under
+    /// the nil tenant
+    async fn subscribe(&self) -> Option<BoxStream<'static, redis::Msg>> {
+        let redis_url = self.config.redis_url.as_ref()?;
+
+        let subscription = async {
+            let client = redis::Client::open(redis_url.as_str())?;
+            let mut pubsub = client.get_async_connection().await?.into_pubsub();
+            for tenant_id in [self.config.tenant_id, Uuid::nil()] {
+                pubsub.psubscribe(format!("events.tenant.{}.FeatureFlag*", tenant_id)).await?;
+            }
+            Ok::<_, redis::RedisError>(pubsub.into_on_message().boxed())
+        };
+
+        match subscription.await {
+            Ok(messages) => Some(messages),
+            Err(e) => {
+                warn!("Failed to subscribe to feature flag updates, polling instead: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn send_exposures(&self) {
+        let receiver = self
+            .pending_exposures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        let Some(mut receiver) = receiver else {
+            return;
+        };
+
+        let batch_size = self.config.exposure_batch_size.max(1);
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut flush = tokio::time::interval(self.config.exposure_flush_interval);
+        loop {
+            tokio::select! {
+                exposure = receiver.recv() => match exposure {
+                    Some(exposure) => {
+                        batch.push(exposure);
+                        if batch.len() >= batch_size {
+                            self.flush_exposures(&mut batch).await;
+                        }
+                    }
+                    None => {
+                        self.flush_exposures(&mut batch).await;
+                        return;
+                    }
+                },
+                _ = flush.tick() => self.flush_exposures(&mut batch).await,
+            }
+        }
+    }
+
+    async fn flush_exposures(&self, batch: &mut Vec<FlagExposure>) {
+        if batch.is_empty() {
+            return;
+        }
+        let exposures = std::mem::take(batch);
+        if let Err(e) = self.http.post::<_, Value>(EXPOSURES_PATH, &exposures).await {
+            warn!("Failed to send {} flag exposures: {}", exposures.len(), e);
+        }
+    }
+}
+
+/// The next update message; never resolves without a subscription
+async fn next_message(messages: &mut Option<BoxStream<'static, redis::Msg>>) -> Option<redis::Msg> {
+    match messages {
+        Some(messages) => messages.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flags::{BucketBy, FeatureFlagStatus, RolloutStrategy, TargetingRules};
+    use serde_json::json;
+
+    fn client() -> FlagClient {
+        let mut config = FlagClientConfig::new(Uuid::new_v4(), ClientConfig::default());
+        config.exposure_queue_size = 2;
+        FlagClient::new(config).unwrap()
+    }
+
+    fn ruleset() -> FlagRuleset {
+        FlagRuleset {
+            tenant_id: None,
+            flags: vec![FlagDefinition {
+                key: "new_menu_layout".to_string(),
+                status: FeatureFlagStatus::Active,
+                default_value: json!(true),
+                rollout_strategy: RolloutStrategy::ABTest,
+                rollout_percentage: None,
+                target_users: vec![],
+                target_groups: vec![],
+                rules: TargetingRules::default(),
+                variants: json!({ "control": 50, "grid": 50 }),
+                bucket_by: BucketBy::User,
+                salt: "menu".to_string(),
+                starts_at: None,
+                ends_at: None,
+                prerequisites: vec![],
+                exclusion: None,
+                killed: false,
+            }],
+            segments: Segments::new(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_evaluates_from_loaded_ruleset() {
+        let client = client();
+        let context = EvaluationContext::default().with_attribute("user.id", json!(Uuid::new_v4()));
+        assert_eq!(client.evaluate("new_menu_layout", &context), FlagEvaluation::not_found());
+
+        // Round-trip through JSON, as the ruleset arrives from the platform
+        let downloaded = serde_json::from_value(serde_json::to_value(ruleset()).unwrap()).unwrap();
+        client.load(downloaded);
+        let evaluation = client.evaluate("new_menu_layout", &context);
+        assert!(evaluation.is_enabled);
+        assert!(evaluation.variant.is_some());
+        assert_eq!(client.variant("new_menu_layout", &context), evaluation.variant);
+    }
+
+    #[test]
+    fn test_exposures_queue_without_blocking() {
+        let client = client();
+        client.load(ruleset());
+        let user_id = Uuid::new_v4();
+        let context = EvaluationContext::default().with_attribute("user.id", json!(user_id));
+
+        // Anonymous evaluations are not exposures; past the queue size they are dropped
+        client.evaluate("new_menu_layout", &EvaluationContext::default());
+        for _ in 0..3 {
+            client.evaluate("new_menu_layout", &context);
+        }
+
+        let mut receiver = client.pending_exposures.lock().unwrap().take().unwrap();
+        let exposure = receiver.try_recv().unwrap();
+        assert_eq!(exposure.user_id, user_id);
+        assert_eq!(exposure.tenant_id, Some(client.config.tenant_id));
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,520 @@
+// ============================================================================
+// OLYMPUS CLOUD - FEATURE FLAG EVALUATION
+// ============================================================================
+// Module: shared/src/flags/evaluation.rs
+// Description: Evaluation of a flag definition against a context, used both by
+//              the platform service and by local-evaluation clients
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::bucketing::{self, BucketBy};
+use super::targeting::{EvaluationContext, RuleServe, Segments, TargetingRule, TargetingRules};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "feature_flag_status", rename_all = "lowercase")]
+pub enum FeatureFlagStatus {
+    Active,
+    Inactive,
+    Scheduled,
+    Expired,
+    Testing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "rollout_strategy", rename_all = "snake_case")]
+pub enum RolloutStrategy {
+    AllUsers,
+    PercentageUsers,
+    SpecificUsers,
+    UserGroups,
+    GradualRollout,
+    ABTest,
+}
+
+/// Longest chain of prerequisites followed; cycles are rejected when flags are saved
+pub const MAX_PREREQUISITE_DEPTH: usize = 8;
+
+/// Flags by key, for resolving prerequisites
+pub type Flags = HashMap<String, FlagDefinition>;
+
+/// Another flag that must be on before this one evaluates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlagPrerequisite {
+    pub key: String,
+    /// When set, the prerequisite must also serve this variant
+    #[serde(default)]
+    pub variant: Option<String>,
+}
+
+/// A flag's share of an exclusion group's traffic, as a percentage range. Members
+/// of a group hold disjoint ranges of the same user buckets, so a user is in at
+/// most one of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExclusionSlot {
+    pub group: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl ExclusionSlot {
+    pub fn overlaps(&self, other: &ExclusionSlot) -> bool {
+        self.group == other.group && self.start < other.end && other.start < self.end
+    }
+
+    fn contains(&self, context: &EvaluationContext) -> bool {
+        let bucket = bucketing::context_bucket(&format!("exclusion.{}", self.group), BucketBy::User, context);
+        !bucketing::in_rollout(bucket, self.start) && bucketing::in_rollout(bucket, self.end)
+    }
+}
+
+/// The outcome of evaluating one flag
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagEvaluation {
+    pub is_enabled: bool,
+    pub value: Value,
+    pub variant: Option<String>,
+    pub reason: String,
+}
+
+impl FlagEvaluation {
+    pub fn not_found() -> Self {
+        Self::disabled(Value::Bool(false), "Flag not found")
+    }
+
+    fn disabled(value: Value, reason: &str) -> Self {
+        Self {
+            is_enabled: false,
+            value,
+            variant: None,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Everything evaluation needs to know about a flag, with its targeting rules
+/// already parsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagDefinition {
+    pub key: String,
+    pub status: FeatureFlagStatus,
+    pub default_value: Value,
+    pub rollout_strategy: RolloutStrategy,
+    pub rollout_percentage: Option<f64>,
+    pub target_users: Vec<Uuid>,
+    pub target_groups: Vec<String>,
+    pub rules: TargetingRules,
+    pub variants: Value,
+    pub bucket_by: BucketBy,
+    pub salt: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub prerequisites: Vec<FlagPrerequisite>,
+    #[serde(default)]
+    pub exclusion: Option<ExclusionSlot>,
+    /// Forced to its default value everywhere by the kill switch
+    #[serde(default)]
+    pub killed: bool,
+}
+
+impl FlagDefinition {
+    /// Whether evaluation reads the tenant's segments
+    pub fn needs_segments(&self) -> bool {
+        self.rules.references_segments() || self.rollout_strategy == RolloutStrategy::UserGroups
+    }
+
+    /// Evaluate the flag; `flags` must hold its prerequisites, and theirs in turn
+    pub fn evaluate(
+        &self,
+        flags: &Flags,
+        context: &EvaluationContext,
+        segments: &Segments,
+        now: DateTime<Utc>,
+    ) -> FlagEvaluation {
+        self.evaluate_at_depth(flags, context, segments, now, 0)
+    }
+
+    fn evaluate_at_depth(
+        &self,
+        flags: &Flags,
+        context: &EvaluationContext,
+        segments: &Segments,
+        now: DateTime<Utc>,
+        depth: usize,
+    ) -> FlagEvaluation {
+        if self.killed {
+            return FlagEvaluation::disabled(self.default_value.clone(), "Flag has been killed");
+        }
+        if self.status != FeatureFlagStatus::Active {
+            return FlagEvaluation::disabled(self.default_value.clone(), "Flag is not active");
+        }
+        if self.starts_at.is_some_and(|starts_at| now < starts_at) {
+            return FlagEvaluation::disabled(self.default_value.clone(), "Flag has not started yet");
+        }
+        if self.ends_at.is_some_and(|ends_at| now > ends_at) {
+            return FlagEvaluation::disabled(self.default_value.clone(), "Flag has expired");
+        }
+
+        for prerequisite in &self.prerequisites {
+            let met = depth < MAX_PREREQUISITE_DEPTH
+                && flags.get(&prerequisite.key).is_some_and(|flag| {
+                    let evaluation = flag.evaluate_at_depth(flags, context, segments, now, depth + 1);
+                    evaluation.is_enabled
+                        && prerequisite.variant.as_ref().map_or(true, |variant| evaluation.variant.as_ref() == Some(variant))
+                });
+            if !met {
+                return FlagEvaluation::disabled(
+                    self.default_value.clone(),
+                    &format!("Prerequisite {} not met", prerequisite.key),
+                );
+            }
+        }
+
+        if let Some(exclusion) = self.exclusion.as_ref().filter(|exclusion| !exclusion.contains(context)) {
+            return FlagEvaluation::disabled(
+                self.default_value.clone(),
+                &format!("Outside exclusion group {}", exclusion.group),
+            );
+        }
+
+        let bucket = bucketing::context_bucket(&self.salt, self.bucket_by, context);
+        if let Some(rule) = self.rules.first_match(context, segments) {
+            return self.serve_rule(bucket, rule);
+        }
+
+        let (is_enabled, variant, reason) = match self.rollout_strategy {
+            RolloutStrategy::AllUsers => (true, None, "All users enabled".to_string()),
+
+            RolloutStrategy::PercentageUsers => {
+                let percentage = self.rollout_percentage.unwrap_or(0.0);
+                (
+                    bucketing::in_rollout(bucket, percentage),
+                    None,
+                    format!("Percentage rollout: {}%", percentage),
+                )
+            }
+
+            RolloutStrategy::SpecificUsers => {
+                let is_enabled = context
+                    .attribute("user.id")
+                    .and_then(Value::as_str)
+                    .and_then(|user_id| Uuid::parse_str(user_id).ok())
+                    .is_some_and(|user_id| self.target_users.contains(&user_id));
+                (
+                    is_enabled,
+                    None,
+                    if is_enabled { "User in target list" } else { "User not in target list" }.to_string(),
+                )
+            }
+
+            RolloutStrategy::UserGroups => {
+                let group = self.matching_group(context, segments);
+                (
+                    group.is_some(),
+                    None,
+                    match group {
+                        Some(group) => format!("User in target group {}", group),
+                        None => "User not in target groups".to_string(),
+                    },
+                )
+            }
+
+            RolloutStrategy::GradualRollout => {
+                let percentage = self.rollout_percentage.unwrap_or(0.0);
+                (
+                    bucketing::in_rollout(bucket, percentage),
+                    None,
+                    format!("Gradual rollout: {}%", percentage),
+                )
+            }
+
+            RolloutStrategy::ABTest => {
+                let variant = bucketing::assign_variant(bucket, &self.variants);
+                (variant.is_some(), variant, "A/B test variant assignment".to_string())
+            }
+        };
+
+        FlagEvaluation {
+            is_enabled,
+            value: self.default_value.clone(),
+            variant,
+            reason,
+        }
+    }
+
+    fn serve_rule(&self, bucket: u32, rule: &TargetingRule) -> FlagEvaluation {
+        let (is_enabled, variant, reason) = match &rule.serve {
+            RuleServe::Variant { variant } => (
+                true,
+                Some(variant.clone()),
+                format!("Matched rule {}: variant {}", rule.id, variant),
+            ),
+            RuleServe::Percentage { percentage } => (
+                bucketing::in_rollout(bucket, *percentage),
+                None,
+                format!("Matched rule {}: {}% rollout", rule.id, percentage),
+            ),
+        };
+
+        FlagEvaluation {
+            is_enabled,
+            value: self.default_value.clone(),
+            variant,
+            reason,
+        }
+    }
+
+    /// A target group matches when the caller lists it in `user.groups` or it names a
+    /// segment the context satisfies
+    fn matching_group(&self, context: &EvaluationContext, segments: &Segments) -> Option<&str> {
+        let user_groups = context.attribute("user.groups").and_then(Value::as_array);
+
+        self.target_groups
+            .iter()
+            .find(|group| {
+                user_groups.is_some_and(|groups| groups.iter().any(|g| g.as_str() == Some(group.as_str())))
+                    || segments.get(group.as_str()).is_some_and(|segment| segment.matches(context, segments))
+            })
+            .map(String::as_str)
+    }
+}
+
+/// The prerequisite chain that would lead from `key` back to itself once its
+/// prerequisites are set to `prerequisites`, e.g. `["a", "b", "a"]`. `graph` maps
+/// the other flags' keys to the keys they require.
+pub fn prerequisite_cycle(
+    key: &str,
+    prerequisites: &[FlagPrerequisite],
+    graph: &HashMap<String, Vec<String>>,
+) -> Option<Vec<String>> {
+    fn visit<'a>(
+        key: &'a str,
+        target: &str,
+        graph: &'a HashMap<String, Vec<String>>,
+        path: &mut Vec<&'a str>,
+        visited: &mut HashSet<&'a str>,
+    ) -> bool {
+        path.push(key);
+        if key == target {
+            return true;
+        }
+        if visited.insert(key) {
+            for next in graph.get(key).into_iter().flatten() {
+                if visit(next, target, graph, path, visited) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+
+    let mut visited = HashSet::new();
+    for prerequisite in prerequisites {
+        let mut path = vec![key];
+        if visit(&prerequisite.key, key, graph, &mut path, &mut visited) {
+            return Some(path.into_iter().map(str::to_string).collect());
+        }
+    }
+    None
+}
+
+/// A tenant's flags, global flags included, and its segments: all a client needs
+/// to evaluate locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagRuleset {
+    pub tenant_id: Option<Uuid>,
+    pub flags: Vec<FlagDefinition>,
+    #[serde(default)]
+    pub segments: Segments,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn flag(rollout_strategy: RolloutStrategy) -> FlagDefinition {
+        FlagDefinition {
+            key: "new_menu_layout".to_string(),
+            status: FeatureFlagStatus::Active,
+            default_value: json!(true),
+            rollout_strategy,
+            rollout_percentage: None,
+            target_users: vec![],
+            target_groups: vec![],
+            rules: TargetingRules::default(),
+            variants: json!({}),
+            bucket_by: BucketBy::User,
+            salt: "menu".to_string(),
+            starts_at: None,
+            ends_at: None,
+            prerequisites: vec![],
+            exclusion: None,
+            killed: false,
+        }
+    }
+
+    fn user(user_id: Uuid) -> EvaluationContext {
+        EvaluationContext::default().with_attribute("user.id", json!(user_id))
+    }
+
+    #[test]
+    fn test_status_and_schedule_window() {
+        let now = Utc::now();
+        let context = user(Uuid::new_v4());
+        let segments = Segments::new();
+
+        let mut inactive = flag(RolloutStrategy::AllUsers);
+        inactive.status = FeatureFlagStatus::Inactive;
+        assert_eq!(inactive.evaluate(&Flags::new(), &context, &segments, now).reason, "Flag is not active");
+
+        // Both ends of the window apply, even when the flag has a start
+        let mut windowed = flag(RolloutStrategy::AllUsers);
+        windowed.starts_at = Some(now - Duration::days(7));
+        windowed.ends_at = Some(now - Duration::days(1));
+        let evaluation = windowed.evaluate(&Flags::new(), &context, &segments, now);
+        assert!(!evaluation.is_enabled);
+        assert_eq!(evaluation.reason, "Flag has expired");
+
+        windowed.ends_at = Some(now + Duration::days(1));
+        assert!(windowed.evaluate(&Flags::new(), &context, &segments, now).is_enabled);
+        assert!(!windowed.evaluate(&Flags::new(), &context, &segments, now - Duration::days(8)).is_enabled);
+    }
+
+    #[test]
+    fn test_rules_take_precedence_over_strategy() {
+        let mut definition = flag(RolloutStrategy::SpecificUsers);
+        definition.rules = TargetingRules::from_conditions(&json!({
+            "rules": [{
+                "id": "pro",
+                "when": { "attribute": "tenant.tier", "operator": "eq", "value": "pro" },
+                "serve": { "variant": "grid" }
+            }]
+        }))
+        .unwrap();
+        let targeted = Uuid::new_v4();
+        definition.target_users = vec![targeted];
+        let segments = Segments::new();
+        let now = Utc::now();
+
+        let pro = user(Uuid::new_v4()).with_attribute("tenant.tier", json!("pro"));
+        let evaluation = definition.evaluate(&Flags::new(), &pro, &segments, now);
+        assert_eq!(evaluation.variant.as_deref(), Some("grid"));
+        assert_eq!(evaluation.reason, "Matched rule pro: variant grid");
+
+        assert!(definition.evaluate(&Flags::new(), &user(targeted), &segments, now).is_enabled);
+        assert!(!definition.evaluate(&Flags::new(), &user(Uuid::new_v4()), &segments, now).is_enabled);
+    }
+
+    #[test]
+    fn test_groups_and_experiments() {
+        let now = Utc::now();
+        let segments: Segments = serde_json::from_value(json!({
+            "staff": { "attribute": "user.email", "operator": "contains", "value": "@olympus.example" }
+        }))
+        .unwrap();
+
+        let mut groups = flag(RolloutStrategy::UserGroups);
+        groups.target_groups = vec!["beta".to_string(), "staff".to_string()];
+        assert!(groups.needs_segments());
+        let beta = user(Uuid::new_v4()).with_attribute("user.groups", json!(["beta"]));
+        let staff = user(Uuid::new_v4()).with_attribute("user.email", json!("ana@olympus.example"));
+        assert_eq!(groups.evaluate(&Flags::new(), &beta, &segments, now).reason, "User in target group beta");
+        assert_eq!(groups.evaluate(&Flags::new(), &staff, &segments, now).reason, "User in target group staff");
+        assert!(!groups.evaluate(&Flags::new(), &user(Uuid::new_v4()), &segments, now).is_enabled);
+
+        let mut experiment = flag(RolloutStrategy::ABTest);
+        experiment.variants = json!({ "control": 50, "grid": 50 });
+        let context = user(Uuid::new_v4());
+        let evaluation = experiment.evaluate(&Flags::new(), &context, &segments, now);
+        assert!(evaluation.is_enabled);
+        assert_eq!(evaluation, experiment.evaluate(&Flags::new(), &context, &segments, now));
+    }
+
+    #[test]
+    fn test_prerequisites_and_kill_switch() {
+        let now = Utc::now();
+        let segments = Segments::new();
+        let context = user(Uuid::new_v4());
+
+        let mut kds = flag(RolloutStrategy::AllUsers);
+        kds.key = "kds_enabled".to_string();
+        let mut kds_v2 = flag(RolloutStrategy::AllUsers);
+        kds_v2.key = "new_kds_v2".to_string();
+        kds_v2.prerequisites = vec![FlagPrerequisite { key: "kds_enabled".to_string(), variant: None }];
+
+        let mut flags = Flags::new();
+        assert_eq!(
+            kds_v2.evaluate(&flags, &context, &segments, now).reason,
+            "Prerequisite kds_enabled not met"
+        );
+        flags.insert(kds.key.clone(), kds.clone());
+        assert!(kds_v2.evaluate(&flags, &context, &segments, now).is_enabled);
+
+        // Killing the prerequisite turns off everything that depends on it
+        kds.killed = true;
+        flags.insert(kds.key.clone(), kds.clone());
+        let evaluation = kds.evaluate(&flags, &context, &segments, now);
+        assert_eq!((evaluation.is_enabled, evaluation.value), (false, json!(true)));
+        assert!(!kds_v2.evaluate(&flags, &context, &segments, now).is_enabled);
+
+        // A variant requirement must be met exactly
+        let mut experiment = flag(RolloutStrategy::ABTest);
+        experiment.key = "menu_test".to_string();
+        experiment.variants = json!({ "grid": 100 });
+        flags.insert(experiment.key.clone(), experiment);
+        let mut follow_up = flag(RolloutStrategy::AllUsers);
+        follow_up.prerequisites = vec![FlagPrerequisite { key: "menu_test".to_string(), variant: Some("list".to_string()) }];
+        assert!(!follow_up.evaluate(&flags, &context, &segments, now).is_enabled);
+        follow_up.prerequisites[0].variant = Some("grid".to_string());
+        assert!(follow_up.evaluate(&flags, &context, &segments, now).is_enabled);
+    }
+
+    #[test]
+    fn test_exclusion_group_slots_are_disjoint() {
+        let now = Utc::now();
+        let segments = Segments::new();
+        let slot = |start, end| ExclusionSlot { group: "menu".to_string(), start, end };
+        let experiments: Vec<FlagDefinition> = [slot(0.0, 30.0), slot(30.0, 60.0), slot(60.0, 100.0)]
+            .into_iter()
+            .map(|exclusion| FlagDefinition { exclusion: Some(exclusion), ..flag(RolloutStrategy::AllUsers) })
+            .collect();
+        assert!(slot(0.0, 30.0).overlaps(&slot(20.0, 40.0)));
+        assert!(!slot(0.0, 30.0).overlaps(&slot(30.0, 60.0)));
+
+        for n in 0..2_000u128 {
+            let context = user(Uuid::from_u128(n));
+            let enrolled = experiments
+                .iter()
+                .filter(|experiment| experiment.evaluate(&Flags::new(), &context, &segments, now).is_enabled)
+                .count();
+            assert_eq!(enrolled, 1);
+        }
+    }
+
+    #[test]
+    fn test_prerequisite_cycles() {
+        let requires = |key: &str| vec![FlagPrerequisite { key: key.to_string(), variant: None }];
+        let graph: HashMap<String, Vec<String>> = HashMap::from([
+            ("b".to_string(), vec!["c".to_string()]),
+            ("c".to_string(), vec!["a".to_string()]),
+            ("d".to_string(), vec![]),
+        ]);
+
+        assert_eq!(prerequisite_cycle("a", &requires("b"), &graph), Some(vec!["a".into(), "b".into(), "c".into(), "a".into()]));
+        assert_eq!(prerequisite_cycle("a", &requires("a"), &graph), Some(vec!["a".into(), "a".into()]));
+        assert_eq!(prerequisite_cycle("a", &requires("d"), &graph), None);
+        assert_eq!(prerequisite_cycle("x", &requires("b"), &graph), None);
+    }
+}
@@ -0,0 +1,24 @@
+// ============================================================================
+// OLYMPUS CLOUD - FEATURE FLAG EVALUATION
+// ============================================================================
+// Module: shared/src/flags/mod.rs
+// Description: Flag evaluation logic shared by the platform service and its clients
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+
+pub mod bucketing;
+pub mod client;
+pub mod evaluation;
+pub mod targeting;
+
+pub use bucketing::{BucketBy, BUCKETS};
+pub use client::{FlagClient, FlagClientConfig, FlagExposure};
+pub use evaluation::{
+    prerequisite_cycle, ExclusionSlot, FeatureFlagStatus, FlagDefinition, FlagEvaluation, FlagPrerequisite, FlagRuleset,
+    Flags, RolloutStrategy, MAX_PREREQUISITE_DEPTH,
+};
+pub use targeting::{
+    Clause, EvaluationContext, RuleServe, Segments, TargetingCondition, TargetingOperator, TargetingRule,
+    TargetingRules, SEGMENTS_CONFIG_KEY,
+};
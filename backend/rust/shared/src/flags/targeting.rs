@@ -0,0 +1,588 @@
+// ============================================================================
+// OLYMPUS CLOUD - FEATURE FLAG TARGETING RULES
+// ============================================================================
+// Module: shared/src/flags/targeting.rs
+// Description: Ordered targeting rules matched against an evaluation context
+// Author: Claude Code Agent
+// Date: 2025-01-22
+// ============================================================================
+//
+// A flag's `conditions` hold an ordered list of rules. The first rule whose
+// `when` clause matches decides what is served; when none match, the flag's
+// rollout strategy applies as before:
+//
+//   {
+//     "rules": [
+//       {
+//         "id": "internal",
+//         "when": { "segment": "staff" },
+//         "serve": { "variant": "treatment" }
+//       },
+//       {
+//         "id": "us-premium",
+//         "when": { "all": [
+//           { "attribute": "tenant.tier", "operator": "in", "value": ["pro", "enterprise"] },
+//           { "attribute": "location.country", "operator": "eq", "value": "US" },
+//           { "any": [
+//             { "attribute": "app_version", "operator": "semver_gte", "value": "2.4.0" },
+//             { "attribute": "platform", "operator": "eq", "value": "web" }
+//           ] }
+//         ] },
+//         "serve": { "percentage": 25 }
+//       }
+//     ]
+//   }
+//
+// Segments are named clauses shared by every flag of a tenant, stored as the
+// `feature_flag_segments` tenant configuration.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{Error, Result};
+use crate::policy::{contains, ordering, values_equal};
+
+/// Tenant configuration key holding the tenant's segments as a JSON object of name to clause
+pub const SEGMENTS_CONFIG_KEY: &str = "feature_flag_segments";
+
+/// Segments may reference other segments; deeper chains are treated as a cycle
+const MAX_SEGMENT_DEPTH: usize = 8;
+
+/// A tenant's segments by name
+pub type Segments = HashMap<String, Clause>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetingOperator {
+    Eq,
+    Ne,
+    In,
+    NotIn,
+    Contains,
+    NotContains,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    SemverEq,
+    SemverLt,
+    SemverLte,
+    SemverGt,
+    SemverGte,
+    Exists,
+}
+
+impl TargetingOperator {
+    fn is_semver(self) -> bool {
+        matches!(
+            self,
+            Self::SemverEq | Self::SemverLt | Self::SemverLte | Self::SemverGt | Self::SemverGte
+        )
+    }
+}
+
+/// A single attribute test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetingCondition {
+    /// Dotted path into the evaluation context, e.g. `tenant.tier`
+    pub attribute: String,
+    pub operator: TargetingOperator,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+/// Conditions combined with AND/OR, or a reference to a segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Clause {
+    All { all: Vec<Clause> },
+    Any { any: Vec<Clause> },
+    Segment { segment: String },
+    Condition(TargetingCondition),
+}
+
+/// What a matching rule serves
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleServe {
+    Variant { variant: String },
+    /// Enable the flag for this share of the matched users
+    Percentage { percentage: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetingRule {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Absent matches everyone, for a catch-all at the end of the list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<Clause>,
+    pub serve: RuleServe,
+}
+
+/// The rules held in a flag's `conditions`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TargetingRules {
+    #[serde(default)]
+    pub rules: Vec<TargetingRule>,
+}
+
+impl TargetingRules {
+    /// Parse a flag's `conditions`; null and `{}` mean no rules
+    pub fn from_conditions(conditions: &Value) -> Result<Self> {
+        if conditions.is_null() {
+            return Ok(Self::default());
+        }
+        serde_json::from_value(conditions.clone())
+            .map_err(|e| Error::Validation(format!("Invalid targeting rules: {}", e)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Reject rules that could never evaluate as their author intended. `variants` is the
+    /// flag's variant map; when it names variants, rules may only serve those.
+    pub fn validate(&self, variants: &Value) -> Result<()> {
+        let known_variants = variants.as_object().filter(|variants| !variants.is_empty());
+        let mut ids = HashSet::new();
+
+        for rule in &self.rules {
+            if rule.id.trim().is_empty() {
+                return Err(Error::Validation("Targeting rule id must not be empty".to_string()));
+            }
+            if !ids.insert(rule.id.as_str()) {
+                return Err(Error::Validation(format!("Duplicate targeting rule id '{}'", rule.id)));
+            }
+
+            match &rule.serve {
+                RuleServe::Variant { variant } => {
+                    if known_variants.is_some_and(|known| !known.contains_key(variant)) {
+                        return Err(Error::Validation(format!(
+                            "Rule '{}' serves unknown variant '{}'",
+                            rule.id, variant
+                        )));
+                    }
+                }
+                RuleServe::Percentage { percentage } => {
+                    if !(0.0..=100.0).contains(percentage) {
+                        return Err(Error::Validation(format!(
+                            "Rule '{}' percentage must be between 0 and 100",
+                            rule.id
+                        )));
+                    }
+                }
+            }
+
+            if let Some(clause) = &rule.when {
+                validate_clause(clause).map_err(|reason| {
+                    Error::Validation(format!("Rule '{}': {}", rule.id, reason))
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether evaluating these rules needs the tenant's segments loaded
+    pub fn references_segments(&self) -> bool {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.when.as_ref())
+            .any(Clause::references_segments)
+    }
+
+    /// The first rule whose clause holds, in declaration order
+    pub fn first_match(&self, context: &EvaluationContext, segments: &Segments) -> Option<&TargetingRule> {
+        self.rules.iter().find(|rule| {
+            rule.when
+                .as_ref()
+                .map_or(true, |clause| clause.matches(context, segments))
+        })
+    }
+}
+
+impl Clause {
+    pub fn matches(&self, context: &EvaluationContext, segments: &Segments) -> bool {
+        self.matches_at(context, segments, 0)
+    }
+
+    fn matches_at(&self, context: &EvaluationContext, segments: &Segments, depth: usize) -> bool {
+        match self {
+            Clause::All { all } => all.iter().all(|clause| clause.matches_at(context, segments, depth)),
+            Clause::Any { any } => any.iter().any(|clause| clause.matches_at(context, segments, depth)),
+            Clause::Segment { segment } => {
+                if depth >= MAX_SEGMENT_DEPTH {
+                    tracing::warn!("Segment '{}' nests too deeply; treating it as unmatched", segment);
+                    return false;
+                }
+                match segments.get(segment) {
+                    Some(clause) => clause.matches_at(context, segments, depth + 1),
+                    None => {
+                        tracing::warn!("Targeting rule references unknown segment '{}'", segment);
+                        false
+                    }
+                }
+            }
+            Clause::Condition(condition) => condition_holds(condition, context),
+        }
+    }
+
+    fn references_segments(&self) -> bool {
+        match self {
+            Clause::All { all: clauses } | Clause::Any { any: clauses } => {
+                clauses.iter().any(Clause::references_segments)
+            }
+            Clause::Segment { .. } => true,
+            Clause::Condition(_) => false,
+        }
+    }
+}
+
+fn validate_clause(clause: &Clause) -> std::result::Result<(), String> {
+    match clause {
+        Clause::All { all: clauses } | Clause::Any { any: clauses } => {
+            clauses.iter().try_for_each(validate_clause)
+        }
+        Clause::Segment { segment } if segment.trim().is_empty() => Err("segment name must not be empty".to_string()),
+        Clause::Segment { .. } => Ok(()),
+        Clause::Condition(condition) => {
+            let value = condition.value.as_ref();
+            match condition.operator {
+                TargetingOperator::Exists => Ok(()),
+                TargetingOperator::In | TargetingOperator::NotIn if !value.is_some_and(Value::is_array) => {
+                    Err(format!("{} needs an array value", condition.attribute))
+                }
+                operator if operator.is_semver() => {
+                    match value.and_then(Value::as_str).and_then(Version::parse) {
+                        Some(_) => Ok(()),
+                        None => Err(format!("{} needs a semantic version value", condition.attribute)),
+                    }
+                }
+                _ if value.is_none() => Err(format!("{} needs a value", condition.attribute)),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+fn condition_holds(condition: &TargetingCondition, context: &EvaluationContext) -> bool {
+    let actual = context.attribute(&condition.attribute);
+    let expected = condition.value.as_ref();
+    let both = actual.zip(expected);
+
+    match condition.operator {
+        TargetingOperator::Exists => {
+            let should_exist = expected.and_then(Value::as_bool).unwrap_or(true);
+            actual.is_some() == should_exist
+        }
+        TargetingOperator::Eq => both.is_some_and(|(a, e)| values_equal(a, e)),
+        TargetingOperator::Ne => !both.is_some_and(|(a, e)| values_equal(a, e)),
+        TargetingOperator::In => both.is_some_and(|(a, e)| contains(e, a)),
+        TargetingOperator::NotIn => !both.is_some_and(|(a, e)| contains(e, a)),
+        TargetingOperator::Contains => both.is_some_and(|(a, e)| contains(a, e)),
+        TargetingOperator::NotContains => !both.is_some_and(|(a, e)| contains(a, e)),
+        TargetingOperator::Lt => ordering(actual, expected) == Some(Ordering::Less),
+        TargetingOperator::Lte => matches!(ordering(actual, expected), Some(Ordering::Less | Ordering::Equal)),
+        TargetingOperator::Gt => ordering(actual, expected) == Some(Ordering::Greater),
+        TargetingOperator::Gte => matches!(ordering(actual, expected), Some(Ordering::Greater | Ordering::Equal)),
+        TargetingOperator::SemverEq => version_ordering(both) == Some(Ordering::Equal),
+        TargetingOperator::SemverLt => version_ordering(both) == Some(Ordering::Less),
+        TargetingOperator::SemverLte => matches!(version_ordering(both), Some(Ordering::Less | Ordering::Equal)),
+        TargetingOperator::SemverGt => version_ordering(both) == Some(Ordering::Greater),
+        TargetingOperator::SemverGte => matches!(version_ordering(both), Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+/// Missing or unparseable versions never match
+fn version_ordering(values: Option<(&Value, &Value)>) -> Option<Ordering> {
+    let (actual, expected) = values?;
+    let actual = Version::parse(actual.as_str()?)?;
+    let expected = Version::parse(expected.as_str()?)?;
+    Some(actual.cmp(&expected))
+}
+
+// ============================================================================
+// SEMANTIC VERSIONS
+// ============================================================================
+
+/// A semantic version; build metadata is ignored and missing minor/patch count as 0,
+/// since app stores report versions like `2.4`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre_release: Vec<String>,
+}
+
+impl Version {
+    fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let input = input.strip_prefix('v').unwrap_or(input);
+        let input = input.split('+').next()?;
+        let (core, pre_release) = match input.split_once('-') {
+            Some((core, pre)) => (core, pre.split('.').map(str::to_string).collect()),
+            None => (input, vec![]),
+        };
+
+        let mut parts = core.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+        let patch = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self { major, minor, patch, pre_release })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre_release.is_empty(), other.pre_release.is_empty()) {
+                // A pre-release sorts before its release
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre_release(&self.pre_release, &other.pre_release),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Numeric identifiers compare numerically and sort before alphanumeric ones
+fn compare_pre_release(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+// ============================================================================
+// EVALUATION CONTEXT
+// ============================================================================
+
+/// Attributes a rule can reference. Callers pass whatever they know, nested
+/// (`{"tenant": {"tier": "pro"}}`) or flat (`{"tenant.tier": "pro"}`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvaluationContext {
+    attributes: Map<String, Value>,
+}
+
+impl EvaluationContext {
+    pub fn new(attributes: Map<String, Value>) -> Self {
+        Self { attributes }
+    }
+
+    /// Set an attribute at a dotted path, creating intermediate objects
+    pub fn with_attribute(mut self, path: &str, value: Value) -> Self {
+        let mut keys: Vec<&str> = path.split('.').collect();
+        let last = keys.pop().unwrap_or(path);
+
+        let mut target = &mut self.attributes;
+        for key in keys {
+            let entry = target.entry(key.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            target = entry.as_object_mut().expect("just made an object");
+        }
+        target.insert(last.to_string(), value);
+        self
+    }
+
+    /// Resolve a dotted attribute path; null values count as missing
+    pub fn attribute(&self, path: &str) -> Option<&Value> {
+        let value = match self.attributes.get(path) {
+            Some(value) => Some(value),
+            None => {
+                let mut keys = path.split('.');
+                let first = self.attributes.get(keys.next()?)?;
+                keys.try_fold(first, |value, key| value.get(key))
+            }
+        };
+        value.filter(|value| !value.is_null())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> TargetingRules {
+        let rules = TargetingRules::from_conditions(&value).unwrap();
+        rules.validate(&json!({})).unwrap();
+        rules
+    }
+
+    fn context(value: Value) -> EvaluationContext {
+        EvaluationContext::new(value.as_object().cloned().unwrap())
+    }
+
+    fn matched<'a>(rules: &'a TargetingRules, context: &EvaluationContext, segments: &Segments) -> Option<&'a str> {
+        rules.first_match(context, segments).map(|rule| rule.id.as_str())
+    }
+
+    #[test]
+    fn test_attribute_comparisons_and_order() {
+        let rules = rules(json!({ "rules": [
+            {
+                "id": "us-premium",
+                "when": { "all": [
+                    { "attribute": "tenant.tier", "operator": "in", "value": ["pro", "enterprise"] },
+                    { "attribute": "location.country", "operator": "eq", "value": "US" }
+                ] },
+                "serve": { "variant": "treatment" }
+            },
+            { "id": "everyone", "serve": { "percentage": 10 } }
+        ] }));
+        let segments = Segments::new();
+
+        let us_pro = context(json!({ "tenant": { "tier": "pro" }, "location": { "country": "US" } }));
+        assert_eq!(matched(&rules, &us_pro, &segments), Some("us-premium"));
+        assert_eq!(
+            rules.first_match(&us_pro, &segments).unwrap().serve,
+            RuleServe::Variant { variant: "treatment".to_string() }
+        );
+
+        // Flat keys work as well as nested objects
+        let flat = context(json!({ "tenant.tier": "enterprise", "location.country": "US" }));
+        assert_eq!(matched(&rules, &flat, &segments), Some("us-premium"));
+
+        let ca_pro = context(json!({ "tenant": { "tier": "pro" }, "location": { "country": "CA" } }));
+        assert_eq!(matched(&rules, &ca_pro, &segments), Some("everyone"));
+
+        let unknown = EvaluationContext::default();
+        assert_eq!(matched(&rules, &unknown, &segments), Some("everyone"));
+    }
+
+    #[test]
+    fn test_any_and_semver() {
+        let rules = rules(json!({ "rules": [{
+            "id": "new-clients",
+            "when": { "any": [
+                { "attribute": "app_version", "operator": "semver_gte", "value": "2.4.0" },
+                { "attribute": "platform", "operator": "eq", "value": "web" }
+            ] },
+            "serve": { "percentage": 100 }
+        }] }));
+        let segments = Segments::new();
+        let app = |version: &str| context(json!({ "app_version": version, "platform": "ios" }));
+
+        assert_eq!(matched(&rules, &app("2.4.0"), &segments), Some("new-clients"));
+        assert_eq!(matched(&rules, &app("v2.10"), &segments), Some("new-clients"));
+        assert_eq!(matched(&rules, &app("2.4.1+build.7"), &segments), Some("new-clients"));
+        // Lexically "2.10" < "2.4"; numerically it is newer, while a pre-release is older
+        assert_eq!(matched(&rules, &app("2.3.9"), &segments), None);
+        assert_eq!(matched(&rules, &app("2.4.0-beta.2"), &segments), None);
+        assert_eq!(matched(&rules, &app("not-a-version"), &segments), None);
+
+        let web = context(json!({ "platform": "web" }));
+        assert_eq!(matched(&rules, &web, &segments), Some("new-clients"));
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let v = |s: &str| Version::parse(s).unwrap();
+
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-alpha.beta"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0-beta.11"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert_eq!(v("1.2"), v("1.2.0"));
+        assert!(Version::parse("1.2.3.4").is_none());
+        assert!(Version::parse("").is_none());
+    }
+
+    #[test]
+    fn test_segments() {
+        let rules = rules(json!({ "rules": [{
+            "id": "beta",
+            "when": { "segment": "beta_testers" },
+            "serve": { "variant": "on" }
+        }] }));
+        assert!(rules.references_segments());
+
+        let segments: Segments = serde_json::from_value(json!({
+            "beta_testers": { "any": [
+                { "segment": "staff" },
+                { "attribute": "user.groups", "operator": "contains", "value": "beta" }
+            ] },
+            "staff": { "attribute": "user.email", "operator": "contains", "value": "@olympuscloud.io" },
+            "loop_a": { "segment": "loop_b" },
+            "loop_b": { "segment": "loop_a" }
+        }))
+        .unwrap();
+
+        let staff = context(json!({ "user": { "email": "ops@olympuscloud.io" } }));
+        assert_eq!(matched(&rules, &staff, &segments), Some("beta"));
+
+        let opted_in = context(json!({ "user": { "groups": ["beta", "loyalty"] } }));
+        assert_eq!(matched(&rules, &opted_in, &segments), Some("beta"));
+
+        let other = context(json!({ "user": { "groups": ["loyalty"] } }));
+        assert_eq!(matched(&rules, &other, &segments), None);
+
+        // Missing and cyclic segments never match rather than erroring
+        assert_eq!(matched(&rules, &staff, &Segments::new()), None);
+        let cyclic = Clause::Segment { segment: "loop_a".to_string() };
+        assert!(!cyclic.matches(&staff, &segments));
+    }
+
+    #[test]
+    fn test_validation() {
+        let invalid = |value: Value, variants: Value| {
+            TargetingRules::from_conditions(&value)
+                .and_then(|rules| rules.validate(&variants))
+                .is_err()
+        };
+
+        assert!(TargetingRules::from_conditions(&json!({})).unwrap().is_empty());
+        assert!(TargetingRules::from_conditions(&Value::Null).unwrap().is_empty());
+
+        let serve_on = json!({ "percentage": 50 });
+        assert!(invalid(json!({ "rules": [{ "id": "a", "serve": serve_on }, { "id": "a", "serve": serve_on }] }), json!({})));
+        assert!(invalid(json!({ "rules": [{ "id": "a", "serve": { "percentage": 150 } }] }), json!({})));
+        assert!(invalid(json!({ "rules": [{ "id": "a", "serve": { "variant": "c" } }] }), json!({ "a": 50, "b": 50 })));
+        assert!(invalid(
+            json!({ "rules": [{
+                "id": "a",
+                "when": { "attribute": "app_version", "operator": "semver_gt", "value": "latest" },
+                "serve": serve_on
+            }] }),
+            json!({})
+        ));
+        assert!(invalid(
+            json!({ "rules": [{
+                "id": "a",
+                "when": { "attribute": "tenant.tier", "operator": "in", "value": "pro" },
+                "serve": serve_on
+            }] }),
+            json!({})
+        ));
+        assert!(invalid(json!({ "rules": [{ "id": "a", "serve": { "everyone": true } }] }), json!({})));
+
+        assert!(!invalid(json!({ "rules": [{ "id": "a", "serve": { "variant": "b" } }] }), json!({ "a": 50, "b": 50 })));
+    }
+}
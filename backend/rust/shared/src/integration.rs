@@ -43,6 +43,9 @@ pub mod go_gateway {
         pub permissions: Vec<String>,
         pub session_id: String,
         pub expires_at: DateTime<Utc>,
+        /// Location the session is bound to, e.g. a shared POS terminal's
+        #[serde(default)]
+        pub location_id: Option<Uuid>,
     }
 
     /// Request wrapper from Go API Gateway
@@ -252,6 +255,7 @@ impl<T> ServiceResponse<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use super::go_gateway::*;
     use super::python_analytics::*;
 
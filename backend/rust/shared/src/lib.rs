@@ -1,3 +1,4 @@
+pub mod authorization;
 pub mod config;
 pub mod database;
 pub mod error;
@@ -7,6 +8,8 @@ pub mod types;
 pub mod clients;
 pub mod monitoring;
 pub mod integration;
+pub mod policy;
+pub mod flags;
 
 pub use config::AppConfig;
 pub use error::{Error, Result, ErrorResponse, ErrorExt};
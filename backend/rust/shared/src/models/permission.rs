@@ -2,15 +2,19 @@
 // OLYMPUS CLOUD - PERMISSION AND RBAC MODELS
 // ============================================================================
 // Module: shared/src/models/permission.rs
-// Description: Role-based access control models
+// Description: Role-based access control models and permission evaluation
 // Author: Claude Code Agent
 // Date: 2025-01-18
 // ============================================================================
 
 use super::{TenantScoped, ValidateEntity};
+use crate::types::PermissionScope;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 use validator::ValidationError;
 
@@ -23,6 +27,10 @@ pub struct Role {
     pub display_name: Option<String>,
     pub description: Option<String>,
     pub permissions: Vec<String>,
+    /// Names of roles whose grants this role inherits
+    #[serde(default)]
+    #[sqlx(default)]
+    pub parent_roles: Vec<String>,
     pub is_system: bool,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
@@ -40,6 +48,7 @@ impl Role {
             display_name: None,
             description: None,
             permissions,
+            parent_roles: vec![],
             is_system: false,
             is_active: true,
             created_at: now,
@@ -47,9 +56,12 @@ impl Role {
         }
     }
 
-    /// Check if role has permission
+    /// Check if the role's own grants allow a permission tenant-wide.
+    /// Inherited grants are resolved through [`RoleHierarchy`].
     pub fn has_permission(&self, permission: &str) -> bool {
-        self.permissions.contains(&permission.to_string())
+        PermissionSet::from_strings(&self.permissions)
+            .evaluate(permission, None)
+            .is_allowed()
     }
 
     /// Add permission
@@ -127,6 +139,331 @@ pub mod permissions {
     pub const ADMIN_USERS: &str = "admin:users";
     pub const ADMIN_SETTINGS: &str = "admin:settings";
     pub const ADMIN_BILLING: &str = "admin:billing";
+    pub const ADMIN_API_KEYS: &str = "admin:api_keys";
+    pub const ADMIN_SIGNING_KEYS: &str = "admin:signing_keys";
+    pub const ADMIN_OAUTH_CLIENTS: &str = "admin:oauth_clients";
+    pub const ADMIN_SCIM: &str = "admin:scim";
+    pub const ADMIN_TERMINALS: &str = "admin:terminals";
+
+    // Support
+    pub const SUPPORT_IMPERSONATE: &str = "support:impersonate";
+
+    // Commerce
+    pub const COMMERCE_PRODUCTS_READ: &str = "commerce.products.read";
+    pub const COMMERCE_PRODUCTS_WRITE: &str = "commerce.products.write";
+    pub const COMMERCE_PRODUCTS_DELETE: &str = "commerce.products.delete";
+    pub const COMMERCE_ORDERS_READ: &str = "commerce.orders.read";
+    pub const COMMERCE_ORDERS_WRITE: &str = "commerce.orders.write";
+    pub const COMMERCE_ORDERS_CANCEL: &str = "commerce.orders.cancel";
+    pub const COMMERCE_ORDERS_EXPORT: &str = "commerce.orders.export";
+    pub const COMMERCE_INVENTORY_READ: &str = "commerce.inventory.read";
+    pub const COMMERCE_PAYMENTS_READ: &str = "commerce.payments.read";
+    pub const COMMERCE_PAYMENTS_WRITE: &str = "commerce.payments.write";
+    pub const COMMERCE_REFUNDS_READ: &str = "commerce.refunds.read";
+    pub const COMMERCE_REFUNDS_CREATE: &str = "commerce.refunds.create";
+    pub const COMMERCE_REFUNDS_PROCESS: &str = "commerce.refunds.process";
+    pub const COMMERCE_RESTAURANT_READ: &str = "commerce.restaurant.read";
+    pub const COMMERCE_RESTAURANT_WRITE: &str = "commerce.restaurant.write";
+    pub const COMMERCE_SHIFTS_READ: &str = "commerce.shifts.read";
+    pub const COMMERCE_SHIFTS_WRITE: &str = "commerce.shifts.write";
+
+    // Platform
+    pub const PLATFORM_FEATURE_FLAGS_READ: &str = "platform.feature_flags.read";
+    pub const PLATFORM_FEATURE_FLAGS_WRITE: &str = "platform.feature_flags.write";
+    pub const PLATFORM_FEATURE_FLAGS_KILL: &str = "platform.feature_flags.kill";
+    pub const PLATFORM_CONFIG_READ: &str = "platform.config.read";
+    pub const PLATFORM_CONFIG_WRITE: &str = "platform.config.write";
+}
+
+// ============================================================================
+// PERMISSION EVALUATION
+// ============================================================================
+//
+// Grants are stored as strings on roles, users, API keys and tokens:
+//
+//   commerce.orders.read                       allow, tenant-wide
+//   commerce.orders.*                          allow everything under commerce.orders
+//   !commerce.refunds.create                   explicit deny; always wins over allows
+//   commerce.refunds.create@location:<uuid>    allow only at one location
+//   commerce.orders.read@own                   allow, limited to the caller's own records
+//
+// Segments are separated by `.` or `:`, so the older `read:users` style keeps working.
+
+/// Prefix marking a grant as an explicit deny
+const DENY_PREFIX: char = '!';
+/// Separator between a grant's pattern and its scope
+const SCOPE_SEPARATOR: char = '@';
+/// Matches one segment, or every remaining segment when it is the last one
+const WILDCARD: &str = "*";
+
+fn segments(value: &str) -> impl Iterator<Item = &str> {
+    value.split(['.', ':'])
+}
+
+/// Whether a grant allows or forbids the permissions it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionEffect {
+    Allow,
+    Deny,
+}
+
+/// A single parsed grant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionGrant {
+    pub pattern: String,
+    pub effect: PermissionEffect,
+    pub scope: PermissionScope,
+    /// Set when `scope` is `PermissionScope::Location`
+    pub location_id: Option<Uuid>,
+}
+
+impl PermissionGrant {
+    /// Check whether the grant's pattern covers a permission
+    pub fn matches(&self, permission: &str) -> bool {
+        let pattern: Vec<&str> = segments(&self.pattern).collect();
+        let permission: Vec<&str> = segments(permission).collect();
+
+        for (index, expected) in pattern.iter().enumerate() {
+            let is_last = index == pattern.len() - 1;
+            match permission.get(index) {
+                Some(_) if *expected == WILDCARD && is_last => return true,
+                Some(actual) if *expected == WILDCARD || expected == actual => continue,
+                _ => return false,
+            }
+        }
+
+        pattern.len() == permission.len()
+    }
+
+    /// Check whether the grant applies to a request at `location_id`. `None` means tenant-wide
+    /// or unknown, so location-scoped denies still apply and location-scoped allows do not.
+    pub fn applies_at(&self, location_id: Option<Uuid>) -> bool {
+        match (self.scope, location_id) {
+            (PermissionScope::Location, None) => self.effect == PermissionEffect::Deny,
+            (PermissionScope::Location, Some(id)) => self.location_id == Some(id),
+            (PermissionScope::Own | PermissionScope::Tenant | PermissionScope::Global, _) => true,
+        }
+    }
+}
+
+impl FromStr for PermissionGrant {
+    type Err = ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (effect, value) = match value.strip_prefix(DENY_PREFIX) {
+            Some(rest) => (PermissionEffect::Deny, rest),
+            None => (PermissionEffect::Allow, value),
+        };
+
+        let (pattern, scope) = match value.split_once(SCOPE_SEPARATOR) {
+            Some((pattern, scope)) => (pattern, Some(scope)),
+            None => (value, None),
+        };
+
+        let well_formed = segments(pattern).all(|segment| {
+            !segment.is_empty()
+                && (segment == WILDCARD || !segment.contains('*'))
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '*'))
+        });
+        if !well_formed {
+            return Err(ValidationError::new("invalid_permission"));
+        }
+
+        let (scope, location_id) = match scope {
+            None | Some("tenant") => (PermissionScope::Tenant, None),
+            Some("own") => (PermissionScope::Own, None),
+            Some("global") => (PermissionScope::Global, None),
+            Some(scope) => {
+                let location_id = scope
+                    .strip_prefix("location:")
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .ok_or_else(|| ValidationError::new("invalid_permission_scope"))?;
+                (PermissionScope::Location, Some(location_id))
+            }
+        };
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            effect,
+            scope,
+            location_id,
+        })
+    }
+}
+
+impl fmt::Display for PermissionGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.effect == PermissionEffect::Deny {
+            write!(f, "{}", DENY_PREFIX)?;
+        }
+        write!(f, "{}", self.pattern)?;
+        match (self.scope, self.location_id) {
+            (PermissionScope::Tenant, _) => Ok(()),
+            (PermissionScope::Own, _) => write!(f, "{}own", SCOPE_SEPARATOR),
+            (PermissionScope::Global, _) => write!(f, "{}global", SCOPE_SEPARATOR),
+            (PermissionScope::Location, Some(id)) => write!(f, "{}location:{}", SCOPE_SEPARATOR, id),
+            (PermissionScope::Location, None) => Ok(()),
+        }
+    }
+}
+
+/// Outcome of evaluating a permission against a set of grants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionDecision {
+    /// Allowed; the scope is the broadest matching grant, so handlers can narrow
+    /// `Own` and `Location` results to the caller's records
+    Allowed(PermissionScope),
+    /// An explicit deny matched
+    Denied,
+    /// Nothing matched
+    NotGranted,
+}
+
+impl PermissionDecision {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, PermissionDecision::Allowed(_))
+    }
+}
+
+fn scope_breadth(scope: PermissionScope) -> u8 {
+    match scope {
+        PermissionScope::Own => 0,
+        PermissionScope::Location => 1,
+        PermissionScope::Tenant => 2,
+        PermissionScope::Global => 3,
+    }
+}
+
+/// The effective grants of a principal, evaluated with deny-overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionSet {
+    grants: Vec<PermissionGrant>,
+}
+
+impl PermissionSet {
+    /// Parse grant strings; malformed entries are skipped so one bad grant
+    /// cannot lock a user out of everything else
+    pub fn from_strings<S: AsRef<str>>(grants: &[S]) -> Self {
+        let grants = grants
+            .iter()
+            .filter_map(|grant| match grant.as_ref().parse::<PermissionGrant>() {
+                Ok(grant) => Some(grant),
+                Err(_) => {
+                    tracing::warn!("Ignoring malformed permission grant {:?}", grant.as_ref());
+                    None
+                }
+            })
+            .collect();
+        Self { grants }
+    }
+
+    pub fn grants(&self) -> &[PermissionGrant] {
+        &self.grants
+    }
+
+    /// Evaluate a permission for a request at `location_id`; `None` means tenant-wide
+    pub fn evaluate(&self, permission: &str, location_id: Option<Uuid>) -> PermissionDecision {
+        let mut matching = self
+            .grants
+            .iter()
+            .filter(|grant| grant.applies_at(location_id) && grant.matches(permission))
+            .peekable();
+
+        if matching.peek().is_none() {
+            return PermissionDecision::NotGranted;
+        }
+
+        let mut allowed: Option<PermissionScope> = None;
+        for grant in matching {
+            if grant.effect == PermissionEffect::Deny {
+                return PermissionDecision::Denied;
+            }
+            if allowed.map_or(true, |scope| scope_breadth(grant.scope) > scope_breadth(scope)) {
+                allowed = Some(grant.scope);
+            }
+        }
+
+        allowed.map_or(PermissionDecision::NotGranted, PermissionDecision::Allowed)
+    }
+
+    /// Whether `permission` may be allowed for some resource before it is known: an allow
+    /// matches and no deny that holds everywhere does. `evaluate` decides once the
+    /// resource's location is known.
+    pub fn may_allow(&self, permission: &str) -> bool {
+        let matching: Vec<&PermissionGrant> = self.grants.iter().filter(|grant| grant.matches(permission)).collect();
+        let denied_everywhere = matching.iter().any(|grant| {
+            grant.effect == PermissionEffect::Deny
+                && matches!(grant.scope, PermissionScope::Tenant | PermissionScope::Global)
+        });
+        !denied_everywhere && matching.iter().any(|grant| grant.effect == PermissionEffect::Allow)
+    }
+
+    /// Shorthand for a tenant-wide check
+    pub fn allows(&self, permission: &str) -> bool {
+        self.evaluate(permission, None).is_allowed()
+    }
+
+    /// Serialize back into grant strings, e.g. for token claims
+    pub fn to_strings(&self) -> Vec<String> {
+        self.grants.iter().map(ToString::to_string).collect()
+    }
+}
+
+/// A tenant's active roles indexed by name, used to resolve inherited grants
+pub struct RoleHierarchy<'a> {
+    roles: HashMap<&'a str, &'a Role>,
+}
+
+impl<'a> RoleHierarchy<'a> {
+    pub fn new(roles: &'a [Role]) -> Self {
+        let roles = roles
+            .iter()
+            .filter(|role| role.is_active)
+            .map(|role| (role.name.as_str(), role))
+            .collect();
+        Self { roles }
+    }
+
+    /// Grant strings of the named roles and every role they inherit from, in
+    /// breadth-first order without duplicates. Cycles and unknown parents are ignored.
+    pub fn effective_permissions(&self, role_names: &[String]) -> Vec<String> {
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut queue: Vec<&str> = role_names.iter().map(String::as_str).collect();
+        let mut seen_grants: HashSet<&str> = HashSet::new();
+        let mut grants = Vec::new();
+
+        while !queue.is_empty() {
+            let name = queue.remove(0);
+            if !visited.insert(name) {
+                continue;
+            }
+            let Some(role) = self.roles.get(name) else {
+                continue;
+            };
+
+            for grant in &role.permissions {
+                if seen_grants.insert(grant.as_str()) {
+                    grants.push(grant.clone());
+                }
+            }
+            queue.extend(role.parent_roles.iter().map(String::as_str));
+        }
+
+        grants
+    }
+
+    /// Effective permission set for a user's roles plus their directly assigned grants
+    pub fn permission_set(&self, role_names: &[String], direct: &[String]) -> PermissionSet {
+        let mut grants = self.effective_permissions(role_names);
+        for grant in direct {
+            if !grants.contains(grant) {
+                grants.push(grant.clone());
+            }
+        }
+        PermissionSet::from_strings(&grants)
+    }
 }
 
 impl ValidateEntity for Role {
@@ -138,4 +475,102 @@ impl ValidateEntity for Role {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_matching() {
+        let grant: PermissionGrant = "commerce.orders.*".parse().unwrap();
+        assert!(grant.matches("commerce.orders.read"));
+        assert!(grant.matches("commerce.orders.refunds.create"));
+        assert!(!grant.matches("commerce.orders"));
+        assert!(!grant.matches("commerce.products.read"));
+
+        let grant: PermissionGrant = "commerce.*.read".parse().unwrap();
+        assert!(grant.matches("commerce.products.read"));
+        assert!(!grant.matches("commerce.products.write"));
+
+        let legacy: PermissionGrant = permissions::READ_ORDERS.parse().unwrap();
+        assert!(legacy.matches("read:orders"));
+        assert!(!legacy.matches("read:orders:extra"));
+
+        assert!("commerce.orders.re*".parse::<PermissionGrant>().is_err());
+        assert!("commerce..read".parse::<PermissionGrant>().is_err());
+        assert!("commerce.read@location:nope".parse::<PermissionGrant>().is_err());
+    }
+
+    #[test]
+    fn test_explicit_deny_wins() {
+        let set = PermissionSet::from_strings(&["commerce.*", "!commerce.refunds.create"]);
+
+        assert!(set.allows("commerce.orders.read"));
+        assert_eq!(set.evaluate("commerce.refunds.create", None), PermissionDecision::Denied);
+        assert_eq!(set.evaluate("platform.config.read", None), PermissionDecision::NotGranted);
+    }
+
+    #[test]
+    fn test_location_scoped_grants() {
+        let store = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let set = PermissionSet::from_strings(&[
+            format!("commerce.refunds.create@location:{}", store),
+            "commerce.orders.read@own".to_string(),
+        ]);
+
+        assert_eq!(
+            set.evaluate("commerce.refunds.create", Some(store)),
+            PermissionDecision::Allowed(PermissionScope::Location)
+        );
+        assert!(!set.evaluate("commerce.refunds.create", Some(other)).is_allowed());
+        assert!(!set.allows("commerce.refunds.create"));
+        assert_eq!(
+            set.evaluate("commerce.orders.read", None),
+            PermissionDecision::Allowed(PermissionScope::Own)
+        );
+
+        let round_trip = PermissionSet::from_strings(&set.to_strings());
+        assert_eq!(round_trip, set);
+    }
+
+    #[test]
+    fn test_location_scoped_deny_applies_when_location_unknown() {
+        let store = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let set = PermissionSet::from_strings(&[
+            "commerce.*".to_string(),
+            format!("!commerce.refunds.create@location:{}", store),
+        ]);
+
+        assert_eq!(set.evaluate("commerce.refunds.create", None), PermissionDecision::Denied);
+        assert_eq!(set.evaluate("commerce.refunds.create", Some(store)), PermissionDecision::Denied);
+        assert!(set.evaluate("commerce.refunds.create", Some(other)).is_allowed());
+        assert!(set.may_allow("commerce.refunds.create"));
+
+        let denied = PermissionSet::from_strings(&["commerce.*", "!commerce.refunds.create"]);
+        assert!(!denied.may_allow("commerce.refunds.create"));
+        assert!(!PermissionSet::default().may_allow("commerce.refunds.create"));
+    }
+
+    #[test]
+    fn test_role_inheritance() {
+        let tenant_id = Uuid::new_v4();
+        let mut staff = Role::new(tenant_id, "staff".to_string(), vec!["commerce.orders.read".to_string()]);
+        let mut manager = Role::new(tenant_id, "manager".to_string(), vec!["commerce.refunds.*".to_string()]);
+        manager.parent_roles = vec!["staff".to_string()];
+        // A cycle must not loop forever
+        staff.parent_roles = vec!["manager".to_string()];
+        let roles = vec![staff, manager];
+
+        let hierarchy = RoleHierarchy::new(&roles);
+        let set = hierarchy.permission_set(&["manager".to_string()], &["!commerce.refunds.process".to_string()]);
+
+        assert!(set.allows("commerce.orders.read"));
+        assert!(set.allows("commerce.refunds.create"));
+        assert!(!set.allows("commerce.refunds.process"));
+        assert!(roles[1].has_permission("commerce.refunds.create"));
+        assert!(!roles[1].has_permission("commerce.orders.read"));
+    }
+}
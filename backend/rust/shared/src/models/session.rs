@@ -48,6 +48,8 @@ pub enum MfaType {
     Sms,
     Email,
     BackupCode,
+    /// Passkeys; the credentials themselves are stored per user in `auth.webauthn_credentials`
+    WebAuthn,
 }
 
 /// User session entity
@@ -282,6 +284,9 @@ pub struct UserMfa {
     pub backup_codes: Vec<String>,
     pub is_enabled: bool,
     pub verified_at: Option<DateTime<Utc>>,
+    /// TOTP time step of the last accepted code; codes from it or earlier steps are refused
+    #[serde(default)]
+    pub last_used_step: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -299,6 +304,7 @@ impl UserMfa {
             backup_codes: vec![],
             is_enabled: false,
             verified_at: None,
+            last_used_step: None,
             created_at: now,
             updated_at: now,
         }
@@ -323,6 +329,17 @@ impl UserMfa {
         self.updated_at = Utc::now();
     }
 
+    /// Record the TOTP time step a code was accepted in; false if that step or a later one
+    /// was already used, which means the code is being replayed
+    pub fn record_totp_step(&mut self, step: i64) -> bool {
+        if self.last_used_step.is_some_and(|last| step <= last) {
+            return false;
+        }
+        self.last_used_step = Some(step);
+        self.updated_at = Utc::now();
+        true
+    }
+
     /// Set phone number for SMS
     pub fn set_phone_number(&mut self, phone: String) {
         self.phone_number = Some(phone);
@@ -543,6 +560,17 @@ mod tests {
         assert!(mfa.secret_key.is_some());
     }
 
+    #[test]
+    fn test_totp_step_replay() {
+        let mut mfa = UserMfa::new(Uuid::new_v4(), MfaType::Totp);
+
+        assert!(mfa.record_totp_step(56_666_666));
+        assert!(!mfa.record_totp_step(56_666_666));
+        assert!(!mfa.record_totp_step(56_666_665));
+        assert!(mfa.record_totp_step(56_666_667));
+        assert_eq!(mfa.last_used_step, Some(56_666_667));
+    }
+
     #[test]
     fn test_backup_codes() {
         let mut mfa = UserMfa::new(Uuid::new_v4(), MfaType::Totp);
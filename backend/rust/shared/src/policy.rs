@@ -0,0 +1,812 @@
+// ============================================================================
+// OLYMPUS CLOUD - ATTRIBUTE-BASED ACCESS POLICIES
+// ============================================================================
+// Module: shared/src/policy.rs
+// Description: Declarative per-tenant policies evaluated against subject,
+//              resource and request context attributes
+// Author: Claude Code Agent
+// Date: 2025-01-21
+// ============================================================================
+//
+// Policies complement RBAC: a permission says *who* may attempt an action,
+// policies decide *under which circumstances*. Tenants store them as JSON:
+//
+//   {
+//     "id": "refund-manager-approval",
+//     "description": "Refunds over $500 need a manager",
+//     "effect": "deny",
+//     "actions": ["commerce.refunds.create"],
+//     "conditions": [
+//       { "attribute": "resource.amount", "operator": "gt", "value": 500 },
+//       { "attribute": "subject.roles", "operator": "not_contains", "value": "manager" }
+//     ]
+//   }
+//
+// A condition compares an attribute with a literal `value` or with another
+// attribute named by `value_from`, e.g. `resource.location_id eq subject.location_id`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::{request::Parts, HeaderMap};
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::integration::go_gateway::AuthContext;
+use crate::models::permission::PermissionGrant;
+
+/// Tenant configuration key holding the tenant's policies as a JSON array
+pub const POLICY_CONFIG_KEY: &str = "access_policies";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyEffect {
+    Permit,
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOperator {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    In,
+    NotIn,
+    Contains,
+    NotContains,
+    InCidr,
+    NotInCidr,
+    Exists,
+}
+
+/// A single attribute test; every condition of a policy must hold for it to apply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyCondition {
+    /// Dotted path rooted at `subject`, `resource` or `context`
+    pub attribute: String,
+    pub operator: ConditionOperator,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    /// Compare against another attribute instead of a literal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_from: Option<String>,
+}
+
+/// A declarative rule targeting one or more actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    pub id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub effect: PolicyEffect,
+    /// Action patterns, with the same wildcard syntax as permissions
+    pub actions: Vec<String>,
+    /// Only subjects holding one of these roles are targeted; empty targets everyone
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub conditions: Vec<PolicyCondition>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Policy {
+    fn targets(&self, action: &str, input: &PolicyInput) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let action_matches = self.actions.iter().any(|pattern| {
+            pattern
+                .parse::<PermissionGrant>()
+                .map(|grant| grant.matches(action))
+                .unwrap_or(false)
+        });
+        if !action_matches {
+            return false;
+        }
+
+        self.roles.is_empty()
+            || self.roles.iter().any(|role| {
+                input
+                    .attribute("subject.roles")
+                    .and_then(Value::as_array)
+                    .is_some_and(|roles| roles.iter().any(|r| r.as_str() == Some(role)))
+            })
+    }
+
+    fn reason(&self) -> String {
+        self.description
+            .clone()
+            .unwrap_or_else(|| format!("Policy {}", self.id))
+    }
+}
+
+// ============================================================================
+// ATTRIBUTES
+// ============================================================================
+
+/// The acting user's attributes, from their token claims and `SubjectDirectory`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicySubject {
+    pub user_id: Uuid,
+    pub roles: Vec<String>,
+    /// Where the user is working: their terminal's location, their shift's, or their only
+    /// assigned location
+    pub location_id: Option<Uuid>,
+    /// Anything else policies may reference, e.g. `location_ids`, `shift_start` / `shift_end`
+    #[serde(flatten)]
+    pub attributes: Map<String, Value>,
+}
+
+impl PolicySubject {
+    pub fn new(user_id: Uuid, roles: Vec<String>, location_id: Option<Uuid>) -> Self {
+        Self {
+            user_id,
+            roles,
+            location_id,
+            attributes: Map::new(),
+        }
+    }
+
+    /// Subject carrying only what the caller's token says; see `PolicyEngine::subject`
+    pub fn from_auth_context(auth: &AuthContext) -> Self {
+        Self::new(auth.user_id, auth.roles.clone(), auth.location_id)
+    }
+
+    pub fn with_attribute(mut self, name: &str, value: Value) -> Self {
+        self.attributes.insert(name.to_string(), value);
+        self
+    }
+}
+
+/// Number of reverse proxies in front of the service, each appending the address it
+/// received a request from to `X-Forwarded-For`. Added to requests as an extension;
+/// without it the connection's peer address is the client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrustedProxies(pub usize);
+
+/// Attributes of the request itself rather than the user or resource
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub time: DateTime<Utc>,
+    pub ip_address: Option<IpAddr>,
+}
+
+impl RequestContext {
+    pub fn now(ip_address: Option<IpAddr>) -> Self {
+        Self {
+            time: Utc::now(),
+            ip_address,
+        }
+    }
+
+    /// Context for an HTTP request received from `peer`. Entries a client put in
+    /// `X-Forwarded-For` sit left of those the trusted proxies appended, so the client is
+    /// the hop `trusted_proxies` places right of the end, counting the peer itself.
+    pub fn from_forwarded(headers: &HeaderMap, peer: Option<IpAddr>, trusted_proxies: TrustedProxies) -> Self {
+        if trusted_proxies.0 == 0 {
+            return Self::now(peer);
+        }
+
+        let mut hops: Vec<Option<IpAddr>> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|hop| hop.trim().parse().ok())
+            .collect();
+        hops.push(peer);
+
+        // Fewer hops than proxies means the request skipped some; the first one is still
+        // one a trusted proxy recorded
+        let client = hops
+            .len()
+            .checked_sub(trusted_proxies.0 + 1)
+            .map_or_else(|| hops.first().copied().flatten(), |index| hops[index]);
+        Self::now(client)
+    }
+
+    fn to_value(&self) -> Value {
+        json!({
+            "time": self.time.to_rfc3339(),
+            "time_of_day": format!("{:02}:{:02}", self.time.hour(), self.time.minute()),
+            "weekday": self.time.weekday().to_string().to_lowercase(),
+            "ip_address": self.ip_address.map(|ip| ip.to_string()),
+        })
+    }
+}
+
+/// Context for the request being handled, with the client address resolved through the
+/// `TrustedProxies` extension
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestContext
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(address)| address.ip());
+        let trusted_proxies = parts.extensions.get::<TrustedProxies>().copied().unwrap_or_default();
+        Ok(Self::from_forwarded(&parts.headers, peer, trusted_proxies))
+    }
+}
+
+/// Everything a policy can reference, as `subject.*`, `resource.*` and `context.*`
+#[derive(Debug, Clone)]
+pub struct PolicyInput {
+    root: Value,
+}
+
+impl PolicyInput {
+    pub fn new(subject: &PolicySubject, resource: Value, context: &RequestContext) -> Self {
+        Self {
+            root: json!({
+                "subject": serde_json::to_value(subject).unwrap_or(Value::Null),
+                "resource": resource,
+                "context": context.to_value(),
+            }),
+        }
+    }
+
+    /// Resolve a dotted attribute path; null values count as missing
+    pub fn attribute(&self, path: &str) -> Option<&Value> {
+        path.split('.')
+            .try_fold(&self.root, |value, key| value.get(key))
+            .filter(|value| !value.is_null())
+    }
+}
+
+// ============================================================================
+// EVALUATION
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOutcome {
+    Permit,
+    Deny,
+    /// No policy targets the action; RBAC alone decides
+    NotApplicable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub outcome: PolicyOutcome,
+    pub reasons: Vec<String>,
+}
+
+impl PolicyDecision {
+    pub fn is_permitted(&self) -> bool {
+        self.outcome != PolicyOutcome::Deny
+    }
+
+    /// Turn a deny into `Error::PolicyDenied` carrying the reasons
+    pub fn ensure_permitted(self) -> Result<()> {
+        if self.is_permitted() {
+            Ok(())
+        } else {
+            Err(Error::PolicyDenied(self.reasons.join("; ")))
+        }
+    }
+}
+
+/// Evaluate policies with deny-overrides. Targeted permit policies act as an
+/// allow-list: if any target the action and subject, at least one must hold.
+pub fn evaluate(policies: &[Policy], action: &str, input: &PolicyInput) -> PolicyDecision {
+    let mut denials = Vec::new();
+    let mut permits = Vec::new();
+    let mut unmet_permits = Vec::new();
+
+    for policy in policies.iter().filter(|policy| policy.targets(action, input)) {
+        let failed = policy.conditions.iter().find(|condition| !condition_holds(condition, input));
+
+        match (policy.effect, failed) {
+            (PolicyEffect::Deny, None) => denials.push(policy.reason()),
+            (PolicyEffect::Deny, Some(_)) => {}
+            (PolicyEffect::Permit, None) => permits.push(policy.reason()),
+            (PolicyEffect::Permit, Some(condition)) => unmet_permits.push(format!(
+                "{}: {} {:?} not satisfied",
+                policy.reason(),
+                condition.attribute,
+                condition.operator
+            )),
+        }
+    }
+
+    let (outcome, reasons) = if !denials.is_empty() {
+        (PolicyOutcome::Deny, denials)
+    } else if !permits.is_empty() {
+        (PolicyOutcome::Permit, permits)
+    } else if !unmet_permits.is_empty() {
+        (PolicyOutcome::Deny, unmet_permits)
+    } else {
+        (PolicyOutcome::NotApplicable, vec![])
+    };
+
+    PolicyDecision { outcome, reasons }
+}
+
+fn condition_holds(condition: &PolicyCondition, input: &PolicyInput) -> bool {
+    let actual = input.attribute(&condition.attribute);
+    let expected = match &condition.value_from {
+        Some(path) => input.attribute(path),
+        None => condition.value.as_ref(),
+    };
+
+    match condition.operator {
+        ConditionOperator::Exists => {
+            let should_exist = expected.and_then(Value::as_bool).unwrap_or(true);
+            actual.is_some() == should_exist
+        }
+        ConditionOperator::Eq => both(actual, expected).is_some_and(|(a, e)| values_equal(a, e)),
+        ConditionOperator::Ne => !both(actual, expected).is_some_and(|(a, e)| values_equal(a, e)),
+        ConditionOperator::Lt => ordering(actual, expected) == Some(Ordering::Less),
+        ConditionOperator::Lte => matches!(ordering(actual, expected), Some(Ordering::Less | Ordering::Equal)),
+        ConditionOperator::Gt => ordering(actual, expected) == Some(Ordering::Greater),
+        ConditionOperator::Gte => matches!(ordering(actual, expected), Some(Ordering::Greater | Ordering::Equal)),
+        ConditionOperator::In => both(actual, expected).is_some_and(|(a, e)| contains(e, a)),
+        ConditionOperator::NotIn => !both(actual, expected).is_some_and(|(a, e)| contains(e, a)),
+        ConditionOperator::Contains => both(actual, expected).is_some_and(|(a, e)| contains(a, e)),
+        ConditionOperator::NotContains => !both(actual, expected).is_some_and(|(a, e)| contains(a, e)),
+        ConditionOperator::InCidr => both(actual, expected).is_some_and(|(a, e)| in_cidr(a, e)),
+        ConditionOperator::NotInCidr => !both(actual, expected).is_some_and(|(a, e)| in_cidr(a, e)),
+    }
+}
+
+fn both<'a>(actual: Option<&'a Value>, expected: Option<&'a Value>) -> Option<(&'a Value, &'a Value)> {
+    actual.zip(expected)
+}
+
+/// Numbers may arrive as JSON numbers or as decimal strings
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Numbers compare numerically, RFC 3339 timestamps and `HH:MM` times chronologically
+pub(crate) fn ordering(actual: Option<&Value>, expected: Option<&Value>) -> Option<Ordering> {
+    let (actual, expected) = both(actual, expected)?;
+
+    if let (Some(a), Some(e)) = (as_number(actual), as_number(expected)) {
+        return a.partial_cmp(&e);
+    }
+
+    let (a, e) = (actual.as_str()?, expected.as_str()?);
+    if let (Ok(a), Ok(e)) = (DateTime::parse_from_rfc3339(a), DateTime::parse_from_rfc3339(e)) {
+        return Some(a.cmp(&e));
+    }
+    if let (Ok(a), Ok(e)) = (NaiveTime::parse_from_str(a, "%H:%M"), NaiveTime::parse_from_str(e, "%H:%M")) {
+        return Some(a.cmp(&e));
+    }
+    None
+}
+
+pub(crate) fn values_equal(actual: &Value, expected: &Value) -> bool {
+    match (as_number(actual), as_number(expected)) {
+        (Some(a), Some(e)) => a == e,
+        _ => actual == expected,
+    }
+}
+
+pub(crate) fn contains(haystack: &Value, needle: &Value) -> bool {
+    match (haystack, needle) {
+        (Value::Array(items), _) => items.iter().any(|item| values_equal(item, needle)),
+        (Value::String(s), Value::String(n)) => s.contains(n.as_str()),
+        _ => false,
+    }
+}
+
+fn in_cidr(actual: &Value, ranges: &Value) -> bool {
+    let Some(ip) = actual.as_str().and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+        return false;
+    };
+
+    match ranges {
+        Value::Array(ranges) => ranges.iter().filter_map(Value::as_str).any(|range| ip_in_range(ip, range)),
+        Value::String(range) => ip_in_range(ip, range),
+        _ => false,
+    }
+}
+
+fn ip_in_range(ip: IpAddr, range: &str) -> bool {
+    let (network, prefix) = match range.split_once('/') {
+        Some((network, prefix)) => (network, prefix.parse::<u32>().ok()),
+        None => (range, None),
+    };
+    let Ok(network) = network.parse::<IpAddr>() else {
+        return false;
+    };
+
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let prefix = prefix.unwrap_or(32).min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let prefix = prefix.unwrap_or(128).min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+// ============================================================================
+// POLICY STORAGE
+// ============================================================================
+
+/// Source of a tenant's policies
+#[async_trait]
+pub trait PolicyStore: Send + Sync {
+    async fn load(&self, tenant_id: Uuid) -> Result<Vec<Policy>>;
+}
+
+/// Policies held in memory, for tests and services without platform configuration
+#[derive(Default)]
+pub struct InMemoryPolicyStore {
+    policies: RwLock<HashMap<Uuid, Vec<Policy>>>,
+}
+
+impl InMemoryPolicyStore {
+    pub async fn set(&self, tenant_id: Uuid, policies: Vec<Policy>) {
+        self.policies.write().await.insert(tenant_id, policies);
+    }
+}
+
+#[async_trait]
+impl PolicyStore for InMemoryPolicyStore {
+    async fn load(&self, tenant_id: Uuid) -> Result<Vec<Policy>> {
+        Ok(self.policies.read().await.get(&tenant_id).cloned().unwrap_or_default())
+    }
+}
+
+// ============================================================================
+// SUBJECT ATTRIBUTES
+// ============================================================================
+
+/// A scheduled shift a user is working
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shift {
+    pub location_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Source of the subject attributes that are not carried in tokens
+#[async_trait]
+pub trait SubjectDirectory: Send + Sync {
+    /// Locations the user is assigned to in the tenant; empty when not limited to any
+    async fn assigned_locations(&self, tenant_id: Uuid, user_id: Uuid) -> Result<Vec<Uuid>>;
+
+    /// The user's shift covering `at`, if they are on one
+    async fn current_shift(&self, tenant_id: Uuid, user_id: Uuid, at: DateTime<Utc>) -> Result<Option<Shift>>;
+}
+
+/// Assignments and shifts held in memory, for tests and services without staff scheduling
+#[derive(Default)]
+pub struct InMemorySubjectDirectory {
+    locations: RwLock<HashMap<(Uuid, Uuid), Vec<Uuid>>>,
+    shifts: RwLock<HashMap<(Uuid, Uuid), Vec<Shift>>>,
+}
+
+impl InMemorySubjectDirectory {
+    pub async fn assign(&self, tenant_id: Uuid, user_id: Uuid, locations: Vec<Uuid>) {
+        self.locations.write().await.insert((tenant_id, user_id), locations);
+    }
+
+    pub async fn schedule(&self, tenant_id: Uuid, user_id: Uuid, shift: Shift) {
+        self.shifts.write().await.entry((tenant_id, user_id)).or_default().push(shift);
+    }
+}
+
+#[async_trait]
+impl SubjectDirectory for InMemorySubjectDirectory {
+    async fn assigned_locations(&self, tenant_id: Uuid, user_id: Uuid) -> Result<Vec<Uuid>> {
+        Ok(self.locations.read().await.get(&(tenant_id, user_id)).cloned().unwrap_or_default())
+    }
+
+    async fn current_shift(&self, tenant_id: Uuid, user_id: Uuid, at: DateTime<Utc>) -> Result<Option<Shift>> {
+        Ok(self
+            .shifts
+            .read()
+            .await
+            .get(&(tenant_id, user_id))
+            .and_then(|shifts| shifts.iter().find(|shift| shift.starts_at <= at && at < shift.ends_at))
+            .cloned())
+    }
+}
+
+/// Loads a tenant's policies and evaluates an action against them
+#[derive(Clone)]
+pub struct PolicyEngine {
+    store: Arc<dyn PolicyStore>,
+    directory: Arc<dyn SubjectDirectory>,
+}
+
+impl Default for PolicyEngine {
+    fn default() -> Self {
+        Self::new(Arc::new(InMemoryPolicyStore::default()))
+    }
+}
+
+impl PolicyEngine {
+    pub fn new(store: Arc<dyn PolicyStore>) -> Self {
+        Self {
+            store,
+            directory: Arc::new(InMemorySubjectDirectory::default()),
+        }
+    }
+
+    /// Look up location assignments and shifts in `directory`
+    pub fn with_subject_directory(mut self, directory: Arc<dyn SubjectDirectory>) -> Self {
+        self.directory = directory;
+        self
+    }
+
+    /// The caller's attributes at the time of `context`. The location is never taken from
+    /// the request: it is the one the session is bound to, else the current shift's, else
+    /// the user's only assigned location.
+    pub async fn subject(&self, auth: &AuthContext, context: &RequestContext) -> Result<PolicySubject> {
+        let assigned = self.directory.assigned_locations(auth.tenant_id, auth.user_id).await?;
+        let shift = self.directory.current_shift(auth.tenant_id, auth.user_id, context.time).await?;
+
+        let only_assignment = match assigned.as_slice() {
+            [location_id] => Some(*location_id),
+            _ => None,
+        };
+        let mut subject = PolicySubject::from_auth_context(auth).with_attribute("location_ids", json!(assigned));
+        subject.location_id = subject
+            .location_id
+            .or_else(|| shift.as_ref().and_then(|shift| shift.location_id))
+            .or(only_assignment);
+
+        if let Some(shift) = shift {
+            subject = subject
+                .with_attribute("shift_start", json!(shift.starts_at.to_rfc3339()))
+                .with_attribute("shift_end", json!(shift.ends_at.to_rfc3339()));
+        }
+        Ok(subject)
+    }
+
+    pub async fn evaluate(&self, tenant_id: Uuid, action: &str, input: &PolicyInput) -> Result<PolicyDecision> {
+        let policies = self.store.load(tenant_id).await?;
+        let decision = evaluate(&policies, action, input);
+
+        if decision.outcome == PolicyOutcome::Deny {
+            tracing::info!(
+                "Policy denied {} for tenant {}: {}",
+                action,
+                tenant_id,
+                decision.reasons.join("; ")
+            );
+        }
+
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policies(value: Value) -> Vec<Policy> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn subject(roles: &[&str], location_id: Uuid) -> PolicySubject {
+        PolicySubject {
+            user_id: Uuid::new_v4(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            location_id: Some(location_id),
+            attributes: Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_refund_threshold_needs_manager() {
+        let policies = policies(json!([{
+            "id": "refund-manager-approval",
+            "description": "Refunds over $500 need a manager",
+            "effect": "deny",
+            "actions": ["commerce.refunds.create"],
+            "conditions": [
+                { "attribute": "resource.amount", "operator": "gt", "value": 500 },
+                { "attribute": "subject.roles", "operator": "not_contains", "value": "manager" }
+            ]
+        }]));
+        let location = Uuid::new_v4();
+        let context = RequestContext::now(None);
+
+        let large = PolicyInput::new(&subject(&["server"], location), json!({ "amount": "750.00" }), &context);
+        let decision = evaluate(&policies, "commerce.refunds.create", &large);
+        assert_eq!(decision.outcome, PolicyOutcome::Deny);
+        assert_eq!(decision.reasons, vec!["Refunds over $500 need a manager".to_string()]);
+
+        let small = PolicyInput::new(&subject(&["server"], location), json!({ "amount": "20.00" }), &context);
+        assert_eq!(evaluate(&policies, "commerce.refunds.create", &small).outcome, PolicyOutcome::NotApplicable);
+
+        let manager = PolicyInput::new(&subject(&["manager"], location), json!({ "amount": 750 }), &context);
+        assert!(evaluate(&policies, "commerce.refunds.create", &manager).is_permitted());
+    }
+
+    #[test]
+    fn test_server_void_window() {
+        let policies = policies(json!([{
+            "id": "server-voids",
+            "effect": "permit",
+            "actions": ["commerce.orders.cancel"],
+            "roles": ["server"],
+            "conditions": [
+                { "attribute": "resource.total", "operator": "lt", "value": 50 },
+                { "attribute": "resource.location_id", "operator": "eq", "value_from": "subject.location_id" },
+                { "attribute": "context.time", "operator": "gte", "value_from": "subject.shift_start" },
+                { "attribute": "context.time", "operator": "lte", "value_from": "subject.shift_end" }
+            ]
+        }]));
+        let location = Uuid::new_v4();
+        let now = Utc::now();
+        let mut server = subject(&["server"], location);
+        server.attributes.insert("shift_start".into(), json!((now - chrono::Duration::hours(1)).to_rfc3339()));
+        server.attributes.insert("shift_end".into(), json!((now + chrono::Duration::hours(1)).to_rfc3339()));
+        let context = RequestContext { time: now, ip_address: None };
+
+        let order = |total: f64, location_id: Uuid| json!({ "total": total, "location_id": location_id });
+
+        let ok = PolicyInput::new(&server, order(30.0, location), &context);
+        assert_eq!(evaluate(&policies, "commerce.orders.cancel", &ok).outcome, PolicyOutcome::Permit);
+
+        let too_large = PolicyInput::new(&server, order(80.0, location), &context);
+        assert_eq!(evaluate(&policies, "commerce.orders.cancel", &too_large).outcome, PolicyOutcome::Deny);
+
+        let elsewhere = PolicyInput::new(&server, order(30.0, Uuid::new_v4()), &context);
+        assert_eq!(evaluate(&policies, "commerce.orders.cancel", &elsewhere).outcome, PolicyOutcome::Deny);
+
+        let off_shift = RequestContext { time: now + chrono::Duration::hours(2), ip_address: None };
+        let late = PolicyInput::new(&server, order(30.0, location), &off_shift);
+        assert_eq!(evaluate(&policies, "commerce.orders.cancel", &late).outcome, PolicyOutcome::Deny);
+
+        // Managers are not targeted by the server policy
+        let manager = PolicyInput::new(&subject(&["manager"], location), order(80.0, location), &context);
+        assert_eq!(evaluate(&policies, "commerce.orders.cancel", &manager).outcome, PolicyOutcome::NotApplicable);
+    }
+
+    #[test]
+    fn test_cidr_conditions() {
+        let policies = policies(json!([{
+            "id": "office-only",
+            "effect": "deny",
+            "actions": ["commerce.refunds.*"],
+            "conditions": [
+                { "attribute": "context.ip_address", "operator": "not_in_cidr", "value": ["10.0.0.0/8", "2001:db8::/32"] }
+            ]
+        }]));
+        let input = |ip: &str| {
+            PolicyInput::new(&PolicySubject::default(), json!({}), &RequestContext::now(ip.parse().ok()))
+        };
+
+        assert!(evaluate(&policies, "commerce.refunds.create", &input("10.1.2.3")).is_permitted());
+        assert!(evaluate(&policies, "commerce.refunds.create", &input("2001:db8::1")).is_permitted());
+        assert!(!evaluate(&policies, "commerce.refunds.create", &input("203.0.113.7")).is_permitted());
+        assert!(evaluate(&policies, "commerce.orders.cancel", &input("203.0.113.7")).is_permitted());
+    }
+
+    #[test]
+    fn test_client_address_behind_trusted_proxies() {
+        let ip = |value: &str| value.parse::<IpAddr>().ok();
+        let forwarded = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-forwarded-for", value.parse().unwrap());
+            headers
+        };
+        let client = |headers: &HeaderMap, peer: &str, proxies: usize| {
+            RequestContext::from_forwarded(headers, ip(peer), TrustedProxies(proxies)).ip_address
+        };
+
+        // Without trusted proxies the header is ignored
+        let spoofed = forwarded("10.1.2.3");
+        assert_eq!(client(&spoofed, "203.0.113.7", 0), ip("203.0.113.7"));
+
+        // Entries the client prepended are skipped
+        let one_proxy = forwarded("10.1.2.3, 198.51.100.4");
+        assert_eq!(client(&one_proxy, "192.0.2.1", 1), ip("198.51.100.4"));
+
+        let two_proxies = forwarded("10.1.2.3, 198.51.100.4, 192.0.2.2");
+        assert_eq!(client(&two_proxies, "192.0.2.1", 2), ip("198.51.100.4"));
+
+        // A request that reached the service without passing the proxies
+        assert_eq!(client(&HeaderMap::new(), "198.51.100.4", 1), ip("198.51.100.4"));
+    }
+
+    fn auth_context(tenant_id: Uuid, user_id: Uuid, location_id: Option<Uuid>) -> AuthContext {
+        AuthContext {
+            user_id,
+            tenant_id,
+            roles: vec!["server".to_string()],
+            permissions: vec![],
+            session_id: Uuid::new_v4().to_string(),
+            expires_at: Utc::now() + chrono::Duration::minutes(15),
+            location_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subject_location_and_shift_from_directory() {
+        let (tenant_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let (home, other, terminal) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let directory = Arc::new(InMemorySubjectDirectory::default());
+        let engine = PolicyEngine::default().with_subject_directory(directory.clone());
+        let now = Utc::now();
+        let context = RequestContext { time: now, ip_address: None };
+
+        // No assignment and no shift: the subject has no location
+        let subject = engine.subject(&auth_context(tenant_id, user_id, None), &context).await.unwrap();
+        assert_eq!(subject.location_id, None);
+
+        directory.assign(tenant_id, user_id, vec![home]).await;
+        let subject = engine.subject(&auth_context(tenant_id, user_id, None), &context).await.unwrap();
+        assert_eq!(subject.location_id, Some(home));
+
+        // With several assignments only a shift names the location
+        directory.assign(tenant_id, user_id, vec![home, other]).await;
+        let subject = engine.subject(&auth_context(tenant_id, user_id, None), &context).await.unwrap();
+        assert_eq!(subject.location_id, None);
+
+        let shift = Shift {
+            location_id: Some(other),
+            starts_at: now - chrono::Duration::hours(1),
+            ends_at: now + chrono::Duration::hours(1),
+        };
+        directory.schedule(tenant_id, user_id, shift.clone()).await;
+        let subject = engine.subject(&auth_context(tenant_id, user_id, None), &context).await.unwrap();
+        assert_eq!(subject.location_id, Some(other));
+        assert_eq!(subject.attributes["shift_start"], json!(shift.starts_at.to_rfc3339()));
+        assert_eq!(subject.attributes["shift_end"], json!(shift.ends_at.to_rfc3339()));
+        assert_eq!(subject.attributes["location_ids"], json!([home, other]));
+
+        // A terminal session's location wins
+        let subject = engine.subject(&auth_context(tenant_id, user_id, Some(terminal)), &context).await.unwrap();
+        assert_eq!(subject.location_id, Some(terminal));
+
+        // After the shift the shift attributes are gone, so shift-bound permits fail
+        let later = RequestContext { time: now + chrono::Duration::hours(2), ip_address: None };
+        let subject = engine.subject(&auth_context(tenant_id, user_id, None), &later).await.unwrap();
+        assert!(!subject.attributes.contains_key("shift_start"));
+        let policies = policies(json!([{
+            "id": "server-voids",
+            "effect": "permit",
+            "actions": ["commerce.orders.cancel"],
+            "roles": ["server"],
+            "conditions": [
+                { "attribute": "context.time", "operator": "gte", "value_from": "subject.shift_start" },
+                { "attribute": "context.time", "operator": "lte", "value_from": "subject.shift_end" }
+            ]
+        }]));
+        let input = PolicyInput::new(&subject, json!({}), &later);
+        assert_eq!(evaluate(&policies, "commerce.orders.cancel", &input).outcome, PolicyOutcome::Deny);
+    }
+}
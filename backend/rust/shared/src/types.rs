@@ -173,7 +173,7 @@ pub struct Permission {
     pub scope: PermissionScope,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PermissionScope {
     Own,      // Can only access own resources
     Tenant,   // Can access all resources in tenant
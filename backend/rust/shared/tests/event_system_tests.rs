@@ -154,6 +154,8 @@ fn create_test_legacy_event() -> EventContainer {
         last_name: Some("Doe".to_string()),
         email_verification_required: true,
         registration_source: "web".to_string(),
+        email_id: None,
+        locale: None,
     };
 
     let domain_event = DomainEvent {
@@ -1,7 +1,20 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
+use axum::Extension;
 use tokio;
+use tokio::sync::Mutex;
+use tracing::info;
 use tracing_subscriber;
 
+use olympus_commerce::CommerceConfig;
+use olympus_platform::services::{ConfigurationService, FeatureFlagsService, PlatformPolicyStore, RolloutScheduler};
+use olympus_platform::PlatformConfig;
+use olympus_shared::config::AppConfig;
+use olympus_shared::database::ConnectionPool;
+use olympus_shared::events::{EventConfig, EventPublisher};
+use olympus_shared::policy::{PolicyEngine, TrustedProxies};
+
 mod batch;
 mod cache;
 mod compression;
@@ -9,34 +22,73 @@ mod config;
 mod graphql;
 mod health;
 mod security;
+mod sso;
 mod versioning;
 mod websocket;
 
+/// How often progressive rollouts of feature flags are checked for their next step
+const ROLLOUT_SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
-    println!("Olympus Cloud Rust Services Starting...");
+    let config = AppConfig::load()?;
+    config.validate()?;
+
+    let pool = ConnectionPool::new(config.database_pool_config()).await?;
+    let db = Arc::new(pool.pool().clone());
+
+    let event_config = EventConfig {
+        redis_url: config.redis.url.clone(),
+        ..EventConfig::default()
+    };
+    let event_publisher = Arc::new(EventPublisher::new(event_config.clone()).await?);
+
+    let configuration = Arc::new(ConfigurationService::new(db.clone(), event_publisher.clone()));
+
+    // Auth keeps its own publisher behind a mutex, and starts its background tasks here;
+    // its event handlers run until the subscriber is dropped at shutdown
+    let (auth_service, _auth_events) = olympus_auth::startup::start_auth_service(
+        &config,
+        db.clone(),
+        Arc::new(sso::PlatformSsoConfigStore::new(configuration.clone())),
+        Some(Arc::new(Mutex::new(EventPublisher::new(event_config).await?))),
+    )
+    .await?;
 
-    // For now, just test that modules compile
-    // Skip auth router creation due to database dependency
-    // let _auth_router = olympus_auth::create_router(Arc::new(
-    //     create_mock_auth_service().await?
-    // ));
-    println!("Auth module compiled successfully (router creation skipped)");
+    // Each instance ticks the rollout schedules; a step is applied by whichever gets there first
+    let feature_flags = FeatureFlagsService::new(db.clone(), event_publisher.clone())
+        .with_configuration(configuration.clone());
+    Arc::new(RolloutScheduler::new(Arc::new(feature_flags))).spawn(ROLLOUT_SCHEDULER_INTERVAL);
 
-    let _platform_router = olympus_platform::create_router();
-    let _commerce_router = olympus_commerce::create_router();
+    let policy_engine = PolicyEngine::new(Arc::new(PlatformPolicyStore::new(configuration)));
+
+    let platform_router = olympus_platform::create_router(PlatformConfig {
+        db: db.clone(),
+        event_publisher: event_publisher.clone(),
+        authenticator: auth_service.clone(),
+    });
+    let commerce_router = olympus_commerce::create_router(CommerceConfig {
+        db,
+        event_publisher,
+        policy_engine,
+        authenticator: auth_service.clone(),
+    });
+
+    // Each service serves its own `/health`, so requests the auth router does not match fall
+    // through to platform, then commerce, instead of merging overlapping routes
+    let app = olympus_auth::create_router(auth_service)
+        .fallback_service(platform_router.fallback_service(commerce_router))
+        .layer(Extension(TrustedProxies(config.server.trusted_proxies)));
+
+    let address = format!("{}:{}", config.server.host, config.server.port);
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    info!("Olympus Cloud Rust services listening on {}", address);
+
+    // Client addresses come from the connection, see `RequestContext`
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
-    println!("All services initialized successfully!");
-    
     Ok(())
 }
-
-async fn create_mock_auth_service() -> Result<olympus_auth::services::AuthService, Box<dyn std::error::Error>> {
-    // For compilation testing only - would use real database in production
-    // Skip actual database connection for now
-    println!("Note: Using mock auth service for compilation testing");
-    Err("Mock auth service - would initialize with real database".into())
-}
\ No newline at end of file
@@ -0,0 +1,126 @@
+//! Tenant SSO settings kept in the platform configuration service
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use uuid::Uuid;
+use olympus_auth::error::Result;
+use olympus_auth::services::sso::{OidcProviderConfig, SsoConfigStore};
+use olympus_platform::models::{
+    ConfigScope, ConfigType, CreateConfigurationRequest, UpdateConfigurationRequest,
+};
+use olympus_platform::services::ConfigurationService;
+
+/// Tenant configuration key holding the non-secret relying party settings
+pub const SSO_CONFIG_KEY: &str = "auth.sso.oidc";
+/// Tenant configuration key holding the client secret, stored as `ConfigType::Encrypted`
+pub const SSO_CLIENT_SECRET_KEY: &str = "auth.sso.oidc.client_secret";
+const SSO_CONFIG_CATEGORY: &str = "authentication";
+
+/// Tenant settings kept in the platform configuration service, with the client secret encrypted
+pub struct PlatformSsoConfigStore {
+    configuration: Arc<ConfigurationService>,
+}
+
+impl PlatformSsoConfigStore {
+    pub fn new(configuration: Arc<ConfigurationService>) -> Self {
+        Self { configuration }
+    }
+
+    async fn upsert(
+        &self,
+        tenant_id: Uuid,
+        key: &str,
+        config_type: ConfigType,
+        value: serde_json::Value,
+        updated_by: Uuid,
+    ) -> Result<()> {
+        let existing = self
+            .configuration
+            .get_configuration_by_key(ConfigScope::Tenant, Some(tenant_id), key, true)
+            .await?;
+
+        match existing {
+            Some(existing) => {
+                self.configuration
+                    .update_configuration(
+                        existing.id,
+                        UpdateConfigurationRequest {
+                            display_name: None,
+                            description: None,
+                            value: Some(value),
+                            default_value: None,
+                            is_sensitive: None,
+                            is_readonly: None,
+                            validation_rules: None,
+                            category: None,
+                            tags: None,
+                        },
+                        updated_by,
+                        None,
+                        None,
+                    )
+                    .await?;
+            }
+            None => {
+                self.configuration
+                    .create_configuration(
+                        CreateConfigurationRequest {
+                            scope: ConfigScope::Tenant,
+                            scope_id: Some(tenant_id),
+                            key: key.to_string(),
+                            display_name: "OIDC single sign-on".to_string(),
+                            description: Some("Identity provider used for staff login".to_string()),
+                            config_type,
+                            value,
+                            default_value: None,
+                            is_sensitive: config_type == ConfigType::Encrypted,
+                            is_readonly: false,
+                            validation_rules: None,
+                            category: SSO_CONFIG_CATEGORY.to_string(),
+                            tags: Some(vec!["sso".to_string()]),
+                        },
+                        updated_by,
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SsoConfigStore for PlatformSsoConfigStore {
+    async fn load(&self, tenant_id: Uuid) -> Result<Option<OidcProviderConfig>> {
+        let Some(settings) = self
+            .configuration
+            .get_configuration_by_key(ConfigScope::Tenant, Some(tenant_id), SSO_CONFIG_KEY, false)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut config: OidcProviderConfig = serde_json::from_value(settings.value)?;
+        config.client_secret = self
+            .configuration
+            .get_configuration_by_key(ConfigScope::Tenant, Some(tenant_id), SSO_CLIENT_SECRET_KEY, true)
+            .await?
+            .and_then(|secret| secret.value.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        Ok(Some(config))
+    }
+
+    async fn save(&self, tenant_id: Uuid, config: &OidcProviderConfig, updated_by: Uuid) -> Result<()> {
+        self.upsert(
+            tenant_id,
+            SSO_CLIENT_SECRET_KEY,
+            ConfigType::Encrypted,
+            serde_json::Value::String(config.client_secret.clone()),
+            updated_by,
+        )
+        .await?;
+        self.upsert(tenant_id, SSO_CONFIG_KEY, ConfigType::Json, serde_json::to_value(config)?, updated_by)
+            .await
+    }
+}